@@ -191,6 +191,17 @@ impl CheckpointManager {
         &self,
         description: Option<String>,
         parent_checkpoint_id: Option<String>,
+    ) -> Result<CheckpointResult> {
+        self.create_tagged_checkpoint(description, Vec::new(), parent_checkpoint_id)
+            .await
+    }
+
+    /// Create a checkpoint carrying user-provided tags
+    pub async fn create_tagged_checkpoint(
+        &self,
+        description: Option<String>,
+        tags: Vec<String>,
+        parent_checkpoint_id: Option<String>,
     ) -> Result<CheckpointResult> {
         let messages = self.current_messages.read().await;
         let message_index = messages.len().saturating_sub(1);
@@ -229,7 +240,17 @@ impl CheckpointManager {
         let mut all_files = Vec::new();
         let project_dir = &self.project_path;
         let _ = collect_files(project_dir.as_path(), project_dir.as_path(), &mut all_files);
-        for rel in all_files {
+        // Re-check tracked files that are gone so their deletion is snapshotted
+        let vanished: Vec<PathBuf> = {
+            let tracker = self.file_tracker.read().await;
+            tracker
+                .tracked_files
+                .iter()
+                .filter(|(path, state)| state.exists && !all_files.contains(path))
+                .map(|(path, _)| path.clone())
+                .collect()
+        };
+        for rel in all_files.into_iter().chain(vanished) {
             if let Some(p) = rel.to_str() {
                 // Track each file for snapshot
                 let _ = self.track_file_modification(p).await;
@@ -250,6 +271,7 @@ impl CheckpointManager {
             message_index,
             timestamp: Utc::now(),
             description,
+            tags: super::normalize_tags(tags),
             parent_checkpoint_id: {
                 if let Some(parent_id) = parent_checkpoint_id {
                     Some(parent_id)
@@ -600,6 +622,68 @@ impl CheckpointManager {
         })
     }
 
+    /// Restore one file from a checkpoint, leaving every other file untouched
+    pub async fn restore_file(
+        &self,
+        checkpoint_id: &str,
+        relative_path: &str,
+    ) -> Result<CheckpointResult> {
+        let rel_path = PathBuf::from(relative_path);
+        if rel_path.is_absolute()
+            || rel_path
+                .components()
+                .any(|c| !matches!(c, std::path::Component::Normal(_) | std::path::Component::CurDir))
+        {
+            anyhow::bail!("Path must be relative to the project root: {}", relative_path);
+        }
+        let rel_path: PathBuf = rel_path
+            .components()
+            .filter(|c| !matches!(c, std::path::Component::CurDir))
+            .collect();
+
+        let (checkpoint, _, _) =
+            self.storage
+                .load_checkpoint(&self.project_id, &self.session_id, checkpoint_id)?;
+        let state =
+            self.storage
+                .load_checkpoint_state(&self.project_id, &self.session_id, checkpoint_id)?;
+        let snapshot = state.get(&rel_path).ok_or_else(|| {
+            anyhow::anyhow!(
+                "File {} is not part of checkpoint {}",
+                rel_path.display(),
+                checkpoint_id
+            )
+        })?;
+
+        // Binary files that weren't valid UTF-8 were stored without their content
+        if snapshot.content.len() as u64 != snapshot.size {
+            anyhow::bail!(
+                "Cannot restore {}: the checkpoint does not hold its binary content",
+                rel_path.display()
+            );
+        }
+
+        self.restore_file_snapshot(snapshot).await?;
+
+        // Record the restored content as the file's current state
+        let mut tracker = self.file_tracker.write().await;
+        tracker.tracked_files.insert(
+            rel_path,
+            FileState {
+                last_hash: snapshot.hash.clone(),
+                is_modified: true,
+                last_modified: Utc::now(),
+                exists: true,
+            },
+        );
+
+        Ok(CheckpointResult {
+            checkpoint,
+            files_processed: 1,
+            warnings: Vec::new(),
+        })
+    }
+
     /// Restore a single file from snapshot
     async fn restore_file_snapshot(&self, snapshot: &FileSnapshot) -> Result<()> {
         let full_path = self.project_path.join(&snapshot.file_path);
@@ -648,6 +732,15 @@ impl CheckpointManager {
         checkpoints
     }
 
+    /// List checkpoints matching `filter` (see `Checkpoint::matches_filter`)
+    pub async fn list_checkpoints_filtered(&self, filter: Option<&str>) -> Vec<Checkpoint> {
+        let mut checkpoints = self.list_checkpoints().await;
+        if let Some(filter) = filter {
+            checkpoints.retain(|checkpoint| checkpoint.matches_filter(filter));
+        }
+        checkpoints
+    }
+
     /// Recursively collect checkpoints from timeline tree
     fn collect_checkpoints_from_node(
         node: &super::TimelineNode,
@@ -787,3 +880,81 @@ impl CheckpointManager {
             .max()
     }
 }
+
+#[cfg(test)]
+pub(super) mod tests {
+    use super::CheckpointManager;
+    use tempfile::TempDir;
+
+    /// Create a manager over `<temp>/project` with checkpoints stored in `<temp>/claude`
+    pub(in crate::checkpoint) async fn test_manager(temp: &TempDir) -> CheckpointManager {
+        let project_path = temp.path().join("project");
+        std::fs::create_dir_all(&project_path).unwrap();
+        CheckpointManager::new(
+            "test-project".to_string(),
+            "test-session".to_string(),
+            project_path,
+            temp.path().join("claude"),
+        )
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_restore_single_file_leaves_siblings() {
+        let temp = TempDir::new().unwrap();
+        let manager = test_manager(&temp).await;
+        let project = temp.path().join("project");
+
+        std::fs::create_dir_all(project.join("src")).unwrap();
+        std::fs::write(project.join("src/main.rs"), "fn main() {}\n").unwrap();
+        std::fs::write(project.join("README.md"), "v1\n").unwrap();
+        let checkpoint = manager.create_checkpoint(None, None).await.unwrap();
+
+        std::fs::write(project.join("src/main.rs"), "mangled").unwrap();
+        std::fs::write(project.join("README.md"), "v2\n").unwrap();
+        std::fs::write(project.join("notes.txt"), "new\n").unwrap();
+
+        let result = manager
+            .restore_file(&checkpoint.checkpoint.id, "./src/main.rs")
+            .await
+            .unwrap();
+        assert_eq!(result.files_processed, 1);
+
+        let read = |p: &str| std::fs::read_to_string(project.join(p)).unwrap();
+        assert_eq!(read("src/main.rs"), "fn main() {}\n");
+        assert_eq!(read("README.md"), "v2\n");
+        assert_eq!(read("notes.txt"), "new\n");
+
+        // Files that weren't in the checkpoint are reported clearly
+        let err = manager
+            .restore_file(&checkpoint.checkpoint.id, "notes.txt")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("not part of checkpoint"));
+
+        assert!(manager
+            .restore_file(&checkpoint.checkpoint.id, "../outside.txt")
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_restore_binary_file_fails_without_touching_it() {
+        let temp = TempDir::new().unwrap();
+        let manager = test_manager(&temp).await;
+        let project = temp.path().join("project");
+
+        // Not valid UTF-8, so the snapshot keeps only the size, not the bytes
+        std::fs::write(project.join("image.bin"), [0u8, 159, 146, 150]).unwrap();
+        let checkpoint = manager.create_checkpoint(None, None).await.unwrap();
+        std::fs::write(project.join("image.bin"), [1u8, 2, 3]).unwrap();
+
+        let err = manager
+            .restore_file(&checkpoint.checkpoint.id, "image.bin")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("does not hold its binary content"));
+        assert_eq!(std::fs::read(project.join("image.bin")).unwrap(), vec![1u8, 2, 3]);
+    }
+}
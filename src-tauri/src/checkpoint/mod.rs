@@ -25,12 +25,45 @@ pub struct Checkpoint {
     pub timestamp: DateTime<Utc>,
     /// User-provided description
     pub description: Option<String>,
+    /// User-provided tags; empty for checkpoints saved before tags existed
+    #[serde(default)]
+    pub tags: Vec<String>,
     /// Parent checkpoint ID for fork tracking
     pub parent_checkpoint_id: Option<String>,
     /// Metadata about the checkpoint
     pub metadata: CheckpointMetadata,
 }
 
+impl Checkpoint {
+    /// Whether this checkpoint matches a list filter
+    ///
+    /// Case-insensitive: matches when a tag equals the filter or the description
+    /// contains it. A blank filter matches everything.
+    pub fn matches_filter(&self, filter: &str) -> bool {
+        let filter = filter.trim().to_lowercase();
+        if filter.is_empty() {
+            return true;
+        }
+        self.tags.iter().any(|tag| tag.to_lowercase() == filter)
+            || self
+                .description
+                .as_deref()
+                .is_some_and(|d| d.to_lowercase().contains(&filter))
+    }
+}
+
+/// Trim tags, dropping empty and repeated ones while keeping their order
+pub fn normalize_tags(tags: Vec<String>) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = tag.trim();
+        if !tag.is_empty() && !normalized.iter().any(|t| t == tag) {
+            normalized.push(tag.to_string());
+        }
+    }
+    normalized
+}
+
 /// Metadata associated with a checkpoint
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -144,6 +177,7 @@ pub struct CheckpointResult {
 
 /// Diff between two checkpoints
 #[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct CheckpointDiff {
     /// Source checkpoint ID
     pub from_checkpoint_id: String,
@@ -161,6 +195,7 @@ pub struct CheckpointDiff {
 
 /// Diff for a single file
 #[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct FileDiff {
     /// File path
     pub path: PathBuf,
@@ -168,8 +203,21 @@ pub struct FileDiff {
     pub additions: usize,
     /// Number of deletions
     pub deletions: usize,
-    /// Unified diff content (optional)
+    /// Unified diff content (None for binary files)
     pub diff_content: Option<String>,
+    /// Whether either version is binary, in which case no text diff is produced
+    #[serde(default)]
+    pub is_binary: bool,
+}
+
+impl FileSnapshot {
+    /// Whether the snapshot holds binary data
+    ///
+    /// Snapshots read files as UTF-8, so binary files end up with content that
+    /// doesn't match their size on disk.
+    pub fn is_binary(&self) -> bool {
+        !self.is_deleted && (self.content.len() as u64 != self.size || self.content.contains('\0'))
+    }
 }
 
 impl Default for CheckpointStrategy {
@@ -2,15 +2,21 @@
 
 use anyhow::{Context, Result};
 use sha2::{Digest, Sha256};
+use similar::{ChangeTag, TextDiff};
+use std::collections::{BTreeMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 use uuid::Uuid;
 use zstd::stream::{decode_all, encode_all};
 
 use super::{
-    Checkpoint, CheckpointPaths, CheckpointResult, FileSnapshot, SessionTimeline, TimelineNode,
+    Checkpoint, CheckpointDiff, CheckpointPaths, CheckpointResult, FileDiff, FileSnapshot,
+    SessionTimeline, TimelineNode,
 };
 
+/// Lines of context around each hunk in checkpoint diffs
+const DIFF_CONTEXT_LINES: usize = 3;
+
 /// Manages checkpoint storage operations
 pub struct CheckpointStorage {
     pub claude_dir: PathBuf,
@@ -239,6 +245,134 @@ impl CheckpointStorage {
         Ok(snapshots)
     }
 
+    /// Rebuild the files present at a checkpoint
+    ///
+    /// A checkpoint only stores the files modified since its parent, so the
+    /// snapshots of every ancestor are replayed from the root down.
+    pub fn load_checkpoint_state(
+        &self,
+        project_id: &str,
+        session_id: &str,
+        checkpoint_id: &str,
+    ) -> Result<BTreeMap<PathBuf, FileSnapshot>> {
+        let paths = CheckpointPaths::new(&self.claude_dir, project_id, session_id);
+
+        // Walk parent links up to the root
+        let mut chain = Vec::new();
+        let mut visited = HashSet::new();
+        let mut next = Some(checkpoint_id.to_string());
+        while let Some(id) = next {
+            if !visited.insert(id.clone()) {
+                anyhow::bail!("Checkpoint parent chain contains a cycle at {}", id);
+            }
+            let metadata_json = fs::read_to_string(paths.checkpoint_metadata_file(&id))
+                .with_context(|| format!("Failed to read checkpoint metadata for {}", id))?;
+            let checkpoint: Checkpoint = serde_json::from_str(&metadata_json)
+                .context("Failed to parse checkpoint metadata")?;
+            next = checkpoint.parent_checkpoint_id;
+            chain.push(id);
+        }
+
+        let mut state = BTreeMap::new();
+        for id in chain.iter().rev() {
+            for snapshot in self.load_file_snapshots(&paths, id)? {
+                if snapshot.is_deleted {
+                    state.remove(&snapshot.file_path);
+                } else {
+                    state.insert(snapshot.file_path.clone(), snapshot);
+                }
+            }
+        }
+
+        Ok(state)
+    }
+
+    /// Compute the file changes between two checkpoints
+    pub fn diff_checkpoints(
+        &self,
+        project_id: &str,
+        session_id: &str,
+        from_checkpoint_id: &str,
+        to_checkpoint_id: &str,
+    ) -> Result<CheckpointDiff> {
+        let paths = CheckpointPaths::new(&self.claude_dir, project_id, session_id);
+        let load_metadata = |id: &str| -> Result<Checkpoint> {
+            let metadata_json = fs::read_to_string(paths.checkpoint_metadata_file(id))
+                .with_context(|| format!("Failed to read checkpoint metadata for {}", id))?;
+            serde_json::from_str(&metadata_json).context("Failed to parse checkpoint metadata")
+        };
+        let from_checkpoint = load_metadata(from_checkpoint_id)?;
+        let to_checkpoint = load_metadata(to_checkpoint_id)?;
+
+        let from_state = self.load_checkpoint_state(project_id, session_id, from_checkpoint_id)?;
+        let to_state = self.load_checkpoint_state(project_id, session_id, to_checkpoint_id)?;
+
+        let mut modified_files = Vec::new();
+        let mut deleted_files = Vec::new();
+        for (path, from_file) in &from_state {
+            match to_state.get(path) {
+                Some(to_file) if to_file.hash != from_file.hash || to_file.size != from_file.size => {
+                    modified_files.push(Self::diff_file(path, from_file, to_file));
+                }
+                Some(_) => {}
+                None => deleted_files.push(path.clone()),
+            }
+        }
+        let added_files = to_state
+            .keys()
+            .filter(|path| !from_state.contains_key(*path))
+            .cloned()
+            .collect();
+
+        Ok(CheckpointDiff {
+            from_checkpoint_id: from_checkpoint_id.to_string(),
+            to_checkpoint_id: to_checkpoint_id.to_string(),
+            modified_files,
+            added_files,
+            deleted_files,
+            token_delta: (to_checkpoint.metadata.total_tokens as i64)
+                - (from_checkpoint.metadata.total_tokens as i64),
+        })
+    }
+
+    /// Build a unified diff for one modified file, skipping binary content
+    fn diff_file(path: &Path, from: &FileSnapshot, to: &FileSnapshot) -> FileDiff {
+        if from.is_binary() || to.is_binary() {
+            return FileDiff {
+                path: path.to_path_buf(),
+                additions: 0,
+                deletions: 0,
+                diff_content: None,
+                is_binary: true,
+            };
+        }
+
+        let diff = TextDiff::from_lines(&from.content, &to.content);
+        let (mut additions, mut deletions) = (0, 0);
+        for change in diff.iter_all_changes() {
+            match change.tag() {
+                ChangeTag::Insert => additions += 1,
+                ChangeTag::Delete => deletions += 1,
+                ChangeTag::Equal => {}
+            }
+        }
+
+        let display_path = path.to_string_lossy().replace('\\', "/");
+        let diff_content = diff
+            .unified_diff()
+            .context_radius(DIFF_CONTEXT_LINES)
+            .header(&format!("a/{}", display_path), &format!("b/{}", display_path))
+            .to_string();
+
+        FileDiff {
+            path: path.to_path_buf(),
+            additions,
+            deletions,
+            diff_content: Some(diff_content),
+            is_binary: false,
+        }
+    }
+
     /// Save timeline to disk
     pub fn save_timeline(&self, timeline_path: &Path, timeline: &SessionTimeline) -> Result<()> {
         let timeline_json =
@@ -460,3 +594,133 @@ impl CheckpointStorage {
         Ok(removed_count)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::super::manager::tests::test_manager;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_diff_checkpoints_classifies_changes() {
+        let temp = TempDir::new().unwrap();
+        let manager = test_manager(&temp).await;
+        let project = temp.path().join("project");
+
+        std::fs::write(project.join("keep.txt"), "unchanged\n").unwrap();
+        std::fs::write(project.join("edit.txt"), "one\ntwo\nthree\n").unwrap();
+        std::fs::write(project.join("remove.txt"), "bye\n").unwrap();
+        std::fs::write(project.join("image.bin"), [0u8, 159, 146, 150]).unwrap();
+        let first = manager.create_checkpoint(None, None).await.unwrap();
+
+        std::fs::write(project.join("edit.txt"), "one\n2\nthree\nfour\n").unwrap();
+        std::fs::remove_file(project.join("remove.txt")).unwrap();
+        std::fs::write(project.join("new.txt"), "hello\n").unwrap();
+        std::fs::write(project.join("image.bin"), [0u8, 1, 2, 3, 4]).unwrap();
+        let second = manager.create_checkpoint(None, None).await.unwrap();
+
+        let diff = manager
+            .storage
+            .diff_checkpoints(
+                "test-project",
+                "test-session",
+                &first.checkpoint.id,
+                &second.checkpoint.id,
+            )
+            .unwrap();
+
+        assert_eq!(diff.added_files, vec![std::path::PathBuf::from("new.txt")]);
+        assert_eq!(diff.deleted_files, vec![std::path::PathBuf::from("remove.txt")]);
+
+        let modified: Vec<_> = diff
+            .modified_files
+            .iter()
+            .map(|f| f.path.to_string_lossy().to_string())
+            .collect();
+        assert_eq!(modified, vec!["edit.txt", "image.bin"]);
+
+        let text = &diff.modified_files[0];
+        assert!(!text.is_binary);
+        assert_eq!((text.additions, text.deletions), (2, 1));
+        let hunk = text.diff_content.as_deref().unwrap();
+        assert!(hunk.starts_with("--- a/edit.txt\n+++ b/edit.txt\n"));
+        assert!(hunk.contains("@@ -1,3 +1,4 @@"));
+        assert!(hunk.contains("-two\n+2\n three\n+four\n"));
+
+        let binary = &diff.modified_files[1];
+        assert!(binary.is_binary);
+        assert!(binary.diff_content.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_tagged_checkpoints_filter_by_tag_or_description() {
+        let temp = TempDir::new().unwrap();
+        let manager = test_manager(&temp).await;
+        let project = temp.path().join("project");
+        let tags = |list: &[&str]| list.iter().map(|t| t.to_string()).collect::<Vec<_>>();
+
+        std::fs::write(project.join("a.txt"), "1\n").unwrap();
+        manager
+            .create_tagged_checkpoint(
+                Some("Before refactor".to_string()),
+                tags(&["stable", " release ", "stable", ""]),
+                None,
+            )
+            .await
+            .unwrap();
+        std::fs::write(project.join("a.txt"), "2\n").unwrap();
+        manager
+            .create_tagged_checkpoint(Some("Half-done parser work".to_string()), tags(&["wip"]), None)
+            .await
+            .unwrap();
+        std::fs::write(project.join("a.txt"), "3\n").unwrap();
+        manager.create_checkpoint(None, None).await.unwrap();
+
+        async fn descriptions(manager: &CheckpointManager, filter: Option<&str>) -> Vec<String> {
+            manager
+                .list_checkpoints_filtered(filter)
+                .await
+                .into_iter()
+                .map(|c| c.description.unwrap_or_default())
+                .collect()
+        }
+
+        assert_eq!(descriptions(&manager, None).await.len(), 3);
+        assert_eq!(descriptions(&manager, Some("  ")).await.len(), 3);
+        assert_eq!(descriptions(&manager, Some("Stable")).await, vec!["Before refactor"]);
+        assert_eq!(
+            descriptions(&manager, Some("parser")).await,
+            vec!["Half-done parser work"]
+        );
+        // Tags match whole, descriptions by substring
+        assert!(descriptions(&manager, Some("stab")).await.is_empty());
+
+        let all = manager.list_checkpoints().await;
+        assert_eq!(all[0].tags, vec!["stable", "release"]);
+        assert!(all[2].tags.is_empty());
+    }
+
+    #[test]
+    fn test_checkpoint_without_tags_still_loads() {
+        let legacy = r#"{
+            "id": "abc",
+            "sessionId": "s",
+            "projectId": "p",
+            "messageIndex": 0,
+            "timestamp": "2024-01-01T00:00:00Z",
+            "parentCheckpointId": null,
+            "metadata": {
+                "totalTokens": 0,
+                "modelUsed": "",
+                "userPrompt": "",
+                "fileChanges": 0,
+                "snapshotSize": 0
+            }
+        }"#;
+
+        let checkpoint: super::Checkpoint = serde_json::from_str(legacy).unwrap();
+        assert!(checkpoint.tags.is_empty());
+        assert!(checkpoint.description.is_none());
+        assert!(checkpoint.matches_filter(""));
+        assert!(!checkpoint.matches_filter("release"));
+    }
+}
@@ -6,8 +6,9 @@ use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
 /// Shared module for detecting Claude Code binary installations
 /// Supports NVM installations, aliased paths, and version-based selection
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::Mutex;
 use tauri::Manager;
 
 #[cfg(target_os = "windows")]
@@ -15,7 +16,21 @@ use std::os::windows::process::CommandExt;
 
 /// Converts command output bytes to UTF-8 string, handling Windows encoding issues
 /// On Windows, cmd.exe outputs GBK/GB2312 encoding by default, which needs conversion
+/// A leading UTF-8/UTF-16 BOM selects that encoding and is dropped, and ANSI escape
+/// sequences (colors, cursor movement) are removed so parsers only see the text
 pub fn decode_command_output(bytes: &[u8]) -> String {
+    strip_ansi_escapes(&decode_bytes(bytes))
+}
+
+/// Decodes raw output bytes; escape sequences are still in the result
+fn decode_bytes(bytes: &[u8]) -> String {
+    if let Some((encoding, bom_length)) = encoding_rs::Encoding::for_bom(bytes) {
+        return encoding
+            .decode_without_bom_handling(&bytes[bom_length..])
+            .0
+            .into_owned();
+    }
+
     // First try UTF-8 (most common case)
     if let Ok(utf8_str) = std::str::from_utf8(bytes) {
         return utf8_str.to_string();
@@ -31,10 +46,57 @@ pub fn decode_command_output(bytes: &[u8]) -> String {
         }
     }
     
-    // Fallback to lossy UTF-8 conversion
+    // Fallback to lossy UTF-8 conversion: each invalid sequence becomes one U+FFFD and the
+    // surrounding text, including line breaks and `Key: value` separators, is kept
     String::from_utf8_lossy(bytes).to_string()
 }
 
+/// Removes ANSI escape sequences: CSI (`ESC [ ... final`), OSC (`ESC ] ... BEL` or `ESC \`)
+/// and two-character escapes such as `ESC ( B`
+fn strip_ansi_escapes(text: &str) -> String {
+    if !text.contains('\u{1b}') {
+        return text.to_string();
+    }
+
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\u{1b}' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            // CSI: parameter and intermediate bytes, then one final byte in @..~
+            Some('[') => {
+                for c in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            // OSC (e.g. hyperlinks and window titles): up to BEL or ST
+            Some(']') => {
+                while let Some(c) = chars.next() {
+                    if c == '\u{7}' {
+                        break;
+                    }
+                    if c == '\u{1b}' && chars.peek() == Some(&'\\') {
+                        chars.next();
+                        break;
+                    }
+                }
+            }
+            // Other escapes: optional intermediate bytes, then one final byte
+            Some(c) if (' '..='/').contains(&c) => {
+                while chars.next_if(|c| (' '..='/').contains(c)).is_some() {}
+                chars.next();
+            }
+            Some(_) | None => {}
+        }
+    }
+    out
+}
+
 /// Async helper function to read and decode a line with proper encoding handling
 /// This is used for streaming command output where encoding conversion is needed
 pub async fn read_decoded_line<R: tokio::io::AsyncReadExt + Unpin>(
@@ -94,47 +156,144 @@ pub struct ClaudeInstallation {
     pub installation_type: InstallationType,
 }
 
+/// Semantic version reported by `claude --version`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ClaudeVersion {
+    /// Trimmed output of `claude --version`
+    pub raw: String,
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+/// Version of the resolved claude binary, detected once until the cache is invalidated
+static CLAUDE_VERSION: Mutex<Option<ClaudeVersion>> = Mutex::new(None);
+
+/// Outcome of the last `claude mcp list --json` attempt, with the binary it was tried on
+static MCP_LIST_JSON_SUPPORT: Mutex<Option<(String, bool)>> = Mutex::new(None);
+
+/// Resolved claude binary path, kept in app state so repeated lookups skip discovery
+#[derive(Default)]
+pub struct ClaudeBinaryCache(Mutex<Option<String>>);
+
+impl ClaudeBinaryCache {
+    /// Return the cached path if it is still present, otherwise resolve and cache it
+    pub fn get_or_resolve(
+        &self,
+        resolve: impl FnOnce() -> Result<String, String>,
+    ) -> Result<String, String> {
+        let mut cached = self.0.lock().map_err(|e| e.to_string())?;
+
+        if let Some(path) = cached.as_ref() {
+            // Bare names like "claude" are looked up on PATH at spawn time
+            if !Path::new(path).is_absolute() || Path::new(path).is_file() {
+                debug!("Using cached claude binary: {}", path);
+                return Ok(path.clone());
+            }
+            warn!("Cached claude binary disappeared, re-resolving: {}", path);
+        }
+
+        let path = resolve()?;
+        *cached = Some(path.clone());
+        Ok(path)
+    }
+
+    /// Forget the cached path so the next lookup runs discovery again
+    pub fn invalidate(&self) {
+        if let Ok(mut cached) = self.0.lock() {
+            *cached = None;
+        }
+    }
+}
+
+/// Clear the cached binary path and version after Claude is installed or updated
+pub fn invalidate_claude_binary_cache(app_handle: &tauri::AppHandle) {
+    if let Some(cache) = app_handle.try_state::<ClaudeBinaryCache>() {
+        cache.invalidate();
+    }
+    if let Ok(mut version) = CLAUDE_VERSION.lock() {
+        *version = None;
+    }
+    if let Ok(mut support) = MCP_LIST_JSON_SUPPORT.lock() {
+        *support = None;
+    }
+    info!("Invalidated claude binary cache");
+}
+
 /// Main function to find the Claude binary
-/// Checks database first for stored path and preference, then prioritizes accordingly
+/// Returns the cached path when available, otherwise resolves it
 pub fn find_claude_binary(app_handle: &tauri::AppHandle) -> Result<String, String> {
+    match app_handle.try_state::<ClaudeBinaryCache>() {
+        Some(cache) => cache.get_or_resolve(|| resolve_claude_binary(app_handle)),
+        None => resolve_claude_binary(app_handle),
+    }
+}
+
+/// Checks the codestudio.json override first, then the stored path, then auto-detection
+fn resolve_claude_binary(app_handle: &tauri::AppHandle) -> Result<String, String> {
     info!("Searching for claude binary...");
 
-    // First check if we have a stored path and preference in the database
-    if let Ok(app_data_dir) = app_handle.path().app_data_dir() {
-        let db_path = app_data_dir.join("agents.db");
-        if db_path.exists() {
-            if let Ok(conn) = rusqlite::Connection::open(&db_path) {
-                // Check for stored path first
-                if let Ok(stored_path) = conn.query_row(
-                    "SELECT value FROM app_settings WHERE key = 'claude_binary_path'",
-                    [],
-                    |row| row.get::<_, String>(0),
-                ) {
-                    info!("Found stored claude path in database: {}", stored_path);
-
-                    // Check if the path still exists
-                    let path_buf = PathBuf::from(&stored_path);
-                    if path_buf.exists() && path_buf.is_file() {
-                        return Ok(stored_path);
-                    } else {
-                        warn!("Stored claude path no longer exists: {}", stored_path);
-                    }
-                }
+    find_claude_binary_with(
+        codestudio_settings_path().as_deref(),
+        stored_binary_path(app_handle),
+        discover_system_installations,
+    )
+}
 
-                // Check user preference
-                let preference = conn.query_row(
-                    "SELECT value FROM app_settings WHERE key = 'claude_installation_preference'",
-                    [],
-                    |row| row.get::<_, String>(0),
-                ).unwrap_or_else(|_| "system".to_string());
+/// Read the legacy binary path and installation preference from the agents database
+fn stored_binary_path(app_handle: &tauri::AppHandle) -> Option<String> {
+    let db_path = app_handle.path().app_data_dir().ok()?.join("agents.db");
+    if !db_path.exists() {
+        return None;
+    }
+    let conn = rusqlite::Connection::open(&db_path).ok()?;
+
+    // Check user preference
+    let preference = conn.query_row(
+        "SELECT value FROM app_settings WHERE key = 'claude_installation_preference'",
+        [],
+        |row| row.get::<_, String>(0),
+    ).unwrap_or_else(|_| "system".to_string());
+
+    info!("User preference for Claude installation: {}", preference);
+
+    conn.query_row(
+        "SELECT value FROM app_settings WHERE key = 'claude_binary_path'",
+        [],
+        |row| row.get::<_, String>(0),
+    )
+    .ok()
+}
 
-                info!("User preference for Claude installation: {}", preference);
+/// Resolve the binary from the explicit override, the stored path, then discovery
+fn find_claude_binary_with(
+    settings_path: Option<&Path>,
+    stored_path: Option<String>,
+    discover: impl FnOnce() -> Vec<ClaudeInstallation>,
+) -> Result<String, String> {
+    // An explicit override in ~/.claude/codestudio.json wins over everything else
+    if let Some(path) = settings_path.and_then(read_binary_path_override) {
+        match validate_binary_path(&path) {
+            Ok(()) => {
+                info!("Using claude binary override: {}", path);
+                return Ok(path);
             }
+            Err(e) => warn!("Ignoring claude binary override, falling back to auto-detection: {}", e),
+        }
+    }
+
+    if let Some(stored_path) = stored_path {
+        info!("Found stored claude path in database: {}", stored_path);
+
+        // Check if the path still exists
+        match validate_binary_path(&stored_path) {
+            Ok(()) => return Ok(stored_path),
+            Err(e) => warn!("Stored claude path is no longer usable: {}", e),
         }
     }
 
     // Discover all available system installations
-    let installations = discover_system_installations();
+    let installations = discover();
 
     if installations.is_empty() {
         error!("Could not find claude binary in any location");
@@ -158,14 +317,186 @@ pub fn find_claude_binary(app_handle: &tauri::AppHandle) -> Result<String, Strin
     }
 }
 
+/// Key in ~/.claude/codestudio.json holding the explicit claude binary path
+const BINARY_PATH_SETTING: &str = "claudeBinaryPath";
+
+/// Location of CodeStudio's own settings file (~/.claude/codestudio.json)
+pub fn codestudio_settings_path() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".claude").join("codestudio.json"))
+}
+
+/// Read the explicit binary path from the settings file, if one is set
+pub fn read_binary_path_override(settings_path: &Path) -> Option<String> {
+    let content = std::fs::read_to_string(settings_path).ok()?;
+    let settings: serde_json::Value = match serde_json::from_str(&content) {
+        Ok(settings) => settings,
+        Err(e) => {
+            warn!("Failed to parse {}: {}", settings_path.display(), e);
+            return None;
+        }
+    };
+
+    settings
+        .get(BINARY_PATH_SETTING)
+        .and_then(|value| value.as_str())
+        .map(str::trim)
+        .filter(|path| !path.is_empty())
+        .map(str::to_string)
+}
+
+/// Store the explicit binary path in the settings file, keeping any other keys
+///
+/// An empty `path` removes the override.
+pub fn write_binary_path_override(settings_path: &Path, path: &str) -> Result<(), String> {
+    let mut settings = match std::fs::read_to_string(settings_path) {
+        Ok(content) => serde_json::from_str::<serde_json::Value>(&content)
+            .map_err(|e| format!("Failed to parse {}: {}", settings_path.display(), e))?,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => serde_json::json!({}),
+        Err(e) => return Err(format!("Failed to read {}: {}", settings_path.display(), e)),
+    };
+    let object = settings
+        .as_object_mut()
+        .ok_or_else(|| format!("{} is not a JSON object", settings_path.display()))?;
+
+    if path.trim().is_empty() {
+        object.remove(BINARY_PATH_SETTING);
+    } else {
+        object.insert(BINARY_PATH_SETTING.to_string(), serde_json::json!(path.trim()));
+    }
+
+    if let Some(parent) = settings_path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let content = serde_json::to_string_pretty(&settings)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    crate::fs_util::write_atomic(settings_path, content)
+        .map_err(|e| format!("Failed to write {}: {}", settings_path.display(), e))
+}
+
+/// Check that `path` is an existing, executable file
+pub fn validate_binary_path(path: &str) -> Result<(), String> {
+    let path_buf = PathBuf::from(path);
+    if !path_buf.is_file() {
+        return Err(format!("File does not exist: {}", path));
+    }
+
+    // Check if it's executable (on Unix systems)
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let metadata = std::fs::metadata(&path_buf)
+            .map_err(|e| format!("Failed to read file metadata: {}", e))?;
+        if metadata.permissions().mode() & 0o111 == 0 {
+            return Err(format!("File is not executable: {}", path));
+        }
+    }
+
+    Ok(())
+}
+
 /// Discovers all available Claude installations and returns them for selection
 /// This allows UI to show a version selector
 pub fn discover_claude_installations() -> Vec<ClaudeInstallation> {
     info!("Discovering all Claude installations...");
 
     let mut installations = discover_system_installations();
+    sort_installations(&mut installations);
+    installations
+}
+
+/// Lists every claude binary the user could pick, including broken ones
+///
+/// Combines the configured override and stored path, every `claude` on PATH
+/// and the well-known install locations. Binaries whose version can't be read
+/// are kept with `version: None`.
+pub fn list_claude_installations(app_handle: &tauri::AppHandle) -> Vec<ClaudeInstallation> {
+    let mut installations = Vec::new();
 
-    // Sort by version (highest first), then by source preference
+    let configured = codestudio_settings_path()
+        .as_deref()
+        .and_then(read_binary_path_override)
+        .into_iter()
+        .chain(stored_binary_path(app_handle));
+    for path in configured {
+        if PathBuf::from(&path).is_file() {
+            installations.push(ClaudeInstallation {
+                version: read_binary_version(&path).ok().flatten(),
+                path,
+                source: "custom".to_string(),
+                installation_type: InstallationType::Custom,
+            });
+        }
+    }
+
+    if let Some(path_var) = std::env::var_os("PATH") {
+        installations.extend(find_path_installations(&path_var));
+    }
+    installations.extend(discover_system_installations());
+
+    // Remove duplicates by path, keeping the most specific source
+    let mut unique_paths = std::collections::HashSet::new();
+    installations.retain(|install| unique_paths.insert(install.path.clone()));
+
+    sort_installations(&mut installations);
+    installations
+}
+
+/// Finds every claude executable in the directories of a PATH-style variable
+pub fn find_path_installations(path_var: &std::ffi::OsStr) -> Vec<ClaudeInstallation> {
+    let candidate_names: &[&str] = if cfg!(windows) {
+        &["claude.exe", "claude.cmd", "claude.bat"]
+    } else {
+        &["claude"]
+    };
+
+    let mut installations = Vec::new();
+    for dir in std::env::split_paths(path_var) {
+        for name in candidate_names {
+            let candidate = dir.join(name);
+            if !candidate.is_file() {
+                continue;
+            }
+
+            let path = candidate.to_string_lossy().to_string();
+            debug!("Found claude on PATH: {}", path);
+            installations.push(ClaudeInstallation {
+                version: read_binary_version(&path).ok().flatten(),
+                source: classify_installation_source(&candidate).to_string(),
+                path,
+                installation_type: InstallationType::System,
+            });
+        }
+    }
+
+    installations
+}
+
+/// Guesses how a binary on PATH was installed from its location
+fn classify_installation_source(path: &Path) -> &'static str {
+    let normalized = path.to_string_lossy().replace('\\', "/").to_lowercase();
+
+    if normalized.contains("/.nvm/") || normalized.contains("/nvm/") {
+        "nvm"
+    } else if normalized.starts_with("/opt/homebrew/")
+        || normalized.starts_with("/home/linuxbrew/")
+        || normalized.contains("/cellar/")
+    {
+        "homebrew"
+    } else if normalized.contains("/node_modules/") {
+        "node-modules"
+    } else if normalized.contains("/.npm-global/")
+        || normalized.contains("/appdata/roaming/npm/")
+        || normalized.contains("/npm/bin/")
+    {
+        "npm-global"
+    } else {
+        "PATH"
+    }
+}
+
+/// Sort by version (highest first), then by source preference
+fn sort_installations(installations: &mut [ClaudeInstallation]) {
     installations.sort_by(|a, b| {
         match (&a.version, &b.version) {
             (Some(v1), Some(v2)) => {
@@ -183,13 +514,12 @@ pub fn discover_claude_installations() -> Vec<ClaudeInstallation> {
             (None, None) => source_preference(a).cmp(&source_preference(b)),
         }
     });
-
-    installations
 }
 
 /// Returns a preference score for installation sources (lower is better)
 fn source_preference(installation: &ClaudeInstallation) -> u8 {
     match installation.source.as_str() {
+        "custom" => 0,
         "which" => 1,
         "homebrew" => 2,
         "system" => 3,
@@ -261,7 +591,7 @@ fn try_which_command() -> Option<ClaudeInstallation> {
             }
 
             // Get version
-            let version = get_claude_version(&path).ok().flatten();
+            let version = read_binary_version(&path).ok().flatten();
 
             Some(ClaudeInstallation {
                 path,
@@ -310,7 +640,7 @@ fn try_which_command() -> Option<ClaudeInstallation> {
                 }
 
                 // Get version
-                let version = get_claude_version(&path).ok().flatten();
+                let version = read_binary_version(&path).ok().flatten();
 
                 return Some(ClaudeInstallation {
                     path,
@@ -336,7 +666,7 @@ fn find_nvm_installations() -> Vec<ClaudeInstallation> {
         let claude_path = PathBuf::from(&nvm_bin).join("claude");
         if claude_path.exists() && claude_path.is_file() {
             debug!("Found Claude via NVM_BIN: {:?}", claude_path);
-            let version = get_claude_version(&claude_path.to_string_lossy())
+            let version = read_binary_version(&claude_path.to_string_lossy())
                 .ok()
                 .flatten();
             installations.push(ClaudeInstallation {
@@ -369,7 +699,7 @@ fn find_nvm_installations() -> Vec<ClaudeInstallation> {
                         debug!("Found Claude in NVM node {}: {}", node_version, path_str);
 
                         // Get Claude version
-                        let version = get_claude_version(&path_str).ok().flatten();
+                        let version = read_binary_version(&path_str).ok().flatten();
 
                         installations.push(ClaudeInstallation {
                             path: path_str,
@@ -405,7 +735,7 @@ fn find_nvm_installations() -> Vec<ClaudeInstallation> {
                         debug!("Found Claude in NVM node {}: {}", node_version, path_str);
 
                         // Get Claude version
-                        let version = get_claude_version(&path_str).ok().flatten();
+                        let version = read_binary_version(&path_str).ok().flatten();
 
                         installations.push(ClaudeInstallation {
                             path: path_str,
@@ -475,7 +805,7 @@ fn find_standard_installations() -> Vec<ClaudeInstallation> {
             debug!("Found claude at standard path: {} ({})", path, source);
 
             // Get version
-            let version = get_claude_version(&path).ok().flatten();
+            let version = read_binary_version(&path).ok().flatten();
 
             installations.push(ClaudeInstallation {
                 path,
@@ -544,7 +874,7 @@ fn find_standard_installations() -> Vec<ClaudeInstallation> {
             debug!("Found claude at standard path: {} ({})", path, source);
 
             // Get version
-            let version = get_claude_version(&path).ok().flatten();
+            let version = read_binary_version(&path).ok().flatten();
 
             installations.push(ClaudeInstallation {
                 path,
@@ -573,8 +903,71 @@ fn find_standard_installations() -> Vec<ClaudeInstallation> {
     installations
 }
 
+/// Whether `claude mcp list --json` worked for the binary at `path`, once it has been tried
+pub fn mcp_list_json_support(path: &str) -> Option<bool> {
+    let support = MCP_LIST_JSON_SUPPORT.lock().ok()?;
+    match support.as_ref() {
+        Some((probed_path, supported)) if probed_path == path => Some(*supported),
+        _ => None,
+    }
+}
+
+/// Remember whether `claude mcp list --json` worked for the binary at `path`
+pub fn record_mcp_list_json_support(path: &str, supported: bool) {
+    if let Ok(mut support) = MCP_LIST_JSON_SUPPORT.lock() {
+        *support = Some((path.to_string(), supported));
+    }
+}
+
+/// Returns the version of the claude binary used by the app
+///
+/// The first successful detection is cached until `invalidate_claude_binary_cache`;
+/// failures are not cached so a later install is picked up.
+pub fn get_claude_version(app_handle: &tauri::AppHandle) -> Result<ClaudeVersion, String> {
+    if let Some(version) = CLAUDE_VERSION.lock().ok().and_then(|v| v.clone()) {
+        return Ok(version);
+    }
+
+    let path = find_claude_binary(app_handle)?;
+    let output = Command::new(&path)
+        .arg("--version")
+        .output()
+        .map_err(|e| format!("Failed to run {} --version: {}", path, e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "{} --version exited with {}: {}",
+            path,
+            output.status,
+            decode_command_output(&output.stderr).trim()
+        ));
+    }
+
+    let stdout = decode_command_output(&output.stdout);
+    let version = parse_claude_version(&stdout)
+        .ok_or_else(|| format!("Could not parse claude version from: {:?}", stdout.trim()))?;
+    info!("Detected claude version {}", version.raw);
+
+    if let Ok(mut cached) = CLAUDE_VERSION.lock() {
+        *cached = Some(version.clone());
+    }
+    Ok(version)
+}
+
+/// Parse `major.minor.patch` out of `claude --version` output
+pub fn parse_claude_version(output: &str) -> Option<ClaudeVersion> {
+    let version_regex = regex::Regex::new(r"(\d+)\.(\d+)\.(\d+)").ok()?;
+    let captures = version_regex.captures(output)?;
+
+    Some(ClaudeVersion {
+        raw: output.trim().to_string(),
+        major: captures[1].parse().ok()?,
+        minor: captures[2].parse().ok()?,
+        patch: captures[3].parse().ok()?,
+    })
+}
+
 /// Get Claude version by running --version command
-fn get_claude_version(path: &str) -> Result<Option<String>, String> {
+fn read_binary_version(path: &str) -> Result<Option<String>, String> {
     match Command::new(path).arg("--version").output() {
         Ok(output) => {
             if output.status.success() {
@@ -653,7 +1046,7 @@ fn select_best_installation(installations: Vec<ClaudeInstallation>) -> Option<Cl
 }
 
 /// Compare two version strings
-fn compare_versions(a: &str, b: &str) -> Ordering {
+pub(crate) fn compare_versions(a: &str, b: &str) -> Ordering {
     // Simple semantic version comparison
     let a_parts: Vec<u32> = a
         .split('.')
@@ -691,6 +1084,30 @@ fn compare_versions(a: &str, b: &str) -> Ordering {
     Ordering::Equal
 }
 
+/// PATH that `create_command_with_env(program)` runs the program with
+/// Programs installed through NVM or Homebrew get their own bin directory prepended
+pub fn command_search_path(program: &str) -> String {
+    let current_path = std::env::var("PATH").unwrap_or_default();
+    let is_managed_install = program.contains("/.nvm/versions/node/")
+        || program.contains("/homebrew/")
+        || program.contains("/opt/homebrew/");
+    if !is_managed_install {
+        return current_path;
+    }
+
+    match Path::new(program).parent() {
+        Some(bin_dir) => {
+            let bin_dir = bin_dir.to_string_lossy();
+            if current_path.contains(bin_dir.as_ref()) {
+                current_path
+            } else {
+                format!("{}:{}", bin_dir, current_path)
+            }
+        }
+        None => current_path,
+    }
+}
+
 /// Helper function to create a Command with proper environment variables
 /// This ensures commands like Claude can find Node.js and other dependencies
 pub fn create_command_with_env(program: &str) -> Command {
@@ -740,36 +1157,237 @@ pub fn create_command_with_env(program: &str) -> Command {
         info!("  HTTPS_PROXY={}", https_proxy);
     }
 
-    // Add NVM support if the program is in an NVM directory
-    if program.contains("/.nvm/versions/node/") {
-        if let Some(node_bin_dir) = std::path::Path::new(program).parent() {
-            // Ensure the Node.js bin directory is in PATH
-            let current_path = std::env::var("PATH").unwrap_or_default();
-            let node_bin_str = node_bin_dir.to_string_lossy();
-            if !current_path.contains(&node_bin_str.as_ref()) {
-                let new_path = format!("{}:{}", node_bin_str, current_path);
-                debug!("Adding NVM bin directory to PATH: {}", node_bin_str);
-                cmd.env("PATH", new_path);
-            }
+    // Put the program's NVM or Homebrew bin directory on PATH so sibling tools resolve
+    let search_path = command_search_path(program);
+    if std::env::var("PATH").ok().as_deref() != Some(search_path.as_str()) {
+        debug!("Adding program bin directory to PATH: {}", search_path);
+        cmd.env("PATH", search_path);
+    }
+
+    cmd
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_decode_command_output_strips_ansi_codes() {
+        let colored = concat!(
+            "\u{1b}[1mgithub\u{1b}[0m: npx -y server - \u{1b}[32m✓ Connected\u{1b}[39m\n",
+            "\u{1b}[2K\u{1b}[1Gdone\n",
+        );
+        assert_eq!(
+            decode_command_output(colored.as_bytes()),
+            "github: npx -y server - ✓ Connected\ndone\n"
+        );
+
+        let hyperlink =
+            "see \u{1b}]8;;https://example.com\u{7}docs\u{1b}]8;;\u{1b}\\ and \u{1b}(Bplain";
+        assert_eq!(decode_command_output(hyperlink.as_bytes()), "see docs and plain");
+
+        // Text that merely looks like a color code is left alone
+        assert_eq!(decode_command_output(b"Args: [32m] x"), "Args: [32m] x");
+    }
+
+    #[test]
+    fn test_decode_command_output_handles_bom_and_invalid_bytes() {
+        let mut utf8 = vec![0xEF, 0xBB, 0xBF];
+        utf8.extend_from_slice("Status: ✓ Connected\n".as_bytes());
+        assert_eq!(decode_command_output(&utf8), "Status: ✓ Connected\n");
+
+        let mut utf16 = vec![0xFF, 0xFE];
+        utf16.extend("Type: stdio\r\n".encode_utf16().flat_map(u16::to_le_bytes));
+        assert_eq!(decode_command_output(&utf16), "Type: stdio\r\n");
+
+        let mut utf16_be = vec![0xFE, 0xFF];
+        utf16_be.extend("Type: sse".encode_utf16().flat_map(u16::to_be_bytes));
+        assert_eq!(decode_command_output(&utf16_be), "Type: sse");
+
+        // Invalid bytes are replaced one-for-one without swallowing the separators around them
+        assert_eq!(
+            decode_command_output(b"  Command: np\xffx\n  Args: -y\n"),
+            "  Command: np\u{fffd}x\n  Args: -y\n"
+        );
+    }
+
+    /// Create an executable stand-in for the claude binary
+    fn fake_binary(dir: &Path, name: &str) -> String {
+        let path = dir.join(name);
+        std::fs::write(&path, "#!/bin/sh\necho 1.0.0\n").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
         }
+        path.to_string_lossy().to_string()
     }
 
-    // Add Homebrew support if the program is in a Homebrew directory
-    if program.contains("/homebrew/") || program.contains("/opt/homebrew/") {
-        if let Some(program_dir) = std::path::Path::new(program).parent() {
-            // Ensure the Homebrew bin directory is in PATH
-            let current_path = std::env::var("PATH").unwrap_or_default();
-            let homebrew_bin_str = program_dir.to_string_lossy();
-            if !current_path.contains(&homebrew_bin_str.as_ref()) {
-                let new_path = format!("{}:{}", homebrew_bin_str, current_path);
-                debug!(
-                    "Adding Homebrew bin directory to PATH: {}",
-                    homebrew_bin_str
-                );
-                cmd.env("PATH", new_path);
-            }
+    fn discovered(path: &str) -> Vec<ClaudeInstallation> {
+        vec![ClaudeInstallation {
+            path: path.to_string(),
+            version: Some("1.0.0".to_string()),
+            source: "which".to_string(),
+            installation_type: InstallationType::System,
+        }]
+    }
+
+    #[test]
+    fn test_binary_override_wins() {
+        let temp = TempDir::new().unwrap();
+        let override_path = fake_binary(temp.path(), "claude-local");
+        let settings_path = temp.path().join("codestudio.json");
+        std::fs::write(&settings_path, r#"{"theme": "dark"}"#).unwrap();
+        write_binary_path_override(&settings_path, &override_path).unwrap();
+
+        let resolved = find_claude_binary_with(
+            Some(&settings_path),
+            Some("/nonexistent/stored/claude".to_string()),
+            || discovered("/usr/local/bin/claude"),
+        )
+        .unwrap();
+        assert_eq!(resolved, override_path);
+
+        // Other settings are preserved
+        let settings: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&settings_path).unwrap()).unwrap();
+        assert_eq!(settings["theme"], "dark");
+    }
+
+    #[test]
+    fn test_invalid_binary_override_falls_back() {
+        let temp = TempDir::new().unwrap();
+        let settings_path = temp.path().join("codestudio.json");
+        write_binary_path_override(&settings_path, "/nonexistent/claude").unwrap();
+
+        let resolved = find_claude_binary_with(Some(&settings_path), None, || {
+            discovered("/usr/local/bin/claude")
+        })
+        .unwrap();
+        assert_eq!(resolved, "/usr/local/bin/claude");
+
+        // A file that is not executable is rejected too
+        #[cfg(unix)]
+        {
+            let not_executable = temp.path().join("claude.txt");
+            std::fs::write(&not_executable, "").unwrap();
+            assert!(validate_binary_path(&not_executable.to_string_lossy()).is_err());
         }
+
+        // Clearing the override removes the key
+        write_binary_path_override(&settings_path, "").unwrap();
+        assert_eq!(read_binary_path_override(&settings_path), None);
     }
 
-    cmd
+    #[cfg(unix)]
+    #[test]
+    fn test_find_path_installations_enumerates_all_shims() {
+        let temp = TempDir::new().unwrap();
+        let global = temp.path().join("usr-bin");
+        let nvm = temp.path().join(".nvm/versions/node/v20.1.0/bin");
+        let local = temp.path().join("project/node_modules/.bin");
+        let empty = temp.path().join("empty");
+        for dir in [&global, &nvm, &local, &empty] {
+            std::fs::create_dir_all(dir).unwrap();
+        }
+
+        let global_claude = fake_binary(&global, "claude");
+        let nvm_claude = fake_binary(&nvm, "claude");
+        // A broken shim that can't report its version
+        let local_claude = fake_binary(&local, "claude");
+        std::fs::write(&local_claude, "#!/bin/sh\nexit 1\n").unwrap();
+
+        let path_var = std::env::join_paths([&global, &empty, &nvm, &local]).unwrap();
+        let found = find_path_installations(&path_var);
+
+        let summary: Vec<(&str, &str, Option<&str>)> = found
+            .iter()
+            .map(|i| (i.path.as_str(), i.source.as_str(), i.version.as_deref()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (global_claude.as_str(), "PATH", Some("1.0.0")),
+                (nvm_claude.as_str(), "nvm", Some("1.0.0")),
+                (local_claude.as_str(), "node-modules", None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_classify_installation_source() {
+        let source = |p: &str| classify_installation_source(Path::new(p));
+        assert_eq!(source("/opt/homebrew/bin/claude"), "homebrew");
+        assert_eq!(source("/home/me/.npm-global/bin/claude"), "npm-global");
+        assert_eq!(source("C:\\Users\\me\\AppData\\Roaming\\npm\\claude.cmd"), "npm-global");
+        assert_eq!(source("/usr/local/bin/claude"), "PATH");
+    }
+
+    #[test]
+    fn test_binary_cache_reuses_resolved_path() {
+        let temp = TempDir::new().unwrap();
+        let binary = fake_binary(temp.path(), "claude");
+        let cache = ClaudeBinaryCache::default();
+        let calls = std::cell::Cell::new(0);
+        let resolve = || {
+            calls.set(calls.get() + 1);
+            Ok(binary.clone())
+        };
+
+        assert_eq!(cache.get_or_resolve(resolve).unwrap(), binary);
+        assert_eq!(cache.get_or_resolve(resolve).unwrap(), binary);
+        assert_eq!(calls.get(), 1);
+
+        cache.invalidate();
+        assert_eq!(cache.get_or_resolve(resolve).unwrap(), binary);
+        assert_eq!(calls.get(), 2);
+    }
+
+    #[test]
+    fn test_binary_cache_re_resolves_deleted_binary() {
+        let temp = TempDir::new().unwrap();
+        let old_binary = fake_binary(temp.path(), "claude-old");
+        let new_binary = fake_binary(temp.path(), "claude-new");
+        let cache = ClaudeBinaryCache::default();
+
+        cache.get_or_resolve(|| Ok(old_binary.clone())).unwrap();
+        std::fs::remove_file(&old_binary).unwrap();
+
+        let resolved = cache.get_or_resolve(|| Ok(new_binary.clone())).unwrap();
+        assert_eq!(resolved, new_binary);
+        assert_eq!(cache.get_or_resolve(|| Err("unused".into())).unwrap(), new_binary);
+    }
+
+    #[test]
+    fn test_parse_claude_version_formats() {
+        let version = parse_claude_version("1.0.41 (Claude Code)\n").unwrap();
+        assert_eq!(version.raw, "1.0.41 (Claude Code)");
+        assert_eq!((version.major, version.minor, version.patch), (1, 0, 41));
+
+        let version = parse_claude_version("claude version 2.0.14-beta.1").unwrap();
+        assert_eq!((version.major, version.minor, version.patch), (2, 0, 14));
+
+        let version = parse_claude_version("\u{feff}0.2.125 (Claude Code)\r\n").unwrap();
+        assert_eq!((version.major, version.minor, version.patch), (0, 2, 125));
+    }
+
+    #[test]
+    fn test_parse_claude_version_rejects_garbage() {
+        assert!(parse_claude_version("").is_none());
+        assert!(parse_claude_version("command not found: claude").is_none());
+        assert!(parse_claude_version("v1.2").is_none());
+    }
+
+    #[test]
+    fn test_mcp_list_json_support_is_per_binary() {
+        record_mcp_list_json_support("/opt/old/claude", false);
+        assert_eq!(mcp_list_json_support("/opt/old/claude"), Some(false));
+        // A different binary (e.g. after switching installs) is probed again
+        assert_eq!(mcp_list_json_support("/opt/new/claude"), None);
+
+        record_mcp_list_json_support("/opt/new/claude", true);
+        assert_eq!(mcp_list_json_support("/opt/new/claude"), Some(true));
+        assert_eq!(mcp_list_json_support("/opt/old/claude"), None);
+    }
 }
@@ -875,6 +875,11 @@ async fn spawn_agent_system(
 ) -> Result<i64, String> {
     // Build the command
     let mut cmd = create_agent_system_command(&claude_path, args, &project_path);
+    let command = crate::process::describe_command(cmd.as_std());
+
+    // Run in its own process group so kill_process also stops its children
+    #[cfg(unix)]
+    cmd.process_group(0);
 
     // Spawn the process
     info!("🚀 Spawning Claude system process...");
@@ -1057,6 +1062,7 @@ async fn spawn_agent_system(
             project_path.clone(),
             task.clone(),
             execution_model.clone(),
+            command,
             child,
         )
         .map_err(|e| format!("Failed to register process: {}", e))?;
@@ -1631,9 +1637,16 @@ pub async fn export_agent_to_file(
     Ok(())
 }
 
-/// Get the stored Claude binary path from settings
+/// Get the Claude binary path override from settings
 #[tauri::command]
 pub async fn get_claude_binary_path(db: State<'_, AgentDb>) -> Result<Option<String>, String> {
+    // The override in ~/.claude/codestudio.json takes precedence over the legacy database value
+    if let Some(path) = crate::claude_binary::codestudio_settings_path()
+        .and_then(|settings| crate::claude_binary::read_binary_path_override(&settings))
+    {
+        return Ok(Some(path));
+    }
+
     let conn = db.0.lock().map_err(|e| e.to_string())?;
 
     match conn.query_row(
@@ -1647,46 +1660,45 @@ pub async fn get_claude_binary_path(db: State<'_, AgentDb>) -> Result<Option<Str
     }
 }
 
-/// Set the Claude binary path in settings
+/// Set the Claude binary path override in ~/.claude/codestudio.json
+///
+/// An empty path clears the override and returns to auto-detection.
 #[tauri::command]
-pub async fn set_claude_binary_path(db: State<'_, AgentDb>, path: String) -> Result<(), String> {
-    let conn = db.0.lock().map_err(|e| e.to_string())?;
+pub async fn set_claude_binary_path(
+    app: AppHandle,
+    db: State<'_, AgentDb>,
+    path: String,
+) -> Result<(), String> {
+    let path = path.trim().to_string();
 
     // Validate that the path exists and is executable
-    let path_buf = std::path::PathBuf::from(&path);
-    if !path_buf.exists() {
-        return Err(format!("File does not exist: {}", path));
+    if !path.is_empty() {
+        crate::claude_binary::validate_binary_path(&path)?;
     }
 
-    // Check if it's executable (on Unix systems)
-    #[cfg(unix)]
-    {
-        use std::os::unix::fs::PermissionsExt;
-        let metadata = std::fs::metadata(&path_buf)
-            .map_err(|e| format!("Failed to read file metadata: {}", e))?;
-        let permissions = metadata.permissions();
-        if permissions.mode() & 0o111 == 0 {
-            return Err(format!("File is not executable: {}", path));
-        }
-    }
+    let settings_path = crate::claude_binary::codestudio_settings_path()
+        .ok_or("Could not find home directory")?;
+    crate::claude_binary::write_binary_path_override(&settings_path, &path)?;
 
-    // Insert or update the setting
+    // Drop the legacy database value so it can't shadow a cleared override
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
     conn.execute(
-        "INSERT INTO app_settings (key, value) VALUES ('claude_binary_path', ?1)
-         ON CONFLICT(key) DO UPDATE SET value = ?1",
-        params![path],
+        "DELETE FROM app_settings WHERE key = 'claude_binary_path'",
+        [],
     )
     .map_err(|e| format!("Failed to save Claude binary path: {}", e))?;
 
+    crate::claude_binary::invalidate_claude_binary_cache(&app);
+
     Ok(())
 }
 
 /// List all available Claude installations on the system
 #[tauri::command]
 pub async fn list_claude_installations(
-    _app: AppHandle,
+    app: AppHandle,
 ) -> Result<Vec<crate::claude_binary::ClaudeInstallation>, String> {
-    let installations = crate::claude_binary::discover_claude_installations();
+    let installations = crate::claude_binary::list_claude_installations(&app);
 
     if installations.is_empty() {
         return Err("No Claude Code installations found on the system".to_string());
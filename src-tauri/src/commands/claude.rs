@@ -7,7 +7,7 @@ use std::io::{BufRead, BufReader};
 use std::path::PathBuf;
 use std::process::Stdio;
 use std::sync::Arc;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tauri::{AppHandle, Emitter, Manager};
 use tokio::process::{Child, Command};
 use tokio::sync::Mutex;
@@ -133,6 +133,17 @@ pub struct ClaudeVersionStatus {
     pub output: String,
 }
 
+/// Installed vs. latest published Claude Code version, as returned by `claude_check_update`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ClaudeUpdateStatus {
+    /// Installed version, if it could be detected
+    pub current: Option<String>,
+    /// Latest published version, if the registry could be reached
+    pub latest: Option<String>,
+    /// Whether `latest` is newer than `current`; None when either is unknown
+    pub update_available: Option<bool>,
+}
+
 /// Represents a CLAUDE.md file found in the project
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ClaudeMdFile {
@@ -903,6 +914,21 @@ pub async fn get_system_prompt() -> Result<String, String> {
     fs::read_to_string(&claude_md_path).map_err(|e| format!("Failed to read CLAUDE.md: {}", e))
 }
 
+/// Gets the parsed version of the claude binary, cached after the first detection
+#[tauri::command]
+pub async fn get_claude_version(
+    app: AppHandle,
+) -> Result<crate::claude_binary::ClaudeVersion, String> {
+    crate::claude_binary::get_claude_version(&app)
+}
+
+/// Forgets the cached claude binary path and version, e.g. after installing or updating Claude
+#[tauri::command]
+pub async fn invalidate_claude_binary_cache(app: AppHandle) -> Result<(), String> {
+    crate::claude_binary::invalidate_claude_binary_cache(&app);
+    Ok(())
+}
+
 /// Checks if Claude Code is installed and gets its version
 #[tauri::command]
 pub async fn check_claude_version(app: AppHandle) -> Result<ClaudeVersionStatus, String> {
@@ -995,6 +1021,115 @@ pub async fn check_claude_version(app: AppHandle) -> Result<ClaudeVersionStatus,
     }
 }
 
+/// npm registry document for the latest Claude Code release
+/// Overridable with `CODESTUDIO_CLAUDE_UPDATE_URL`
+const CLAUDE_LATEST_VERSION_URL: &str = "https://registry.npmjs.org/@anthropic-ai/claude-code/latest";
+
+/// How long a successful latest-version lookup is reused
+const CLAUDE_UPDATE_CACHE_TTL: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// How long the registry may take before the latest version is treated as unknown
+const CLAUDE_UPDATE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Latest versions looked up so far: (url, fetched at, version)
+static LATEST_CLAUDE_VERSIONS: std::sync::Mutex<Vec<(String, Instant, String)>> =
+    std::sync::Mutex::new(Vec::new());
+
+/// Fetches the `version` field of a registry document such as npm's `<package>/latest`
+async fn fetch_latest_claude_version(url: &str) -> Result<String, String> {
+    let client = reqwest::Client::builder()
+        .timeout(CLAUDE_UPDATE_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to reach {}: {}", url, e))?;
+    if !response.status().is_success() {
+        return Err(format!("{} returned {}", url, response.status()));
+    }
+
+    let body: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| format!("Invalid response from {}: {}", url, e))?;
+    body.get("version")
+        .and_then(|version| version.as_str())
+        .map(str::to_string)
+        .ok_or_else(|| format!("No version in response from {}", url))
+}
+
+/// Latest published version from `url`, reusing a lookup younger than `ttl`
+async fn latest_claude_version(url: &str, ttl: Duration) -> Result<String, String> {
+    if let Ok(cache) = LATEST_CLAUDE_VERSIONS.lock() {
+        if let Some((_, fetched_at, version)) = cache.iter().find(|(cached, _, _)| cached == url) {
+            if fetched_at.elapsed() < ttl {
+                return Ok(version.clone());
+            }
+        }
+    }
+
+    let version = fetch_latest_claude_version(url).await?;
+    if let Ok(mut cache) = LATEST_CLAUDE_VERSIONS.lock() {
+        cache.retain(|(cached, _, _)| cached != url);
+        cache.push((url.to_string(), Instant::now(), version.clone()));
+    }
+    Ok(version)
+}
+
+/// Compares `current` with the latest version published at `url`
+/// A failed lookup leaves `latest` and `update_available` empty instead of failing
+async fn check_claude_update_against(
+    current: Option<String>,
+    url: &str,
+    ttl: Duration,
+) -> ClaudeUpdateStatus {
+    let latest = match latest_claude_version(url, ttl).await {
+        Ok(latest) => Some(latest),
+        Err(e) => {
+            log::warn!("Could not look up the latest Claude Code version: {}", e);
+            None
+        }
+    };
+    let update_available = match (&current, &latest) {
+        (Some(current), Some(latest)) => Some(
+            crate::claude_binary::compare_versions(latest, current) == std::cmp::Ordering::Greater,
+        ),
+        _ => None,
+    };
+
+    ClaudeUpdateStatus {
+        current,
+        latest,
+        update_available,
+    }
+}
+
+/// Checks whether a newer Claude Code release than the installed one is available
+#[tauri::command]
+pub async fn claude_check_update(app: AppHandle) -> Result<ClaudeUpdateStatus, String> {
+    let current = match crate::claude_binary::get_claude_version(&app) {
+        Ok(version) => Some(format!("{}.{}.{}", version.major, version.minor, version.patch)),
+        Err(e) => {
+            log::warn!("Could not detect the installed Claude Code version: {}", e);
+            None
+        }
+    };
+    let url = std::env::var("CODESTUDIO_CLAUDE_UPDATE_URL")
+        .ok()
+        .filter(|url| !url.trim().is_empty())
+        .unwrap_or_else(|| CLAUDE_LATEST_VERSION_URL.to_string());
+
+    let status = check_claude_update_against(current, &url, CLAUDE_UPDATE_CACHE_TTL).await;
+    log::info!(
+        "Claude Code update check: current={:?} latest={:?}",
+        status.current,
+        status.latest
+    );
+    Ok(status)
+}
+
 /// Saves the CLAUDE.md system prompt file
 #[tauri::command]
 pub async fn save_system_prompt(content: String) -> Result<String, String> {
@@ -1490,6 +1625,12 @@ async fn spawn_claude_process(
     use std::sync::Mutex;
     use tokio::io::{BufReader};
 
+    let command = crate::process::describe_command(cmd.as_std());
+
+    // Run in its own process group so kill_process also stops its children
+    #[cfg(unix)]
+    cmd.process_group(0);
+
     // Spawn the process
     let mut child = cmd
         .spawn()
@@ -1563,6 +1704,7 @@ async fn spawn_claude_process(
                                 project_path_clone.clone(),
                                 prompt_clone.clone(),
                                 model_clone.clone(),
+                                command.clone(),
                             ) {
                                 Ok(run_id) => {
                                     log::info!("Registered Claude session with run_id: {}", run_id);
@@ -2007,6 +2149,7 @@ pub async fn create_checkpoint(
     project_path: String,
     message_index: Option<usize>,
     description: Option<String>,
+    tags: Option<Vec<String>>,
 ) -> Result<crate::checkpoint::CheckpointResult, String> {
     log::info!(
         "Creating checkpoint for session: {} in project: {}",
@@ -2053,7 +2196,7 @@ pub async fn create_checkpoint(
     }
 
     manager
-        .create_checkpoint(description, None)
+        .create_tagged_checkpoint(description, tags.unwrap_or_default(), None)
         .await
         .map_err(|e| format!("Failed to create checkpoint: {}", e))
 }
@@ -2107,6 +2250,34 @@ pub async fn restore_checkpoint(
     Ok(result)
 }
 
+/// Restores a single file from a checkpoint without touching the rest of the project
+#[tauri::command]
+pub async fn checkpoint_restore_file(
+    app: tauri::State<'_, crate::checkpoint::state::CheckpointState>,
+    checkpoint_id: String,
+    relative_path: String,
+    session_id: String,
+    project_id: String,
+    project_path: String,
+) -> Result<crate::checkpoint::CheckpointResult, String> {
+    log::info!(
+        "Restoring {} from checkpoint: {} for session: {}",
+        relative_path,
+        checkpoint_id,
+        session_id
+    );
+
+    let manager = app
+        .get_or_create_manager(session_id, project_id, PathBuf::from(&project_path))
+        .await
+        .map_err(|e| format!("Failed to get checkpoint manager: {}", e))?;
+
+    manager
+        .restore_file(&checkpoint_id, &relative_path)
+        .await
+        .map_err(|e| format!("Failed to restore file: {}", e))
+}
+
 /// Lists all checkpoints for a session
 #[tauri::command]
 pub async fn list_checkpoints(
@@ -2129,6 +2300,30 @@ pub async fn list_checkpoints(
     Ok(manager.list_checkpoints().await)
 }
 
+/// Lists checkpoints for a session, optionally filtered by tag or description substring
+#[tauri::command]
+pub async fn checkpoint_list(
+    app: tauri::State<'_, crate::checkpoint::state::CheckpointState>,
+    session_id: String,
+    project_id: String,
+    project_path: String,
+    filter: Option<String>,
+) -> Result<Vec<crate::checkpoint::Checkpoint>, String> {
+    log::info!(
+        "Listing checkpoints for session: {} in project: {} (filter: {:?})",
+        session_id,
+        project_id,
+        filter
+    );
+
+    let manager = app
+        .get_or_create_manager(session_id, project_id, PathBuf::from(&project_path))
+        .await
+        .map_err(|e| format!("Failed to get checkpoint manager: {}", e))?;
+
+    Ok(manager.list_checkpoints_filtered(filter.as_deref()).await)
+}
+
 /// Forks a new timeline branch from a checkpoint
 #[tauri::command]
 pub async fn fork_from_checkpoint(
@@ -2258,72 +2453,9 @@ pub async fn get_checkpoint_diff(
     let claude_dir = get_claude_dir().map_err(|e| e.to_string())?;
     let storage = CheckpointStorage::new(claude_dir);
 
-    // Load both checkpoints
-    let (from_checkpoint, from_files, _) = storage
-        .load_checkpoint(&project_id, &session_id, &from_checkpoint_id)
-        .map_err(|e| format!("Failed to load source checkpoint: {}", e))?;
-    let (to_checkpoint, to_files, _) = storage
-        .load_checkpoint(&project_id, &session_id, &to_checkpoint_id)
-        .map_err(|e| format!("Failed to load target checkpoint: {}", e))?;
-
-    // Build file maps
-    let mut from_map: std::collections::HashMap<PathBuf, &crate::checkpoint::FileSnapshot> =
-        std::collections::HashMap::new();
-    for file in &from_files {
-        from_map.insert(file.file_path.clone(), file);
-    }
-
-    let mut to_map: std::collections::HashMap<PathBuf, &crate::checkpoint::FileSnapshot> =
-        std::collections::HashMap::new();
-    for file in &to_files {
-        to_map.insert(file.file_path.clone(), file);
-    }
-
-    // Calculate differences
-    let mut modified_files = Vec::new();
-    let mut added_files = Vec::new();
-    let mut deleted_files = Vec::new();
-
-    // Check for modified and deleted files
-    for (path, from_file) in &from_map {
-        if let Some(to_file) = to_map.get(path) {
-            if from_file.hash != to_file.hash {
-                // File was modified
-                let additions = to_file.content.lines().count();
-                let deletions = from_file.content.lines().count();
-
-                modified_files.push(crate::checkpoint::FileDiff {
-                    path: path.clone(),
-                    additions,
-                    deletions,
-                    diff_content: None, // TODO: Generate actual diff
-                });
-            }
-        } else {
-            // File was deleted
-            deleted_files.push(path.clone());
-        }
-    }
-
-    // Check for added files
-    for (path, _) in &to_map {
-        if !from_map.contains_key(path) {
-            added_files.push(path.clone());
-        }
-    }
-
-    // Calculate token delta
-    let token_delta = (to_checkpoint.metadata.total_tokens as i64)
-        - (from_checkpoint.metadata.total_tokens as i64);
-
-    Ok(crate::checkpoint::CheckpointDiff {
-        from_checkpoint_id,
-        to_checkpoint_id,
-        modified_files,
-        added_files,
-        deleted_files,
-        token_delta,
-    })
+    storage
+        .diff_checkpoints(&project_id, &session_id, &from_checkpoint_id, &to_checkpoint_id)
+        .map_err(|e| format!("Failed to diff checkpoints: {}", e))
 }
 
 /// Tracks a message for checkpointing
@@ -2919,4 +3051,71 @@ mod tests {
         let path = result.unwrap();
         assert!(path == "/path1" || path == "/path2");
     }
+
+    /// Serve `{"version": <latest>}` like the npm registry, counting requests
+    async fn spawn_fake_registry(latest: &'static str) -> (String, Arc<std::sync::atomic::AtomicUsize>) {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = hits.clone();
+        let app = Router::new().route(
+            "/@anthropic-ai/claude-code/latest",
+            axum::routing::get(move || {
+                counter.fetch_add(1, Ordering::SeqCst);
+                let body = serde_json::json!({ "name": "@anthropic-ai/claude-code", "version": latest });
+                async move { axum::Json(body) }
+            }),
+        );
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (format!("http://{}/@anthropic-ai/claude-code/latest", addr), hits)
+    }
+
+    #[tokio::test]
+    async fn test_check_update_up_to_date_and_cached() {
+        let (url, hits) = spawn_fake_registry("2.0.14").await;
+        let ttl = Duration::from_secs(60);
+
+        let status = check_claude_update_against(Some("2.0.14".to_string()), &url, ttl).await;
+        assert_eq!(status.latest.as_deref(), Some("2.0.14"));
+        assert_eq!(status.update_available, Some(false));
+
+        // A newer local build is not an update either, and the lookup is cached
+        let status = check_claude_update_against(Some("2.1.0".to_string()), &url, ttl).await;
+        assert_eq!(status.update_available, Some(false));
+        assert_eq!(hits.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_check_update_outdated() {
+        let (url, _) = spawn_fake_registry("2.0.14").await;
+
+        let status = check_claude_update_against(Some("1.0.41".to_string()), &url, Duration::ZERO).await;
+        assert_eq!(
+            status,
+            ClaudeUpdateStatus {
+                current: Some("1.0.41".to_string()),
+                latest: Some("2.0.14".to_string()),
+                update_available: Some(true),
+            }
+        );
+
+        // Unknown installed version: report the latest but make no claim
+        let status = check_claude_update_against(None, &url, Duration::ZERO).await;
+        assert_eq!(status.latest.as_deref(), Some("2.0.14"));
+        assert_eq!(status.update_available, None);
+    }
+
+    #[tokio::test]
+    async fn test_check_update_survives_unreachable_registry() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/latest", listener.local_addr().unwrap());
+        drop(listener);
+
+        let status = check_claude_update_against(Some("1.0.41".to_string()), &url, Duration::ZERO).await;
+        assert_eq!(status.current.as_deref(), Some("1.0.41"));
+        assert_eq!(status.latest, None);
+        assert_eq!(status.update_available, None);
+    }
 }
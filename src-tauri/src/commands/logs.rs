@@ -0,0 +1,439 @@
+use env_logger::filter::Filter;
+use log::LevelFilter;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::{OnceLock, RwLock};
+
+/// Bytes read per step when scanning a log file backwards
+const TAIL_CHUNK_SIZE: u64 = 8 * 1024;
+
+/// Upper bound on lines returned by `get_recent_logs`
+const MAX_TAIL_LINES: usize = 5000;
+
+/// Level overrides applied on top of the startup filter by `set_log_level`
+#[derive(Default)]
+struct LevelOverrides {
+    default: Option<LevelFilter>,
+    modules: BTreeMap<String, LevelFilter>,
+}
+
+static LEVEL_OVERRIDES: RwLock<LevelOverrides> = RwLock::new(LevelOverrides {
+    default: None,
+    modules: BTreeMap::new(),
+});
+
+/// Filter built at startup from RUST_LOG (or the built-in defaults)
+static BASE_FILTER: OnceLock<Filter> = OnceLock::new();
+
+/// File the logger opened at startup; it keeps writing there even after midnight
+static ACTIVE_LOG_FILE: OnceLock<PathBuf> = OnceLock::new();
+
+/// Register the startup filter consulted when no runtime override applies
+pub fn install_base_filter(filter: Filter) {
+    let _ = BASE_FILTER.set(filter);
+}
+
+/// Find the most specific runtime override for a log target
+fn override_for(overrides: &LevelOverrides, target: &str) -> Option<LevelFilter> {
+    overrides
+        .modules
+        .iter()
+        .filter(|(module, _)| {
+            target == module.as_str() || target.starts_with(&format!("{}::", module))
+        })
+        .max_by_key(|(module, _)| module.len())
+        .map(|(_, level)| *level)
+        .or(overrides.default)
+}
+
+/// Whether a record with this metadata should be logged right now
+pub fn log_enabled(metadata: &log::Metadata) -> bool {
+    let overrides = LEVEL_OVERRIDES.read().unwrap_or_else(|e| e.into_inner());
+    enabled_with(&overrides, BASE_FILTER.get(), metadata)
+}
+
+/// Whether `overrides` on top of `base` let a record with this metadata through
+fn enabled_with(
+    overrides: &LevelOverrides,
+    base: Option<&Filter>,
+    metadata: &log::Metadata,
+) -> bool {
+    if let Some(level) = override_for(overrides, metadata.target()) {
+        return metadata.level() <= level;
+    }
+    match base {
+        Some(filter) => filter.enabled(metadata),
+        None => true,
+    }
+}
+
+/// Most verbose level currently enabled for a target
+fn effective_level(target: &str) -> LevelFilter {
+    let overrides = LEVEL_OVERRIDES.read().unwrap_or_else(|e| e.into_inner());
+    effective_level_with(&overrides, BASE_FILTER.get(), target)
+}
+
+/// Most verbose level `overrides` on top of `base` enable for a target
+fn effective_level_with(
+    overrides: &LevelOverrides,
+    base: Option<&Filter>,
+    target: &str,
+) -> LevelFilter {
+    [
+        log::Level::Trace,
+        log::Level::Debug,
+        log::Level::Info,
+        log::Level::Warn,
+        log::Level::Error,
+    ]
+    .into_iter()
+    .find(|level| {
+        enabled_with(
+            overrides,
+            base,
+            &log::Metadata::builder()
+                .level(*level)
+                .target(target)
+                .build(),
+        )
+    })
+    .map(|level| level.to_level_filter())
+    .unwrap_or(LevelFilter::Off)
+}
+
+/// Parse a level name such as "debug" or "WARN"
+fn parse_level_filter(level: &str) -> Result<LevelFilter, String> {
+    level.trim().parse::<LevelFilter>().map_err(|_| {
+        format!(
+            "Invalid log level: {} (expected off, error, warn, info, debug or trace)",
+            level
+        )
+    })
+}
+
+/// Set `level` for `module`, or globally when no module is given
+fn apply_level_override(overrides: &mut LevelOverrides, module: Option<&str>, level: LevelFilter) {
+    match module {
+        Some(module) => {
+            overrides.modules.insert(module.to_string(), level);
+        }
+        None => {
+            // A new global level replaces any per-module tweaks
+            overrides.default = Some(level);
+            overrides.modules.clear();
+        }
+    }
+}
+
+/// Change the log level at runtime, for one module or globally; returns the previous level
+#[tauri::command]
+pub async fn set_log_level(module: Option<String>, level: String) -> Result<String, String> {
+    let new_level = parse_level_filter(&level)?;
+    let module = module
+        .map(|m| m.trim().to_string())
+        .filter(|m| !m.is_empty());
+
+    let previous = {
+        let mut overrides = LEVEL_OVERRIDES.write().unwrap_or_else(|e| e.into_inner());
+        let target = module.as_deref().unwrap_or("");
+        let previous = effective_level_with(&overrides, BASE_FILTER.get(), target);
+        apply_level_override(&mut overrides, module.as_deref(), new_level);
+        previous
+    };
+
+    // Let records through the `log` macros' fast path if the new level is more verbose
+    if new_level > log::max_level() {
+        log::set_max_level(new_level);
+    }
+
+    log::info!(
+        "Log level for {} changed from {} to {}",
+        module.as_deref().unwrap_or("all modules"),
+        previous,
+        new_level
+    );
+    Ok(previous.to_string().to_lowercase())
+}
+
+/// Directory log files are written to:
+/// - Development: ./logs (relative to current directory)
+/// - Production: <exe_dir>/logs (next to the executable)
+pub fn log_directory() -> PathBuf {
+    if cfg!(debug_assertions) {
+        // Development mode: use current directory
+        std::env::current_dir()
+            .unwrap_or_else(|_| PathBuf::from("."))
+            .join("logs")
+    } else {
+        // Production mode: use exe directory
+        std::env::current_exe()
+            .ok()
+            .and_then(|exe_path| exe_path.parent().map(|p| p.to_path_buf()))
+            .map(|exe_dir| exe_dir.join("logs"))
+            .unwrap_or_else(|| {
+                // Fallback to current directory if we can't determine exe path
+                std::env::current_dir()
+                    .unwrap_or_else(|_| PathBuf::from("."))
+                    .join("logs")
+            })
+    }
+}
+
+/// Path of today's log file, e.g. logs/codestudio-20240101.log
+pub fn today_log_file() -> PathBuf {
+    let timestamp = chrono::Local::now().format("%Y%m%d");
+    log_directory().join(format!("codestudio-{}.log", timestamp))
+}
+
+/// Record the file the logger writes to
+pub fn set_active_log_file(path: PathBuf) {
+    let _ = ACTIVE_LOG_FILE.set(path);
+}
+
+/// The file log records are going to: the one opened at startup, or today's file before then
+pub fn active_log_file() -> PathBuf {
+    ACTIVE_LOG_FILE.get().cloned().unwrap_or_else(today_log_file)
+}
+
+/// Where logs are written and how verbose they are, as reported at startup
+#[derive(Debug, Clone, Serialize)]
+pub struct LogInfo {
+    pub log_dir: String,
+    pub log_file: String,
+    /// Level applied to modules without a specific filter ("warn", "info", ...)
+    pub level: String,
+    /// RUST_LOG if set, otherwise the built-in defaults
+    pub filter: String,
+    /// "development" (logs in the current directory) or "production" (next to the executable)
+    pub mode: String,
+}
+
+/// Report the log location and level so the UI can point users at the right file
+#[tauri::command]
+pub async fn get_log_info() -> Result<LogInfo, String> {
+    let log_file = active_log_file();
+    let log_dir = log_file
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_else(log_directory);
+
+    Ok(LogInfo {
+        log_dir: log_dir.to_string_lossy().to_string(),
+        log_file: log_file.to_string_lossy().to_string(),
+        level: effective_level("codestudio").to_string().to_lowercase(),
+        filter: std::env::var("RUST_LOG")
+            .unwrap_or_else(|_| "warn (info for claude modules)".to_string()),
+        mode: if cfg!(debug_assertions) { "development" } else { "production" }.to_string(),
+    })
+}
+
+/// Detect the level of a formatted log line (pretty or JSON format)
+fn line_level(line: &str) -> Option<log::Level> {
+    [
+        log::Level::Error,
+        log::Level::Warn,
+        log::Level::Info,
+        log::Level::Debug,
+        log::Level::Trace,
+    ]
+    .into_iter()
+    .find(|level| {
+        line.contains(&format!("[{}]", level)) || line.contains(&format!("\"level\":\"{}\"", level))
+    })
+}
+
+/// Read up to `max_lines` lines from the end of `path`, oldest first
+///
+/// The file is read backwards in fixed-size chunks so large logs are never loaded
+/// whole. With `min_level` set, only lines at that level or more severe are kept.
+fn tail_log_lines(
+    path: &Path,
+    max_lines: usize,
+    min_level: Option<log::Level>,
+) -> io::Result<Vec<String>> {
+    let mut file = File::open(path)?;
+    let mut pos = file.seek(SeekFrom::End(0))?;
+    let mut pending: Vec<u8> = Vec::new();
+    let mut lines = Vec::new(); // newest first
+
+    let push_line = |bytes: &[u8], lines: &mut Vec<String>| {
+        let line = String::from_utf8_lossy(bytes).trim_end_matches('\r').to_string();
+        if line.is_empty() {
+            return;
+        }
+        let keep = match min_level {
+            Some(min_level) => line_level(&line).is_some_and(|level| level <= min_level),
+            None => true,
+        };
+        if keep {
+            lines.push(line);
+        }
+    };
+
+    while pos > 0 && lines.len() < max_lines {
+        let read_size = TAIL_CHUNK_SIZE.min(pos);
+        pos -= read_size;
+        file.seek(SeekFrom::Start(pos))?;
+
+        let mut chunk = vec![0; read_size as usize];
+        file.read_exact(&mut chunk)?;
+        chunk.extend_from_slice(&pending);
+        pending = chunk;
+
+        // Everything after the last newline in `pending` is a complete line
+        while let Some(idx) = pending.iter().rposition(|&b| b == b'\n') {
+            let line = pending.split_off(idx + 1);
+            pending.truncate(idx);
+            push_line(&line, &mut lines);
+            if lines.len() >= max_lines {
+                break;
+            }
+        }
+    }
+
+    if pos == 0 && lines.len() < max_lines {
+        push_line(&pending, &mut lines);
+    }
+
+    lines.truncate(max_lines);
+    lines.reverse();
+    Ok(lines)
+}
+
+/// Return the last `lines` lines of today's log, optionally filtered by minimum level
+#[tauri::command]
+pub async fn get_recent_logs(
+    lines: usize,
+    level_filter: Option<String>,
+) -> Result<Vec<String>, String> {
+    let min_level = match level_filter.as_deref().map(str::trim) {
+        Some(level) if !level.is_empty() => Some(
+            level
+                .parse::<log::Level>()
+                .map_err(|_| format!("Invalid log level: {}", level))?,
+        ),
+        _ => None,
+    };
+
+    let log_file = active_log_file();
+    if !log_file.exists() {
+        return Ok(Vec::new());
+    }
+
+    tail_log_lines(&log_file, lines.min(MAX_TAIL_LINES), min_level)
+        .map_err(|e| format!("Failed to read log file {:?}: {}", log_file, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write_fixture(dir: &Path, line_count: usize) -> PathBuf {
+        let levels = ["INFO", "WARN", "ERROR", "DEBUG"];
+        let content: String = (0..line_count)
+            .map(|i| {
+                let level = levels[i % levels.len()];
+                format!(
+                    "[2024-01-01 00:00:00.000] [{}] [{}] src/main.rs:{} - message {}\n",
+                    level, level, i, i
+                )
+            })
+            .collect();
+        let path = dir.join("codestudio-20240101.log");
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_tail_log_lines_returns_last_lines_in_order() {
+        let temp_dir = TempDir::new().unwrap();
+        // Large enough to span several chunks
+        let path = write_fixture(temp_dir.path(), 2000);
+
+        let lines = tail_log_lines(&path, 5, None).unwrap();
+        assert_eq!(lines.len(), 5);
+        assert!(lines[0].ends_with("message 1995"));
+        assert!(lines[4].ends_with("message 1999"));
+
+        let all = tail_log_lines(&path, 10_000, None).unwrap();
+        assert_eq!(all.len(), 2000);
+        assert!(all[0].ends_with("message 0"));
+    }
+
+    #[test]
+    fn test_tail_log_lines_filters_by_minimum_level() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = write_fixture(temp_dir.path(), 2000);
+
+        let warnings = tail_log_lines(&path, 4, Some(log::Level::Warn)).unwrap();
+        assert_eq!(warnings.len(), 4);
+        assert!(warnings
+            .iter()
+            .all(|line| line.contains("[WARN]") || line.contains("[ERROR]")));
+        assert!(warnings[3].ends_with("message 1998"));
+
+        let errors = tail_log_lines(&path, 10_000, Some(log::Level::Error)).unwrap();
+        assert_eq!(errors.len(), 500);
+    }
+
+    #[test]
+    fn test_level_override_enables_previously_filtered_records() {
+        // Local filter state, so neither the global filter nor other tests are affected
+        let base = env_logger::filter::Builder::new()
+            .filter_level(LevelFilter::Warn)
+            .build();
+        let mut overrides = LevelOverrides::default();
+        let enabled = |overrides: &LevelOverrides, metadata: &log::Metadata| {
+            enabled_with(overrides, Some(&base), metadata)
+        };
+
+        let target = "codestudio::reload_test";
+        let debug = log::Metadata::builder()
+            .level(log::Level::Debug)
+            .target(target)
+            .build();
+        let trace = log::Metadata::builder()
+            .level(log::Level::Trace)
+            .target(target)
+            .build();
+        let other = log::Metadata::builder()
+            .level(log::Level::Debug)
+            .target("codestudio::other_module")
+            .build();
+
+        assert!(!enabled(&overrides, &debug));
+        assert_eq!(
+            effective_level_with(&overrides, Some(&base), target),
+            LevelFilter::Warn
+        );
+
+        let level = parse_level_filter("DEBUG").unwrap();
+        apply_level_override(&mut overrides, Some(target), level);
+        assert!(enabled(&overrides, &debug));
+        assert!(!enabled(&overrides, &trace));
+        assert!(!enabled(&overrides, &other));
+
+        // A global level replaces the per-module one
+        apply_level_override(&mut overrides, None, LevelFilter::Error);
+        assert!(!enabled(&overrides, &debug));
+        assert_eq!(
+            effective_level_with(&overrides, Some(&base), "codestudio::other_module"),
+            LevelFilter::Error
+        );
+
+        assert!(parse_level_filter("verbose").is_err());
+    }
+
+    #[test]
+    fn test_line_level_detects_json_lines() {
+        assert_eq!(
+            line_level(r#"{"level":"WARN","message":"x"}"#),
+            Some(log::Level::Warn)
+        );
+        assert_eq!(line_level("continuation line"), None);
+    }
+}
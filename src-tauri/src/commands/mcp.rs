@@ -1,14 +1,148 @@
 #![allow(dead_code)]
 
-use anyhow::{Context, Result};
+use anyhow::Result;
 use dirs;
-use log::{error, info};
+use indexmap::IndexMap;
+use log::{error, info, warn};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
-use std::process::Command;
-use tauri::AppHandle;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::io::{AsyncReadExt, AsyncWriteExt, BufReader};
+
+use crate::fs_util::{write_atomic, write_atomic_with};
+use crate::process::{describe_command, ProcessInfo, ProcessRegistry, ProcessRegistryState, ProcessType};
+
+/// How long a connection test waits for a server before giving up
+const CONNECTION_TEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long `mcp_list` results are reused before the CLI is queried again
+const MCP_LIST_CACHE_TTL: Duration = Duration::from_secs(30);
+
+/// How long a single `claude mcp` invocation may run before it is killed
+const MCP_COMMAND_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Maximum log lines queued between a server and the UI before new lines are dropped
+const MCP_LOG_BUFFER_LINES: usize = 1000;
+
+/// Global state caching the last `mcp_list` result
+pub struct MCPListCache {
+    pub entry: Arc<Mutex<Option<(Instant, Vec<MCPServer>)>>>,
+}
+
+impl Default for MCPListCache {
+    fn default() -> Self {
+        Self {
+            entry: Arc::new(Mutex::new(None)),
+        }
+    }
+}
+
+impl MCPListCache {
+    /// Returns the cached servers if they are younger than `ttl`
+    fn get(&self, ttl: Duration) -> Option<Vec<MCPServer>> {
+        let entry = self.entry.lock().ok()?;
+        match entry.as_ref() {
+            Some((stored_at, servers)) if stored_at.elapsed() < ttl => Some(servers.clone()),
+            _ => None,
+        }
+    }
+
+    fn set(&self, servers: Vec<MCPServer>) {
+        if let Ok(mut entry) = self.entry.lock() {
+            *entry = Some((Instant::now(), servers));
+        }
+    }
+
+    fn invalidate(&self) {
+        if let Ok(mut entry) = self.entry.lock() {
+            *entry = None;
+        }
+    }
+}
+
+/// Number of status checks kept per server in `MCPStatusHistory`
+const MCP_STATUS_HISTORY_LIMIT: usize = 50;
+
+/// One recorded connection check of a server
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StatusCheck {
+    /// Unix timestamp (seconds) of the check
+    pub timestamp: u64,
+    pub reachable: bool,
+    pub latency_ms: u64,
+    pub error: Option<String>,
+}
+
+/// Global state keeping the most recent connection checks of each server, oldest first
+pub struct MCPStatusHistory {
+    checks: Mutex<HashMap<String, std::collections::VecDeque<StatusCheck>>>,
+    capacity: usize,
+}
+
+impl Default for MCPStatusHistory {
+    fn default() -> Self {
+        Self::with_capacity(MCP_STATUS_HISTORY_LIMIT)
+    }
+}
+
+impl MCPStatusHistory {
+    fn with_capacity(capacity: usize) -> Self {
+        Self {
+            checks: Mutex::new(HashMap::new()),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Appends a check, dropping the oldest ones beyond the capacity
+    fn record(&self, name: &str, check: StatusCheck) {
+        if let Ok(mut checks) = self.checks.lock() {
+            let history = checks.entry(name.to_string()).or_default();
+            history.push_back(check);
+            while history.len() > self.capacity {
+                history.pop_front();
+            }
+        }
+    }
+
+    /// The last `limit` checks of a server (all of them when `limit` is None), oldest first
+    fn recent(&self, name: &str, limit: Option<usize>) -> Vec<StatusCheck> {
+        let Ok(checks) = self.checks.lock() else {
+            return Vec::new();
+        };
+        let Some(history) = checks.get(name) else {
+            return Vec::new();
+        };
+        let skip = history.len().saturating_sub(limit.unwrap_or(history.len()));
+        history.iter().skip(skip).cloned().collect()
+    }
+}
+
+/// Records a connection check in the status history, if that state is managed
+fn record_status_check(app: &AppHandle, name: &str, latency_ms: u64, error: Option<&str>) {
+    if let Some(history) = app.try_state::<MCPStatusHistory>() {
+        history.record(
+            name,
+            StatusCheck {
+                timestamp: chrono::Utc::now().timestamp().max(0) as u64,
+                reachable: error.is_none(),
+                latency_ms,
+                error: error.map(str::to_string),
+            },
+        );
+    }
+}
+
+/// Drops the cached `mcp_list` result so the next call sees fresh data
+fn invalidate_list_cache(app: &AppHandle) {
+    if let Some(cache) = app.try_state::<MCPListCache>() {
+        cache.invalidate();
+    }
+}
 
 /// Helper function to create a std::process::Command with proper environment variables
 /// This ensures commands like Claude can find Node.js and other dependencies
@@ -16,40 +150,30 @@ fn create_command_with_env(program: &str) -> Command {
     crate::claude_binary::create_command_with_env(program)
 }
 
+/// Glyphs the claude CLI uses to start a status segment, across locales and versions
+const STATUS_GLYPHS: &[char] = &[
+    '✓', '✔', '✅', '✗', '✘', '✕', '❌', '×', '⚠', '⏳', '⏸', '●', '○',
+];
+
 /// Cleans up command string by removing status indicators from claude mcp list output
-/// Examples of patterns to remove:
+/// Everything from the first " - " followed by a status glyph is dropped, e.g.
 /// - "- ✓ Connected"
 /// - "- ✗ Failed to connect"
-/// - "- ✓ connected"
-/// - "- ✗ failed"
+/// - "- ⚠ degraded"
+///
+/// Runs of whitespace are collapsed to a single space
 fn clean_command_string(command: &str) -> String {
-    // Pattern: " - ✓ ..." or " - ✗ ..." at the end
-    let patterns = [
-        " - ✓ Connected",
-        " - ✗ Failed to connect",
-        " - ✓ connected",
-        " - ✗ failed",
-        " - ✓",
-        " - ✗",
-    ];
-
-    let mut result = command.to_string();
-    for pattern in patterns {
-        if let Some(pos) = result.find(pattern) {
-            result = result[..pos].trim().to_string();
-            break;
-        }
-    }
+    let normalized = command.split_whitespace().collect::<Vec<_>>().join(" ");
 
-    // Also handle case-insensitive and variations
-    // Look for pattern: " - " followed by checkmark or X symbol
-    if let Some(pos) = result.find(" - ✓") {
-        result = result[..pos].trim().to_string();
-    } else if let Some(pos) = result.find(" - ✗") {
-        result = result[..pos].trim().to_string();
-    }
+    let status_start = normalized
+        .match_indices(" - ")
+        .map(|(pos, _)| pos)
+        .find(|&pos| normalized[pos + 3..].starts_with(STATUS_GLYPHS));
 
-    result
+    match status_start {
+        Some(pos) => normalized[..pos].to_string(),
+        None => normalized,
+    }
 }
 
 /// Finds the full path to the claude binary
@@ -63,7 +187,7 @@ fn find_claude_binary(app_handle: &AppHandle) -> Result<String> {
 pub struct MCPServer {
     /// Server name/identifier
     pub name: String,
-    /// Transport type: "stdio" or "sse"
+    /// Transport type: "stdio", "sse" or "http"
     pub transport: String,
     /// Command to execute (for stdio)
     pub command: Option<String>,
@@ -73,6 +197,9 @@ pub struct MCPServer {
     pub env: HashMap<String, String>,
     /// URL endpoint (for SSE)
     pub url: Option<String>,
+    /// HTTP headers sent to the endpoint (for SSE)
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
     /// Configuration scope: "local", "project", or "user"
     pub scope: String,
     /// Whether the server is currently active
@@ -104,10 +231,20 @@ pub struct MCPConfigPaths {
 }
 
 /// MCP configuration for project scope (.mcp.json)
+/// Maps keep the order they were read in so re-saving an unchanged file is byte-identical
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MCPProjectConfig {
     #[serde(rename = "mcpServers")]
-    pub mcp_servers: HashMap<String, MCPServerConfig>,
+    pub mcp_servers: IndexMap<String, MCPServerConfig>,
+    /// Undefined `${VAR}` references per server, filled in only when reading with `expand_env`
+    /// Never read from the caller or written to the file
+    #[serde(
+        rename = "envWarnings",
+        default,
+        skip_deserializing,
+        skip_serializing_if = "IndexMap::is_empty"
+    )]
+    pub env_warnings: IndexMap<String, Vec<String>>,
 }
 
 /// Individual server configuration in .mcp.json
@@ -115,15 +252,17 @@ pub struct MCPProjectConfig {
 pub struct MCPServerConfig {
     #[serde(rename = "type")]
     pub transport_type: String,
+    /// Command to execute (empty for sse/http servers)
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub command: String,
     #[serde(default)]
     pub args: Vec<String>,
     #[serde(default)]
-    pub env: HashMap<String, String>,
-    #[serde(default)]
+    pub env: IndexMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub url: Option<String>,
-    #[serde(default)]
-    pub headers: Option<HashMap<String, String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub headers: Option<IndexMap<String, String>>,
 }
 
 /// Result of adding a server
@@ -134,6 +273,56 @@ pub struct AddServerResult {
     pub server_name: Option<String>,
 }
 
+/// Result of removing a server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoveServerResult {
+    pub success: bool,
+    /// False when no server with that name was configured
+    pub removed: bool,
+    /// Scope the removed server was configured in, if known
+    pub scope: Option<String>,
+    pub message: String,
+}
+
+/// Result of saving `.mcp.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectConfigSaveResult {
+    pub message: String,
+    /// Servers the claude CLI would refuse to load; only filled in when verification was asked for
+    pub rejected: Vec<RejectedServer>,
+}
+
+/// A server in a saved `.mcp.json` that the claude CLI would reject
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RejectedServer {
+    pub name: String,
+    pub errors: Vec<String>,
+}
+
+/// Result of probing an MCP server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConnectionTestResult {
+    pub success: bool,
+    pub transport: String,
+    /// Time taken by the probe in milliseconds
+    pub latency_ms: u64,
+    pub message: String,
+    /// Concrete failure reason if the probe failed
+    pub error: Option<String>,
+}
+
+/// One row of `mcp_test_all`: whether a configured server answered its probe
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerReachability {
+    pub name: String,
+    pub scope: String,
+    pub reachable: bool,
+    /// Time taken by the probe in milliseconds
+    pub latency_ms: u64,
+    /// Why the probe failed, if it did
+    pub error: Option<String>,
+}
+
 /// Import result for multiple servers
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ImportResult {
@@ -150,370 +339,609 @@ pub struct ImportServerResult {
     pub error: Option<String>,
 }
 
-/// Executes a claude mcp command
-fn execute_claude_mcp_command(app_handle: &AppHandle, args: Vec<&str>) -> Result<String> {
-    info!("Executing claude mcp command with args: {:?}", args);
-
-    let claude_path = find_claude_binary(app_handle)?;
-    let mut cmd = create_command_with_env(&claude_path);
-    cmd.arg("mcp");
-    for arg in args {
-        cmd.arg(arg);
-    }
+/// Payload of the `mcp-import-progress` event, sent once per imported server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MCPImportProgress {
+    pub name: String,
+    /// 1-based position of this server in the import
+    pub index: usize,
+    pub total: usize,
+    pub success: bool,
+}
 
-    let output = cmd.output().context("Failed to execute claude command")?;
+/// One server in an `mcp_add_many` batch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MCPServerSpec {
+    pub name: String,
+    pub transport: String,
+    #[serde(default)]
+    pub command: Option<String>,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+}
 
-    if output.status.success() {
-        Ok(crate::claude_binary::decode_command_output(&output.stdout))
-    } else {
-        let stderr = crate::claude_binary::decode_command_output(&output.stderr);
-        Err(anyhow::anyhow!("Command failed: {}", stderr))
-    }
+/// Error returned by MCP commands
+/// Serialized as `{ "kind": "...", "message": "...", ...fields }` so the UI can branch on
+/// `kind` and still display `message` directly
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub enum McpError {
+    /// The claude binary could not be located
+    BinaryNotFound { message: String },
+    /// The claude CLI ran but exited unsuccessfully
+    CommandFailed { message: String, stderr: String },
+    /// The claude CLI did not finish within `timeout_secs`
+    Timeout { message: String, timeout_secs: u64 },
+    /// Input or CLI output could not be parsed
+    ParseError { message: String },
+    /// A server with this name already exists in the scope
+    DuplicateName {
+        message: String,
+        name: String,
+        scope: String,
+    },
+    /// No server with this name is configured
+    NotFound { message: String, name: String },
+    /// Reading or writing a config file failed
+    Io { message: String },
 }
 
-/// Adds a new MCP server
-#[tauri::command]
-pub async fn mcp_add(
-    app: AppHandle,
-    name: String,
-    transport: String,
-    command: Option<String>,
-    args: Vec<String>,
-    env: HashMap<String, String>,
-    url: Option<String>,
-    scope: String,
-) -> Result<AddServerResult, String> {
-    info!("Adding MCP server: {} with transport: {}", name, transport);
+impl McpError {
+    pub fn binary_not_found(reason: impl std::fmt::Display) -> Self {
+        McpError::BinaryNotFound {
+            message: format!("Claude binary not found: {}", reason),
+        }
+    }
 
-    // Prepare owned strings for environment variables
-    let env_args: Vec<String> = env
-        .iter()
-        .map(|(key, value)| format!("{}={}", key, value))
-        .collect();
+    pub fn command_failed(stderr: impl Into<String>) -> Self {
+        let stderr = stderr.into();
+        McpError::CommandFailed {
+            message: format!("Command failed: {}", stderr.trim()),
+            stderr,
+        }
+    }
 
-    let mut cmd_args = vec!["add"];
+    pub fn timeout(after: Duration) -> Self {
+        McpError::Timeout {
+            message: format!("claude mcp command timed out after {}s", after.as_secs()),
+            timeout_secs: after.as_secs(),
+        }
+    }
 
-    // Add scope flag
-    cmd_args.push("-s");
-    cmd_args.push(&scope);
+    pub fn parse(message: impl Into<String>) -> Self {
+        McpError::ParseError {
+            message: message.into(),
+        }
+    }
 
-    // Add transport flag for SSE
-    if transport == "sse" {
-        cmd_args.push("--transport");
-        cmd_args.push("sse");
+    pub fn duplicate_name(name: &str, scope: &str) -> Self {
+        McpError::DuplicateName {
+            message: format!("Server '{}' already exists in scope '{}'", name, scope),
+            name: name.to_string(),
+            scope: scope.to_string(),
+        }
     }
 
-    // Add environment variables
-    for (i, _) in env.iter().enumerate() {
-        cmd_args.push("-e");
-        cmd_args.push(&env_args[i]);
+    pub fn not_found(name: &str) -> Self {
+        McpError::NotFound {
+            message: format!("MCP server '{}' not found", name),
+            name: name.to_string(),
+        }
     }
 
-    // Add name
-    cmd_args.push(&name);
+    pub fn io(message: impl Into<String>) -> Self {
+        McpError::Io {
+            message: message.into(),
+        }
+    }
 
-    // Add command/URL based on transport
-    if transport == "stdio" {
-        if let Some(cmd) = &command {
-            // Add "--" separator before command to prevent argument parsing issues
-            if !args.is_empty() || cmd.contains('-') {
-                cmd_args.push("--");
-            }
-            cmd_args.push(cmd);
-            // Add arguments
-            for arg in &args {
-                cmd_args.push(arg);
-            }
-        } else {
-            return Ok(AddServerResult {
-                success: false,
-                message: "Command is required for stdio transport".to_string(),
-                server_name: None,
-            });
+    /// Human-readable description for display
+    pub fn message(&self) -> &str {
+        match self {
+            McpError::BinaryNotFound { message }
+            | McpError::CommandFailed { message, .. }
+            | McpError::Timeout { message, .. }
+            | McpError::ParseError { message }
+            | McpError::DuplicateName { message, .. }
+            | McpError::NotFound { message, .. }
+            | McpError::Io { message } => message,
         }
-    } else if transport == "sse" {
-        if let Some(url_str) = &url {
-            cmd_args.push(url_str);
-        } else {
-            return Ok(AddServerResult {
-                success: false,
-                message: "URL is required for SSE transport".to_string(),
-                server_name: None,
-            });
+    }
+
+    /// Lowercased stderr of a CLI failure, if this is one
+    fn cli_stderr(&self) -> Option<String> {
+        match self {
+            McpError::CommandFailed { stderr, .. } => Some(stderr.to_lowercase()),
+            _ => None,
         }
     }
 
-    match execute_claude_mcp_command(&app, cmd_args) {
-        Ok(output) => {
-            info!("Successfully added MCP server: {}", name);
-            Ok(AddServerResult {
-                success: true,
-                message: output.trim().to_string(),
-                server_name: Some(name),
-            })
+    /// Turns a CLI failure saying `name` doesn't exist into `NotFound`
+    /// Only the CLI's own "No MCP server found" counts; a shell's "command not found" is a
+    /// broken environment, not a missing server
+    fn or_not_found(self, name: &str) -> Self {
+        match self.cli_stderr() {
+            Some(stderr) if stderr.contains("no mcp server found") => McpError::not_found(name),
+            _ => self,
         }
-        Err(e) => {
-            error!("Failed to add MCP server: {}", e);
-            Ok(AddServerResult {
-                success: false,
-                message: e.to_string(),
-                server_name: None,
-            })
+    }
+
+    /// Turns a CLI failure saying `name` is taken in `scope` into `DuplicateName`
+    fn or_duplicate(self, name: &str, scope: &str) -> Self {
+        match self.cli_stderr() {
+            Some(stderr) if stderr.contains("already exists") => {
+                McpError::duplicate_name(name, scope)
+            }
+            _ => self,
         }
     }
 }
 
-/// Lists all configured MCP servers
-#[tauri::command]
-pub async fn mcp_list(app: AppHandle) -> Result<Vec<MCPServer>, String> {
-    info!("Listing MCP servers");
+impl std::fmt::Display for McpError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.message())
+    }
+}
 
-    match execute_claude_mcp_command(&app, vec!["list"]) {
-        Ok(output) => {
-            info!("Raw output from 'claude mcp list': {:?}", output);
-            let trimmed = output.trim();
-            info!("Trimmed output: {:?}", trimmed);
+impl std::error::Error for McpError {}
 
-            // Check if no servers are configured
-            if trimmed.contains("No MCP servers configured") || trimmed.is_empty() {
-                info!("No servers found - empty or 'No MCP servers' message");
-                return Ok(vec![]);
-            }
+impl From<McpError> for String {
+    fn from(error: McpError) -> Self {
+        error.message().to_string()
+    }
+}
 
-            // Parse the text output to get server names
-            let mut server_names = Vec::new();
-            let lines: Vec<&str> = trimmed.lines().collect();
-            info!("Total lines in output: {}", lines.len());
-            for (idx, line) in lines.iter().enumerate() {
-                info!("Line {}: {:?}", idx, line);
-            }
-
-            let mut i = 0;
-
-            while i < lines.len() {
-                let line = lines[i];
-                info!("Processing line {}: {:?}", i, line);
-
-                // Check if this line starts a new server entry
-                if let Some(colon_pos) = line.find(':') {
-                    info!("Found colon at position {} in line: {:?}", colon_pos, line);
-                    // Make sure this is a server name line (not part of a path)
-                    // Server names typically don't contain '/' or '\'
-                    let potential_name = line[..colon_pos].trim();
-                    info!("Potential server name: {:?}", potential_name);
-
-                    if !potential_name.contains('/') && !potential_name.contains('\\') {
-                        info!("Valid server name detected: {:?}", potential_name);
-                        server_names.push(potential_name.to_string());
-                        info!("Added server name to list: {:?}", potential_name);
-
-                        // Skip to next server (skip continuation lines)
-                        i += 1;
-                        while i < lines.len() {
-                            let next_line = lines[i];
-                            info!("Checking next line {} for continuation: {:?}", i, next_line);
-
-                            // If the next line starts with a server name pattern, break
-                            if next_line.contains(':') {
-                                let potential_next_name =
-                                    next_line.split(':').next().unwrap_or("").trim();
-                                info!(
-                                    "Found colon in next line, potential name: {:?}",
-                                    potential_next_name
-                                );
-                                if !potential_next_name.is_empty()
-                                    && !potential_next_name.contains('/')
-                                    && !potential_next_name.contains('\\')
-                                {
-                                    info!("Next line is a new server, breaking");
-                                    break;
-                                }
-                            }
-                            // Otherwise, this line is a continuation - skip it
-                            info!("Line {} is a continuation, skipping", i);
-                            i += 1;
-                        }
+/// Executes a claude mcp command
+fn execute_claude_mcp_command(
+    app_handle: &AppHandle,
+    args: Vec<&str>,
+) -> std::result::Result<String, McpError> {
+    execute_claude_mcp_command_in(app_handle, args, None)
+}
 
-                        continue;
-                    } else {
-                        info!("Skipping line - name contains path separators");
-                    }
-                } else {
-                    info!("No colon found in line {}", i);
-                }
+/// Executes a claude mcp command from `cwd`, so project scope targets that directory's `.mcp.json`
+fn execute_claude_mcp_command_in(
+    app_handle: &AppHandle,
+    args: Vec<&str>,
+    cwd: Option<&Path>,
+) -> std::result::Result<String, McpError> {
+    // Any command that changes the server set makes the cached list stale
+    if matches!(args.first(), Some(&"add") | Some(&"add-json") | Some(&"remove")) {
+        invalidate_list_cache(app_handle);
+    }
 
-                i += 1;
-            }
+    run_claude_mcp_command(app_handle, args, cwd)
+}
 
-            info!("Found {} MCP servers total", server_names.len());
-            for (idx, name) in server_names.iter().enumerate() {
-                info!("Server {}: name='{}'", idx, name);
-            }
+/// Checks an optional working directory for the claude CLI; blank means the default
+fn resolve_mcp_cwd(cwd: Option<&str>) -> std::result::Result<Option<PathBuf>, McpError> {
+    let Some(cwd) = cwd.map(str::trim).filter(|cwd| !cwd.is_empty()) else {
+        return Ok(None);
+    };
+    let path = PathBuf::from(cwd);
+    if !path.is_dir() {
+        return Err(McpError::io(format!(
+            "Working directory does not exist or is not a directory: {}",
+            cwd
+        )));
+    }
+    Ok(Some(path))
+}
 
-            // Get detailed information for each server including correct scope
-            let mut servers = Vec::new();
-            for name in server_names {
-                info!("Getting details for server: {:?}", name);
-                match mcp_get(app.clone(), name.clone()).await {
-                    Ok(server_details) => {
-                        info!("Successfully got details for server '{}': scope={}, transport={}",
-                              name, server_details.scope, server_details.transport);
-                        servers.push(server_details);
-                    }
-                    Err(e) => {
-                        error!("Failed to get details for server '{}': {}", name, e);
-                        // Add a basic server entry with the name if we can't get details
-                        servers.push(MCPServer {
-                            name: name.clone(),
-                            transport: "stdio".to_string(),
-                            command: None,
-                            args: vec![],
-                            env: HashMap::new(),
-                            url: None,
-                            scope: "local".to_string(),
-                            is_active: false,
-                            status: ServerStatus {
-                                running: false,
-                                error: Some(format!("Failed to get details: {}", e)),
-                                last_checked: None,
-                            },
-                        });
-                    }
-                }
-            }
+/// Reads a child's pipe to the end on a separate thread
+fn read_pipe_in_background<R: std::io::Read + Send + 'static>(
+    pipe: Option<R>,
+) -> std::thread::JoinHandle<Vec<u8>> {
+    std::thread::spawn(move || {
+        let mut buffer = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buffer);
+        }
+        buffer
+    })
+}
 
-            Ok(servers)
+/// Executes a claude mcp command without touching the list cache
+/// Transient failures are retried according to `McpRetryPolicy::from_env`
+fn run_claude_mcp_command(
+    app_handle: &AppHandle,
+    args: Vec<&str>,
+    cwd: Option<&Path>,
+) -> std::result::Result<String, McpError> {
+    match cwd {
+        Some(cwd) => info!("Executing claude mcp command with args: {:?} in {:?}", args, cwd),
+        None => info!("Executing claude mcp command with args: {:?}", args),
+    }
+
+    let claude_path = find_claude_binary(app_handle).map_err(McpError::binary_not_found)?;
+    retry_transient(McpRetryPolicy::from_env(), || {
+        run_mcp_cli_once(&claude_path, &args, cwd)
+    })
+}
+
+/// How `claude mcp` invocations are retried after transient failures
+///
+/// Overridable with `CODESTUDIO_MCP_RETRY_ATTEMPTS` and `CODESTUDIO_MCP_RETRY_BACKOFF_MS`.
+#[derive(Debug, Clone, Copy)]
+pub struct McpRetryPolicy {
+    /// Total tries, including the first
+    pub max_attempts: u32,
+    /// Delay before the second try; doubled before each later one
+    pub initial_backoff: Duration,
+}
+
+impl Default for McpRetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(250),
         }
-        Err(e) => {
-            error!("Failed to list MCP servers: {}", e);
-            Err(e.to_string())
+    }
+}
+
+impl McpRetryPolicy {
+    pub fn from_env() -> Self {
+        let read = |key: &str| std::env::var(key).ok().and_then(|v| v.trim().parse::<u64>().ok());
+        let default = Self::default();
+        Self {
+            max_attempts: read("CODESTUDIO_MCP_RETRY_ATTEMPTS")
+                .map(|n| n.clamp(1, 10) as u32)
+                .unwrap_or(default.max_attempts),
+            initial_backoff: read("CODESTUDIO_MCP_RETRY_BACKOFF_MS")
+                .map(Duration::from_millis)
+                .unwrap_or(default.initial_backoff),
         }
     }
 }
 
-/// Gets details for a specific MCP server
-#[tauri::command]
-pub async fn mcp_get(app: AppHandle, name: String) -> Result<MCPServer, String> {
-    info!("Getting MCP server details for: {}", name);
+/// A failed `claude mcp` attempt, classified for `retry_transient`
+#[derive(Debug)]
+enum AttemptFailure {
+    /// The CLI couldn't start, or exited non-zero without printing anything
+    Transient(McpError),
+    /// A deterministic CLI error (e.g. "already exists"); retrying won't help
+    Fatal(McpError),
+}
 
-    match execute_claude_mcp_command(&app, vec!["get", &name]) {
-        Ok(output) => {
-            // Parse the structured text output
-            let mut scope = "local".to_string();
-            let mut transport = "stdio".to_string();
-            let mut command = None;
-            let mut args = vec![];
-            let env = HashMap::new();
-            let mut url = None;
-            let mut is_connected = false;
-            let mut status_error: Option<String> = None;
-
-            for line in output.lines() {
-                let line = line.trim();
-
-                if line.starts_with("Scope:") {
-                    let scope_part = line.replace("Scope:", "").trim().to_string();
-                    if scope_part.to_lowercase().contains("local") {
-                        scope = "local".to_string();
-                    } else if scope_part.to_lowercase().contains("project") {
-                        scope = "project".to_string();
-                    } else if scope_part.to_lowercase().contains("user")
-                        || scope_part.to_lowercase().contains("global")
-                    {
-                        scope = "user".to_string();
-                    }
-                } else if line.starts_with("Status:") {
-                    let status_part = line.replace("Status:", "").trim().to_string();
-                    if status_part.contains("✓") || status_part.to_lowercase().contains("connected") {
-                        is_connected = true;
-                    } else if status_part.contains("✗") || status_part.to_lowercase().contains("failed") {
-                        is_connected = false;
-                        status_error = Some(status_part);
-                    }
-                } else if line.starts_with("Type:") {
-                    transport = line.replace("Type:", "").trim().to_string();
-                } else if line.starts_with("Command:") {
-                    command = Some(line.replace("Command:", "").trim().to_string());
-                } else if line.starts_with("Args:") {
-                    let args_str = line.replace("Args:", "").trim().to_string();
-                    if !args_str.is_empty() {
-                        args = args_str.split_whitespace().map(|s| s.to_string()).collect();
-                    }
-                } else if line.starts_with("URL:") {
-                    url = Some(line.replace("URL:", "").trim().to_string());
-                } else if line.starts_with("Environment:") {
-                    // TODO: Parse environment variables if they're listed
-                    // For now, we'll leave it empty
-                }
+/// Runs `attempt` until it succeeds, fails fatally or runs out of tries,
+/// sleeping with exponential backoff between transient failures
+fn retry_transient<T>(
+    policy: McpRetryPolicy,
+    mut attempt: impl FnMut() -> std::result::Result<T, AttemptFailure>,
+) -> std::result::Result<T, McpError> {
+    let mut backoff = policy.initial_backoff;
+    let mut tries = 1;
+    loop {
+        match attempt() {
+            Ok(value) => return Ok(value),
+            Err(AttemptFailure::Fatal(e)) => return Err(e),
+            Err(AttemptFailure::Transient(e)) if tries >= policy.max_attempts => return Err(e),
+            Err(AttemptFailure::Transient(e)) => {
+                warn!(
+                    "claude mcp attempt {} of {} failed transiently ({}), retrying in {:?}",
+                    tries, policy.max_attempts, e, backoff
+                );
+                std::thread::sleep(backoff);
+                backoff = backoff.saturating_mul(2);
+                tries += 1;
             }
+        }
+    }
+}
 
-            Ok(MCPServer {
-                name,
-                transport,
-                command,
-                args,
-                env,
-                url,
-                scope,
-                is_active: is_connected,
-                status: ServerStatus {
-                    running: is_connected,
-                    error: status_error,
-                    last_checked: Some(std::time::SystemTime::now()
-                        .duration_since(std::time::UNIX_EPOCH)
-                        .unwrap_or_default()
-                        .as_secs()),
-                },
-            })
+/// Runs `claude mcp <args>` once, from `cwd` when given
+/// The CLI is killed if it runs longer than `MCP_COMMAND_TIMEOUT`
+fn run_mcp_cli_once(
+    claude_path: &str,
+    args: &[&str],
+    cwd: Option<&Path>,
+) -> std::result::Result<String, AttemptFailure> {
+    let mut cmd = create_command_with_env(claude_path);
+    cmd.arg("mcp");
+    for arg in args {
+        cmd.arg(arg);
+    }
+    if let Some(cwd) = cwd {
+        cmd.current_dir(cwd);
+    }
+
+    let mut child = cmd
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| {
+            AttemptFailure::Transient(McpError::command_failed(format!(
+                "Failed to execute claude command: {}",
+                e
+            )))
+        })?;
+
+    // Drain both pipes while waiting so a chatty CLI can't block on a full pipe
+    let stdout = read_pipe_in_background(child.stdout.take());
+    let stderr = read_pipe_in_background(child.stderr.take());
+
+    let deadline = Instant::now() + MCP_COMMAND_TIMEOUT;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() >= deadline => {
+                warn!("claude mcp command timed out, killing it");
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(AttemptFailure::Fatal(McpError::timeout(MCP_COMMAND_TIMEOUT)));
+            }
+            Ok(None) => std::thread::sleep(Duration::from_millis(50)),
+            Err(e) => return Err(AttemptFailure::Fatal(McpError::command_failed(e.to_string()))),
         }
-        Err(e) => {
-            error!("Failed to get MCP server: {}", e);
-            Err(e.to_string())
+    };
+
+    let stdout = crate::claude_binary::decode_command_output(&stdout.join().unwrap_or_default());
+    let stderr = crate::claude_binary::decode_command_output(&stderr.join().unwrap_or_default());
+
+    if status.success() {
+        Ok(stdout)
+    } else if stdout.trim().is_empty() && stderr.trim().is_empty() {
+        Err(AttemptFailure::Transient(McpError::command_failed(format!(
+            "claude exited with {} and no output",
+            status
+        ))))
+    } else {
+        Err(AttemptFailure::Fatal(McpError::command_failed(stderr)))
+    }
+}
+
+/// Finds a stdio server command on the PATH it will be launched with
+/// A `PATH` in the server's own env wins over the one the claude CLI inherits
+fn resolve_stdio_command(
+    command: &str,
+    env: &HashMap<String, String>,
+    default_path: &str,
+) -> Option<PathBuf> {
+    let search_path = env.get("PATH").map(String::as_str).unwrap_or(default_path);
+    let cwd = std::env::current_dir().ok()?;
+    which::which_in(command, Some(search_path), cwd).ok()
+}
+
+/// Returns an error message when a stdio server's command can't be found
+/// Skipped for remote transports, missing commands (reported elsewhere) and when `force` is set
+fn check_stdio_command(
+    transport: &str,
+    command: Option<&str>,
+    env: &HashMap<String, String>,
+    default_path: &str,
+    force: bool,
+) -> Option<String> {
+    if force || transport != "stdio" {
+        return None;
+    }
+    let command = command.map(str::trim).filter(|c| !c.is_empty())?;
+
+    match resolve_stdio_command(command, env, default_path) {
+        Some(path) => {
+            info!("Resolved stdio command '{}' to {:?}", command, path);
+            None
         }
+        None => Some(format!("Command '{}' not found on PATH", command)),
     }
 }
 
-/// Removes an MCP server
-#[tauri::command]
-pub async fn mcp_remove(app: AppHandle, name: String) -> Result<String, String> {
-    info!("Removing MCP server: {}", name);
+/// Validates an SSE/HTTP server URL and returns it in normalized form
+/// Surrounding whitespace and a single trailing slash are removed
+fn normalize_server_url(raw: &str) -> Result<String, String> {
+    let trimmed = raw.trim();
+    if trimmed.is_empty() {
+        return Err("URL must not be empty".to_string());
+    }
 
-    match execute_claude_mcp_command(&app, vec!["remove", &name]) {
-        Ok(output) => {
-            info!("Successfully removed MCP server: {}", name);
-            Ok(output.trim().to_string())
+    let parsed = match url::Url::parse(trimmed) {
+        Ok(parsed) => parsed,
+        Err(url::ParseError::RelativeUrlWithoutBase) => {
+            return Err(format!(
+                "URL '{}' is missing a scheme (expected http:// or https://)",
+                trimmed
+            ))
         }
-        Err(e) => {
-            error!("Failed to remove MCP server: {}", e);
-            Err(e.to_string())
+        Err(e) => return Err(format!("Invalid URL '{}': {}", trimmed, e)),
+    };
+
+    if parsed.scheme() != "http" && parsed.scheme() != "https" {
+        return Err(format!(
+            "Unsupported URL scheme '{}' in '{}' (expected http or https)",
+            parsed.scheme(),
+            trimmed
+        ));
+    }
+    if parsed.host_str().unwrap_or_default().is_empty() {
+        return Err(format!("URL '{}' has no host", trimmed));
+    }
+
+    let normalized = parsed.to_string();
+    match normalized.strip_suffix('/') {
+        Some(stripped) if parsed.query().is_none() && parsed.fragment().is_none() => {
+            Ok(stripped.to_string())
         }
+        _ => Ok(normalized),
     }
 }
 
-/// Adds an MCP server from JSON configuration
+/// Builds the argument list for `claude mcp add`
+fn build_mcp_add_args(
+    name: &str,
+    transport: &str,
+    command: Option<&str>,
+    args: &[String],
+    env: &HashMap<String, String>,
+    url: Option<&str>,
+    headers: &HashMap<String, String>,
+    scope: &str,
+) -> Result<Vec<String>, String> {
+    let mut cmd_args = vec!["add".to_string()];
+
+    // Add scope flag
+    cmd_args.push("-s".to_string());
+    cmd_args.push(scope.to_string());
+
+    // Add transport flag for SSE / streamable HTTP
+    let is_remote = transport == "sse" || transport == "http";
+    if is_remote {
+        cmd_args.push("--transport".to_string());
+        cmd_args.push(transport.to_string());
+    }
+
+    // Add environment variables (sorted so the invocation is deterministic)
+    let mut env_entries: Vec<_> = env.iter().collect();
+    env_entries.sort();
+    for (key, value) in env_entries {
+        cmd_args.push("-e".to_string());
+        cmd_args.push(format!("{}={}", key, value));
+    }
+
+    // Add headers; each "Key: Value" pair is a single argument
+    if is_remote {
+        let mut header_entries: Vec<_> = headers.iter().collect();
+        header_entries.sort();
+        for (key, value) in header_entries {
+            cmd_args.push("--header".to_string());
+            cmd_args.push(format!("{}: {}", key, value));
+        }
+    }
+
+    // Add name
+    cmd_args.push(name.to_string());
+
+    // Add command/URL based on transport
+    if transport == "stdio" {
+        let cmd = command.ok_or_else(|| "Command is required for stdio transport".to_string())?;
+        // Add "--" separator before command to prevent argument parsing issues
+        if !args.is_empty() || cmd.contains('-') {
+            cmd_args.push("--".to_string());
+        }
+        cmd_args.push(cmd.to_string());
+        // Add arguments
+        cmd_args.extend(args.iter().cloned());
+    } else if is_remote {
+        let url_str = url.ok_or_else(|| {
+            format!("URL is required for {} transport", transport.to_uppercase())
+        })?;
+        cmd_args.push(normalize_server_url(url_str)?);
+    }
+
+    Ok(cmd_args)
+}
+
+/// Whether the config file behind `scope` already defines `name`
+///
+/// User and local servers live in `<home>/.claude.json` (local ones under the project's entry),
+/// project servers in `<project_dir>/.mcp.json`. A missing or unreadable file counts as no
+/// match; the CLI's own "already exists" error still catches anything this misses.
+fn scope_config_has_server(name: &str, scope: &str, home: &Path, project_dir: &Path) -> bool {
+    let read_json = |path: PathBuf| -> Option<serde_json::Value> {
+        serde_json::from_str(&fs::read_to_string(path).ok()?).ok()
+    };
+    let has_server = |servers: Option<&serde_json::Value>| {
+        servers
+            .and_then(|servers| servers.get("mcpServers"))
+            .and_then(|servers| servers.get(name))
+            .is_some()
+    };
+
+    match scope {
+        "user" => has_server(read_json(home.join(".claude.json")).as_ref()),
+        "local" => {
+            let project_dir = fs::canonicalize(project_dir).unwrap_or(project_dir.to_path_buf());
+            let config = read_json(home.join(".claude.json"));
+            let project = config
+                .as_ref()
+                .and_then(|config| config.get("projects"))
+                .and_then(|projects| projects.get(project_dir.to_string_lossy().as_ref()));
+            has_server(project)
+        }
+        "project" => has_server(read_json(project_dir.join(".mcp.json")).as_ref()),
+        _ => false,
+    }
+}
+
+/// Adds a new MCP server
+/// Stdio servers whose command can't be found are rejected unless `force` is set
+/// `cwd` runs the CLI from another directory, e.g. a monorepo package with its own `.mcp.json`
 #[tauri::command]
-pub async fn mcp_add_json(
+pub async fn mcp_add(
     app: AppHandle,
     name: String,
-    json_config: String,
+    transport: String,
+    command: Option<String>,
+    args: Vec<String>,
+    env: HashMap<String, String>,
+    url: Option<String>,
+    headers: Option<HashMap<String, String>>,
     scope: String,
-) -> Result<AddServerResult, String> {
-    info!(
-        "Adding MCP server from JSON: {} with scope: {}",
-        name, scope
-    );
+    force: Option<bool>,
+    cwd: Option<String>,
+) -> Result<AddServerResult, McpError> {
+    info!("Adding MCP server: {} with transport: {}", name, transport);
+    let cwd = resolve_mcp_cwd(cwd.as_deref())?;
 
-    // Build command args
-    let mut cmd_args = vec!["add-json", &name, &json_config];
+    // The claude CLI launches the server, so resolve against the PATH it will run with
+    let claude_path = find_claude_binary(&app).unwrap_or_else(|_| "claude".to_string());
+    let default_path = crate::claude_binary::command_search_path(&claude_path);
+    if let Some(message) = check_stdio_command(
+        &transport,
+        command.as_deref(),
+        &env,
+        &default_path,
+        force.unwrap_or(false),
+    ) {
+        warn!("Rejecting MCP server '{}': {}", name, message);
+        return Ok(AddServerResult {
+            success: false,
+            message,
+            server_name: None,
+        });
+    }
 
-    // Add scope flag
-    let scope_flag = "-s";
-    cmd_args.push(scope_flag);
-    cmd_args.push(&scope);
+    // Reject duplicates up front instead of letting the CLI fail opaquely or overwrite.
+    // Only the target scope's config file is read, so this never spawns the CLI.
+    let project_dir = cwd.clone().or_else(|| std::env::current_dir().ok());
+    if let (Some(home), Some(project_dir)) = (dirs::home_dir(), project_dir) {
+        if scope_config_has_server(&name, &scope, &home, &project_dir) {
+            return Err(McpError::duplicate_name(&name, &scope));
+        }
+    }
 
-    match execute_claude_mcp_command(&app, cmd_args) {
+    let cmd_args = match build_mcp_add_args(
+        &name,
+        &transport,
+        command.as_deref(),
+        &args,
+        &env,
+        url.as_deref(),
+        &headers.unwrap_or_default(),
+        &scope,
+    ) {
+        Ok(cmd_args) => cmd_args,
+        Err(message) => {
+            return Ok(AddServerResult {
+                success: false,
+                message,
+                server_name: None,
+            });
+        }
+    };
+
+    match execute_claude_mcp_command_in(
+        &app,
+        cmd_args.iter().map(String::as_str).collect(),
+        cwd.as_deref(),
+    ) {
         Ok(output) => {
-            info!("Successfully added MCP server from JSON: {}", name);
+            info!("Successfully added MCP server: {}", name);
             Ok(AddServerResult {
                 success: true,
                 message: output.trim().to_string(),
@@ -521,342 +949,4798 @@ pub async fn mcp_add_json(
             })
         }
         Err(e) => {
-            error!("Failed to add MCP server from JSON: {}", e);
-            Ok(AddServerResult {
-                success: false,
-                message: e.to_string(),
-                server_name: None,
-            })
+            error!("Failed to add MCP server: {}", e);
+            Err(e.or_duplicate(&name, &scope))
         }
     }
 }
 
-/// Imports MCP servers from Claude Desktop
-#[tauri::command]
-pub async fn mcp_add_from_claude_desktop(
-    app: AppHandle,
-    scope: String,
-) -> Result<ImportResult, String> {
-    info!(
-        "Importing MCP servers from Claude Desktop with scope: {}",
-        scope
-    );
-
-    // Get Claude Desktop config path based on platform
-    let config_path = if cfg!(target_os = "macos") {
-        dirs::home_dir()
-            .ok_or_else(|| "Could not find home directory".to_string())?
-            .join("Library")
-            .join("Application Support")
-            .join("Claude")
-            .join("claude_desktop_config.json")
-    } else if cfg!(target_os = "linux") {
-        // For WSL/Linux, check common locations
-        dirs::config_dir()
-            .ok_or_else(|| "Could not find config directory".to_string())?
-            .join("Claude")
-            .join("claude_desktop_config.json")
-    } else {
-        return Err(
-            "Import from Claude Desktop is only supported on macOS and Linux/WSL".to_string(),
-        );
-    };
-
-    // Check if config file exists
-    if !config_path.exists() {
-        return Err(
-            "Claude Desktop configuration not found. Make sure Claude Desktop is installed."
-                .to_string(),
-        );
+/// Extracts server names from the human-readable `claude mcp list` output
+/// Returns each server name with its scope when the line spells it out
+fn parse_mcp_list_text(output: &str) -> Vec<(String, Option<String>)> {
+    let mut server_names = Vec::new();
+    let lines: Vec<&str> = output.lines().collect();
+    info!("Total lines in output: {}", lines.len());
+    for (idx, line) in lines.iter().enumerate() {
+        info!("Line {}: {:?}", idx, line);
     }
 
-    // Read and parse the config file
-    let config_content = fs::read_to_string(&config_path)
-        .map_err(|e| format!("Failed to read Claude Desktop config: {}", e))?;
+    let mut i = 0;
 
-    let config: serde_json::Value = serde_json::from_str(&config_content)
-        .map_err(|e| format!("Failed to parse Claude Desktop config: {}", e))?;
-
-    // Extract MCP servers
-    let mcp_servers = config
-        .get("mcpServers")
-        .and_then(|v| v.as_object())
-        .ok_or_else(|| "No MCP servers found in Claude Desktop config".to_string())?;
+    while i < lines.len() {
+        let line = lines[i];
+        info!("Processing line {}: {:?}", i, line);
 
-    let mut imported_count = 0;
-    let mut failed_count = 0;
-    let mut server_results = Vec::new();
+        // Check if this line starts a new server entry
+        if let Some(colon_pos) = line.find(':') {
+            info!("Found colon at position {} in line: {:?}", colon_pos, line);
+            // Make sure this is a server name line (not part of a path)
+            // Server names typically don't contain '/' or '\'
+            let potential_name = clean_command_string(&line[..colon_pos]);
+            info!("Potential server name: {:?}", potential_name);
 
-    // Import each server using add-json
-    for (name, server_config) in mcp_servers {
-        info!("Importing server: {}", name);
+            if !potential_name.contains('/') && !potential_name.contains('\\') {
+                info!("Valid server name detected: {:?}", potential_name);
+                if server_names.iter().any(|(name, _)| *name == potential_name) {
+                    info!("Skipping duplicate server name: {:?}", potential_name);
+                } else {
+                    let scope = scope_hint_from_list_line(&line[colon_pos + 1..]);
+                    info!("Added server name to list: {:?}", potential_name);
+                    server_names.push((potential_name, scope));
+                }
 
-        // Convert Claude Desktop format to add-json format
-        let mut json_config = serde_json::Map::new();
+                // Skip to next server (skip continuation lines)
+                i += 1;
+                while i < lines.len() {
+                    let next_line = lines[i];
+                    info!("Checking next line {} for continuation: {:?}", i, next_line);
 
-        // All Claude Desktop servers are stdio type
-        json_config.insert(
-            "type".to_string(),
-            serde_json::Value::String("stdio".to_string()),
-        );
+                    // If the next line starts with a server name pattern, break
+                    if next_line.contains(':') {
+                        let potential_next_name = next_line.split(':').next().unwrap_or("").trim();
+                        info!(
+                            "Found colon in next line, potential name: {:?}",
+                            potential_next_name
+                        );
+                        if !potential_next_name.is_empty()
+                            && !potential_next_name.contains('/')
+                            && !potential_next_name.contains('\\')
+                        {
+                            info!("Next line is a new server, breaking");
+                            break;
+                        }
+                    }
+                    // Otherwise, this line is a continuation - skip it
+                    info!("Line {} is a continuation, skipping", i);
+                    i += 1;
+                }
 
-        // Add command
-        if let Some(command) = server_config.get("command").and_then(|v| v.as_str()) {
-            json_config.insert(
-                "command".to_string(),
-                serde_json::Value::String(command.to_string()),
-            );
+                continue;
+            } else {
+                info!("Skipping line - name contains path separators");
+            }
         } else {
-            failed_count += 1;
-            server_results.push(ImportServerResult {
-                name: name.clone(),
-                success: false,
-                error: Some("Missing command field".to_string()),
-            });
-            continue;
+            info!("No colon found in line {}", i);
         }
 
-        // Add args if present
-        if let Some(args) = server_config.get("args").and_then(|v| v.as_array()) {
-            json_config.insert("args".to_string(), args.clone().into());
-        } else {
-            json_config.insert("args".to_string(), serde_json::Value::Array(vec![]));
-        }
+        i += 1;
+    }
 
-        // Add env if present
-        if let Some(env) = server_config.get("env").and_then(|v| v.as_object()) {
-            json_config.insert("env".to_string(), env.clone().into());
-        } else {
-            json_config.insert(
-                "env".to_string(),
-                serde_json::Value::Object(serde_json::Map::new()),
-            );
-        }
+    server_names
+}
 
-        // Convert to JSON string
-        let json_str = serde_json::to_string(&json_config)
-            .map_err(|e| format!("Failed to serialize config for {}: {}", name, e))?;
+/// Extracts a scope from the text after a server name in `claude mcp list` output
+/// Only explicit `scope: x` markers or parenthesized/bracketed groups are trusted, so
+/// commands and URLs that merely mention "local" or "user" are not misread
+fn scope_hint_from_list_line(rest: &str) -> Option<String> {
+    let lower = rest.to_lowercase();
+    if let Some(pos) = lower.find("scope:") {
+        let value = lower[pos + "scope:".len()..]
+            .split(|c: char| c.is_whitespace() || c == ',' || c == ')' || c == ']')
+            .find(|word| !word.is_empty())?;
+        return normalize_scope(value);
+    }
 
-        // Call add-json command
-        match mcp_add_json(app.clone(), name.clone(), json_str, scope.clone()).await {
-            Ok(result) => {
-                if result.success {
-                    imported_count += 1;
-                    server_results.push(ImportServerResult {
-                        name: name.clone(),
-                        success: true,
-                        error: None,
-                    });
-                    info!("Successfully imported server: {}", name);
-                } else {
-                    failed_count += 1;
-                    let error_msg = result.message.clone();
-                    server_results.push(ImportServerResult {
-                        name: name.clone(),
-                        success: false,
-                        error: Some(result.message),
-                    });
-                    error!("Failed to import server {}: {}", name, error_msg);
-                }
-            }
-            Err(e) => {
-                failed_count += 1;
-                let error_msg = e.clone();
-                server_results.push(ImportServerResult {
-                    name: name.clone(),
-                    success: false,
-                    error: Some(e),
-                });
-                error!("Error importing server {}: {}", name, error_msg);
-            }
-        }
+    lower
+        .match_indices(['(', '['])
+        .filter_map(|(start, open)| {
+            let close = if open == "(" { ')' } else { ']' };
+            let inner = &lower[start + 1..];
+            inner.find(close).map(|end| inner[..end].trim())
+        })
+        .find_map(|group| match group {
+            "local" | "project" | "user" | "global" => normalize_scope(group),
+            _ if group.ends_with(" scope") || group.ends_with(" config") => normalize_scope(group),
+            _ => None,
+        })
+}
+
+/// Validates a `scope_filter` argument, accepting only "local", "project" or "user"
+fn parse_scope_filter(scope_filter: Option<&str>) -> Result<Option<String>, String> {
+    match scope_filter.map(|s| s.trim().to_lowercase()) {
+        None => Ok(None),
+        Some(scope) if matches!(scope.as_str(), "local" | "project" | "user") => Ok(Some(scope)),
+        Some(scope) => Err(format!(
+            "Invalid scope filter '{}': expected local, project or user",
+            scope
+        )),
     }
+}
 
-    info!(
-        "Import complete: {} imported, {} failed",
-        imported_count, failed_count
-    );
+/// Keeps only the servers in `scope`, or all of them when no scope is given
+fn filter_servers_by_scope(servers: Vec<MCPServer>, scope: Option<&str>) -> Vec<MCPServer> {
+    match scope {
+        Some(scope) => servers
+            .into_iter()
+            .filter(|server| server.scope == scope)
+            .collect(),
+        None => servers,
+    }
+}
 
-    Ok(ImportResult {
-        imported_count,
-        failed_count,
-        servers: server_results,
-    })
+/// Reads a JSON array of strings, ignoring non-string items
+fn json_string_array(value: Option<&serde_json::Value>) -> Vec<String> {
+    value
+        .and_then(|v| v.as_array())
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|item| item.as_str().map(|s| s.to_string()))
+                .collect()
+        })
+        .unwrap_or_default()
 }
 
-/// Starts Claude Code as an MCP server
-#[tauri::command]
-pub async fn mcp_serve(app: AppHandle) -> Result<String, String> {
-    info!("Starting Claude Code as MCP server");
+/// Reads a JSON object of string values into a map
+fn json_string_map(value: Option<&serde_json::Value>) -> HashMap<String, String> {
+    value
+        .and_then(|v| v.as_object())
+        .map(|map| {
+            map.iter()
+                .filter_map(|(k, v)| v.as_str().map(|s| (k.clone(), s.to_string())))
+                .collect()
+        })
+        .unwrap_or_default()
+}
 
-    // Start the server in a separate process
-    let claude_path = match find_claude_binary(&app) {
-        Ok(path) => path,
-        Err(e) => {
-            error!("Failed to find claude binary: {}", e);
-            return Err(e.to_string());
-        }
-    };
+/// Builds an `MCPServer` from one entry of `claude mcp list --json`
+fn server_from_json(name: String, entry: &serde_json::Value) -> MCPServer {
+    let get_str = |key: &str| entry.get(key).and_then(|v| v.as_str()).map(|s| s.to_string());
 
-    let mut cmd = create_command_with_env(&claude_path);
-    cmd.arg("mcp").arg("serve");
+    let url = get_str("url");
+    let transport = get_str("type")
+        .or_else(|| get_str("transport"))
+        .map(|t| normalize_transport(&t))
+        .unwrap_or_else(|| if url.is_some() { "sse" } else { "stdio" }.to_string());
+    let scope = get_str("scope")
+        .and_then(|s| normalize_scope(&s))
+        .unwrap_or_else(|| "local".to_string());
 
-    match cmd.spawn() {
-        Ok(_) => {
-            info!("Successfully started Claude Code MCP server");
-            Ok("Claude Code MCP server started".to_string())
-        }
-        Err(e) => {
-            error!("Failed to start MCP server: {}", e);
-            Err(e.to_string())
-        }
+    let status_text = get_str("status").unwrap_or_default();
+    let is_connected = entry
+        .get("connected")
+        .and_then(|v| v.as_bool())
+        .unwrap_or_else(|| is_connected_status(&status_text));
+    let status_error = if !is_connected && !status_text.is_empty() {
+        Some(status_text)
+    } else {
+        None
+    };
+
+    MCPServer {
+        name,
+        transport,
+        command: get_str("command"),
+        args: json_string_array(entry.get("args")),
+        env: json_string_map(entry.get("env")),
+        url,
+        headers: json_string_map(entry.get("headers")),
+        scope,
+        is_active: is_connected,
+        status: ServerStatus {
+            running: is_connected,
+            error: status_error,
+            last_checked: Some(
+                std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs(),
+            ),
+        },
     }
 }
 
-/// Tests connection to an MCP server
-#[tauri::command]
-pub async fn mcp_test_connection(app: AppHandle, name: String) -> Result<String, String> {
-    info!("Testing connection to MCP server: {}", name);
+/// Parses `claude mcp list --json` output
+/// Accepts either a name-keyed object (optionally wrapped in "mcpServers") or an array of named entries
+fn parse_mcp_list_json(output: &str) -> Result<Vec<MCPServer>, String> {
+    let value: serde_json::Value = serde_json::from_str(output.trim())
+        .map_err(|e| format!("Failed to parse JSON server list: {}", e))?;
+    let value = match value.get("mcpServers") {
+        Some(servers) => servers.clone(),
+        None => value,
+    };
 
-    // For now, we'll use the get command to test if the server exists
-    match execute_claude_mcp_command(&app, vec!["get", &name]) {
-        Ok(_) => Ok(format!("Connection to {} successful", name)),
-        Err(e) => Err(e.to_string()),
+    match value {
+        serde_json::Value::Object(map) => Ok(map
+            .into_iter()
+            .map(|(name, entry)| server_from_json(name, &entry))
+            .collect()),
+        serde_json::Value::Array(items) => Ok(items
+            .iter()
+            .filter_map(|entry| {
+                let name = entry.get("name")?.as_str()?.to_string();
+                Some(server_from_json(name, entry))
+            })
+            .collect()),
+        _ => Err("Unexpected JSON shape for server list".to_string()),
     }
 }
 
-/// Resets project-scoped server approval choices
-#[tauri::command]
-pub async fn mcp_reset_project_choices(app: AppHandle) -> Result<String, String> {
-    info!("Resetting MCP project choices");
-
-    match execute_claude_mcp_command(&app, vec!["reset-project-choices"]) {
-        Ok(output) => {
-            info!("Successfully reset MCP project choices");
-            Ok(output.trim().to_string())
-        }
-        Err(e) => {
-            error!("Failed to reset project choices: {}", e);
-            Err(e.to_string())
-        }
+/// Whether a CLI status such as "✓ Connected" means the server is connected
+/// The text is matched whole, so "disconnected" or "not connected" don't count
+fn is_connected_status(status: &str) -> bool {
+    let status = status.trim();
+    if status.starts_with('✓') {
+        return true;
     }
+    status.eq_ignore_ascii_case("connected")
 }
 
-/// Gets the status of MCP servers
-#[tauri::command]
-pub async fn mcp_get_server_status() -> Result<HashMap<String, ServerStatus>, String> {
-    info!("Getting MCP server status");
+/// File under ~/.claude holding servers disabled via `mcp_toggle`
+fn disabled_servers_path() -> Result<PathBuf, String> {
+    dirs::home_dir()
+        .ok_or_else(|| "Could not find home directory".to_string())
+        .map(|home| home.join(".claude").join("codestudio_disabled_mcp_servers.json"))
+}
 
-    // TODO: Implement actual status checking
-    // For now, return empty status
-    Ok(HashMap::new())
+/// A server snapshot in the disabled servers sidecar
+/// Entries are keyed by scope, project and name, so same-named servers never collide
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct DisabledServer {
+    #[serde(flatten)]
+    server: MCPServer,
+    /// Directory the CLI ran from when a local or project server was disabled; it is re-added
+    /// there. None for user scope and for entries written before projects were recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    project_path: Option<String>,
 }
 
-/// Gets the MCP configuration file paths
+impl DisabledServer {
+    /// Whether this entry belongs to `project`, the directory the CLI runs from
+    fn applies_to(&self, project: Option<&str>) -> bool {
+        self.server.scope == "user"
+            || self.project_path.is_none()
+            || self.project_path.as_deref() == project
+    }
+}
+
+/// The project a server of `scope` is tied to when the CLI runs from `cwd`
+/// User scope is global; other scopes belong to `cwd`, or the app's own directory without one
+fn disabled_server_project(scope: &str, cwd: Option<&Path>) -> Option<String> {
+    if scope == "user" {
+        return None;
+    }
+    let dir = match cwd {
+        Some(cwd) => cwd.to_path_buf(),
+        None => std::env::current_dir().ok()?,
+    };
+    let dir = fs::canonicalize(&dir).unwrap_or(dir);
+    Some(dir.to_string_lossy().to_string())
+}
+
+/// Finds the disabled entry for `name` visible from `project`, optionally limited to `scope`
+///
+/// Errors when the name is disabled in several scopes and no scope was given.
+fn find_disabled_server(
+    disabled: &[DisabledServer],
+    name: &str,
+    scope: Option<&str>,
+    project: Option<&str>,
+) -> std::result::Result<Option<usize>, String> {
+    let matches: Vec<usize> = disabled
+        .iter()
+        .enumerate()
+        .filter(|(_, entry)| {
+            entry.server.name == name
+                && scope.map_or(true, |scope| entry.server.scope == scope)
+                && entry.applies_to(project)
+        })
+        .map(|(index, _)| index)
+        .collect();
+    match matches.as_slice() {
+        [] => Ok(None),
+        [index] => Ok(Some(*index)),
+        _ => Err(format!(
+            "Server '{}' is disabled in more than one scope; specify which scope to use",
+            name
+        )),
+    }
+}
+
+/// Loads the disabled servers sidecar, treating a missing or unreadable file as empty
+fn load_disabled_servers(path: &Path) -> Vec<DisabledServer> {
+    match fs::read_to_string(path) {
+        Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+            warn!("Ignoring malformed disabled servers file {:?}: {}", path, e);
+            Vec::new()
+        }),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Persists the disabled servers sidecar
+fn save_disabled_servers(path: &Path, servers: &[DisabledServer]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+
+    let json_content = serde_json::to_string_pretty(servers)
+        .map_err(|e| format!("Failed to serialize disabled servers: {}", e))?;
+    write_atomic(path, json_content)
+        .map_err(|e| format!("Failed to write disabled servers file: {}", e))
+}
+
+/// Lists all configured MCP servers, including ones disabled via `mcp_toggle`
+/// Results are cached for a short time unless `force_refresh` is set
+/// `scope_filter` ("local", "project" or "user") restricts the result to a single scope
 #[tauri::command]
-pub async fn mcp_get_config_paths(project_path: Option<String>) -> Result<MCPConfigPaths, String> {
-    info!("Getting MCP config paths");
+pub async fn mcp_list(
+    app: AppHandle,
+    force_refresh: Option<bool>,
+    scope_filter: Option<String>,
+) -> Result<Vec<MCPServer>, McpError> {
+    let scope_filter = parse_scope_filter(scope_filter.as_deref()).map_err(McpError::parse)?;
+    info!("Listing MCP servers (scope filter: {:?})", scope_filter);
 
-    // Get home directory for user config
-    let home_dir = dirs::home_dir()
-        .ok_or_else(|| "Could not find home directory".to_string())?;
+    let cache = app.try_state::<MCPListCache>();
+    list_servers_cached(
+        cache.as_deref(),
+        force_refresh.unwrap_or(false),
+        scope_filter.as_deref(),
+        || async {
+            let mut servers = list_servers_from_cli(&app, scope_filter.as_deref()).await?;
 
-    // User config: ~/.claude.json (global, available in all projects)
-    let user_path = home_dir.join(".claude.json");
+            let disabled = match disabled_servers_path() {
+                Ok(path) => load_disabled_servers(&path),
+                Err(_) => Vec::new(),
+            };
+            // The CLI lists the app's own directory, so only that project's snapshots belong here
+            let project = disabled_server_project("project", None);
+            let visible = disabled
+                .into_iter()
+                .filter(|entry| entry.applies_to(project.as_deref()));
+            for entry in visible {
+                let mut server = entry.server;
+                server.is_active = false;
+                server.status.running = false;
+                servers.push(server);
+            }
+            Ok(servers)
+        },
+    )
+    .await
+}
 
-    // Local config: <project>/.claude/settings.local.json
-    let local_path = if let Some(ref project) = project_path {
-        PathBuf::from(project).join(".claude").join("settings.local.json")
-    } else {
-        PathBuf::from(".claude").join("settings.local.json")
-    };
+/// Returns the servers from `cache` while it is fresh, otherwise from `load`
+///
+/// `force_refresh` skips the cache. A filtered listing may have skipped servers, so only a
+/// full one is stored.
+async fn list_servers_cached<F, Fut>(
+    cache: Option<&MCPListCache>,
+    force_refresh: bool,
+    scope_filter: Option<&str>,
+    load: F,
+) -> Result<Vec<MCPServer>, McpError>
+where
+    F: FnOnce() -> Fut,
+    Fut: std::future::Future<Output = Result<Vec<MCPServer>, McpError>>,
+{
+    if !force_refresh {
+        if let Some(servers) = cache.and_then(|cache| cache.get(MCP_LIST_CACHE_TTL)) {
+            info!("Using cached MCP server list ({} servers)", servers.len());
+            return Ok(filter_servers_by_scope(servers, scope_filter));
+        }
+    }
 
-    // Project config: <project>/.mcp.json
-    let project_config_path = if let Some(ref project) = project_path {
-        PathBuf::from(project).join(".mcp.json")
+    let servers = load().await?;
+    if scope_filter.is_none() {
+        if let Some(cache) = cache {
+            cache.set(servers.clone());
+        }
+    }
+
+    Ok(filter_servers_by_scope(servers, scope_filter))
+}
+
+/// Lists the servers currently registered with the claude CLI
+/// With a `scope_filter`, servers whose listed scope already rules them out are not
+/// looked up individually; the caller still filters the returned list
+async fn list_servers_from_cli(
+    app: &AppHandle,
+    scope_filter: Option<&str>,
+) -> Result<Vec<MCPServer>, McpError> {
+    let app = app.clone();
+
+    // Prefer structured output; `--json` is tried until the CLI rejects it, and that answer
+    // is remembered for the binary so older CLIs don't pay for a failing call on every list
+    let claude_path = find_claude_binary(&app).ok();
+    let json_support = claude_path
+        .as_deref()
+        .and_then(crate::claude_binary::mcp_list_json_support);
+    if json_support != Some(false) {
+        let record = |supported: bool| {
+            if let Some(path) = claude_path.as_deref() {
+                crate::claude_binary::record_mcp_list_json_support(path, supported);
+            }
+        };
+        match execute_claude_mcp_command(&app, vec!["list", "--json"]) {
+            Ok(output) => match parse_mcp_list_json(&output) {
+                Ok(servers) => {
+                    record(true);
+                    info!("Found {} MCP servers via JSON output", servers.len());
+                    return Ok(servers);
+                }
+                Err(e) => {
+                    record(false);
+                    warn!("Falling back to text parsing: {}", e);
+                }
+            },
+            // Only a CLI that ran and refused the flag says anything about support
+            Err(e @ McpError::CommandFailed { .. }) => {
+                record(false);
+                warn!("'claude mcp list --json' failed, falling back to text: {}", e);
+            }
+            Err(e) => warn!("'claude mcp list --json' failed, falling back to text: {}", e),
+        }
+    }
+
+    match execute_claude_mcp_command(&app, vec!["list"]) {
+        Ok(output) => {
+            info!("Raw output from 'claude mcp list': {:?}", output);
+            let trimmed = output.trim();
+            info!("Trimmed output: {:?}", trimmed);
+
+            // Check if no servers are configured
+            if trimmed.contains("No MCP servers configured") || trimmed.is_empty() {
+                info!("No servers found - empty or 'No MCP servers' message");
+                return Ok(vec![]);
+            }
+
+            // Parse the text output to get server names
+            let server_names = parse_mcp_list_text(trimmed);
+
+            info!("Found {} MCP servers total", server_names.len());
+            for (idx, (name, scope)) in server_names.iter().enumerate() {
+                info!("Server {}: name='{}', listed scope={:?}", idx, name, scope);
+            }
+
+            // Get detailed information for each server including correct scope
+            let mut servers = Vec::new();
+            for (name, listed_scope) in server_names {
+                if let (Some(filter), Some(scope)) = (scope_filter, listed_scope.as_deref()) {
+                    if filter != scope {
+                        info!("Skipping details for '{}' (scope {} excluded)", name, scope);
+                        continue;
+                    }
+                }
+
+                info!("Getting details for server: {:?}", name);
+                match mcp_get(app.clone(), name.clone()).await {
+                    Ok(server_details) => {
+                        info!("Successfully got details for server '{}': scope={}, transport={}",
+                              name, server_details.scope, server_details.transport);
+                        servers.push(server_details);
+                    }
+                    Err(e) => {
+                        error!("Failed to get details for server '{}': {}", name, e);
+                        // Add a basic server entry with the name if we can't get details
+                        servers.push(MCPServer {
+                            name: name.clone(),
+                            transport: "stdio".to_string(),
+                            command: None,
+                            args: vec![],
+                            env: HashMap::new(),
+                            url: None,
+                            headers: HashMap::new(),
+                            scope: "local".to_string(),
+                            is_active: false,
+                            status: ServerStatus {
+                                running: false,
+                                error: Some(format!("Failed to get details: {}", e)),
+                                last_checked: None,
+                            },
+                        });
+                    }
+                }
+            }
+
+            Ok(servers)
+        }
+        Err(e) => {
+            error!("Failed to list MCP servers: {}", e);
+            Err(e)
+        }
+    }
+}
+
+/// Maps the scope descriptions printed by the CLI onto "local", "project" or "user"
+fn normalize_scope(raw: &str) -> Option<String> {
+    let scope = raw.to_lowercase();
+    if scope.contains("local") {
+        Some("local".to_string())
+    } else if scope.contains("project") {
+        Some("project".to_string())
+    } else if scope.contains("user") || scope.contains("global") {
+        Some("user".to_string())
     } else {
-        PathBuf::from(".mcp.json")
-    };
+        None
+    }
+}
 
-    Ok(MCPConfigPaths {
-        local: local_path.to_string_lossy().to_string(),
-        project: project_config_path.to_string_lossy().to_string(),
-        user: user_path.to_string_lossy().to_string(),
-    })
+/// Maps the transport names printed by the CLI onto "stdio", "sse" or "http"
+fn normalize_transport(raw: &str) -> String {
+    let transport = raw.trim().to_lowercase();
+    match transport.as_str() {
+        "http" | "streamable-http" | "streamable_http" | "streamablehttp" => "http".to_string(),
+        _ => transport,
+    }
 }
 
-/// Reads .mcp.json from the current project
-#[tauri::command]
-pub async fn mcp_read_project_config(project_path: String) -> Result<MCPProjectConfig, String> {
-    info!("Reading .mcp.json from project: {}", project_path);
+/// Parses a single `KEY=VALUE` environment entry, splitting only on the first `=`
+fn parse_env_entry(entry: &str) -> Option<(String, String)> {
+    let (key, value) = entry.split_once('=')?;
+    let key = key.trim();
 
-    let mcp_json_path = PathBuf::from(&project_path).join(".mcp.json");
+    // Reject things that are clearly not variable names (e.g. "URL: http://host?a=b")
+    if key.is_empty() || key.contains(char::is_whitespace) || key.contains(':') {
+        return None;
+    }
 
-    if !mcp_json_path.exists() {
-        return Ok(MCPProjectConfig {
-            mcp_servers: HashMap::new(),
-        });
+    Some((key.to_string(), value.trim().to_string()))
+}
+
+/// Parses a "Name: value" header line, splitting on the first colon only
+fn parse_header_entry(entry: &str) -> Option<(String, String)> {
+    let (key, value) = entry.split_once(':')?;
+    let key = key.trim();
+
+    if key.is_empty() || key.contains(char::is_whitespace) {
+        return None;
     }
 
-    match fs::read_to_string(&mcp_json_path) {
-        Ok(content) => match serde_json::from_str::<MCPProjectConfig>(&content) {
-            Ok(config) => Ok(config),
-            Err(e) => {
-                error!("Failed to parse .mcp.json: {}", e);
-                Err(format!("Failed to parse .mcp.json: {}", e))
+    Some((key.to_string(), value.trim().to_string()))
+}
+
+/// Field labels `claude mcp get` prints for a server
+const MCP_GET_LABELS: &[&str] = &[
+    "Scope:",
+    "Status:",
+    "Type:",
+    "Command:",
+    "Args:",
+    "URL:",
+    "Environment:",
+    "Headers:",
+];
+
+/// Parses the structured text output of `claude mcp get <name>`
+fn parse_mcp_get_output(name: String, output: &str) -> MCPServer {
+    let mut scope = "local".to_string();
+    let mut transport = "stdio".to_string();
+    let mut command = None;
+    let mut args = vec![];
+    let mut env = HashMap::new();
+    let mut url = None;
+    let mut headers = HashMap::new();
+    let mut is_connected = false;
+    let mut status_error: Option<String> = None;
+    let mut in_env_section = false;
+    // Indentation of the "Headers:" label while reading the lines nested below it
+    let mut headers_indent: Option<usize> = None;
+
+    for raw_line in output.lines() {
+        let line = raw_line.trim();
+
+        // Headers may be listed one per line, indented below the "Headers:" label
+        if let Some(indent) = headers_indent {
+            let line_indent = raw_line.len() - raw_line.trim_start().len();
+            let is_label = MCP_GET_LABELS.iter().any(|label| line.starts_with(label));
+            if line_indent > indent && !is_label {
+                if let Some((key, value)) = parse_header_entry(line) {
+                    headers.insert(key, value);
+                    continue;
+                }
+            }
+            headers_indent = None;
+        }
+
+        // Environment variables may be listed one per line below the "Environment:" header
+        if in_env_section {
+            if let Some((key, value)) = parse_env_entry(line) {
+                env.insert(key, value);
+                continue;
+            }
+            in_env_section = false;
+        }
+
+        if line.starts_with("Scope:") {
+            if let Some(parsed) = normalize_scope(&line.replace("Scope:", "")) {
+                scope = parsed;
+            }
+        } else if line.starts_with("Status:") {
+            let status_part = line.replace("Status:", "").trim().to_string();
+            if is_connected_status(&status_part) {
+                is_connected = true;
+            } else if status_part.contains("✗") || status_part.to_lowercase().contains("failed") {
+                is_connected = false;
+                status_error = Some(status_part);
+            }
+        } else if line.starts_with("Type:") {
+            transport = normalize_transport(&line.replace("Type:", ""));
+        } else if line.starts_with("Command:") {
+            command = Some(clean_command_string(&line.replace("Command:", "")));
+        } else if line.starts_with("Args:") {
+            let args_str = line.replace("Args:", "").trim().to_string();
+            if !args_str.is_empty() {
+                args = args_str.split_whitespace().map(|s| s.to_string()).collect();
+            }
+        } else if line.starts_with("URL:") {
+            url = Some(clean_command_string(&line.replace("URL:", "")));
+        } else if let Some(rest) = line.strip_prefix("Environment:") {
+            // Single-line form: "Environment: KEY=VALUE"
+            if let Some((key, value)) = parse_env_entry(rest) {
+                env.insert(key, value);
             }
+            in_env_section = true;
+        } else if let Some(rest) = line.strip_prefix("Headers:") {
+            // Single-line form: "Headers: Authorization: Bearer abc"
+            if let Some((key, value)) = parse_header_entry(rest) {
+                headers.insert(key, value);
+            }
+            headers_indent = Some(raw_line.len() - raw_line.trim_start().len());
+        }
+    }
+
+    MCPServer {
+        name,
+        transport,
+        command,
+        args,
+        env,
+        url,
+        headers,
+        scope,
+        is_active: is_connected,
+        status: ServerStatus {
+            running: is_connected,
+            error: status_error,
+            last_checked: Some(
+                std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs(),
+            ),
         },
+    }
+}
+
+/// Gets details for a specific MCP server
+#[tauri::command]
+pub async fn mcp_get(app: AppHandle, name: String) -> Result<MCPServer, McpError> {
+    get_server_in(&app, name, None)
+}
+
+/// Returns the decoded but otherwise untouched stdout of `claude mcp get <name>`
+/// Lets the UI show what the CLI actually printed when the parsed details look wrong
+#[tauri::command]
+pub async fn mcp_get_raw(app: AppHandle, name: String) -> Result<String, McpError> {
+    info!("Getting raw MCP server output for: {}", name);
+    execute_claude_mcp_command(&app, vec!["get", &name]).map_err(|e| e.or_not_found(&name))
+}
+
+/// Returns the decoded but otherwise untouched stdout of `claude mcp list`
+/// This is the text `mcp_list` parses when the CLI has no JSON output
+#[tauri::command]
+pub async fn mcp_list_raw(app: AppHandle) -> Result<String, McpError> {
+    info!("Getting raw MCP server list output");
+    execute_claude_mcp_command(&app, vec!["list"])
+}
+
+/// Reads a server's details with `claude mcp get`, run from `cwd` when given
+fn get_server_in(
+    app: &AppHandle,
+    name: String,
+    cwd: Option<&Path>,
+) -> std::result::Result<MCPServer, McpError> {
+    info!("Getting MCP server details for: {}", name);
+
+    match execute_claude_mcp_command_in(app, vec!["get", &name], cwd) {
+        Ok(output) => Ok(parse_mcp_get_output(name, &output)),
         Err(e) => {
-            error!("Failed to read .mcp.json: {}", e);
-            Err(format!("Failed to read .mcp.json: {}", e))
+            error!("Failed to get MCP server: {}", e);
+            Err(e.or_not_found(&name))
         }
     }
 }
 
-/// Updates an existing MCP server (remove + add)
-#[tauri::command(rename_all = "snake_case")]
-pub async fn mcp_update(
+/// Removes `name` through `remove`, reporting a missing server as `removed: false`
+///
+/// `existing` is the known server list, used only to report the scope; it may be a stale
+/// cached copy, so a server missing from it is still handed to the CLI, whose "not found"
+/// answer decides.
+fn remove_server_with<F>(
+    name: &str,
+    existing: Option<&[MCPServer]>,
+    remove: F,
+) -> std::result::Result<RemoveServerResult, McpError>
+where
+    F: FnOnce() -> std::result::Result<String, McpError>,
+{
+    let not_found = || RemoveServerResult {
+        success: true,
+        removed: false,
+        scope: None,
+        message: format!("MCP server '{}' not found", name),
+    };
+
+    let scope = existing
+        .and_then(|servers| servers.iter().find(|s| s.name == name))
+        .map(|server| server.scope.clone());
+
+    match remove() {
+        Ok(output) => Ok(RemoveServerResult {
+            success: true,
+            removed: true,
+            scope,
+            message: output.trim().to_string(),
+        }),
+        Err(e) => match e.or_not_found(name) {
+            McpError::NotFound { .. } => Ok(not_found()),
+            e => Err(e),
+        },
+    }
+}
+
+/// Removes an MCP server
+/// `cwd` runs the CLI from another directory, e.g. a monorepo package with its own `.mcp.json`
+#[tauri::command]
+pub async fn mcp_remove(
     app: AppHandle,
-    old_name: String,
     name: String,
-    transport: String,
-    command: Option<String>,
-    args: Vec<String>,
-    env: HashMap<String, String>,
-    url: Option<String>,
-    scope: String,
-) -> Result<AddServerResult, String> {
-    info!("Updating MCP server: {} -> {}", old_name, name);
+    cwd: Option<String>,
+) -> Result<RemoveServerResult, McpError> {
+    info!("Removing MCP server: {}", name);
+    let cwd = resolve_mcp_cwd(cwd.as_deref())?;
 
-    // Step 1: 删除旧服务器
-    if let Err(e) = execute_claude_mcp_command(&app, vec!["remove", &old_name]) {
-        error!("Failed to remove old server: {}", e);
-        return Ok(AddServerResult {
-            success: false,
-            message: format!("Failed to remove old server: {}", e),
-            server_name: None,
+    // Disabled servers only live in the sidecar file
+    let disabled_path = disabled_servers_path().map_err(McpError::io)?;
+    let mut disabled = load_disabled_servers(&disabled_path);
+    let project = disabled_server_project("project", cwd.as_deref());
+    if let Some(index) = find_disabled_server(&disabled, &name, None, project.as_deref())
+        .map_err(McpError::parse)?
+    {
+        let server = disabled.remove(index).server;
+        save_disabled_servers(&disabled_path, &disabled).map_err(McpError::io)?;
+        invalidate_list_cache(&app);
+        info!("Removed disabled MCP server: {}", name);
+        return Ok(RemoveServerResult {
+            success: true,
+            removed: true,
+            scope: Some(server.scope),
+            message: format!("Removed disabled MCP server {}", name),
         });
     }
 
-    // Step 2: 添加新配置
-    mcp_add(app, name, transport, command, args, env, url, scope).await
+    // The (usually cached) list only supplies the scope for the result; it covers the default
+    // directory alone, so with a `cwd` it isn't consulted at all
+    let existing = match &cwd {
+        Some(_) => None,
+        None => match mcp_list(app.clone(), None, None).await {
+            Ok(servers) => Some(servers),
+            Err(e) => {
+                warn!("Could not list MCP servers before removing {}: {}", name, e);
+                None
+            }
+        },
+    };
+
+    let result = remove_server_with(&name, existing.as_deref(), || {
+        execute_claude_mcp_command_in(&app, vec!["remove", &name], cwd.as_deref())
+    });
+    match &result {
+        Ok(outcome) if outcome.removed => info!("Successfully removed MCP server: {}", name),
+        Ok(_) => info!("MCP server {} was not configured; nothing to remove", name),
+        Err(e) => error!("Failed to remove MCP server: {}", e),
+    }
+    result
 }
 
-/// Saves .mcp.json to the current project
+/// Adds an MCP server from JSON configuration
 #[tauri::command]
-pub async fn mcp_save_project_config(
-    project_path: String,
-    config: MCPProjectConfig,
-) -> Result<String, String> {
-    info!("Saving .mcp.json to project: {}", project_path);
+pub async fn mcp_add_json(
+    app: AppHandle,
+    name: String,
+    json_config: String,
+    scope: String,
+) -> Result<AddServerResult, McpError> {
+    info!(
+        "Adding MCP server from JSON: {} with scope: {}",
+        name, scope
+    );
 
-    let mcp_json_path = PathBuf::from(&project_path).join(".mcp.json");
+    // Catch malformed configs here rather than relaying a confusing CLI error
+    let json_config = match normalize_server_json(&name, &json_config) {
+        Ok(json_config) => json_config,
+        Err(message) => {
+            warn!("Rejected JSON config for MCP server {}: {}", name, message);
+            return Ok(AddServerResult {
+                success: false,
+                message,
+                server_name: None,
+            });
+        }
+    };
 
-    let json_content = serde_json::to_string_pretty(&config)
-        .map_err(|e| format!("Failed to serialize config: {}", e))?;
+    // Build command args
+    let mut cmd_args = vec!["add-json", &name, &json_config];
 
-    fs::write(&mcp_json_path, json_content)
-        .map_err(|e| format!("Failed to write .mcp.json: {}", e))?;
+    // Add scope flag
+    let scope_flag = "-s";
+    cmd_args.push(scope_flag);
+    cmd_args.push(&scope);
 
-    Ok("Project MCP configuration saved".to_string())
+    match execute_claude_mcp_command(&app, cmd_args) {
+        Ok(output) => {
+            info!("Successfully added MCP server from JSON: {}", name);
+            Ok(AddServerResult {
+                success: true,
+                message: output.trim().to_string(),
+                server_name: Some(name),
+            })
+        }
+        Err(e) => {
+            error!("Failed to add MCP server from JSON: {}", e);
+            Err(e.or_duplicate(&name, &scope))
+        }
+    }
+}
+
+/// Resolves the Claude Desktop config file for the current platform
+fn claude_desktop_config_path() -> Result<PathBuf, String> {
+    if cfg!(target_os = "macos") {
+        Ok(dirs::home_dir()
+            .ok_or_else(|| "Could not find home directory".to_string())?
+            .join("Library")
+            .join("Application Support")
+            .join("Claude")
+            .join("claude_desktop_config.json"))
+    } else if cfg!(target_os = "windows") {
+        // %APPDATA%\Claude\claude_desktop_config.json
+        Ok(dirs::config_dir()
+            .ok_or_else(|| "Could not find %APPDATA% directory".to_string())?
+            .join("Claude")
+            .join("claude_desktop_config.json"))
+    } else if cfg!(target_os = "linux") {
+        // For WSL/Linux, check common locations
+        Ok(dirs::config_dir()
+            .ok_or_else(|| "Could not find config directory".to_string())?
+            .join("Claude")
+            .join("claude_desktop_config.json"))
+    } else {
+        Err(
+            "Import from Claude Desktop is only supported on macOS, Windows and Linux/WSL"
+                .to_string(),
+        )
+    }
+}
+
+/// Reads and parses a Claude Desktop config file
+fn read_claude_desktop_config(config_path: &Path) -> Result<serde_json::Value, String> {
+    // Check if config file exists
+    if !config_path.exists() {
+        return Err(
+            "Claude Desktop configuration not found. Make sure Claude Desktop is installed."
+                .to_string(),
+        );
+    }
+
+    // Read and parse the config file
+    let config_content = fs::read_to_string(config_path)
+        .map_err(|e| format!("Failed to read Claude Desktop config: {}", e))?;
+
+    serde_json::from_str(&config_content)
+        .map_err(|e| format!("Failed to parse Claude Desktop config: {}", e))
+}
+
+/// Converts one Claude Desktop server entry into the add-json format
+/// Entries with a `url` are imported as SSE/HTTP servers, everything else as stdio
+fn desktop_entry_to_json_config(
+    server_config: &serde_json::Value,
+) -> Result<serde_json::Map<String, serde_json::Value>, String> {
+    let mut json_config = serde_json::Map::new();
+
+    if let Some(url) = server_config.get("url").and_then(|v| v.as_str()) {
+        // Keep an explicit http/sse type, default to sse otherwise
+        let transport = match server_config.get("type").and_then(|v| v.as_str()) {
+            Some(t) if normalize_transport(t) == "http" => "http",
+            _ => "sse",
+        };
+        json_config.insert("type".to_string(), transport.into());
+        json_config.insert("url".to_string(), url.into());
+
+        if let Some(headers) = server_config.get("headers").and_then(|v| v.as_object()) {
+            json_config.insert("headers".to_string(), headers.clone().into());
+        }
+    } else {
+        json_config.insert("type".to_string(), "stdio".into());
+
+        // Add command
+        let command = server_config
+            .get("command")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| "Missing command or url field".to_string())?;
+        json_config.insert("command".to_string(), command.into());
+    }
+
+    // Add args if present
+    if let Some(args) = server_config.get("args").and_then(|v| v.as_array()) {
+        json_config.insert("args".to_string(), args.clone().into());
+    } else {
+        json_config.insert("args".to_string(), serde_json::Value::Array(vec![]));
+    }
+
+    // Add env if present
+    if let Some(env) = server_config.get("env").and_then(|v| v.as_object()) {
+        json_config.insert("env".to_string(), env.clone().into());
+    } else {
+        json_config.insert(
+            "env".to_string(),
+            serde_json::Value::Object(serde_json::Map::new()),
+        );
+    }
+
+    Ok(json_config)
+}
+
+/// Imports MCP servers from Claude Desktop
+#[tauri::command]
+pub async fn mcp_add_from_claude_desktop(
+    app: AppHandle,
+    scope: String,
+) -> Result<ImportResult, String> {
+    info!(
+        "Importing MCP servers from Claude Desktop with scope: {}",
+        scope
+    );
+
+    let config_path = claude_desktop_config_path()?;
+    let config = read_claude_desktop_config(&config_path)?;
+
+    // Extract MCP servers
+    let mcp_servers = config
+        .get("mcpServers")
+        .and_then(|v| v.as_object())
+        .ok_or_else(|| "No MCP servers found in Claude Desktop config".to_string())?;
+
+    import_desktop_servers(
+        mcp_servers,
+        |name, json_str| mcp_add_json(app.clone(), name, json_str, scope.clone()),
+        |progress| {
+            let _ = app.emit("mcp-import-progress", &progress);
+        },
+    )
+    .await
+}
+
+/// Imports each Claude Desktop server through `add`, calling `emit` after every one
+/// `add` receives the server name and its add-json config string
+async fn import_desktop_servers<A, Fut, E>(
+    mcp_servers: &serde_json::Map<String, serde_json::Value>,
+    mut add: A,
+    mut emit: E,
+) -> Result<ImportResult, String>
+where
+    A: FnMut(String, String) -> Fut,
+    Fut: std::future::Future<Output = Result<AddServerResult, McpError>>,
+    E: FnMut(MCPImportProgress),
+{
+    let total = mcp_servers.len();
+    let mut imported_count = 0;
+    let mut failed_count = 0;
+    let mut server_results = Vec::new();
+
+    // Import each server using add-json
+    for (index, (name, server_config)) in mcp_servers.iter().enumerate() {
+        info!("Importing server: {}", name);
+
+        // Convert Claude Desktop format to add-json format
+        let outcome = match desktop_entry_to_json_config(server_config) {
+            Ok(json_config) => {
+                // Convert to JSON string
+                let json_str = serde_json::to_string(&json_config)
+                    .map_err(|e| format!("Failed to serialize config for {}: {}", name, e))?;
+
+                // Call add-json command
+                match add(name.clone(), json_str).await {
+                    Ok(result) if result.success => Ok(()),
+                    Ok(result) => Err(result.message),
+                    Err(e) => Err(e.to_string()),
+                }
+            }
+            Err(e) => Err(e),
+        };
+
+        let success = outcome.is_ok();
+        match outcome {
+            Ok(()) => {
+                imported_count += 1;
+                info!("Successfully imported server: {}", name);
+                server_results.push(ImportServerResult {
+                    name: name.clone(),
+                    success: true,
+                    error: None,
+                });
+            }
+            Err(e) => {
+                failed_count += 1;
+                error!("Failed to import server {}: {}", name, e);
+                server_results.push(ImportServerResult {
+                    name: name.clone(),
+                    success: false,
+                    error: Some(e),
+                });
+            }
+        }
+
+        emit(MCPImportProgress {
+            name: name.clone(),
+            index: index + 1,
+            total,
+            success,
+        });
+    }
+
+    info!(
+        "Import complete: {} imported, {} failed",
+        imported_count, failed_count
+    );
+
+    Ok(ImportResult {
+        imported_count,
+        failed_count,
+        servers: server_results,
+    })
+}
+
+/// Adds each spec in turn, recording failures instead of stopping at the first one
+/// `exists` reports whether the scope's config already defines a name; `add` runs
+/// `claude mcp add` with the prepared arguments
+fn add_servers_sequentially(
+    specs: &[MCPServerSpec],
+    scope: &str,
+    exists: impl Fn(&str) -> bool,
+    default_path: &str,
+    mut add: impl FnMut(&[String]) -> Result<String, String>,
+) -> ImportResult {
+    let mut imported_count = 0;
+    let mut failed_count = 0;
+    let mut server_results = Vec::new();
+    let mut added: Vec<String> = Vec::new();
+
+    for spec in specs {
+        let name = spec.name.trim();
+        info!("Adding server {} of batch", name);
+
+        let outcome = if name.is_empty() {
+            Err("Server name is required".to_string())
+        } else if exists(name) || added.iter().any(|n| n == name) {
+            Err(format!("Server '{}' already exists in scope '{}'", name, scope))
+        } else if let Some(message) = check_stdio_command(
+            &spec.transport,
+            spec.command.as_deref(),
+            &spec.env,
+            default_path,
+            false,
+        ) {
+            Err(message)
+        } else {
+            build_mcp_add_args(
+                name,
+                &spec.transport,
+                spec.command.as_deref(),
+                &spec.args,
+                &spec.env,
+                spec.url.as_deref(),
+                &spec.headers,
+                scope,
+            )
+            .and_then(|cmd_args| add(&cmd_args))
+        };
+
+        match outcome {
+            Ok(_) => {
+                imported_count += 1;
+                added.push(name.to_string());
+                server_results.push(ImportServerResult {
+                    name: name.to_string(),
+                    success: true,
+                    error: None,
+                });
+            }
+            Err(e) => {
+                error!("Failed to add server {}: {}", name, e);
+                failed_count += 1;
+                server_results.push(ImportServerResult {
+                    name: name.to_string(),
+                    success: false,
+                    error: Some(e),
+                });
+            }
+        }
+    }
+
+    ImportResult {
+        imported_count,
+        failed_count,
+        servers: server_results,
+    }
+}
+
+/// Adds several MCP servers to one scope, continuing past individual failures
+/// `cwd` runs the CLI from another directory, as in `mcp_add`
+#[tauri::command]
+pub async fn mcp_add_many(
+    app: AppHandle,
+    servers: Vec<MCPServerSpec>,
+    scope: String,
+    cwd: Option<String>,
+) -> Result<ImportResult, String> {
+    info!("Adding {} MCP servers with scope: {}", servers.len(), scope);
+    let cwd = resolve_mcp_cwd(cwd.as_deref())?;
+
+    // Duplicates are checked against the scope's own config file, like `mcp_add` does
+    let home = dirs::home_dir();
+    let project_dir = cwd.clone().or_else(|| std::env::current_dir().ok());
+    let exists = |name: &str| match (home.as_deref(), project_dir.as_deref()) {
+        (Some(home), Some(project_dir)) => scope_config_has_server(name, &scope, home, project_dir),
+        _ => false,
+    };
+
+    let claude_path = find_claude_binary(&app).unwrap_or_else(|_| "claude".to_string());
+    let default_path = crate::claude_binary::command_search_path(&claude_path);
+
+    let result = add_servers_sequentially(&servers, &scope, exists, &default_path, |cmd_args| {
+        run_claude_mcp_command(
+            &app,
+            cmd_args.iter().map(String::as_str).collect(),
+            cwd.as_deref(),
+        )
+        .map_err(|e| e.to_string())
+    });
+
+    // Invalidate once for the whole batch rather than after every add
+    invalidate_list_cache(&app);
+
+    info!(
+        "Batch add complete: {} added, {} failed",
+        result.imported_count, result.failed_count
+    );
+    Ok(result)
+}
+
+/// Starts Claude Code as an MCP server
+#[tauri::command]
+pub async fn mcp_serve(app: AppHandle) -> Result<i64, String> {
+    info!("Starting Claude Code as MCP server");
+
+    let registry = app
+        .try_state::<ProcessRegistryState>()
+        .ok_or("Process tracking is not available")?;
+
+    // Start the server in a separate process
+    let claude_path = match find_claude_binary(&app) {
+        Ok(path) => path,
+        Err(e) => {
+            error!("Failed to find claude binary: {}", e);
+            return Err(e.to_string());
+        }
+    };
+
+    let mut cmd = create_command_with_env(&claude_path);
+    cmd.arg("mcp").arg("serve");
+
+    let id = start_mcp_serve(&registry.0, cmd)?;
+    info!("Successfully started Claude Code MCP server (id {})", id);
+    Ok(id)
+}
+
+/// Stops the MCP server started by `mcp_serve`; returns false if it was no longer running
+#[tauri::command]
+pub async fn mcp_serve_stop(app: AppHandle, id: i64) -> Result<bool, String> {
+    info!("Stopping Claude Code MCP server (id {})", id);
+
+    let registry = app
+        .try_state::<ProcessRegistryState>()
+        .ok_or("Process tracking is not available")?;
+    stop_mcp_serve(&registry.0, id).await
+}
+
+/// Reports the MCP server started by `mcp_serve`, or None when it isn't running
+#[tauri::command]
+pub async fn mcp_serve_status(app: AppHandle) -> Result<Option<ProcessInfo>, String> {
+    let registry = app
+        .try_state::<ProcessRegistryState>()
+        .ok_or("Process tracking is not available")?;
+    registry.0.get_mcp_server(MCP_SERVE_NAME)
+}
+
+/// Name `claude mcp serve` is tracked under in the process registry
+const MCP_SERVE_NAME: &str = "claude-code";
+
+/// Spawns `cmd` and tracks it as the app's MCP server, refusing to start a second one
+fn start_mcp_serve(registry: &ProcessRegistry, cmd: Command) -> Result<i64, String> {
+    if let Some(existing) = registry.get_mcp_server(MCP_SERVE_NAME)? {
+        return Err(format!(
+            "Claude Code MCP server is already running (id {})",
+            existing.run_id
+        ));
+    }
+
+    let command = describe_command(&cmd);
+    let mut cmd = tokio::process::Command::from(cmd);
+    // A duplicate that loses the registration race is killed when dropped
+    cmd.kill_on_drop(true);
+    // Run in its own process group so stopping it also stops its children
+    #[cfg(unix)]
+    cmd.process_group(0);
+
+    let child = cmd.spawn().map_err(|e| {
+        error!("Failed to start MCP server: {}", e);
+        e.to_string()
+    })?;
+    let pid = child.id().ok_or("MCP server exited immediately")?;
+
+    registry.register_mcp_server(MCP_SERVE_NAME.to_string(), pid, command, child)
+}
+
+/// Stops a tracked MCP server, refusing ids that belong to other kinds of process
+async fn stop_mcp_serve(registry: &ProcessRegistry, id: i64) -> Result<bool, String> {
+    match registry.get_process(id)? {
+        Some(info) if matches!(info.process_type, ProcessType::McpServer { .. }) => {
+            registry.kill_process(id).await
+        }
+        Some(_) => Err(format!("Process {} is not an MCP server", id)),
+        None => Ok(false),
+    }
+}
+
+/// A line of output from a server started by `mcp_stream_logs`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MCPLogEvent {
+    /// Server name the line belongs to
+    pub server: String,
+    /// "stdout", "stderr" or "system" for lifecycle notices
+    pub stream: String,
+    pub line: String,
+}
+
+/// Global state tracking servers whose logs are being streamed
+#[derive(Default)]
+pub struct MCPLogStreams {
+    pub streams: Arc<Mutex<HashMap<String, (u64, tokio::sync::oneshot::Sender<()>)>>>,
+    next_id: std::sync::atomic::AtomicU64,
+}
+
+/// Forwards a child's output lines to `emit` until it exits or `stop` fires
+///
+/// Lines are queued in a bounded buffer; when the UI can't keep up, extra
+/// lines are dropped and reported with a single "system" notice.
+async fn pump_server_logs<F>(
+    server: String,
+    mut child: tokio::process::Child,
+    mut stop: tokio::sync::oneshot::Receiver<()>,
+    mut emit: F,
+) where
+    F: FnMut(MCPLogEvent),
+{
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    let (tx, mut rx) = tokio::sync::mpsc::channel::<(&'static str, String)>(MCP_LOG_BUFFER_LINES);
+    let dropped = Arc::new(AtomicUsize::new(0));
+
+    fn spawn_reader<R>(
+        stream: &'static str,
+        reader: Option<R>,
+        tx: tokio::sync::mpsc::Sender<(&'static str, String)>,
+        dropped: Arc<AtomicUsize>,
+    ) where
+        R: tokio::io::AsyncRead + Unpin + Send + 'static,
+    {
+        let Some(reader) = reader else { return };
+        tokio::spawn(async move {
+            let mut reader = BufReader::new(reader);
+            while let Ok(Some(line)) = crate::claude_binary::read_decoded_line(&mut reader).await {
+                if tx.try_send((stream, line)).is_err() {
+                    dropped.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        });
+    }
+    spawn_reader("stdout", child.stdout.take(), tx.clone(), dropped.clone());
+    spawn_reader("stderr", child.stderr.take(), tx, dropped.clone());
+
+    let event = |stream: &str, line: String| MCPLogEvent {
+        server: server.clone(),
+        stream: stream.to_string(),
+        line,
+    };
+
+    let stopped = loop {
+        tokio::select! {
+            _ = &mut stop => break true,
+            next = rx.recv() => match next {
+                Some((stream, line)) => {
+                    let missed = dropped.swap(0, Ordering::Relaxed);
+                    if missed > 0 {
+                        emit(event("system", format!("[{} log lines dropped]", missed)));
+                    }
+                    emit(event(stream, line));
+                }
+                // Both pipes closed: the server exited
+                None => break false,
+            },
+        }
+    };
+
+    if stopped {
+        let _ = child.kill().await;
+        emit(event("system", "Log streaming stopped".to_string()));
+    } else {
+        let status = match child.wait().await {
+            Ok(status) => status.to_string(),
+            Err(e) => e.to_string(),
+        };
+        emit(event("system", format!("Server exited ({})", status)));
+    }
+}
+
+/// Launches a stdio server and emits its stdout/stderr as `mcp-log` events
+/// until `mcp_stop_logs` is called or the server exits
+#[tauri::command]
+pub async fn mcp_stream_logs(app: AppHandle, name: String) -> Result<(), String> {
+    info!("Streaming logs for MCP server: {}", name);
+
+    let streams = app
+        .try_state::<MCPLogStreams>()
+        .ok_or("Log streaming is not available")?;
+    if streams.streams.lock().map_err(|e| e.to_string())?.contains_key(&name) {
+        return Err(format!("Already streaming logs for {}", name));
+    }
+
+    let server = mcp_get(app.clone(), name.clone()).await?;
+    if server.transport != "stdio" {
+        return Err(format!(
+            "Only stdio servers can be launched for logs ({} uses {})",
+            name, server.transport
+        ));
+    }
+    let command = server
+        .command
+        .as_deref()
+        .filter(|c| !c.is_empty())
+        .ok_or_else(|| "No command configured for stdio server".to_string())?;
+
+    let mut std_cmd = create_command_with_env(command);
+    std_cmd
+        .args(&server.args)
+        .envs(&server.env)
+        // Keep stdin open: many stdio servers exit as soon as it closes
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    let mut cmd = tokio::process::Command::from(std_cmd);
+    cmd.kill_on_drop(true);
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| format!("Failed to spawn '{}': {}", command, e))?;
+    let stdin = child.stdin.take();
+
+    let (stop_tx, stop_rx) = tokio::sync::oneshot::channel();
+    let id = streams
+        .next_id
+        .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    {
+        let mut active = streams.streams.lock().map_err(|e| e.to_string())?;
+        if active.contains_key(&name) {
+            return Err(format!("Already streaming logs for {}", name));
+        }
+        active.insert(name.clone(), (id, stop_tx));
+    }
+
+    let active = streams.streams.clone();
+    tauri::async_runtime::spawn(async move {
+        let _stdin = stdin;
+        let emitter = app.clone();
+        pump_server_logs(name.clone(), child, stop_rx, move |event| {
+            let _ = emitter.emit("mcp-log", &event);
+        })
+        .await;
+
+        // Forget the stream unless a newer one replaced it
+        if let Ok(mut active) = active.lock() {
+            if active.get(&name).is_some_and(|(current, _)| *current == id) {
+                active.remove(&name);
+            }
+        }
+        info!("Stopped streaming logs for MCP server: {}", name);
+    });
+
+    Ok(())
+}
+
+/// Stops a log stream started by `mcp_stream_logs`; returns false if none was running
+#[tauri::command]
+pub async fn mcp_stop_logs(app: AppHandle, name: String) -> Result<bool, String> {
+    let streams = app
+        .try_state::<MCPLogStreams>()
+        .ok_or("Log streaming is not available")?;
+    let stream = streams.streams.lock().map_err(|e| e.to_string())?.remove(&name);
+
+    match stream {
+        Some((_, stop)) => {
+            let _ = stop.send(());
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+/// Spawns a stdio server and performs an MCP `initialize` handshake
+async fn probe_stdio_server(server: &MCPServer) -> Result<String, String> {
+    let command = server
+        .command
+        .as_deref()
+        .filter(|c| !c.is_empty())
+        .ok_or_else(|| "No command configured for stdio server".to_string())?;
+
+    let mut std_cmd = create_command_with_env(command);
+    std_cmd
+        .args(&server.args)
+        .envs(&server.env)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+
+    let mut cmd = tokio::process::Command::from(std_cmd);
+    cmd.kill_on_drop(true);
+
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| format!("Failed to spawn '{}': {}", command, e))?;
+
+    let initialize = serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "initialize",
+        "params": {
+            "protocolVersion": "2024-11-05",
+            "capabilities": {},
+            "clientInfo": { "name": "codestudio", "version": env!("CARGO_PKG_VERSION") }
+        }
+    });
+
+    let mut stdin = child.stdin.take().ok_or("Failed to open server stdin")?;
+    let stdout = child.stdout.take().ok_or("Failed to open server stdout")?;
+    let mut reader = BufReader::new(stdout);
+
+    let handshake = tokio::time::timeout(CONNECTION_TEST_TIMEOUT, async {
+        stdin
+            .write_all(format!("{}\n", initialize).as_bytes())
+            .await
+            .map_err(|e| format!("Failed to send initialize request: {}", e))?;
+
+        while let Some(line) = crate::claude_binary::read_decoded_line(&mut reader)
+            .await
+            .map_err(|e| format!("Failed to read server output: {}", e))?
+        {
+            // Servers may log non-JSON lines to stdout before answering
+            if let Ok(response) = serde_json::from_str::<serde_json::Value>(&line) {
+                if response.get("id") == Some(&serde_json::json!(1)) {
+                    return Ok(response);
+                }
+            }
+        }
+
+        Err("Server closed its output before answering initialize".to_string())
+    })
+    .await;
+
+    let outcome = match handshake {
+        Ok(Ok(response)) => match response.get("error") {
+            Some(err) => Err(format!("Server rejected initialize: {}", err)),
+            None => {
+                let server_name = response
+                    .pointer("/result/serverInfo/name")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("unknown");
+                Ok(format!(
+                    "Server '{}' answered the initialize handshake",
+                    server_name
+                ))
+            }
+        },
+        Ok(Err(e)) => Err(e),
+        Err(_) => match child.try_wait() {
+            // Still alive but silent: it launched fine, it just didn't speak MCP in time
+            Ok(None) => {
+                Ok("Server process is running but did not answer initialize in time".to_string())
+            }
+            Ok(Some(status)) => Err(format!("Server process exited with {}", status)),
+            Err(e) => Err(format!("Failed to query server process: {}", e)),
+        },
+    };
+
+    let _ = child.kill().await;
+
+    let error = match outcome {
+        Ok(message) => return Ok(message),
+        Err(e) => e,
+    };
+
+    // Attach whatever the server printed to stderr so the user can see why it failed
+    let mut stderr_output = String::new();
+    if let Some(mut stderr) = child.stderr.take() {
+        let _ = tokio::time::timeout(
+            Duration::from_secs(1),
+            stderr.read_to_string(&mut stderr_output),
+        )
+        .await;
+    }
+
+    let stderr_output = stderr_output.trim();
+    if stderr_output.is_empty() {
+        Err(error)
+    } else {
+        Err(format!("{} (stderr: {})", error, stderr_output))
+    }
+}
+
+/// Issues an HTTP GET against an SSE/HTTP server endpoint
+async fn probe_http_server(server: &MCPServer) -> Result<String, String> {
+    let url = server
+        .url
+        .as_deref()
+        .filter(|u| !u.is_empty())
+        .ok_or_else(|| format!("No URL configured for {} server", server.transport))?;
+
+    let client = reqwest::Client::builder()
+        .timeout(CONNECTION_TEST_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+
+    let mut request = client
+        .get(url)
+        .header(reqwest::header::ACCEPT, "application/json, text/event-stream");
+    for (key, value) in &server.headers {
+        request = request.header(key.as_str(), value.as_str());
+    }
+
+    let response = request
+        .send()
+        .await
+        .map_err(|e| format!("Request to {} failed: {}", url, e))?;
+
+    let status = response.status();
+    let is_event_stream = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(|ct| ct.contains("text/event-stream"))
+        .unwrap_or(false);
+
+    if status.is_success() || is_event_stream {
+        Ok(format!("Endpoint responded with HTTP {}", status))
+    } else {
+        Err(format!("Endpoint responded with HTTP {}", status))
+    }
+}
+
+/// Probes a server over its transport: an initialize handshake for stdio, a GET for SSE/HTTP
+async fn probe_server(server: &MCPServer) -> Result<String, String> {
+    match server.transport.as_str() {
+        "stdio" => probe_stdio_server(server).await,
+        "sse" | "http" => probe_http_server(server).await,
+        other => Err(format!("Unsupported transport: {}", other)),
+    }
+}
+
+/// Maximum number of servers `mcp_test_all` probes at once
+const MCP_TEST_ALL_CONCURRENCY: usize = 4;
+
+/// Probes every server with at most `concurrency` probes in flight, keeping the input order
+async fn test_servers_with<F, Fut>(
+    servers: Vec<MCPServer>,
+    concurrency: usize,
+    probe: F,
+) -> Vec<ServerReachability>
+where
+    F: Fn(MCPServer) -> Fut,
+    Fut: std::future::Future<Output = Result<String, String>>,
+{
+    use futures_util::StreamExt;
+
+    futures_util::stream::iter(servers)
+        .map(|server| {
+            let name = server.name.clone();
+            let scope = server.scope.clone();
+            let probe = probe(server);
+            async move {
+                let started = Instant::now();
+                let outcome = probe.await;
+                let latency_ms = started.elapsed().as_millis() as u64;
+                if let Err(e) = &outcome {
+                    warn!("MCP server {} is unreachable: {}", name, e);
+                }
+                ServerReachability {
+                    name,
+                    scope,
+                    reachable: outcome.is_ok(),
+                    latency_ms,
+                    error: outcome.err(),
+                }
+            }
+        })
+        .buffered(concurrency.max(1))
+        .collect()
+        .await
+}
+
+/// Probes every configured server (optionally only one scope) and reports each result
+/// A server that can't be reached is reported as such rather than failing the call
+#[tauri::command]
+pub async fn mcp_test_all(
+    app: AppHandle,
+    scope: Option<String>,
+) -> Result<Vec<ServerReachability>, McpError> {
+    let servers = mcp_list(app.clone(), None, scope).await?;
+    info!("Testing connections to {} MCP servers", servers.len());
+
+    let results = test_servers_with(servers, MCP_TEST_ALL_CONCURRENCY, |server| async move {
+        probe_server(&server).await
+    })
+    .await;
+    for result in &results {
+        record_status_check(&app, &result.name, result.latency_ms, result.error.as_deref());
+    }
+    info!(
+        "{} of {} MCP servers reachable",
+        results.iter().filter(|r| r.reachable).count(),
+        results.len()
+    );
+    Ok(results)
+}
+
+/// Tests connection to an MCP server by actually probing it
+#[tauri::command]
+pub async fn mcp_test_connection(
+    app: AppHandle,
+    name: String,
+) -> Result<ConnectionTestResult, String> {
+    info!("Testing connection to MCP server: {}", name);
+
+    let server = mcp_get(app.clone(), name.clone()).await?;
+
+    let started = Instant::now();
+    let outcome = probe_server(&server).await;
+    let latency_ms = started.elapsed().as_millis() as u64;
+    record_status_check(&app, &name, latency_ms, outcome.as_ref().err().map(String::as_str));
+
+    match outcome {
+        Ok(message) => {
+            info!("Connection test for {} succeeded in {}ms", name, latency_ms);
+            Ok(ConnectionTestResult {
+                success: true,
+                transport: server.transport,
+                latency_ms,
+                message,
+                error: None,
+            })
+        }
+        Err(e) => {
+            error!("Connection test for {} failed: {}", name, e);
+            Ok(ConnectionTestResult {
+                success: false,
+                transport: server.transport,
+                latency_ms,
+                message: format!("Connection to {} failed", name),
+                error: Some(e),
+            })
+        }
+    }
+}
+
+/// Returns the recent connection checks of a server, oldest first
+/// Checks are recorded by `mcp_test_connection` and `mcp_test_all`; `limit` keeps only the newest
+#[tauri::command]
+pub async fn mcp_get_status_history(
+    app: AppHandle,
+    name: String,
+    limit: Option<usize>,
+) -> Result<Vec<StatusCheck>, String> {
+    let history = app
+        .try_state::<MCPStatusHistory>()
+        .ok_or_else(|| "MCP status history is not available".to_string())?;
+    Ok(history.recent(&name, limit))
+}
+
+/// Enables or disables a server without losing its configuration
+/// Disabling snapshots the server into a sidecar file and unregisters it from the CLI;
+/// enabling re-adds it from the snapshot, in the project it was disabled from
+/// `cwd` selects the project as in `mcp_remove`; `scope` picks between same-named servers
+#[tauri::command]
+pub async fn mcp_toggle(
+    app: AppHandle,
+    name: String,
+    enabled: bool,
+    scope: Option<String>,
+    cwd: Option<String>,
+) -> Result<String, String> {
+    info!("Toggling MCP server {}: enabled={}", name, enabled);
+    let cwd = resolve_mcp_cwd(cwd.as_deref()).map_err(|e| e.to_string())?;
+    let scope = scope.filter(|scope| !scope.trim().is_empty());
+
+    let disabled_path = disabled_servers_path()?;
+    let mut disabled = load_disabled_servers(&disabled_path);
+
+    if enabled {
+        let project = disabled_server_project("project", cwd.as_deref());
+        let index =
+            match find_disabled_server(&disabled, &name, scope.as_deref(), project.as_deref())? {
+                Some(index) => index,
+                None => return Ok(format!("Server '{}' is already enabled", name)),
+            };
+
+        // Drop the snapshot first so mcp_add doesn't see it as a duplicate
+        let entry = disabled.remove(index);
+        save_disabled_servers(&disabled_path, &disabled)?;
+        invalidate_list_cache(&app);
+
+        let server = &entry.server;
+        let result = mcp_add(
+            app.clone(),
+            server.name.clone(),
+            server.transport.clone(),
+            server.command.clone(),
+            server.args.clone(),
+            server.env.clone(),
+            server.url.clone(),
+            Some(server.headers.clone()),
+            server.scope.clone(),
+            // Re-enabling restores a configuration the user already accepted
+            Some(true),
+            entry.project_path.clone(),
+        )
+        .await;
+
+        let failure = match result {
+            Ok(result) if result.success => None,
+            Ok(result) => Some(result.message),
+            Err(e) => Some(e.to_string()),
+        };
+        if let Some(message) = failure {
+            disabled.insert(index, entry);
+            save_disabled_servers(&disabled_path, &disabled)?;
+            invalidate_list_cache(&app);
+            return Err(format!("Failed to enable server '{}': {}", name, message));
+        }
+
+        Ok(format!("Server '{}' enabled", name))
+    } else {
+        let mut server = get_server_in(&app, name.clone(), cwd.as_deref())?;
+        if scope.as_deref().is_some_and(|scope| scope != server.scope) {
+            return Err(format!(
+                "Server '{}' is configured in {} scope, not {}",
+                name,
+                server.scope,
+                scope.unwrap_or_default()
+            ));
+        }
+        let project_path = disabled_server_project(&server.scope, cwd.as_deref());
+        if find_disabled_server(&disabled, &name, Some(&server.scope), project_path.as_deref())?
+            .is_some()
+        {
+            return Ok(format!("Server '{}' is already disabled", name));
+        }
+
+        execute_claude_mcp_command_in(
+            &app,
+            vec!["remove", &name, "-s", &server.scope],
+            cwd.as_deref(),
+        )
+        .map_err(|e| format!("Failed to disable server '{}': {}", name, e))?;
+
+        server.is_active = false;
+        server.status.running = false;
+        disabled.push(DisabledServer {
+            server,
+            project_path,
+        });
+        save_disabled_servers(&disabled_path, &disabled)?;
+        invalidate_list_cache(&app);
+
+        Ok(format!("Server '{}' disabled", name))
+    }
+}
+
+/// Resets project-scoped server approval choices
+#[tauri::command]
+pub async fn mcp_reset_project_choices(app: AppHandle) -> Result<String, String> {
+    info!("Resetting MCP project choices");
+
+    match execute_claude_mcp_command(&app, vec!["reset-project-choices"]) {
+        Ok(output) => {
+            info!("Successfully reset MCP project choices");
+            Ok(output.trim().to_string())
+        }
+        Err(e) => {
+            error!("Failed to reset project choices: {}", e);
+            Err(e.to_string())
+        }
+    }
+}
+
+/// Gets the status of MCP servers
+#[tauri::command]
+pub async fn mcp_get_server_status() -> Result<HashMap<String, ServerStatus>, String> {
+    info!("Getting MCP server status");
+
+    // TODO: Implement actual status checking
+    // For now, return empty status
+    Ok(HashMap::new())
+}
+
+/// Gets the MCP configuration file paths
+#[tauri::command]
+pub async fn mcp_get_config_paths(project_path: Option<String>) -> Result<MCPConfigPaths, String> {
+    info!("Getting MCP config paths");
+
+    // Get home directory for user config
+    let home_dir = dirs::home_dir()
+        .ok_or_else(|| "Could not find home directory".to_string())?;
+
+    // User config: ~/.claude.json (global, available in all projects)
+    let user_path = home_dir.join(".claude.json");
+
+    // Local config: <project>/.claude/settings.local.json
+    let local_path = if let Some(ref project) = project_path {
+        PathBuf::from(project).join(".claude").join("settings.local.json")
+    } else {
+        PathBuf::from(".claude").join("settings.local.json")
+    };
+
+    // Project config: <project>/.mcp.json
+    let project_config_path = if let Some(ref project) = project_path {
+        PathBuf::from(project).join(".mcp.json")
+    } else {
+        PathBuf::from(".mcp.json")
+    };
+
+    Ok(MCPConfigPaths {
+        local: local_path.to_string_lossy().to_string(),
+        project: project_config_path.to_string_lossy().to_string(),
+        user: user_path.to_string_lossy().to_string(),
+    })
+}
+
+/// Checks a single `.mcp.json` server entry and returns its field errors
+fn validate_server_entry(name: &str, entry: &serde_json::Value) -> Vec<String> {
+    let mut errors = Vec::new();
+
+    let entry = match entry.as_object() {
+        Some(entry) => entry,
+        None => {
+            errors.push(format!("Server '{}': entry must be an object", name));
+            return errors;
+        }
+    };
+
+    let transport = match entry.get("type") {
+        None => {
+            errors.push(format!("Server '{}': missing 'type'", name));
+            None
+        }
+        Some(serde_json::Value::String(t)) if ["stdio", "sse", "http"].contains(&t.as_str()) => {
+            Some(t.as_str())
+        }
+        Some(serde_json::Value::String(t)) => {
+            errors.push(format!(
+                "Server '{}': unknown type '{}' (expected stdio, sse or http)",
+                name, t
+            ));
+            None
+        }
+        Some(_) => {
+            errors.push(format!("Server '{}': 'type' must be a string", name));
+            None
+        }
+    };
+
+    match entry.get("command") {
+        Some(serde_json::Value::String(_)) => {}
+        Some(_) => errors.push(format!("Server '{}': 'command' must be a string", name)),
+        None if transport == Some("stdio") => {
+            errors.push(format!("Server '{}': missing 'command'", name))
+        }
+        None => {}
+    }
+
+    match entry.get("url") {
+        Some(serde_json::Value::String(_)) | Some(serde_json::Value::Null) => {}
+        Some(_) => errors.push(format!("Server '{}': 'url' must be a string", name)),
+        None if transport == Some("sse") || transport == Some("http") => {
+            errors.push(format!("Server '{}': missing 'url'", name))
+        }
+        None => {}
+    }
+
+    if let Some(args) = entry.get("args") {
+        let valid = args
+            .as_array()
+            .map(|items| items.iter().all(|item| item.is_string()))
+            .unwrap_or(false);
+        if !valid {
+            errors.push(format!("Server '{}': 'args' must be an array of strings", name));
+        }
+    }
+
+    for field in ["env", "headers"] {
+        if let Some(value) = entry.get(field) {
+            let valid = value.is_null()
+                || value
+                    .as_object()
+                    .map(|map| map.values().all(|v| v.is_string()))
+                    .unwrap_or(false);
+            if !valid {
+                errors.push(format!(
+                    "Server '{}': '{}' must be an object of string values",
+                    name, field
+                ));
+            }
+        }
+    }
+
+    errors
+}
+
+/// Parses and checks an `add-json` server config, returning it re-serialized compactly
+///
+/// The error lists every problem found, e.g. a missing `url` for an http server.
+fn normalize_server_json(name: &str, json_config: &str) -> Result<String, String> {
+    let value: serde_json::Value =
+        serde_json::from_str(json_config).map_err(|e| format!("Invalid JSON: {}", e))?;
+
+    let errors = validate_server_entry(name, &value);
+    if !errors.is_empty() {
+        return Err(errors.join("; "));
+    }
+
+    serde_json::to_string(&value).map_err(|e| format!("Failed to serialize config: {}", e))
+}
+
+/// Turns JSONC (`//` and `/* */` comments, trailing commas) into strict JSON
+/// Removed characters become spaces and newlines are kept, so parse errors still
+/// point at the right line and column of the original file
+fn strip_jsonc(content: &str) -> String {
+    let chars: Vec<char> = content.chars().collect();
+    let mut out: Vec<char> = Vec::with_capacity(chars.len());
+    let mut in_string = false;
+    let mut i = 0;
+
+    // Pass 1: blank out comments, leaving string contents untouched
+    while i < chars.len() {
+        let c = chars[i];
+        if in_string {
+            out.push(c);
+            if c == '\\' {
+                if let Some(&next) = chars.get(i + 1) {
+                    out.push(next);
+                    i += 1;
+                }
+            } else if c == '"' {
+                in_string = false;
+            }
+            i += 1;
+            continue;
+        }
+
+        match (c, chars.get(i + 1)) {
+            ('"', _) => {
+                in_string = true;
+                out.push(c);
+                i += 1;
+            }
+            ('/', Some('/')) => {
+                while i < chars.len() && chars[i] != '\n' {
+                    out.push(' ');
+                    i += 1;
+                }
+            }
+            ('/', Some('*')) => {
+                out.extend([' ', ' ']);
+                i += 2;
+                while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                    out.push(if chars[i] == '\n' { '\n' } else { ' ' });
+                    i += 1;
+                }
+                if i < chars.len() {
+                    out.extend([' ', ' ']);
+                    i += 2;
+                }
+            }
+            _ => {
+                out.push(c);
+                i += 1;
+            }
+        }
+    }
+
+    // Pass 2: drop commas that directly precede a closing bracket
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut trailing_commas = Vec::new();
+    for (i, &c) in out.iter().enumerate() {
+        if in_string {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '"' {
+                in_string = false;
+            }
+            continue;
+        }
+
+        if c == '"' {
+            in_string = true;
+        } else if c == ',' {
+            let next = out[i + 1..].iter().find(|c| !c.is_whitespace());
+            if matches!(next, Some('}') | Some(']')) {
+                trailing_commas.push(i);
+            }
+        }
+    }
+    for i in trailing_commas {
+        out[i] = ' ';
+    }
+
+    out.into_iter().collect()
+}
+
+/// Validates raw `.mcp.json` content and returns per-server field errors
+/// Comments and trailing commas are accepted, see `strip_jsonc`
+fn validate_project_config_content(content: &str) -> Vec<String> {
+    let value: serde_json::Value = match serde_json::from_str(&strip_jsonc(content)) {
+        Ok(value) => value,
+        Err(e) => return vec![format!("Invalid JSON: {}", e)],
+    };
+
+    let servers = match value.get("mcpServers") {
+        Some(serde_json::Value::Object(servers)) => servers,
+        Some(_) => return vec!["'mcpServers' must be an object".to_string()],
+        None => return vec!["Missing 'mcpServers' object".to_string()],
+    };
+
+    servers
+        .iter()
+        .flat_map(|(name, entry)| validate_server_entry(name, entry))
+        .collect()
+}
+
+/// Checks each server of saved `.mcp.json` content against what the CLI accepts
+/// (a known `type` and the fields that type needs) and returns the ones that fail
+fn find_rejected_servers(content: &str) -> Vec<RejectedServer> {
+    let value: serde_json::Value = match serde_json::from_str(&strip_jsonc(content)) {
+        Ok(value) => value,
+        Err(_) => return Vec::new(),
+    };
+    let Some(servers) = value.get("mcpServers").and_then(|v| v.as_object()) else {
+        return Vec::new();
+    };
+
+    servers
+        .iter()
+        .filter_map(|(name, entry)| {
+            let errors = validate_server_entry(name, entry);
+            (!errors.is_empty()).then(|| RejectedServer {
+                name: name.clone(),
+                errors,
+            })
+        })
+        .collect()
+}
+
+/// Validates .mcp.json in the given project and returns the list of problems found
+#[tauri::command]
+pub async fn mcp_validate_project_config(project_path: String) -> Result<Vec<String>, String> {
+    info!("Validating .mcp.json in project: {}", project_path);
+
+    let mcp_json_path = PathBuf::from(&project_path).join(".mcp.json");
+    if !mcp_json_path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let content = fs::read_to_string(&mcp_json_path)
+        .map_err(|e| format!("Failed to read .mcp.json: {}", e))?;
+
+    Ok(validate_project_config_content(&content))
+}
+
+/// Expands `${VAR}` and `${VAR:-default}` in `value`
+///
+/// The default applies when the variable is unset or empty, as in the shell. A variable with
+/// neither a value nor a default is left as written and its name pushed to `missing`.
+fn expand_env_vars<F>(value: &str, lookup: &F, missing: &mut Vec<String>) -> String
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let mut expanded = String::with_capacity(value.len());
+    let mut rest = value;
+
+    while let Some(start) = rest.find("${") {
+        let Some(len) = rest[start + 2..].find('}') else {
+            break;
+        };
+        let reference = &rest[start + 2..start + 2 + len];
+        let (name, default) = match reference.split_once(":-") {
+            Some((name, default)) => (name, Some(default)),
+            None => (reference, None),
+        };
+
+        expanded.push_str(&rest[..start]);
+        match (lookup(name).filter(|v| !v.is_empty()), default) {
+            (Some(resolved), _) => expanded.push_str(&resolved),
+            (None, Some(default)) => expanded.push_str(default),
+            (None, None) => {
+                expanded.push_str(&rest[start..start + 3 + len]);
+                if !missing.iter().any(|m| m == name) {
+                    missing.push(name.to_string());
+                }
+            }
+        }
+        rest = &rest[start + 3 + len..];
+    }
+
+    expanded.push_str(rest);
+    expanded
+}
+
+/// Expands environment references in a server's command, args, env, url and headers
+///
+/// Returns the names of variables that could not be resolved.
+fn expand_server_env<F>(config: &mut MCPServerConfig, lookup: &F) -> Vec<String>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let mut missing = Vec::new();
+
+    config.command = expand_env_vars(&config.command, lookup, &mut missing);
+    for arg in config.args.iter_mut() {
+        *arg = expand_env_vars(arg, lookup, &mut missing);
+    }
+    for value in config.env.values_mut() {
+        *value = expand_env_vars(value, lookup, &mut missing);
+    }
+    if let Some(url) = config.url.as_mut() {
+        *url = expand_env_vars(url, lookup, &mut missing);
+    }
+    for value in config.headers.iter_mut().flat_map(|h| h.values_mut()) {
+        *value = expand_env_vars(value, lookup, &mut missing);
+    }
+
+    missing
+}
+
+/// Expands environment references in every server of `config`
+///
+/// Servers with unresolved variables are listed in `env_warnings`.
+fn expand_project_config_env<F>(config: &mut MCPProjectConfig, lookup: &F)
+where
+    F: Fn(&str) -> Option<String>,
+{
+    for (name, server) in config.mcp_servers.iter_mut() {
+        let missing = expand_server_env(server, lookup);
+        if !missing.is_empty() {
+            warn!(
+                "MCP server '{}' in .mcp.json references undefined environment variable(s): {}",
+                name,
+                missing.join(", ")
+            );
+            config.env_warnings.insert(name.clone(), missing);
+        }
+    }
+}
+
+/// Reads .mcp.json from the current project
+/// The file may contain comments and trailing commas; saving always writes strict JSON
+/// `${VAR}` / `${VAR:-default}` references are left as written so the config can be edited and
+/// saved back without leaking secrets. `expand_env` resolves them from the environment for
+/// display or launching only; such a config must not be saved.
+#[tauri::command]
+pub async fn mcp_read_project_config(
+    project_path: String,
+    expand_env: Option<bool>,
+) -> Result<MCPProjectConfig, String> {
+    info!("Reading .mcp.json from project: {}", project_path);
+
+    let mcp_json_path = PathBuf::from(&project_path).join(".mcp.json");
+
+    if !mcp_json_path.exists() {
+        return Ok(MCPProjectConfig {
+            mcp_servers: IndexMap::new(),
+            env_warnings: IndexMap::new(),
+        });
+    }
+
+    match fs::read_to_string(&mcp_json_path) {
+        Ok(content) => match serde_json::from_str::<MCPProjectConfig>(&strip_jsonc(&content)) {
+            Ok(mut config) => {
+                if expand_env.unwrap_or(false) {
+                    expand_project_config_env(&mut config, &|name: &str| std::env::var(name).ok());
+                }
+                Ok(config)
+            }
+            Err(e) => {
+                error!("Failed to parse .mcp.json: {}", e);
+                let errors = validate_project_config_content(&content);
+                if errors.is_empty() {
+                    Err(format!("Failed to parse .mcp.json: {}", e))
+                } else {
+                    Err(format!("Invalid .mcp.json:\n{}", errors.join("\n")))
+                }
+            }
+        },
+        Err(e) => {
+            error!("Failed to read .mcp.json: {}", e);
+            Err(format!("Failed to read .mcp.json: {}", e))
+        }
+    }
+}
+
+/// Updates an existing MCP server (remove + add)
+/// `cwd` runs the CLI from another directory, e.g. a monorepo package with its own `.mcp.json`
+#[tauri::command(rename_all = "snake_case")]
+pub async fn mcp_update(
+    app: AppHandle,
+    old_name: String,
+    name: String,
+    transport: String,
+    command: Option<String>,
+    args: Vec<String>,
+    env: HashMap<String, String>,
+    url: Option<String>,
+    headers: Option<HashMap<String, String>>,
+    scope: String,
+    cwd: Option<String>,
+) -> Result<AddServerResult, String> {
+    info!("Updating MCP server: {} -> {}", old_name, name);
+    let cwd_path = resolve_mcp_cwd(cwd.as_deref())?;
+
+    // Step 1: 保存旧配置，以便添加失败时回滚
+    let original = match get_server_in(&app, old_name.clone(), cwd_path.as_deref()) {
+        Ok(server) => server,
+        Err(e) => {
+            error!("Failed to read existing server before update: {}", e);
+            return Ok(AddServerResult {
+                success: false,
+                message: format!("Failed to read existing server configuration: {}", e),
+                server_name: None,
+            });
+        }
+    };
+
+    let updated = MCPServer {
+        name,
+        transport,
+        command,
+        args,
+        env,
+        url,
+        headers: headers.unwrap_or_default(),
+        scope,
+        is_active: true,
+        status: ServerStatus {
+            running: false,
+            error: None,
+            last_checked: None,
+        },
+    };
+
+    let result = update_server_with(
+        original,
+        updated,
+        || execute_claude_mcp_command_in(&app, vec!["remove", &old_name], cwd_path.as_deref()),
+        |server, force| {
+            mcp_add(
+                app.clone(),
+                server.name,
+                server.transport,
+                server.command,
+                server.args,
+                server.env,
+                server.url,
+                Some(server.headers),
+                server.scope,
+                Some(force),
+                cwd.clone(),
+            )
+        },
+    )
+    .await;
+    Ok(result)
+}
+
+/// Replaces `original` with `updated`: `remove` drops the original, then `add` adds the update
+///
+/// If the add fails, `original` is added back (with `force`, since it was already accepted)
+/// and the result reports both the failure and whether the rollback worked.
+async fn update_server_with<R, A, Fut>(
+    original: MCPServer,
+    updated: MCPServer,
+    remove: R,
+    mut add: A,
+) -> AddServerResult
+where
+    R: FnOnce() -> Result<String, McpError>,
+    A: FnMut(MCPServer, bool) -> Fut,
+    Fut: std::future::Future<Output = Result<AddServerResult, McpError>>,
+{
+    let old_name = original.name.clone();
+
+    // Step 2: 删除旧服务器
+    if let Err(e) = remove() {
+        error!("Failed to remove old server: {}", e);
+        return AddServerResult {
+            success: false,
+            message: format!("Failed to remove old server: {}", e),
+            server_name: None,
+        };
+    }
+
+    // Step 3: 添加新配置
+    let failure = match add(updated, false).await {
+        Ok(result) if result.success => return result,
+        Ok(result) => result.message,
+        Err(e) => e.to_string(),
+    };
+
+    // Step 4: 添加失败，恢复原有配置
+    error!(
+        "Failed to add updated server, restoring '{}': {}",
+        old_name, failure
+    );
+    let message = match add(original, true).await {
+        Ok(restore_result) if restore_result.success => format!(
+            "Update failed: {}. Rolled back to the original configuration of '{}'",
+            failure, old_name
+        ),
+        Ok(restore_result) => format!(
+            "Update failed: {}. Rollback of '{}' also failed: {}",
+            failure, old_name, restore_result.message
+        ),
+        Err(e) => format!(
+            "Update failed: {}. Rollback of '{}' also failed: {}",
+            failure, old_name, e
+        ),
+    };
+
+    AddServerResult {
+        success: false,
+        message,
+        server_name: None,
+    }
+}
+
+/// Copies a map with its keys sorted so exported files don't depend on hash order
+fn sorted_map(map: &HashMap<String, String>) -> IndexMap<String, String> {
+    let mut sorted: IndexMap<String, String> =
+        map.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+    sorted.sort_keys();
+    sorted
+}
+
+/// Converts a server into the portable `.mcp.json` / add-json entry shape
+fn server_to_config(server: &MCPServer) -> MCPServerConfig {
+    let is_remote = server.transport == "sse" || server.transport == "http";
+
+    MCPServerConfig {
+        transport_type: server.transport.clone(),
+        command: if is_remote {
+            String::new()
+        } else {
+            server.command.clone().unwrap_or_default()
+        },
+        args: server.args.clone(),
+        env: sorted_map(&server.env),
+        url: if is_remote { server.url.clone() } else { None },
+        headers: if server.headers.is_empty() {
+            None
+        } else {
+            Some(sorted_map(&server.headers))
+        },
+    }
+}
+
+/// Builds the `{"mcpServers": {...}}` document for a set of servers
+fn build_export_config(servers: &[MCPServer], scope: Option<&str>) -> MCPProjectConfig {
+    MCPProjectConfig {
+        mcp_servers: servers
+            .iter()
+            .filter(|server| scope.is_none() || scope == Some(server.scope.as_str()))
+            .map(|server| (server.name.clone(), server_to_config(server)))
+            .collect(),
+        env_warnings: IndexMap::new(),
+    }
+}
+
+/// Exports all MCP servers (optionally from a single scope) to a portable JSON file
+#[tauri::command]
+pub async fn mcp_export(
+    app: AppHandle,
+    scope: Option<String>,
+    output_path: String,
+) -> Result<String, String> {
+    info!("Exporting MCP servers (scope: {:?}) to {}", scope, output_path);
+
+    let servers = mcp_list(app, None, None).await?;
+    let config = build_export_config(&servers, scope.as_deref());
+
+    let json_content = serde_json::to_string_pretty(&config)
+        .map_err(|e| format!("Failed to serialize servers: {}", e))?;
+
+    fs::write(&output_path, json_content).map_err(|e| {
+        error!("Failed to write export file: {}", e);
+        format!("Failed to write {}: {}", output_path, e)
+    })?;
+
+    info!("Exported {} MCP servers", config.mcp_servers.len());
+    Ok(format!(
+        "Exported {} MCP servers to {}",
+        config.mcp_servers.len(),
+        output_path
+    ))
+}
+
+/// A `.mcp.json` read for in-place edits
+/// Server entries stay untyped so unknown fields survive; the IndexMaps keep the file's order
+#[derive(Debug, Serialize, Deserialize)]
+struct ProjectConfigDocument {
+    #[serde(rename = "mcpServers")]
+    mcp_servers: IndexMap<String, serde_json::Value>,
+    #[serde(flatten)]
+    other: IndexMap<String, serde_json::Value>,
+}
+
+/// Renames a server key inside a parsed `.mcp.json`, keeping its position and config
+fn rename_server_in_project_config(
+    config: &mut ProjectConfigDocument,
+    old_name: &str,
+    new_name: &str,
+) -> Result<(), String> {
+    let servers = &mut config.mcp_servers;
+
+    if !servers.contains_key(old_name) {
+        return Err(format!("Server '{}' not found in .mcp.json", old_name));
+    }
+    if servers.contains_key(new_name) {
+        return Err(format!("Server '{}' already exists in .mcp.json", new_name));
+    }
+
+    // Rebuild the map so the renamed entry stays where it was
+    let entries = std::mem::take(servers);
+    *servers = entries
+        .into_iter()
+        .map(|(name, entry)| {
+            if name == old_name {
+                (new_name.to_string(), entry)
+            } else {
+                (name, entry)
+            }
+        })
+        .collect();
+
+    Ok(())
+}
+
+/// Renames an MCP server, keeping its configuration and scope
+#[tauri::command]
+pub async fn mcp_rename(
+    app: AppHandle,
+    old_name: String,
+    new_name: String,
+    scope: String,
+    project_path: Option<String>,
+) -> Result<AddServerResult, String> {
+    info!("Renaming MCP server {} -> {} in scope {}", old_name, new_name, scope);
+
+    if old_name == new_name {
+        return Ok(AddServerResult {
+            success: true,
+            message: "Name unchanged".to_string(),
+            server_name: Some(new_name),
+        });
+    }
+
+    if scope == "project" {
+        // Edit .mcp.json in place so the server keeps its position in the shared file
+        let project_dir = match project_path {
+            Some(path) => PathBuf::from(path),
+            None => std::env::current_dir().map_err(|e| e.to_string())?,
+        };
+        let mcp_json_path = project_dir.join(".mcp.json");
+
+        let content = fs::read_to_string(&mcp_json_path)
+            .map_err(|e| format!("Failed to read .mcp.json: {}", e))?;
+        let mut config: ProjectConfigDocument = serde_json::from_str(&strip_jsonc(&content))
+            .map_err(|e| format!("Failed to parse .mcp.json: {}", e))?;
+
+        if let Err(message) = rename_server_in_project_config(&mut config, &old_name, &new_name) {
+            return Ok(AddServerResult {
+                success: false,
+                message,
+                server_name: None,
+            });
+        }
+
+        let json_content = serde_json::to_string_pretty(&config)
+            .map_err(|e| format!("Failed to serialize config: {}", e))?;
+        write_project_config(&mcp_json_path, &json_content)?;
+        invalidate_list_cache(&app);
+
+        info!("Renamed project MCP server {} -> {}", old_name, new_name);
+        return Ok(AddServerResult {
+            success: true,
+            message: format!("Renamed '{}' to '{}'", old_name, new_name),
+            server_name: Some(new_name),
+        });
+    }
+
+    // CLI-managed scopes: re-add under the new name, rolling back on failure
+    let server = mcp_get(app.clone(), old_name.clone()).await?;
+    mcp_update(
+        app,
+        old_name,
+        new_name,
+        server.transport,
+        server.command,
+        server.args,
+        server.env,
+        server.url,
+        Some(server.headers),
+        scope,
+        None,
+    )
+    .await
+}
+
+/// Moves `server` to `to_scope`: `remove` drops it from its current scope, then `add` re-adds
+/// it with the same transport, command, args, env, url and headers
+/// If the re-add fails, the original is added back (with `force`, as it was already accepted)
+async fn migrate_server_with<R, A, Fut>(
+    server: MCPServer,
+    to_scope: &str,
+    remove: R,
+    mut add: A,
+) -> AddServerResult
+where
+    R: FnOnce() -> Result<String, McpError>,
+    A: FnMut(MCPServer, bool) -> Fut,
+    Fut: std::future::Future<Output = Result<AddServerResult, McpError>>,
+{
+    let name = server.name.clone();
+    let from_scope = server.scope.clone();
+
+    if let Err(e) = remove() {
+        error!("Failed to remove {} from {} scope: {}", name, from_scope, e);
+        return AddServerResult {
+            success: false,
+            message: format!("Failed to remove '{}' from {} scope: {}", name, from_scope, e),
+            server_name: None,
+        };
+    }
+
+    let mut migrated = server.clone();
+    migrated.scope = to_scope.to_string();
+    let failure = match add(migrated, false).await {
+        Ok(result) if result.success => {
+            info!("Moved MCP server {} from {} to {} scope", name, from_scope, to_scope);
+            return AddServerResult {
+                success: true,
+                message: format!("Moved '{}' from {} to {} scope", name, from_scope, to_scope),
+                server_name: Some(name),
+            };
+        }
+        Ok(result) => result.message,
+        Err(e) => e.to_string(),
+    };
+
+    error!(
+        "Failed to add {} at {} scope, restoring it at {} scope: {}",
+        name, to_scope, from_scope, failure
+    );
+    let message = match add(server, true).await {
+        Ok(restored) if restored.success => format!(
+            "Migration failed: {}. Restored '{}' at {} scope",
+            failure, name, from_scope
+        ),
+        Ok(restored) => format!(
+            "Migration failed: {}. Restoring '{}' at {} scope also failed: {}",
+            failure, name, from_scope, restored.message
+        ),
+        Err(e) => format!(
+            "Migration failed: {}. Restoring '{}' at {} scope also failed: {}",
+            failure, name, from_scope, e
+        ),
+    };
+
+    AddServerResult {
+        success: false,
+        message,
+        server_name: None,
+    }
+}
+
+/// Moves an MCP server from one scope to another, e.g. promoting a local experiment to project
+#[tauri::command(rename_all = "snake_case")]
+pub async fn mcp_migrate_scope(
+    app: AppHandle,
+    name: String,
+    from_scope: String,
+    to_scope: String,
+) -> Result<AddServerResult, String> {
+    info!("Migrating MCP server {} from {} to {} scope", name, from_scope, to_scope);
+
+    let from_scope = parse_scope_filter(Some(&from_scope))?.unwrap_or_default();
+    let to_scope = parse_scope_filter(Some(&to_scope))?.unwrap_or_default();
+    if from_scope == to_scope {
+        return Ok(AddServerResult {
+            success: true,
+            message: format!("'{}' is already in {} scope", name, to_scope),
+            server_name: Some(name),
+        });
+    }
+
+    let server = mcp_get(app.clone(), name.clone()).await?;
+    if server.scope != from_scope {
+        return Ok(AddServerResult {
+            success: false,
+            message: format!(
+                "Server '{}' is in {} scope, not {}",
+                name, server.scope, from_scope
+            ),
+            server_name: None,
+        });
+    }
+
+    let result = migrate_server_with(
+        server,
+        &to_scope,
+        || execute_claude_mcp_command(&app, vec!["remove", &name, "-s", &from_scope]),
+        |server, force| {
+            mcp_add(
+                app.clone(),
+                server.name,
+                server.transport,
+                server.command,
+                server.args,
+                server.env,
+                server.url,
+                Some(server.headers),
+                server.scope,
+                Some(force),
+                None,
+            )
+        },
+    )
+    .await;
+    invalidate_list_cache(&app);
+    Ok(result)
+}
+
+/// Number of `.mcp.json.bak*` files kept next to the project config
+const MCP_JSON_BACKUP_COUNT: usize = 3;
+
+/// Path of the n-th backup: `.mcp.json.bak`, `.mcp.json.bak.1`, `.mcp.json.bak.2`, ...
+fn backup_path(path: &Path, index: usize) -> PathBuf {
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    if index == 0 {
+        path.with_file_name(format!("{}.bak", file_name))
+    } else {
+        path.with_file_name(format!("{}.bak.{}", file_name, index))
+    }
+}
+
+/// Shifts existing backups down by one slot, dropping the oldest
+fn rotate_backups(path: &Path, keep: usize) {
+    if keep == 0 {
+        return;
+    }
+    for index in (0..keep - 1).rev() {
+        let from = backup_path(path, index);
+        if from.exists() {
+            if let Err(e) = fs::rename(&from, backup_path(path, index + 1)) {
+                warn!("Failed to rotate backup {:?}: {}", from, e);
+            }
+        }
+    }
+}
+
+/// Writes the config via a temp file and atomic rename, keeping the previous version as a backup
+/// The backup is only taken once the new content is on disk; if anything fails, the original
+/// file is left untouched
+fn write_project_config(path: &Path, content: &str) -> Result<(), String> {
+    write_atomic_with(path, content.as_bytes(), |temp_path, path| {
+        if path.exists() {
+            rotate_backups(path, MCP_JSON_BACKUP_COUNT);
+            fs::copy(path, backup_path(path, 0))?;
+        }
+        fs::rename(temp_path, path)
+    })
+    .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Saves .mcp.json to the current project
+/// With `verify`, the written file is checked and servers the CLI would reject are returned
+#[tauri::command]
+pub async fn mcp_save_project_config(
+    project_path: String,
+    config: MCPProjectConfig,
+    verify: Option<bool>,
+) -> Result<ProjectConfigSaveResult, String> {
+    info!("Saving .mcp.json to project: {}", project_path);
+
+    let mcp_json_path = PathBuf::from(&project_path).join(".mcp.json");
+
+    let json_content = serde_json::to_string_pretty(&config)
+        .map_err(|e| format!("Failed to serialize config: {}", e))?;
+
+    write_project_config(&mcp_json_path, &json_content).map_err(|e| {
+        error!("Failed to save .mcp.json: {}", e);
+        e
+    })?;
+
+    let rejected = if verify.unwrap_or(false) {
+        let saved = fs::read_to_string(&mcp_json_path)
+            .map_err(|e| format!("Failed to read back .mcp.json: {}", e))?;
+        find_rejected_servers(&saved)
+    } else {
+        Vec::new()
+    };
+    for server in &rejected {
+        warn!(
+            "Saved .mcp.json contains a server claude will reject: {}",
+            server.errors.join("; ")
+        );
+    }
+
+    Ok(ProjectConfigSaveResult {
+        message: "Project MCP configuration saved".to_string(),
+        rejected,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Spawn a shell script as a fake stdio server
+    #[cfg(unix)]
+    fn spawn_fake_server(script: &str) -> tokio::process::Child {
+        tokio::process::Command::new("sh")
+            .args(["-c", script])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .unwrap()
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_pump_server_logs_delivers_lines_in_order() {
+        let child = spawn_fake_server("echo one; echo two; echo oops >&2; echo three");
+        let (_stop_tx, stop_rx) = tokio::sync::oneshot::channel();
+        let mut events = Vec::new();
+
+        pump_server_logs("fake".to_string(), child, stop_rx, |event| events.push(event)).await;
+
+        let lines = |stream: &str| -> Vec<String> {
+            events
+                .iter()
+                .filter(|e| e.stream == stream)
+                .map(|e| e.line.clone())
+                .collect()
+        };
+        assert_eq!(lines("stdout"), vec!["one", "two", "three"]);
+        assert_eq!(lines("stderr"), vec!["oops"]);
+        assert!(events.iter().all(|e| e.server == "fake"));
+
+        let last = events.last().unwrap();
+        assert_eq!(last.stream, "system");
+        assert!(last.line.starts_with("Server exited"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_pump_server_logs_stops_on_request() {
+        let child = spawn_fake_server("echo ready; sleep 30");
+        let (stop_tx, stop_rx) = tokio::sync::oneshot::channel();
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = events.clone();
+
+        let pump = tokio::spawn(pump_server_logs("fake".to_string(), child, stop_rx, move |event| {
+            sink.lock().unwrap().push(event);
+        }));
+
+        // Wait for the first line, then stop
+        for _ in 0..50 {
+            if !events.lock().unwrap().is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        stop_tx.send(()).unwrap();
+        tokio::time::timeout(Duration::from_secs(5), pump).await.unwrap().unwrap();
+
+        let events = events.lock().unwrap();
+        assert_eq!(events[0].line, "ready");
+        assert_eq!(events.last().unwrap().line, "Log streaming stopped");
+    }
+
+    #[test]
+    fn test_parse_mcp_get_output_without_env() {
+        let output = "my-server:\n  Scope: Local config (private to you in this project)\n  Status: ✓ Connected\n  Type: stdio\n  Command: npx\n  Args: -y @scope/server\n";
+
+        let server = parse_mcp_get_output("my-server".to_string(), output);
+        assert_eq!(server.scope, "local");
+        assert_eq!(server.command.as_deref(), Some("npx"));
+        assert_eq!(server.args, vec!["-y", "@scope/server"]);
+        assert!(server.env.is_empty());
+    }
+
+    #[test]
+    fn test_parse_mcp_get_output_from_colored_output_with_bom() {
+        let mut raw = vec![0xEF, 0xBB, 0xBF];
+        raw.extend_from_slice(
+            concat!(
+                "\u{1b}[1mmy-server\u{1b}[22m:\n",
+                "  Scope: \u{1b}[36mUser config\u{1b}[39m (available in all your projects)\n",
+                "  Status: \u{1b}[32m✓ Connected\u{1b}[39m\n",
+                "  Type: stdio\n",
+                "  Command: \u{1b}[1mnode\u{1b}[22m\n",
+                "  Environment: API_KEY=abc123\n",
+            )
+            .as_bytes(),
+        );
+
+        let output = crate::claude_binary::decode_command_output(&raw);
+        let server = parse_mcp_get_output("my-server".to_string(), &output);
+        assert_eq!(server.scope, "user");
+        assert_eq!(server.transport, "stdio");
+        assert_eq!(server.command.as_deref(), Some("node"));
+        assert_eq!(server.env.get("API_KEY").map(String::as_str), Some("abc123"));
+    }
+
+    #[test]
+    fn test_parse_mcp_get_output_single_line_env() {
+        let output = "my-server:\n  Scope: User config (available in all your projects)\n  Type: stdio\n  Command: node\n  Environment: API_KEY=abc123\n";
+
+        let server = parse_mcp_get_output("my-server".to_string(), output);
+        assert_eq!(server.scope, "user");
+        assert_eq!(server.env.len(), 1);
+        assert_eq!(server.env.get("API_KEY").map(String::as_str), Some("abc123"));
+    }
+
+    #[test]
+    fn test_parse_mcp_get_output_multi_line_env() {
+        let output = "my-server:\n  Scope: Project config (shared via .mcp.json)\n  Type: stdio\n  Command: node\n  Environment:\n    API_KEY = abc123\n    DATABASE_URL=postgres://u:p@host/db?sslmode=require\n    EMPTY=\n\nTo remove this server, run: claude mcp remove \"my-server\" -s project\n";
+
+        let server = parse_mcp_get_output("my-server".to_string(), output);
+        assert_eq!(server.scope, "project");
+        assert_eq!(server.env.len(), 3);
+        assert_eq!(server.env.get("API_KEY").map(String::as_str), Some("abc123"));
+        assert_eq!(
+            server.env.get("DATABASE_URL").map(String::as_str),
+            Some("postgres://u:p@host/db?sslmode=require")
+        );
+        assert_eq!(server.env.get("EMPTY").map(String::as_str), Some(""));
+    }
+
+    #[test]
+    fn test_parse_mcp_get_output_http_transport() {
+        let output = "remote:\n  Scope: User config (available in all your projects)\n  Type: streamable-http\n  URL: https://example.com/mcp\n";
+
+        let server = parse_mcp_get_output("remote".to_string(), output);
+        assert_eq!(server.transport, "http");
+        assert_eq!(server.url.as_deref(), Some("https://example.com/mcp"));
+        assert!(server.command.is_none());
+    }
+
+    #[test]
+    fn test_parse_mcp_get_output_sse_headers() {
+        let output = "remote:\n  Scope: User config (available in all your projects)\n  Status: ✓ Connected\n  Type: sse\n  URL: https://example.com/sse\n  Headers:\n    Authorization: Bearer abc123\n    X-Callback-Url: https://example.com:8443/hook?a=b\n    X-Empty:\n\nTo remove this server, run: claude mcp remove \"remote\" -s user\n";
+
+        let server = parse_mcp_get_output("remote".to_string(), output);
+        assert_eq!(server.transport, "sse");
+        assert_eq!(server.url.as_deref(), Some("https://example.com/sse"));
+        assert_eq!(server.headers.len(), 3);
+        assert_eq!(
+            server.headers.get("Authorization").map(String::as_str),
+            Some("Bearer abc123")
+        );
+        assert_eq!(
+            server.headers.get("X-Callback-Url").map(String::as_str),
+            Some("https://example.com:8443/hook?a=b")
+        );
+        assert_eq!(server.headers.get("X-Empty").map(String::as_str), Some(""));
+
+        // The headers feed straight back into `claude mcp add` on update
+        let cmd_args = build_mcp_add_args(
+            &server.name,
+            &server.transport,
+            None,
+            &[],
+            &HashMap::new(),
+            server.url.as_deref(),
+            &server.headers,
+            &server.scope,
+        )
+        .unwrap();
+        assert!(cmd_args.contains(&"Authorization: Bearer abc123".to_string()));
+        assert!(cmd_args.contains(&"X-Callback-Url: https://example.com:8443/hook?a=b".to_string()));
+    }
+
+    #[test]
+    fn test_parse_mcp_get_output_inline_headers_section_ends() {
+        let output = "remote:\n  Type: http\n  Headers: Authorization: Bearer abc\n    X-Team: core\n  URL: https://example.com/mcp\n  Status: ✓ Connected\n";
+
+        let server = parse_mcp_get_output("remote".to_string(), output);
+        assert_eq!(server.headers.len(), 2);
+        assert_eq!(server.headers.get("Authorization").map(String::as_str), Some("Bearer abc"));
+        assert_eq!(server.headers.get("X-Team").map(String::as_str), Some("core"));
+        assert_eq!(server.url.as_deref(), Some("https://example.com/mcp"));
+        assert!(server.is_active);
+    }
+
+    #[test]
+    fn test_http_server_config_round_trip() {
+        let json = r#"{"mcpServers":{"remote":{"type":"http","url":"https://example.com/mcp","headers":{"Authorization":"Bearer token"}}}}"#;
+
+        let config: MCPProjectConfig = serde_json::from_str(json).unwrap();
+        let server = &config.mcp_servers["remote"];
+        assert_eq!(server.transport_type, "http");
+        assert!(server.command.is_empty());
+        assert_eq!(server.url.as_deref(), Some("https://example.com/mcp"));
+        assert_eq!(
+            server.headers.as_ref().and_then(|h| h.get("Authorization")).map(String::as_str),
+            Some("Bearer token")
+        );
+
+        let serialized = serde_json::to_value(&config).unwrap();
+        let entry = &serialized["mcpServers"]["remote"];
+        assert_eq!(entry["type"], "http");
+        assert_eq!(entry["url"], "https://example.com/mcp");
+        assert!(entry.get("command").is_none());
+
+        let reparsed: MCPProjectConfig = serde_json::from_value(serialized).unwrap();
+        assert_eq!(reparsed.mcp_servers["remote"].transport_type, "http");
+    }
+
+    #[test]
+    fn test_parse_mcp_list_text_legacy_output() {
+        let output = "Checking MCP server health...\n\nfilesystem: npx -y @modelcontextprotocol/server-filesystem /tmp - ✓ Connected\nremote: https://example.com/sse (SSE) - ✗ Failed to connect";
+
+        let names = parse_mcp_list_text(output);
+        assert_eq!(
+            names,
+            vec![("filesystem".to_string(), None), ("remote".to_string(), None)]
+        );
+    }
+
+    #[test]
+    fn test_mcp_error_serializes_with_kind_tag() {
+        let cases = [
+            (McpError::binary_not_found("no claude on PATH"), "BinaryNotFound"),
+            (McpError::command_failed("boom\n"), "CommandFailed"),
+            (McpError::timeout(Duration::from_secs(120)), "Timeout"),
+            (McpError::parse("Invalid scope filter 'x'"), "ParseError"),
+            (McpError::duplicate_name("fs", "user"), "DuplicateName"),
+            (McpError::not_found("fs"), "NotFound"),
+            (McpError::io("disk full"), "Io"),
+        ];
+
+        for (error, kind) in cases {
+            let value = serde_json::to_value(&error).unwrap();
+            assert_eq!(value["kind"], kind);
+            assert_eq!(value["message"], error.message());
+            assert!(!error.message().is_empty());
+
+            let round_trip: McpError = serde_json::from_value(value).unwrap();
+            assert_eq!(round_trip, error);
+        }
+
+        let value = serde_json::to_value(McpError::command_failed("boom\n")).unwrap();
+        assert_eq!(value["stderr"], "boom\n");
+        assert_eq!(value["message"], "Command failed: boom");
+
+        let value = serde_json::to_value(McpError::duplicate_name("fs", "user")).unwrap();
+        assert_eq!(value["name"], "fs");
+        assert_eq!(value["scope"], "user");
+
+        let value = serde_json::to_value(McpError::timeout(Duration::from_secs(120))).unwrap();
+        assert_eq!(value["timeout_secs"], 120);
+    }
+
+    #[test]
+    fn test_mcp_error_classifies_cli_failures() {
+        let missing = McpError::command_failed("No MCP server found with name: fs");
+        assert_eq!(missing.or_not_found("fs"), McpError::not_found("fs"));
+
+        let taken = McpError::command_failed("MCP server fs already exists in user config");
+        assert_eq!(
+            taken.or_duplicate("fs", "user"),
+            McpError::duplicate_name("fs", "user")
+        );
+
+        // Unrelated failures and non-CLI errors pass through unchanged
+        let other = McpError::command_failed("permission denied");
+        assert_eq!(other.clone().or_not_found("fs"), other);
+        for broken in ["/bin/sh: claude: command not found", "env: node: not found"] {
+            let broken = McpError::command_failed(broken);
+            assert_eq!(broken.clone().or_not_found("fs"), broken);
+        }
+        let timeout = McpError::timeout(Duration::from_secs(1));
+        assert_eq!(timeout.clone().or_duplicate("fs", "user"), timeout);
+
+        assert_eq!(String::from(McpError::not_found("fs")), "MCP server 'fs' not found");
+    }
+
+    #[test]
+    fn test_clean_command_string_strips_english_status() {
+        for (input, expected) in [
+            ("npx -y server - ✓ Connected", "npx -y server"),
+            ("npx -y server - ✗ Failed to connect", "npx -y server"),
+            ("npx -y server - ✓ connected", "npx -y server"),
+            ("npx -y server - ✗ failed", "npx -y server"),
+            ("npx -y server - ✓", "npx -y server"),
+            ("https://example.com/sse (SSE) - ✗", "https://example.com/sse (SSE)"),
+        ] {
+            assert_eq!(clean_command_string(input), expected);
+        }
+    }
+
+    #[test]
+    fn test_clean_command_string_strips_other_status_wording() {
+        for (input, expected) in [
+            ("uvx mcp-server-git - ⚠ degraded", "uvx mcp-server-git"),
+            ("uvx mcp-server-git - ✔ Verbunden", "uvx mcp-server-git"),
+            ("uvx mcp-server-git  -  ✘ Échec de la connexion", "uvx mcp-server-git"),
+            ("node server.js - ⏳ Pending approval", "node server.js"),
+        ] {
+            assert_eq!(clean_command_string(input), expected);
+        }
+
+        // A plain " - " argument is part of the command, not a status
+        assert_eq!(clean_command_string("cat - input.txt"), "cat - input.txt");
+        assert_eq!(clean_command_string("  node\t server.js  "), "node server.js");
+    }
+
+    #[test]
+    fn test_parse_mcp_list_text_dedupes_names() {
+        let output = "filesystem: npx server - ✓ Connected\nfilesystem: npx server - ⚠ degraded\n  my  server : node s.js - ✓ Connected";
+        let names: Vec<String> = parse_mcp_list_text(output)
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        assert_eq!(names, vec!["filesystem", "my server"]);
+    }
+
+    #[test]
+    fn test_parse_mcp_list_text_picks_up_scope_hints() {
+        let output = "local-db: /usr/local/bin/db-server - ✓ Connected (local)\nshared: npx shared-server [Project config] - ✓ Connected\nremote: https://user.example.com/sse (SSE) scope: user - ✗ Failed";
+
+        let entries = parse_mcp_list_text(output);
+        assert_eq!(
+            entries,
+            vec![
+                ("local-db".to_string(), Some("local".to_string())),
+                ("shared".to_string(), Some("project".to_string())),
+                ("remote".to_string(), Some("user".to_string())),
+            ]
+        );
+
+        // Paths and hosts mentioning a scope word are not scope markers
+        let entries = parse_mcp_list_text("fs: /usr/local/bin/fs --user me - ✓ Connected");
+        assert_eq!(entries, vec![("fs".to_string(), None)]);
+    }
+
+    #[test]
+    fn test_filter_servers_by_scope() {
+        let servers = || {
+            vec![
+                test_server("a", "stdio", "local"),
+                test_server("b", "sse", "project"),
+                test_server("c", "http", "user"),
+                test_server("d", "stdio", "project"),
+            ]
+        };
+        let names = |servers: Vec<MCPServer>| {
+            servers.into_iter().map(|s| s.name).collect::<Vec<_>>()
+        };
+
+        for (filter, expected) in [
+            ("local", vec!["a"]),
+            ("project", vec!["b", "d"]),
+            ("user", vec!["c"]),
+        ] {
+            let scope = parse_scope_filter(Some(filter)).unwrap();
+            assert_eq!(names(filter_servers_by_scope(servers(), scope.as_deref())), expected);
+        }
+
+        let scope = parse_scope_filter(None).unwrap();
+        assert_eq!(
+            names(filter_servers_by_scope(servers(), scope.as_deref())),
+            vec!["a", "b", "c", "d"]
+        );
+    }
+
+    #[test]
+    fn test_parse_scope_filter() {
+        assert_eq!(parse_scope_filter(Some(" Project ")).unwrap().as_deref(), Some("project"));
+        assert!(parse_scope_filter(Some("global")).is_err());
+        assert!(parse_scope_filter(Some("")).is_err());
+    }
+
+    #[test]
+    fn test_parse_mcp_list_json_object() {
+        let output = r#"{
+            "filesystem": {
+                "type": "stdio",
+                "command": "npx",
+                "args": ["-y", "@modelcontextprotocol/server-filesystem"],
+                "env": {"ROOT": "/tmp"},
+                "scope": "project",
+                "status": "connected"
+            },
+            "remote": {
+                "type": "http",
+                "url": "https://example.com/mcp",
+                "headers": {"Authorization": "Bearer token"},
+                "scope": "user",
+                "status": "✗ failed"
+            }
+        }"#;
+
+        let mut servers = parse_mcp_list_json(output).unwrap();
+        servers.sort_by(|a, b| a.name.cmp(&b.name));
+
+        assert_eq!(servers.len(), 2);
+        assert_eq!(servers[0].name, "filesystem");
+        assert_eq!(servers[0].scope, "project");
+        assert_eq!(servers[0].args, vec!["-y", "@modelcontextprotocol/server-filesystem"]);
+        assert_eq!(servers[0].env.get("ROOT").map(String::as_str), Some("/tmp"));
+        assert!(servers[0].is_active);
+
+        assert_eq!(servers[1].transport, "http");
+        assert_eq!(servers[1].scope, "user");
+        assert_eq!(
+            servers[1].headers.get("Authorization").map(String::as_str),
+            Some("Bearer token")
+        );
+        assert!(!servers[1].is_active);
+    }
+
+    #[test]
+    fn test_parse_mcp_list_json_array() {
+        let output = r#"[{"name": "remote", "url": "https://example.com/sse", "scope": "local"}]"#;
+
+        let servers = parse_mcp_list_json(output).unwrap();
+        assert_eq!(servers.len(), 1);
+        assert_eq!(servers[0].transport, "sse");
+        assert_eq!(servers[0].url.as_deref(), Some("https://example.com/sse"));
+    }
+
+    #[test]
+    fn test_parse_mcp_list_json_rejects_text() {
+        assert!(parse_mcp_list_json("filesystem: npx server - ✓ Connected").is_err());
+    }
+
+    #[test]
+    fn test_is_connected_status_matches_whole_status() {
+        assert!(is_connected_status("connected"));
+        assert!(is_connected_status(" Connected "));
+        assert!(is_connected_status("✓ Connected"));
+        assert!(!is_connected_status("disconnected"));
+        assert!(!is_connected_status("Not connected"));
+        assert!(!is_connected_status("✗ Failed to connect"));
+        assert!(!is_connected_status(""));
+    }
+
+    fn test_server(name: &str, transport: &str, scope: &str) -> MCPServer {
+        MCPServer {
+            name: name.to_string(),
+            transport: transport.to_string(),
+            command: None,
+            args: vec![],
+            env: HashMap::new(),
+            url: None,
+            headers: HashMap::new(),
+            scope: scope.to_string(),
+            is_active: true,
+            status: ServerStatus {
+                running: true,
+                error: None,
+                last_checked: None,
+            },
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_servers_reports_mixed_reachability() {
+        let app = axum::Router::new().route("/mcp", axum::routing::get(|| async { "ok" }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let live_addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        let closed_addr = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+
+        let mut stdio_ok = test_server("stdio-ok", "stdio", "local");
+        stdio_ok.command = Some("sh".to_string());
+        stdio_ok.args = vec![
+            "-c".to_string(),
+            r#"read line; echo '{"jsonrpc":"2.0","id":1,"result":{"serverInfo":{"name":"fake"}}}'"#
+                .to_string(),
+        ];
+        let mut stdio_missing = test_server("stdio-missing", "stdio", "user");
+        stdio_missing.command = Some("/nonexistent/mcp-server".to_string());
+        let mut http_ok = test_server("http-ok", "http", "project");
+        http_ok.url = Some(format!("http://{}/mcp", live_addr));
+        let mut http_down = test_server("http-down", "sse", "local");
+        http_down.url = Some(format!("http://{}/sse", closed_addr));
+
+        let servers = vec![stdio_ok, stdio_missing, http_ok, http_down];
+        let results =
+            test_servers_with(servers, 2, |server| async move { probe_server(&server).await }).await;
+
+        let summary: Vec<(&str, &str, bool)> = results
+            .iter()
+            .map(|r| (r.name.as_str(), r.scope.as_str(), r.reachable))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("stdio-ok", "local", true),
+                ("stdio-missing", "user", false),
+                ("http-ok", "project", true),
+                ("http-down", "local", false),
+            ]
+        );
+        assert!(results[0].error.is_none());
+        assert!(results[1].error.as_deref().unwrap().contains("Failed to spawn"));
+        assert!(results[3].error.as_deref().unwrap().contains("failed"));
+    }
+
+    #[tokio::test]
+    async fn test_servers_bounds_parallel_probes() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let servers = (0..6)
+            .map(|i| test_server(&format!("server-{}", i), "stdio", "local"))
+            .collect();
+
+        let results = test_servers_with(servers, 2, |server| {
+            let in_flight = in_flight.clone();
+            let peak = peak.clone();
+            async move {
+                let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                if server.name == "server-3" {
+                    Err("boom".to_string())
+                } else {
+                    Ok("ok".to_string())
+                }
+            }
+        })
+        .await;
+
+        assert_eq!(results.len(), 6);
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+        assert_eq!(results.iter().filter(|r| !r.reachable).count(), 1);
+        assert_eq!(results[3].error.as_deref(), Some("boom"));
+    }
+
+    #[test]
+    fn test_export_round_trip() {
+        let mut local = test_server("filesystem", "stdio", "local");
+        local.command = Some("npx".to_string());
+        local.args = vec!["-y".to_string(), "server-filesystem".to_string()];
+        local.env.insert("ROOT".to_string(), "/tmp".to_string());
+
+        let mut remote = test_server("remote", "sse", "user");
+        remote.url = Some("https://example.com/sse".to_string());
+        remote.headers.insert("Authorization".to_string(), "Bearer token".to_string());
+
+        let servers = vec![local, remote];
+        let exported =
+            serde_json::to_string_pretty(&build_export_config(&servers, None)).unwrap();
+
+        // Re-import into an empty config
+        let imported: MCPProjectConfig = serde_json::from_str(&exported).unwrap();
+        let mut names: Vec<_> = imported.mcp_servers.keys().cloned().collect();
+        names.sort();
+        assert_eq!(names, vec!["filesystem", "remote"]);
+
+        let filesystem = &imported.mcp_servers["filesystem"];
+        assert_eq!(filesystem.command, "npx");
+        assert_eq!(filesystem.args, vec!["-y", "server-filesystem"]);
+        assert_eq!(filesystem.env.get("ROOT").map(String::as_str), Some("/tmp"));
+
+        let remote = &imported.mcp_servers["remote"];
+        assert_eq!(remote.transport_type, "sse");
+        assert_eq!(remote.url.as_deref(), Some("https://example.com/sse"));
+        assert_eq!(
+            remote.headers.as_ref().and_then(|h| h.get("Authorization")).map(String::as_str),
+            Some("Bearer token")
+        );
+    }
+
+    #[test]
+    fn test_export_filters_by_scope() {
+        let servers = vec![
+            test_server("a", "stdio", "local"),
+            test_server("b", "stdio", "user"),
+        ];
+
+        let config = build_export_config(&servers, Some("user"));
+        assert_eq!(config.mcp_servers.len(), 1);
+        assert!(config.mcp_servers.contains_key("b"));
+    }
+
+    #[tokio::test]
+    async fn test_update_server_restores_original_when_add_fails() {
+        let mut original = test_server("github", "stdio", "local");
+        original.command = Some("github-mcp".to_string());
+        original.env.insert("GITHUB_TOKEN".to_string(), "secret".to_string());
+        let mut updated = original.clone();
+        updated.name = "gh".to_string();
+        updated.command = Some("/nonexistent/github-mcp".to_string());
+
+        let removed = std::cell::Cell::new(false);
+        let added = Mutex::new(Vec::new());
+        let result = update_server_with(
+            original.clone(),
+            updated,
+            || {
+                removed.set(true);
+                Ok("removed".to_string())
+            },
+            |server, force| {
+                added.lock().unwrap().push((server.clone(), force));
+                async move {
+                    Ok(AddServerResult {
+                        success: server.name == "github",
+                        message: "command not executable".to_string(),
+                        server_name: None,
+                    })
+                }
+            },
+        )
+        .await;
+
+        assert!(removed.get());
+        assert!(!result.success);
+        assert!(result.message.contains("command not executable"), "{}", result.message);
+        assert!(result.message.contains("Rolled back"), "{}", result.message);
+
+        let added = added.into_inner().unwrap();
+        assert_eq!(added.len(), 2);
+        assert_eq!((added[0].0.name.as_str(), added[0].1), ("gh", false));
+        let (restored, force) = &added[1];
+        assert!(force);
+        assert_eq!(restored.name, "github");
+        assert_eq!(restored.scope, "local");
+        assert_eq!(restored.command, original.command);
+        assert_eq!(restored.env, original.env);
+    }
+
+    #[tokio::test]
+    async fn test_update_server_stops_when_remove_fails() {
+        let original = test_server("github", "stdio", "local");
+        let add_called = std::cell::Cell::new(false);
+        let result = update_server_with(
+            original.clone(),
+            original,
+            || Err(McpError::command_failed("permission denied")),
+            |server, _| {
+                add_called.set(true);
+                async move {
+                    Ok(AddServerResult {
+                        success: true,
+                        message: "added".to_string(),
+                        server_name: Some(server.name),
+                    })
+                }
+            },
+        )
+        .await;
+
+        assert!(!add_called.get());
+        assert!(!result.success);
+        assert!(result.message.contains("Failed to remove old server"));
+    }
+
+    #[tokio::test]
+    async fn test_migrate_server_keeps_config_across_scopes() {
+        let mut stdio = test_server("github", "stdio", "local");
+        stdio.command = Some("npx".to_string());
+        stdio.args = vec!["-y".to_string(), "@modelcontextprotocol/server-github".to_string()];
+        stdio.env.insert("GITHUB_TOKEN".to_string(), "secret".to_string());
+        let mut sse = test_server("docs", "sse", "user");
+        sse.url = Some("https://example.com/sse".to_string());
+        sse.headers.insert("Authorization".to_string(), "Bearer abc".to_string());
+
+        for (server, to_scope) in [(stdio, "project"), (sse, "local")] {
+            let removed = std::cell::Cell::new(false);
+            let added = Mutex::new(Vec::new());
+            let result = migrate_server_with(
+                server.clone(),
+                to_scope,
+                || {
+                    removed.set(true);
+                    Ok("removed".to_string())
+                },
+                |server, force| {
+                    added.lock().unwrap().push((server.clone(), force));
+                    async move {
+                        Ok(AddServerResult {
+                            success: true,
+                            message: "added".to_string(),
+                            server_name: Some(server.name),
+                        })
+                    }
+                },
+            )
+            .await;
+
+            assert!(result.success, "{}", result.message);
+            assert!(removed.get());
+            let added = added.into_inner().unwrap();
+            assert_eq!(added.len(), 1);
+            let (moved, force) = &added[0];
+            assert!(!force);
+            assert_eq!(moved.scope, to_scope);
+            assert_eq!(moved.name, server.name);
+            assert_eq!(moved.transport, server.transport);
+            assert_eq!(moved.command, server.command);
+            assert_eq!(moved.args, server.args);
+            assert_eq!(moved.env, server.env);
+            assert_eq!(moved.url, server.url);
+            assert_eq!(moved.headers, server.headers);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_migrate_server_restores_original_scope_on_failure() {
+        let mut sse = test_server("docs", "sse", "local");
+        sse.url = Some("https://example.com/sse".to_string());
+        sse.headers.insert("X-Api-Key".to_string(), "k".to_string());
+
+        let added = Mutex::new(Vec::new());
+        let result = migrate_server_with(
+            sse.clone(),
+            "project",
+            || Ok("removed".to_string()),
+            |server, force| {
+                added.lock().unwrap().push((server.scope.clone(), force, server.headers.clone()));
+                async move {
+                    Ok(AddServerResult {
+                        success: server.scope == "local",
+                        message: "project scope rejected".to_string(),
+                        server_name: None,
+                    })
+                }
+            },
+        )
+        .await;
+
+        assert!(!result.success);
+        assert!(result.message.contains("project scope rejected"));
+        assert!(result.message.contains("Restored 'docs' at local scope"));
+        let added = added.into_inner().unwrap();
+        assert_eq!(
+            added,
+            vec![
+                ("project".to_string(), false, sse.headers.clone()),
+                ("local".to_string(), true, sse.headers.clone()),
+            ]
+        );
+
+        // Nothing is re-added when the removal itself fails
+        let attempted = std::cell::Cell::new(false);
+        let result = migrate_server_with(
+            sse,
+            "project",
+            || Err(McpError::io("permission denied")),
+            |_, _| {
+                attempted.set(true);
+                async { Err(McpError::io("unexpected add")) }
+            },
+        )
+        .await;
+        assert!(!result.success);
+        assert!(result.message.contains("permission denied"));
+        assert!(!attempted.get());
+    }
+
+    #[test]
+    fn test_status_history_keeps_recent_checks_in_order() {
+        let history = MCPStatusHistory::with_capacity(3);
+        let check = |latency_ms: u64, error: Option<&str>| StatusCheck {
+            timestamp: 1_700_000_000 + latency_ms,
+            reachable: error.is_none(),
+            latency_ms,
+            error: error.map(str::to_string),
+        };
+
+        assert!(history.recent("flaky", None).is_empty());
+        history.record("flaky", check(10, None));
+        history.record("flaky", check(20, Some("timeout")));
+        history.record("steady", check(5, None));
+        history.record("flaky", check(30, None));
+        history.record("flaky", check(40, Some("connection refused")));
+        history.record("flaky", check(50, None));
+
+        let latencies: Vec<u64> = history
+            .recent("flaky", None)
+            .iter()
+            .map(|c| c.latency_ms)
+            .collect();
+        assert_eq!(latencies, vec![30, 40, 50]);
+        let newest = history.recent("flaky", Some(2));
+        assert_eq!(newest, vec![check(40, Some("connection refused")), check(50, None)]);
+        assert_eq!(history.recent("flaky", Some(10)).len(), 3);
+        assert_eq!(history.recent("steady", None), vec![check(5, None)]);
+    }
+
+    #[test]
+    fn test_scope_config_has_server_reads_only_target_scope() {
+        let home = tempfile::TempDir::new().unwrap();
+        let project = tempfile::TempDir::new().unwrap();
+        let project_key = fs::canonicalize(project.path()).unwrap();
+        let claude_json = serde_json::json!({
+            "mcpServers": {"github": {"type": "stdio", "command": "gh"}},
+            "projects": {
+                project_key.to_string_lossy().to_string(): {
+                    "mcpServers": {"local-db": {"type": "stdio", "command": "db"}}
+                }
+            }
+        });
+        fs::write(home.path().join(".claude.json"), claude_json.to_string()).unwrap();
+        fs::write(
+            project.path().join(".mcp.json"),
+            r#"{"mcpServers":{"docs":{"type":"http","url":"https://example.com"}}}"#,
+        )
+        .unwrap();
+
+        let has = |name: &str, scope: &str| {
+            scope_config_has_server(name, scope, home.path(), project.path())
+        };
+        assert!(has("github", "user"));
+        assert!(has("local-db", "local"));
+        assert!(has("docs", "project"));
+        // The same name may legitimately live in another scope
+        assert!(!has("github", "project"));
+        assert!(!has("docs", "user"));
+        assert!(!has("local-db", "user"));
+
+        // Another project's local servers don't count
+        let other = tempfile::TempDir::new().unwrap();
+        assert!(!scope_config_has_server("local-db", "local", home.path(), other.path()));
+        // Missing config files are not duplicates
+        let empty_home = tempfile::TempDir::new().unwrap();
+        assert!(!scope_config_has_server("github", "user", empty_home.path(), other.path()));
+    }
+
+    #[test]
+    fn test_disabled_servers_persist_across_reload() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join(".claude").join("disabled.json");
+
+        // Nothing stored yet
+        assert!(load_disabled_servers(&path).is_empty());
+
+        let mut server = test_server("github", "stdio", "user");
+        server.command = Some("github-mcp".to_string());
+        server.env.insert("TOKEN".to_string(), "secret".to_string());
+        let entry = DisabledServer {
+            server,
+            project_path: None,
+        };
+        save_disabled_servers(&path, &[entry]).unwrap();
+
+        // Simulate a restart by reading the sidecar back from disk
+        let reloaded = load_disabled_servers(&path);
+        assert_eq!(reloaded.len(), 1);
+        assert_eq!(reloaded[0].server.name, "github");
+        assert_eq!(reloaded[0].server.scope, "user");
+        assert_eq!(reloaded[0].server.env.get("TOKEN").map(String::as_str), Some("secret"));
+
+        // Re-enabling removes it from the sidecar
+        save_disabled_servers(&path, &[]).unwrap();
+        assert!(load_disabled_servers(&path).is_empty());
+    }
+
+    #[test]
+    fn test_disabled_servers_are_keyed_by_scope_and_project() {
+        let entry = |scope: &str, project: Option<&str>| DisabledServer {
+            server: test_server("db", "stdio", scope),
+            project_path: project.map(str::to_string),
+        };
+        let disabled = vec![
+            entry("project", Some("/work/app-a")),
+            entry("project", Some("/work/app-b")),
+            entry("user", None),
+        ];
+
+        // Each project only sees its own project-scoped snapshot
+        assert_eq!(
+            find_disabled_server(&disabled, "db", Some("project"), Some("/work/app-a")),
+            Ok(Some(0))
+        );
+        assert_eq!(
+            find_disabled_server(&disabled, "db", Some("project"), Some("/work/app-b")),
+            Ok(Some(1))
+        );
+        assert_eq!(
+            find_disabled_server(&disabled, "db", Some("project"), Some("/work/other")),
+            Ok(None)
+        );
+        assert_eq!(
+            find_disabled_server(&disabled, "db", Some("user"), Some("/work/other")),
+            Ok(Some(2))
+        );
+        // Without a scope the user and project snapshots can't be told apart
+        assert!(find_disabled_server(&disabled, "db", None, Some("/work/app-a")).is_err());
+        assert_eq!(
+            find_disabled_server(&disabled, "db", None, Some("/work/other")),
+            Ok(Some(2))
+        );
+    }
+
+    #[test]
+    fn test_disabled_servers_sidecar_records_project() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("disabled.json");
+
+        // Entries written before projects were recorded still load, visible everywhere
+        let legacy = serde_json::to_string(&vec![test_server("old", "stdio", "project")]).unwrap();
+        fs::write(&path, legacy).unwrap();
+        let loaded = load_disabled_servers(&path);
+        assert!(loaded[0].project_path.is_none());
+        assert!(loaded[0].applies_to(Some("/anywhere")));
+
+        let project = temp_dir.path().join("repo");
+        fs::create_dir_all(&project).unwrap();
+        let recorded = disabled_server_project("project", Some(&project)).unwrap();
+        assert_eq!(PathBuf::from(&recorded), fs::canonicalize(&project).unwrap());
+        assert!(disabled_server_project("user", Some(&project)).is_none());
+
+        let entry = DisabledServer {
+            server: test_server("db", "stdio", "local"),
+            project_path: Some(recorded.clone()),
+        };
+        save_disabled_servers(&path, &[entry]).unwrap();
+        let reloaded = load_disabled_servers(&path);
+        assert_eq!(reloaded[0].project_path.as_deref(), Some(recorded.as_str()));
+        assert!(!reloaded[0].applies_to(Some("/somewhere/else")));
+    }
+
+    #[test]
+    fn test_missing_claude_desktop_config_is_friendly() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("Claude").join("claude_desktop_config.json");
+
+        let err = read_claude_desktop_config(&path).unwrap_err();
+        assert!(err.contains("Make sure Claude Desktop is installed"));
+    }
+
+    #[cfg(target_os = "windows")]
+    #[test]
+    fn test_claude_desktop_config_path_windows() {
+        let path = claude_desktop_config_path().unwrap();
+        assert!(path.starts_with(dirs::config_dir().unwrap()));
+        assert!(path.ends_with("Claude\\claude_desktop_config.json"));
+    }
+
+    #[test]
+    fn test_desktop_entries_import_mixed_transports() {
+        let config: serde_json::Value = serde_json::from_str(
+            r#"{
+                "mcpServers": {
+                    "filesystem": {
+                        "command": "npx",
+                        "args": ["-y", "@modelcontextprotocol/server-filesystem"],
+                        "env": {"ROOT": "/tmp"}
+                    },
+                    "remote-sse": {
+                        "url": "https://example.com/sse",
+                        "headers": {"Authorization": "Bearer token"},
+                        "env": {"REGION": "eu"}
+                    },
+                    "remote-http": {
+                        "type": "http",
+                        "url": "https://example.com/mcp"
+                    },
+                    "broken": {}
+                }
+            }"#,
+        )
+        .unwrap();
+        let servers = &config["mcpServers"];
+
+        let filesystem = desktop_entry_to_json_config(&servers["filesystem"]).unwrap();
+        assert_eq!(filesystem["type"], "stdio");
+        assert_eq!(filesystem["command"], "npx");
+        assert_eq!(filesystem["env"]["ROOT"], "/tmp");
+
+        let sse = desktop_entry_to_json_config(&servers["remote-sse"]).unwrap();
+        assert_eq!(sse["type"], "sse");
+        assert_eq!(sse["url"], "https://example.com/sse");
+        assert_eq!(sse["headers"]["Authorization"], "Bearer token");
+        assert_eq!(sse["env"]["REGION"], "eu");
+        assert!(sse.get("command").is_none());
+
+        let http = desktop_entry_to_json_config(&servers["remote-http"]).unwrap();
+        assert_eq!(http["type"], "http");
+
+        assert!(desktop_entry_to_json_config(&servers["broken"]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_desktop_import_emits_progress_per_server() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("claude_desktop_config.json");
+        fs::write(
+            &path,
+            r#"{
+                "mcpServers": {
+                    "alpha": {"command": "npx", "args": ["-y", "alpha-server"]},
+                    "beta": {"url": "https://example.com/sse"},
+                    "gamma": {"command": "uvx", "args": ["gamma-server"]}
+                }
+            }"#,
+        )
+        .unwrap();
+        let config = read_claude_desktop_config(&path).unwrap();
+        let servers = config["mcpServers"].as_object().unwrap();
+
+        let mut events = Vec::new();
+        let result = import_desktop_servers(
+            servers,
+            |name, _json| async move {
+                if name == "beta" {
+                    return Ok(AddServerResult {
+                        success: false,
+                        message: "rejected".to_string(),
+                        server_name: None,
+                    });
+                }
+                Ok(AddServerResult {
+                    success: true,
+                    message: "added".to_string(),
+                    server_name: Some(name),
+                })
+            },
+            |progress| events.push(progress),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(events.len(), 3);
+        let summary: Vec<_> = events
+            .iter()
+            .map(|e| (e.name.as_str(), e.index, e.total, e.success))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![("alpha", 1, 3, true), ("beta", 2, 3, false), ("gamma", 3, 3, true)]
+        );
+
+        assert_eq!(result.imported_count, 2);
+        assert_eq!(result.failed_count, 1);
+        assert_eq!(result.servers[1].error.as_deref(), Some("rejected"));
+    }
+
+    #[test]
+    fn test_add_servers_sequentially_continues_past_failures() {
+        let remote = |name: &str, url: Option<&str>| MCPServerSpec {
+            name: name.to_string(),
+            transport: "sse".to_string(),
+            command: None,
+            args: vec![],
+            env: HashMap::new(),
+            url: url.map(str::to_string),
+            headers: HashMap::new(),
+        };
+        let specs = vec![
+            remote("docs", Some("https://example.com/sse")),
+            remote("no-url", None),
+            MCPServerSpec {
+                command: Some("definitely-not-installed-mcp".to_string()),
+                transport: "stdio".to_string(),
+                ..remote("missing-cmd", None)
+            },
+            remote("existing", Some("https://example.com/other")),
+            remote("docs", Some("https://example.com/again")),
+            remote("cli-error", Some("https://example.com/bad")),
+            remote("search", Some("https://example.com/search")),
+        ];
+        let empty_path = tempfile::TempDir::new().unwrap();
+
+        let mut invocations = Vec::new();
+        let result = add_servers_sequentially(
+            &specs,
+            "user",
+            |name| name == "existing",
+            &empty_path.path().to_string_lossy(),
+            |cmd_args| {
+                invocations.push(cmd_args.to_vec());
+                if cmd_args.iter().any(|a| a == "cli-error") {
+                    Err("Command failed: invalid URL".to_string())
+                } else {
+                    Ok("Added".to_string())
+                }
+            },
+        );
+
+        assert_eq!(result.imported_count, 2);
+        assert_eq!(result.failed_count, 5);
+        // Only specs that pass validation reach the CLI
+        assert_eq!(invocations.len(), 3);
+
+        let error_for = |index: usize| result.servers[index].error.clone().unwrap_or_default();
+        assert!(result.servers[0].success);
+        assert!(error_for(1).contains("URL"));
+        assert_eq!(error_for(2), "Command 'definitely-not-installed-mcp' not found on PATH");
+        assert_eq!(error_for(3), "Server 'existing' already exists in scope 'user'");
+        assert_eq!(error_for(4), "Server 'docs' already exists in scope 'user'");
+        assert_eq!(error_for(5), "Command failed: invalid URL");
+        assert!(result.servers[6].success);
+    }
+
+    #[test]
+    fn test_add_servers_sequentially_rejects_project_scope_duplicate() {
+        let home = tempfile::TempDir::new().unwrap();
+        let project = tempfile::TempDir::new().unwrap();
+        fs::write(
+            project.path().join(".mcp.json"),
+            r#"{"mcpServers":{"docs":{"type":"sse","url":"https://example.com/sse"}}}"#,
+        )
+        .unwrap();
+        let specs = vec![MCPServerSpec {
+            name: "docs".to_string(),
+            transport: "sse".to_string(),
+            command: None,
+            args: vec![],
+            env: HashMap::new(),
+            url: Some("https://example.com/sse".to_string()),
+            headers: HashMap::new(),
+        }];
+
+        let add_in_scope = |scope: &str| {
+            let mut invocations = 0;
+            let result = add_servers_sequentially(
+                &specs,
+                scope,
+                |name| scope_config_has_server(name, scope, home.path(), project.path()),
+                "",
+                |_| {
+                    invocations += 1;
+                    Ok("Added".to_string())
+                },
+            );
+            (result, invocations)
+        };
+
+        let (result, invocations) = add_in_scope("project");
+        assert_eq!(invocations, 0);
+        assert_eq!(
+            result.servers[0].error.as_deref(),
+            Some("Server 'docs' already exists in scope 'project'")
+        );
+
+        // The project's .mcp.json says nothing about the local scope
+        let (result, invocations) = add_in_scope("local");
+        assert_eq!(invocations, 1);
+        assert!(result.servers[0].success);
+    }
+
+    #[test]
+    fn test_validate_project_config_reports_per_server_errors() {
+        let content = r#"{
+            "mcpServers": {
+                "good": {"type": "stdio", "command": "npx", "args": ["-y", "server"]},
+                "string-args": {"type": "stdio", "command": "npx", "args": "-y server"},
+                "no-command": {"type": "stdio"},
+                "odd-type": {"type": "websocket", "url": "ws://localhost"}
+            }
+        }"#;
+
+        // serde rejects this file outright
+        assert!(serde_json::from_str::<MCPProjectConfig>(content).is_err());
+
+        let errors = validate_project_config_content(content);
+        assert_eq!(errors.len(), 3);
+        assert!(errors.iter().any(|e| e.contains("'string-args'") && e.contains("'args'")));
+        assert!(errors.iter().any(|e| e.contains("'no-command'") && e.contains("missing 'command'")));
+        assert!(errors.iter().any(|e| e.contains("'odd-type'") && e.contains("unknown type")));
+        assert!(!errors.iter().any(|e| e.contains("'good'")));
+    }
+
+    #[test]
+    fn test_project_config_with_comments_parses() {
+        let content = r#"{
+            // Shared servers for the whole team
+            "mcpServers": {
+                /* Local file access,
+                   scoped to the repo */
+                "filesystem": {
+                    "type": "stdio",
+                    "command": "npx", // pinned in package.json
+                    "args": ["-y", "@modelcontextprotocol/server-filesystem", "./"]
+                },
+                "docs": {"type": "sse", "url": "https://example.com/sse?a=1//b"}
+            }
+        }"#;
+
+        let config: MCPProjectConfig = serde_json::from_str(&strip_jsonc(content)).unwrap();
+        assert_eq!(config.mcp_servers.len(), 2);
+        assert_eq!(config.mcp_servers["filesystem"].args[2], "./");
+        // Comment markers inside strings are left alone
+        assert_eq!(
+            config.mcp_servers["docs"].url.as_deref(),
+            Some("https://example.com/sse?a=1//b")
+        );
+        assert!(validate_project_config_content(content).is_empty());
+    }
+
+    #[test]
+    fn test_project_config_with_trailing_commas_parses() {
+        let content = r#"{
+            "mcpServers": {
+                "filesystem": {
+                    "type": "stdio",
+                    "command": "npx",
+                    "args": ["-y", "server", "a,]",],
+                },
+            },
+        }"#;
+
+        let config: MCPProjectConfig = serde_json::from_str(&strip_jsonc(content)).unwrap();
+        assert_eq!(config.mcp_servers["filesystem"].args, vec!["-y", "server", "a,]"]);
+        assert!(validate_project_config_content(content).is_empty());
+    }
+
+    #[test]
+    fn test_malformed_project_config_still_reports_position() {
+        let content = "{\n  // comment\n  \"mcpServers\": {\n    \"a\": \n  }\n}";
+        let errors = validate_project_config_content(content);
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].starts_with("Invalid JSON"));
+        assert!(errors[0].contains("line 5"));
+    }
+
+    #[test]
+    fn test_validate_project_config_accepts_valid_file() {
+        let content = r#"{"mcpServers": {"remote": {"type": "sse", "url": "https://example.com/sse", "headers": {"X-Key": "1"}}}}"#;
+        assert!(validate_project_config_content(content).is_empty());
+    }
+
+    #[test]
+    fn test_write_project_config_keeps_rotating_backups() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join(".mcp.json");
+
+        for version in 1..=5 {
+            write_project_config(&path, &format!("v{}", version)).unwrap();
+        }
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "v5");
+        assert_eq!(fs::read_to_string(backup_path(&path, 0)).unwrap(), "v4");
+        assert_eq!(fs::read_to_string(backup_path(&path, 1)).unwrap(), "v3");
+        assert_eq!(fs::read_to_string(backup_path(&path, 2)).unwrap(), "v2");
+        assert!(!backup_path(&path, 3).exists());
+    }
+
+    #[test]
+    fn test_write_project_config_failure_leaves_original_intact() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join(".mcp.json");
+        fs::write(&path, "original").unwrap();
+
+        // Non-empty directories in every backup slot make both rotation and the backup fail
+        for index in 0..MCP_JSON_BACKUP_COUNT {
+            let slot = backup_path(&path, index);
+            fs::create_dir(&slot).unwrap();
+            fs::write(slot.join("keep"), "").unwrap();
+        }
+
+        assert!(write_project_config(&path, "replacement").is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "original");
+        // The temp file holding the replacement is cleaned up
+        let leftovers: Vec<_> = fs::read_dir(temp_dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .filter(|name| name.ends_with(".tmp"))
+            .collect();
+        assert!(leftovers.is_empty(), "{:?}", leftovers);
+    }
+
+    #[test]
+    fn test_rename_server_in_project_config() {
+        let mut config: ProjectConfigDocument = serde_json::from_str(
+            r#"{"mcpServers": {
+                "github": {"type": "stdio", "command": "github-mcp", "env": {"TOKEN": "x"}},
+                "remote": {"type": "sse", "url": "https://example.com/sse"}
+            }}"#,
+        )
+        .unwrap();
+
+        rename_server_in_project_config(&mut config, "github", "gh").unwrap();
+
+        let servers = &config.mcp_servers;
+        assert_eq!(servers.len(), 2);
+        assert!(!servers.contains_key("github"));
+        assert_eq!(servers["gh"]["command"], "github-mcp");
+        assert_eq!(servers["gh"]["env"]["TOKEN"], "x");
+    }
+
+    #[test]
+    fn test_rename_server_in_project_config_rejects_collisions() {
+        let mut config: ProjectConfigDocument = serde_json::from_str(
+            r#"{"mcpServers": {"a": {"type": "stdio", "command": "a"}, "b": {"type": "stdio", "command": "b"}}}"#,
+        )
+        .unwrap();
+
+        assert!(rename_server_in_project_config(&mut config, "a", "b").is_err());
+        assert!(rename_server_in_project_config(&mut config, "missing", "c").is_err());
+        assert_eq!(config.mcp_servers["a"]["command"], "a");
+    }
+
+    #[test]
+    fn test_rename_server_in_project_config_keeps_position() {
+        let mut config: ProjectConfigDocument = serde_json::from_str(
+            r#"{"mcpServers": {
+                "zeta": {"type": "stdio", "command": "z"},
+                "alpha": {"type": "stdio", "command": "a"},
+                "mid": {"type": "stdio", "command": "m"}
+            }, "inputs": []}"#,
+        )
+        .unwrap();
+
+        rename_server_in_project_config(&mut config, "alpha", "beta").unwrap();
+
+        let names: Vec<_> = config.mcp_servers.keys().map(String::as_str).collect();
+        assert_eq!(names, vec!["zeta", "beta", "mid"]);
+        let saved = serde_json::to_string(&config).unwrap();
+        let position = |key: &str| saved.find(&format!("\"{}\":", key)).unwrap();
+        assert!(position("zeta") < position("beta") && position("beta") < position("mid"));
+        assert!(saved.ends_with(r#""inputs":[]}"#), "{}", saved);
+    }
+
+    #[tokio::test]
+    async fn test_list_cache_reuses_results_within_ttl() {
+        let cache = MCPListCache::default();
+        let cli_calls = std::cell::Cell::new(0);
+        let load = || async {
+            cli_calls.set(cli_calls.get() + 1);
+            Ok(vec![
+                test_server("github", "stdio", "user"),
+                test_server("fs", "stdio", "project"),
+            ])
+        };
+        let list = |force_refresh: bool, scope: Option<&'static str>| {
+            list_servers_cached(Some(&cache), force_refresh, scope, load)
+        };
+
+        assert_eq!(list(false, None).await.unwrap().len(), 2);
+        assert_eq!(list(false, None).await.unwrap().len(), 2);
+        assert_eq!(cli_calls.get(), 1);
+
+        // Filtered requests are answered from a full cached list
+        let user_only = list(false, Some("user")).await.unwrap();
+        assert_eq!(user_only.len(), 1);
+        assert_eq!(cli_calls.get(), 1);
+
+        list(true, None).await.unwrap();
+        assert_eq!(cli_calls.get(), 2);
+        cache.invalidate();
+        list(false, None).await.unwrap();
+        assert_eq!(cli_calls.get(), 3);
+
+        // A filtered load is not cached, so the next full listing goes back to the CLI
+        cache.invalidate();
+        list(false, Some("project")).await.unwrap();
+        list(false, None).await.unwrap();
+        assert_eq!(cli_calls.get(), 5);
+
+        // Without managed cache state every call loads
+        list_servers_cached(None, false, None, load).await.unwrap();
+        assert_eq!(cli_calls.get(), 6);
+    }
+
+    #[test]
+    fn test_list_cache_expires() {
+        let cache = MCPListCache::default();
+        cache.set(vec![test_server("github", "stdio", "user")]);
+
+        assert!(cache.get(MCP_LIST_CACHE_TTL).is_some());
+        assert!(cache.get(Duration::ZERO).is_none());
+    }
+
+    #[test]
+    fn test_normalize_server_url_accepts_http_urls() {
+        assert_eq!(
+            normalize_server_url("  https://Example.com/sse  ").unwrap(),
+            "https://example.com/sse"
+        );
+        assert_eq!(
+            normalize_server_url("http://localhost:3000/mcp/").unwrap(),
+            "http://localhost:3000/mcp"
+        );
+        assert_eq!(
+            normalize_server_url("https://example.com").unwrap(),
+            "https://example.com"
+        );
+        // Query strings are kept exactly
+        assert_eq!(
+            normalize_server_url("https://example.com/sse?key=a/").unwrap(),
+            "https://example.com/sse?key=a/"
+        );
+    }
+
+    #[test]
+    fn test_normalize_server_url_rejects_schemeless_url() {
+        let err = normalize_server_url("example.com/sse").unwrap_err();
+        assert!(err.contains("missing a scheme"), "{}", err);
+
+        let err = normalize_server_url("htttp://example.com/sse").unwrap_err();
+        assert!(err.contains("Unsupported URL scheme 'htttp'"), "{}", err);
+    }
+
+    #[test]
+    fn test_normalize_server_url_rejects_garbage() {
+        assert!(normalize_server_url("").is_err());
+        assert!(normalize_server_url("   ").is_err());
+        assert!(normalize_server_url("not a url at all").is_err());
+        assert!(normalize_server_url("http://").is_err());
+
+        let result = build_mcp_add_args(
+            "remote",
+            "http",
+            None,
+            &[],
+            &HashMap::new(),
+            Some("::::"),
+            &HashMap::new(),
+            "local",
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_build_mcp_add_args_with_headers() {
+        let mut headers = HashMap::new();
+        headers.insert("Authorization".to_string(), "Bearer abc: def".to_string());
+        headers.insert("X-Api-Key".to_string(), "key with spaces".to_string());
+
+        let args = build_mcp_add_args(
+            "remote",
+            "sse",
+            None,
+            &[],
+            &HashMap::new(),
+            Some("https://example.com/sse"),
+            &headers,
+            "user",
+        )
+        .unwrap();
+
+        assert_eq!(
+            args,
+            vec![
+                "add",
+                "-s",
+                "user",
+                "--transport",
+                "sse",
+                "--header",
+                "Authorization: Bearer abc: def",
+                "--header",
+                "X-Api-Key: key with spaces",
+                "remote",
+                "https://example.com/sse",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_build_mcp_add_args_requires_url_for_remote() {
+        let result = build_mcp_add_args(
+            "remote",
+            "http",
+            None,
+            &[],
+            &HashMap::new(),
+            None,
+            &HashMap::new(),
+            "local",
+        );
+        assert!(result.is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_check_stdio_command_resolves_on_path() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let script = dir.path().join("fake-mcp-server");
+        std::fs::write(&script, "#!/bin/sh\n").unwrap();
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let search_path = dir.path().to_string_lossy().to_string();
+
+        let env = HashMap::new();
+        assert_eq!(
+            check_stdio_command("stdio", Some("fake-mcp-server"), &env, &search_path, false),
+            None
+        );
+        assert!(resolve_stdio_command("fake-mcp-server", &env, &search_path)
+            .is_some_and(|path| path.ends_with("fake-mcp-server")));
+
+        // A PATH in the server env takes precedence over the inherited one
+        let env = HashMap::from([("PATH".to_string(), search_path.clone())]);
+        assert_eq!(
+            check_stdio_command("stdio", Some("fake-mcp-server"), &env, "/nonexistent", false),
+            None
+        );
+    }
+
+    #[test]
+    fn test_check_stdio_command_reports_missing_command() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let search_path = dir.path().to_string_lossy().to_string();
+
+        assert_eq!(
+            check_stdio_command("stdio", Some("npxx"), &HashMap::new(), &search_path, false),
+            Some("Command 'npxx' not found on PATH".to_string())
+        );
+
+        // Remote transports don't launch anything locally
+        assert_eq!(
+            check_stdio_command("sse", Some("npxx"), &HashMap::new(), &search_path, false),
+            None
+        );
+    }
+
+    #[test]
+    fn test_check_stdio_command_force_skips_lookup() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let search_path = dir.path().to_string_lossy().to_string();
+
+        assert_eq!(
+            check_stdio_command("stdio", Some("npxx"), &HashMap::new(), &search_path, true),
+            None
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_mcp_serve_start_status_stop() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let fake_claude = temp_dir.path().join("claude");
+        fs::write(&fake_claude, "#!/bin/sh\nexec sleep 30\n").unwrap();
+        fs::set_permissions(&fake_claude, fs::Permissions::from_mode(0o755)).unwrap();
+        let serve_cmd = || {
+            let mut cmd = Command::new(&fake_claude);
+            cmd.arg("mcp").arg("serve");
+            cmd
+        };
+
+        let registry = ProcessRegistry::new();
+        assert!(registry.get_mcp_server(MCP_SERVE_NAME).unwrap().is_none());
+
+        let id = start_mcp_serve(&registry, serve_cmd()).unwrap();
+        let status = registry.get_mcp_server(MCP_SERVE_NAME).unwrap().unwrap();
+        assert_eq!(status.run_id, id);
+        assert!(status.command.ends_with("claude mcp serve"));
+
+        // A second start is refused while the first is running
+        let err = start_mcp_serve(&registry, serve_cmd()).unwrap_err();
+        assert!(err.contains("already running"));
+        assert_eq!(registry.get_running_processes().unwrap().len(), 1);
+
+        assert!(stop_mcp_serve(&registry, id).await.unwrap());
+        assert!(registry.get_mcp_server(MCP_SERVE_NAME).unwrap().is_none());
+        assert!(!stop_mcp_serve(&registry, id).await.unwrap());
+
+        // Once stopped it can be started again
+        let restarted = start_mcp_serve(&registry, serve_cmd()).unwrap();
+        assert_ne!(restarted, id);
+        assert!(stop_mcp_serve(&registry, restarted).await.unwrap());
+    }
+
+    #[test]
+    fn test_retry_transient_stops_on_success_or_fatal_error() {
+        let policy = McpRetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::ZERO,
+        };
+
+        // Fails twice, then succeeds on the last allowed attempt
+        let mut calls = 0;
+        let result = retry_transient(policy, || {
+            calls += 1;
+            if calls < 3 {
+                Err(AttemptFailure::Transient(McpError::command_failed("")))
+            } else {
+                Ok("listed")
+            }
+        });
+        assert_eq!(result.unwrap(), "listed");
+        assert_eq!(calls, 3);
+
+        // Gives up after max_attempts transient failures
+        let mut calls = 0;
+        let result: std::result::Result<(), _> = retry_transient(policy, || {
+            calls += 1;
+            Err(AttemptFailure::Transient(McpError::command_failed("")))
+        });
+        assert!(result.is_err());
+        assert_eq!(calls, 3);
+
+        // Deterministic errors are never retried
+        let mut calls = 0;
+        let result: std::result::Result<(), _> = retry_transient(policy, || {
+            calls += 1;
+            Err(AttemptFailure::Fatal(McpError::command_failed(
+                "MCP server test already exists in local config",
+            )))
+        });
+        assert!(result.unwrap_err().message().contains("already exists"));
+        assert_eq!(calls, 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_cli_output_is_returned_verbatim() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let fake_claude = temp_dir.path().join("claude");
+        let script = r#"#!/bin/sh
+printf 'Checking MCP server health...\n\n'
+printf 'github: npx -y server-github  - \033[32m✓ Connected\033[0m  \r\n'
+printf 'odd: \377 byte\n\n'
+"#;
+        fs::write(&fake_claude, script).unwrap();
+        fs::set_permissions(&fake_claude, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let fake_claude = fake_claude.to_string_lossy().to_string();
+        let output = run_mcp_cli_once(&fake_claude, &["list"], None).unwrap();
+
+        // Whitespace, blank lines and \r survive; decoding only drops the color codes and
+        // replaces the invalid byte
+        assert_eq!(
+            output,
+            concat!(
+                "Checking MCP server health...\n\n",
+                "github: npx -y server-github  - ✓ Connected  \r\n",
+                "odd: \u{fffd} byte\n\n",
+            )
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_cli_that_fails_twice_then_succeeds_is_retried() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let counter = temp_dir.path().join("count");
+        let write_cli = |name: &str, body: &str| {
+            let path = temp_dir.path().join(name);
+            fs::write(
+                &path,
+                format!(
+                    "#!/bin/sh\nn=$(cat '{0}' 2>/dev/null || echo 0)\nn=$((n + 1))\necho $n > '{0}'\n{1}\n",
+                    counter.display(),
+                    body
+                ),
+            )
+            .unwrap();
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+            path.to_string_lossy().to_string()
+        };
+        let policy = McpRetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::ZERO,
+        };
+        let count = || fs::read_to_string(&counter).unwrap().trim().to_string();
+
+        // Silent non-zero exits count as transient
+        let flaky = write_cli("flaky", "[ $n -le 2 ] && exit 1\necho \"ok $*\"");
+        let output = retry_transient(policy, || run_mcp_cli_once(&flaky, &["list"], None)).unwrap();
+        assert_eq!(output.trim(), "ok mcp list");
+        assert_eq!(count(), "3");
+
+        // A CLI error message is final
+        fs::remove_file(&counter).unwrap();
+        let failing = write_cli("failing", "echo 'MCP server x already exists' >&2\nexit 1");
+        let err = retry_transient(policy, || run_mcp_cli_once(&failing, &["add", "x"], None)).unwrap_err();
+        assert!(err.message().contains("already exists"));
+        assert_eq!(count(), "1");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_cli_runs_in_requested_working_directory() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let cli = temp_dir.path().join("claude");
+        fs::write(&cli, "#!/bin/sh\npwd -P\n").unwrap();
+        fs::set_permissions(&cli, fs::Permissions::from_mode(0o755)).unwrap();
+        let cli = cli.to_string_lossy().to_string();
+
+        let package = temp_dir.path().join("packages").join("api");
+        fs::create_dir_all(&package).unwrap();
+        let cwd = resolve_mcp_cwd(Some(&package.to_string_lossy())).unwrap();
+        let output = run_mcp_cli_once(&cli, &["remove", "fs"], cwd.as_deref()).unwrap();
+        assert_eq!(PathBuf::from(output.trim()), package.canonicalize().unwrap());
+
+        // Without a cwd the CLI inherits ours
+        let output = run_mcp_cli_once(&cli, &["list"], None).unwrap();
+        assert_eq!(
+            PathBuf::from(output.trim()),
+            std::env::current_dir().unwrap().canonicalize().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_resolve_mcp_cwd_requires_existing_directory() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let file = temp_dir.path().join(".mcp.json");
+        fs::write(&file, "{}").unwrap();
+
+        assert_eq!(resolve_mcp_cwd(None).unwrap(), None);
+        assert_eq!(resolve_mcp_cwd(Some("  ")).unwrap(), None);
+        assert_eq!(
+            resolve_mcp_cwd(Some(&temp_dir.path().to_string_lossy())).unwrap(),
+            Some(temp_dir.path().to_path_buf())
+        );
+        for bad in [file, temp_dir.path().join("missing")] {
+            let err = resolve_mcp_cwd(Some(&bad.to_string_lossy())).unwrap_err();
+            assert!(matches!(err, McpError::Io { .. }));
+            assert!(err.message().contains("does not exist"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resaving_project_config_is_byte_identical() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let project_path = temp_dir.path().to_string_lossy().to_string();
+        let original = r#"{
+  "mcpServers": {
+    "zeta": {
+      "type": "stdio",
+      "command": "npx",
+      "args": [],
+      "env": {
+        "Z_TOKEN": "z",
+        "A_TOKEN": "a"
+      }
+    },
+    "alpha": {
+      "type": "http",
+      "args": [],
+      "env": {},
+      "url": "https://example.com/mcp",
+      "headers": {
+        "X-Trace": "1",
+        "Authorization": "Bearer token"
+      }
+    },
+    "mid": {
+      "type": "stdio",
+      "command": "uvx",
+      "args": [
+        "mcp-server-git"
+      ],
+      "env": {}
+    }
+  }
+}"#;
+        let mcp_json_path = temp_dir.path().join(".mcp.json");
+        fs::write(&mcp_json_path, original).unwrap();
+
+        let config = mcp_read_project_config(project_path.clone(), None).await.unwrap();
+        let names: Vec<_> = config.mcp_servers.keys().map(String::as_str).collect();
+        assert_eq!(names, vec!["zeta", "alpha", "mid"]);
+
+        mcp_save_project_config(project_path, config, None).await.unwrap();
+        assert_eq!(fs::read_to_string(&mcp_json_path).unwrap(), original);
+    }
+
+    fn bogus_type_config() -> MCPProjectConfig {
+        let config = r#"{
+  "mcpServers": {
+    "good": { "type": "stdio", "command": "uvx", "args": ["mcp-server-git"] },
+    "bogus": { "type": "websocket", "url": "ws://localhost:9000" },
+    "no-url": { "type": "sse" }
+  }
+}"#;
+        serde_json::from_str(config).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_save_project_config_verify_reports_rejected_servers() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let project_path = temp_dir.path().to_string_lossy().to_string();
+
+        let result = mcp_save_project_config(project_path, bogus_type_config(), Some(true))
+            .await
+            .unwrap();
+
+        let names: Vec<&str> = result.rejected.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["bogus", "no-url"]);
+        assert!(result.rejected[0].errors[0].contains("unknown type 'websocket'"));
+        assert!(result.rejected[1].errors[0].contains("missing 'url'"));
+        // The save itself still happens; verification only warns
+        assert!(temp_dir.path().join(".mcp.json").exists());
+    }
+
+    #[tokio::test]
+    async fn test_save_project_config_skips_verification_by_default() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let project_path = temp_dir.path().to_string_lossy().to_string();
+
+        let result = mcp_save_project_config(project_path, bogus_type_config(), None)
+            .await
+            .unwrap();
+
+        assert!(result.rejected.is_empty());
+        let saved = fs::read_to_string(temp_dir.path().join(".mcp.json")).unwrap();
+        assert!(saved.contains("websocket"));
+    }
+
+    #[test]
+    fn test_remove_existing_server() {
+        let servers = vec![test_server("filesystem", "stdio", "project")];
+        let mut calls = 0;
+
+        let result = remove_server_with("filesystem", Some(&servers), || {
+            calls += 1;
+            Ok("Removed MCP server filesystem from project config\n".to_string())
+        })
+        .unwrap();
+
+        assert_eq!(calls, 1);
+        assert!(result.success && result.removed);
+        assert_eq!(result.scope.as_deref(), Some("project"));
+        assert_eq!(result.message, "Removed MCP server filesystem from project config");
+    }
+
+    #[test]
+    fn test_remove_nonexistent_server_is_not_an_error() {
+        // A (possibly stale) list without the server still lets the CLI decide
+        let servers = vec![test_server("filesystem", "stdio", "local")];
+        let result = remove_server_with("missing", Some(&servers), || {
+            Err(McpError::command_failed("No MCP server found with name: missing"))
+        })
+        .unwrap();
+        assert!(result.success && !result.removed);
+        assert!(result.scope.is_none());
+
+        // A server added outside the app since the list was cached is removed
+        let result = remove_server_with("added-later", Some(&servers), || {
+            Ok("Removed MCP server added-later from local config".to_string())
+        })
+        .unwrap();
+        assert!(result.success && result.removed);
+        assert!(result.scope.is_none());
+
+        // Unknown list: the CLI's "not found" answer is interpreted
+        let result = remove_server_with("missing", None, || {
+            Err(McpError::command_failed("No MCP server found with name: missing"))
+        })
+        .unwrap();
+        assert!(result.success && !result.removed);
+
+        // Other failures still surface
+        let err = remove_server_with("filesystem", None, || {
+            Err(McpError::command_failed("EACCES: permission denied"))
+        })
+        .unwrap_err();
+        assert!(matches!(err, McpError::CommandFailed { .. }));
+    }
+
+    fn env_lookup(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        move |name| vars.get(name).cloned()
+    }
+
+    #[test]
+    fn test_expand_env_vars_resolves_and_defaults() {
+        let lookup = env_lookup(&[("TOKEN", "s3cret"), ("HOST", "api.example.com"), ("EMPTY", "")]);
+        let mut missing = Vec::new();
+
+        assert_eq!(
+            expand_env_vars("Bearer ${TOKEN}", &lookup, &mut missing),
+            "Bearer s3cret"
+        );
+        assert_eq!(
+            expand_env_vars("https://${HOST}:${PORT:-8443}/mcp", &lookup, &mut missing),
+            "https://api.example.com:8443/mcp"
+        );
+        // Set-but-empty falls back to the default, like the shell
+        assert_eq!(expand_env_vars("${EMPTY:-fallback}", &lookup, &mut missing), "fallback");
+        assert_eq!(expand_env_vars("${TOKEN:-unused}", &lookup, &mut missing), "s3cret");
+        // Plain `$` and unterminated references are left alone
+        assert_eq!(expand_env_vars("$HOME and ${OPEN", &lookup, &mut missing), "$HOME and ${OPEN");
+        assert!(missing.is_empty());
+    }
+
+    #[test]
+    fn test_expand_server_env_reports_missing_variables() {
+        let lookup = env_lookup(&[("ROOT", "/srv/data")]);
+        let mut config: MCPServerConfig = serde_json::from_str(
+            r#"{
+                "type": "stdio",
+                "command": "${NODE_BIN:-npx}",
+                "args": ["server-filesystem", "${ROOT}"],
+                "env": {"API_KEY": "${API_KEY}", "OTHER": "${API_KEY}-${REGION}"}
+            }"#,
+        )
+        .unwrap();
+
+        let missing = expand_server_env(&mut config, &lookup);
+
+        assert_eq!(config.command, "npx");
+        assert_eq!(config.args, vec!["server-filesystem", "/srv/data"]);
+        // Unresolved references stay visible instead of silently becoming empty
+        assert_eq!(config.env["API_KEY"], "${API_KEY}");
+        assert_eq!(missing, vec!["API_KEY", "REGION"]);
+    }
+
+    #[tokio::test]
+    async fn test_read_project_config_keeps_env_references_by_default() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let project_path = temp_dir.path().to_string_lossy().to_string();
+        let original = r#"{
+  "mcpServers": {
+    "remote": {
+      "type": "http",
+      "args": [],
+      "env": {},
+      "url": "https://example.com/mcp",
+      "headers": {
+        "Authorization": "Bearer ${CODESTUDIO_TEST_UNSET_TOKEN}"
+      }
+    }
+  }
+}"#;
+        let mcp_json_path = temp_dir.path().join(".mcp.json");
+        fs::write(&mcp_json_path, original).unwrap();
+
+        let config = mcp_read_project_config(project_path.clone(), None).await.unwrap();
+        assert!(config.env_warnings.is_empty());
+        mcp_save_project_config(project_path.clone(), config, None).await.unwrap();
+        assert_eq!(fs::read_to_string(&mcp_json_path).unwrap(), original);
+
+        let expanded = mcp_read_project_config(project_path, Some(true)).await.unwrap();
+        assert_eq!(
+            expanded.env_warnings["remote"],
+            vec!["CODESTUDIO_TEST_UNSET_TOKEN"]
+        );
+        // Warnings go back to the caller but never into a saved file
+        let echoed: MCPProjectConfig =
+            serde_json::from_value(serde_json::to_value(&expanded).unwrap()).unwrap();
+        assert!(echoed.env_warnings.is_empty());
+    }
+
+    #[test]
+    fn test_expand_project_config_env_collects_warnings_per_server() {
+        let lookup = env_lookup(&[("TOKEN", "s3cret")]);
+        let mut config: MCPProjectConfig = serde_json::from_str(
+            r#"{"mcpServers": {
+                "ok": {"type": "http", "url": "https://example.com", "headers": {"Authorization": "Bearer ${TOKEN}"}},
+                "bad": {"type": "stdio", "command": "npx", "env": {"KEY": "${KEY}"}}
+            }}"#,
+        )
+        .unwrap();
+
+        expand_project_config_env(&mut config, &lookup);
+
+        assert_eq!(
+            config.mcp_servers["ok"].headers.as_ref().unwrap()["Authorization"],
+            "Bearer s3cret"
+        );
+        assert_eq!(config.env_warnings.len(), 1);
+        assert_eq!(config.env_warnings["bad"], vec!["KEY"]);
+    }
+
+    #[test]
+    fn test_expand_server_env_covers_url_and_headers() {
+        let lookup = env_lookup(&[("DOCS_TOKEN", "abc")]);
+        let mut config: MCPServerConfig = serde_json::from_str(
+            r#"{
+                "type": "http",
+                "url": "https://${DOCS_HOST:-docs.example.com}/mcp",
+                "headers": {"Authorization": "Bearer ${DOCS_TOKEN}"}
+            }"#,
+        )
+        .unwrap();
+
+        assert!(expand_server_env(&mut config, &lookup).is_empty());
+        assert_eq!(config.url.as_deref(), Some("https://docs.example.com/mcp"));
+        assert_eq!(config.headers.unwrap()["Authorization"], "Bearer abc");
+    }
+
+    #[test]
+    fn test_normalize_server_json_accepts_valid_config() {
+        let json = r#"{
+            "type": "stdio",
+            "command": "npx",
+            "args": ["-y", "@modelcontextprotocol/server-filesystem"],
+            "env": {"ROOT": "/tmp"}
+        }"#;
+
+        let normalized = normalize_server_json("filesystem", json).unwrap();
+        assert!(!normalized.contains('\n'));
+        let config: MCPServerConfig = serde_json::from_str(&normalized).unwrap();
+        assert_eq!(config.command, "npx");
+        assert_eq!(config.env["ROOT"], "/tmp");
+    }
+
+    #[test]
+    fn test_normalize_server_json_rejects_invalid_syntax() {
+        let err = normalize_server_json("broken", r#"{"type": "stdio", "command": "npx",}"#).unwrap_err();
+        assert!(err.starts_with("Invalid JSON"), "{}", err);
+    }
+
+    #[test]
+    fn test_normalize_server_json_rejects_wrong_shape() {
+        let err = normalize_server_json("remote", r#"{"type": "http", "headers": {"X-Retry": 3}}"#)
+            .unwrap_err();
+        assert!(err.contains("Server 'remote': missing 'url'"), "{}", err);
+        assert!(err.contains("'headers' must be an object of string values"), "{}", err);
+
+        let err = normalize_server_json("local", r#"{"type": "stdio", "command": "npx", "args": "-y"}"#)
+            .unwrap_err();
+        assert!(err.contains("'args' must be an array of strings"), "{}", err);
+
+        assert!(normalize_server_json("list", "[]").unwrap_err().contains("must be an object"));
+    }
 }
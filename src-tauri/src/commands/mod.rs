@@ -1,8 +1,11 @@
 pub mod agents;
 pub mod claude;
+pub mod logs;
 pub mod mcp;
+pub mod processes;
 pub mod proxy;
 pub mod slash_commands;
 pub mod skills;
 pub mod storage;
 pub mod usage;
+pub mod web_access;
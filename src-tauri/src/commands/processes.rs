@@ -0,0 +1,23 @@
+use tauri::State;
+
+use crate::process::{ProcessInfo, ProcessRegistryState};
+
+/// Lists every tracked Claude/agent process, oldest first
+#[tauri::command]
+pub async fn list_running_processes(
+    registry: State<'_, ProcessRegistryState>,
+) -> Result<Vec<ProcessInfo>, String> {
+    registry.0.get_running_processes()
+}
+
+/// Terminates a tracked process (and its process group on Unix)
+///
+/// Returns false when no process with that id is tracked.
+#[tauri::command]
+pub async fn kill_process(
+    registry: State<'_, ProcessRegistryState>,
+    id: i64,
+) -> Result<bool, String> {
+    log::info!("Killing process {}", id);
+    registry.0.kill_process(id).await
+}
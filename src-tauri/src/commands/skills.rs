@@ -1,21 +1,84 @@
 #![allow(dead_code)]
 
 use anyhow::Result;
+use base64::{engine::general_purpose::STANDARD, Engine as _};
 use chrono;
 use dirs;
 use log::{debug, error, info, warn};
 use serde::{Deserialize, Serialize};
+use similar::{ChangeTag, TextDiff};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use pulldown_cmark::{Options, Parser};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fs;
 use std::io::Read;
-use std::path::{Path, PathBuf};
-use tauri::AppHandle;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::fs_util::write_atomic;
 
 /// Represents a Skill's metadata extracted from YAML frontmatter
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct SkillMetadata {
     pub name: String,
     pub description: String,
+    #[serde(
+        rename = "allowed-tools",
+        alias = "allowed_tools",
+        default,
+        with = "allowed_tools_format",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub allowed_tools: Option<Vec<String>>,
+    /// Any other frontmatter keys (e.g. `version`, `author`, `tags`), kept as-is
+    #[serde(flatten)]
+    pub extra: serde_yaml::Mapping,
+}
+
+/// (De)serializes `allowed-tools` as a YAML list
+///
+/// Hand-written files may instead use a comma-separated string or a block scalar with one
+/// tool per line; all three read back as the same list.
+mod allowed_tools_format {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S>(tools: &Option<Vec<String>>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match tools {
+            Some(tools) => serializer.collect_seq(tools),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<Vec<String>>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum RawTools {
+            List(Vec<String>),
+            Text(String),
+        }
+
+        let raw = Option::<RawTools>::deserialize(deserializer)?;
+        Ok(raw.map(|raw| match raw {
+            RawTools::List(items) => items
+                .into_iter()
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect(),
+            RawTools::Text(text) => text
+                .split([',', '\n'])
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect(),
+        }))
+    }
 }
 
 /// Represents a skill file
@@ -25,22 +88,205 @@ pub struct SkillFile {
     pub path: String,
     pub content: Option<String>,
     pub is_directory: bool,
+    pub is_binary: bool,
+    pub size: u64,
+}
+
+/// A node in a skill's file tree, as returned by `skill_get_file_tree`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TreeNode {
+    pub name: String,
+    pub path: String, // relative to the skill root, "/"-separated; empty for the root
+    pub is_directory: bool,
+    pub children: Vec<TreeNode>,
+    pub size: u64, // for directories, the total size of the files below it
+}
+
+/// File extensions that are always treated as binary without reading them
+const BINARY_EXTENSIONS: &[&str] = &[
+    "png", "jpg", "jpeg", "gif", "bmp", "ico", "webp", "pdf", "zip", "gz", "tar", "7z",
+    "exe", "dll", "so", "dylib", "bin", "wasm", "class", "jar", "woff", "woff2", "ttf", "otf",
+    "mp3", "mp4", "wav", "mov",
+];
+
+/// Check whether a path has a well-known binary file extension
+fn has_binary_extension(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| BINARY_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+        .unwrap_or(false)
 }
 
 /// Represents a complete Skill
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Skill {
     pub name: String,
-    pub skill_type: String, // "personal" or "project"
+    pub skill_type: String, // "personal", "project" or "extra" (read-only, from CODESTUDIO_SKILL_PATHS)
+    #[serde(default)]
+    pub source: String, // "personal", "project" or the extra skills directory the skill came from
     pub description: String,
     pub file_path: String,
     pub yaml_frontmatter: Option<String>,
     pub markdown_content: String,
     pub files: Vec<SkillFile>,
     pub allowed_tools: Option<Vec<String>>,
+    #[serde(default)]
+    pub extra_frontmatter: serde_yaml::Mapping, // frontmatter keys other than name/description/allowed-tools
+    #[serde(default = "default_skill_enabled")]
+    pub enabled: bool, // false when listed in the skills dir's `.disabled` manifest
     pub last_modified: String,
 }
 
+fn default_skill_enabled() -> bool {
+    true
+}
+
+/// Personal and project skills with their counts, as returned by `skill_summary`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SkillSummary {
+    pub personal: Vec<Skill>,
+    pub project: Vec<Skill>,
+    pub personal_count: usize,
+    pub project_count: usize,
+    pub total: usize,
+    pub personal_error: Option<String>, // set when personal skills couldn't be listed
+    pub project_error: Option<String>,  // set when project skills couldn't be listed
+}
+
+/// A skill matched by `skill_search`, with the fields the query hit
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SkillSearchResult {
+    pub skill: Skill,
+    pub matched_fields: Vec<String>, // "name", "description" and/or "content"
+    pub score: u32,
+    pub snippet: Option<String>,
+}
+
+/// A skill returned by `skill_recent`, with when it was last read
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RecentSkill {
+    pub skill: Skill,
+    pub last_used: Option<String>, // RFC 3339; None if the skill has never been read
+}
+
+/// A soft-deleted skill in a skills directory's `.trash` folder
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TrashedSkill {
+    pub name: String,
+    pub skill_type: String,
+    pub trash_id: String,   // folder name inside `.trash`, `<name>-<timestamp>`
+    pub deleted_at: String, // RFC 3339
+    pub path: String,
+}
+
+/// Payload of the `skills-changed` event
+#[derive(Debug, Serialize, Clone)]
+pub struct SkillsChangedEvent {
+    pub path: String,
+}
+
+/// Holds the active skills directory watcher, if any
+pub struct SkillWatcherState {
+    pub watcher: Arc<Mutex<Option<RecommendedWatcher>>>,
+}
+
+impl Default for SkillWatcherState {
+    fn default() -> Self {
+        Self {
+            watcher: Arc::new(Mutex::new(None)),
+        }
+    }
+}
+
+/// Quiet period used to coalesce bursts of filesystem events (e.g. editor saves)
+const SKILL_WATCH_DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Longest a batch is held back, so a file written continuously still reports changes
+const SKILL_WATCH_MAX_DELAY: Duration = Duration::from_secs(2);
+
+/// A built-in starter template for a new skill
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SkillTemplate {
+    pub id: String,
+    pub title: String,
+    pub description: String,
+    pub allowed_tools: Option<Vec<String>>,
+    pub markdown_content: String, // `{{var}}` placeholders are filled in by `render_skill_template`
+}
+
+/// A skill created from a template, with any placeholders that were left unfilled
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SkillFromTemplateResult {
+    pub skill: Skill,
+    pub warnings: Vec<String>,
+}
+
+/// How far a skill's SKILL.md has drifted from the template it was created from
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SkillTemplateDiff {
+    pub template_id: String,
+    pub diff: String, // unified diff from the rendered template to SKILL.md; empty if unchanged
+    pub additions: usize,
+    pub deletions: usize,
+}
+
+/// A SKILL.md rendered for display, as returned by `skill_preview`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SkillPreview {
+    pub frontmatter: Option<SkillMetadata>, // None when missing or unparseable
+    pub html: String,                        // sanitized; code blocks keep `language-*` classes
+}
+
+/// Outcome of `skill_delete`; when `deleted` is false nothing was touched
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SkillDeleteResult {
+    pub deleted: bool,
+    pub file_count: usize,              // files in the skill directory, for the confirmation prompt
+    pub confirmation_token: String,     // pass back as `confirm_token` to delete without `confirm`
+    pub trash_path: Option<String>,     // where a soft-deleted skill was moved
+}
+
+/// A whole skill set as written by `skills_export_all`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SkillBundle {
+    pub version: u32,
+    pub exported_at: String,
+    pub skill_type: String,
+    pub skills: Vec<SkillBundleEntry>,
+}
+
+/// One skill inside a `SkillBundle`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SkillBundleEntry {
+    pub name: String,
+    pub metadata: Option<SkillMetadata>, // informational; import recreates the skill from `content`
+    pub content: String,                 // the full SKILL.md
+    pub files: Vec<SkillBundleFile>,
+}
+
+/// A helper file inside a `SkillBundleEntry`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SkillBundleFile {
+    pub path: String, // relative to the skill root, "/"-separated
+    pub data: String, // base64, so binary helpers survive the round trip
+}
+
+/// Outcome of `skills_import_all`
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct SkillBundleImportResult {
+    pub imported: Vec<String>,
+    pub skipped: Vec<String>, // already present and `overwrite` was false
+    pub failed: Vec<String>,  // "<name>: <reason>"
+}
+
+/// Whether a name can be used for a new skill, as returned by `skill_name_available`
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct SkillNameAvailability {
+    pub valid_format: bool,
+    pub available: bool,          // false when the format is invalid or the directory exists
+    pub reason: Option<String>,   // why the name can't be used
+}
+
 /// Validation result for a skill
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ValidationResult {
@@ -49,15 +295,73 @@ pub struct ValidationResult {
     pub warnings: Vec<String>,
 }
 
-/// Get the personal skills directory path
-fn get_personal_skills_dir(_app_handle: &AppHandle) -> Result<PathBuf, String> {
-    dirs::home_dir()
-        .ok_or("无法获取用户主目录".to_string())
+/// Environment variable the Claude CLI reads its config directory from instead of `~/.claude`
+const CLAUDE_CONFIG_DIR_ENV: &str = "CLAUDE_CONFIG_DIR";
+
+/// `app_settings` key holding an explicit personal skills directory
+const PERSONAL_SKILLS_DIR_SETTING: &str = "personal_skills_dir";
+
+/// Resolve the personal skills directory
+///
+/// An explicit app setting wins; otherwise skills live in `skills` under the Claude config
+/// directory, which is `$CLAUDE_CONFIG_DIR` when set (as the CLI resolves it) or `~/.claude`.
+fn resolve_personal_skills_dir(
+    setting: Option<&str>,
+    config_dir: Option<&std::ffi::OsStr>,
+    home: Option<PathBuf>,
+) -> Result<PathBuf, String> {
+    if let Some(dir) = setting.map(str::trim).filter(|dir| !dir.is_empty()) {
+        return Ok(PathBuf::from(dir));
+    }
+    if let Some(config_dir) = config_dir.filter(|dir| !dir.is_empty()) {
+        return Ok(PathBuf::from(config_dir).join("skills"));
+    }
+    home.ok_or("无法获取用户主目录".to_string())
         .map(|home| home.join(".claude").join("skills"))
 }
 
+/// Read the personal skills directory override from the app settings, if one is set
+fn personal_skills_dir_setting(app_handle: &AppHandle) -> Option<String> {
+    let db = app_handle.try_state::<crate::commands::agents::AgentDb>()?;
+    let conn = db.0.lock().ok()?;
+    conn.query_row(
+        "SELECT value FROM app_settings WHERE key = ?1",
+        [PERSONAL_SKILLS_DIR_SETTING],
+        |row| row.get::<_, String>(0),
+    )
+    .ok()
+}
+
+/// Get the personal skills directory path
+fn get_personal_skills_dir(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    let skills_dir = resolve_personal_skills_dir(
+        personal_skills_dir_setting(app_handle).as_deref(),
+        std::env::var_os(CLAUDE_CONFIG_DIR_ENV).as_deref(),
+        dirs::home_dir(),
+    )?;
+    debug!("个人技能目录路径: {:?}", skills_dir);
+    Ok(skills_dir)
+}
+
+/// Resolve the skills directory for an explicit project root
+fn project_skills_dir_for(project_path: &str) -> PathBuf {
+    Path::new(project_path).join(".claude").join("skills")
+}
+
 /// Get the project skills directory path
-fn get_project_skills_dir(_app_handle: &AppHandle) -> Result<PathBuf, String> {
+///
+/// Uses `<project_path>/.claude/skills` when the caller supplies the active project;
+/// otherwise falls back to guessing the project root from the working directory.
+fn get_project_skills_dir(
+    _app_handle: &AppHandle,
+    project_path: Option<&str>,
+) -> Result<PathBuf, String> {
+    if let Some(project_path) = project_path.filter(|p| !p.trim().is_empty()) {
+        let skills_dir = project_skills_dir_for(project_path);
+        debug!("项目技能目录路径（显式指定）: {:?}", skills_dir);
+        return Ok(skills_dir);
+    }
+
     // Try to get the current working directory
     std::env::current_dir()
         .map_err(|e| {
@@ -87,630 +391,5438 @@ fn get_project_skills_dir(_app_handle: &AppHandle) -> Result<PathBuf, String> {
         })
 }
 
-/// Parse YAML frontmatter from SKILL.md content
-fn parse_yaml_frontmatter(content: &str) -> Result<(Option<String>, String), String> {
-    let trimmed = content.trim();
-
-    if trimmed.starts_with("---") {
-        // Find the closing --- by searching for it after the opening
-        let after_opening = &trimmed[3..];
-        let end_marker = after_opening.find("---\n")
-            .or_else(|| after_opening.find("---\r\n"))
-            .or_else(|| after_opening.find("---\n").or_else(|| after_opening.find("---\r\n")));
-
-        match end_marker {
-            Some(end_pos) => {
-                let yaml_content = &after_opening[..end_pos].trim();
-                let markdown_start = end_pos + 3; // Skip "---\n" or "---\r\n"
-                let markdown_content = if markdown_start < after_opening.len() {
-                    &after_opening[markdown_start..].trim()
-                } else {
-                    ""
-                };
-                Ok((Some(yaml_content.to_string()), markdown_content.to_string()))
-            }
-            None => Err("未找到 YAML 前置元数据结束符 '---'".to_string())
-        }
+/// Check that `skill_type` names a skills directory the app manages: "personal" or "project"
+///
+/// Skills from extra directories are listed read-only, so "extra" is refused here instead of
+/// quietly acting on a same-named project skill.
+fn ensure_managed_skill_type(skill_type: &str) -> Result<(), String> {
+    match skill_type {
+        "personal" | "project" => Ok(()),
+        "extra" => Err("附加目录中的技能是只读的，不支持此操作".to_string()),
+        other => Err(format!("未知的技能类型: '{}'", other)),
+    }
+}
+
+/// Resolve the skills directory for `skill_type` ("personal" or "project")
+fn skills_dir_for(
+    app_handle: &AppHandle,
+    skill_type: &str,
+    project_path: Option<&str>,
+) -> Result<PathBuf, String> {
+    ensure_managed_skill_type(skill_type)?;
+    if skill_type == "personal" {
+        get_personal_skills_dir(app_handle)
     } else {
-        Ok((None, trimmed.to_string()))
+        get_project_skills_dir(app_handle, project_path)
     }
 }
 
-/// Parse skill metadata from YAML frontmatter
-fn parse_skill_metadata(yaml_content: &str) -> Result<SkillMetadata, String> {
-    serde_yaml::from_str::<SkillMetadata>(yaml_content)
-        .map_err(|e| format!("解析 YAML 元数据失败: {}", e))
+/// Directory of the existing skill `name` inside `skills_dir`
+///
+/// The name is validated before it is joined, so `..`, `.` or `.trash` can't turn a
+/// per-skill operation into one on the skills root or beyond it.
+fn existing_skill_dir(skills_dir: &Path, name: &str) -> Result<PathBuf, String> {
+    validate_skill_name(name)?;
+    let skill_dir = skills_dir.join(name);
+    if !skill_dir.exists() {
+        return Err(format!("技能 '{}' 不存在", name));
+    }
+    Ok(skill_dir)
 }
 
-/// Validate skill format
-fn validate_skill(skill: &Skill) -> ValidationResult {
-    let mut errors = Vec::new();
-    let mut warnings = Vec::new();
+/// Directory of the existing skill `name` of `skill_type`
+fn skill_dir_for(
+    app_handle: &AppHandle,
+    skill_type: &str,
+    name: &str,
+    project_path: Option<&str>,
+) -> Result<PathBuf, String> {
+    existing_skill_dir(&skills_dir_for(app_handle, skill_type, project_path)?, name)
+}
 
-    // Validate name
-    if skill.name.len() > 64 {
-        errors.push("技能名称不能超过 64 个字符".to_string());
+/// Create a skills directory (and its `.claude` parent) if missing
+///
+/// An existing `.claude` or `skills` entry must be a directory or a symlink to one; a plain
+/// file or a dangling symlink in its place is reported with what to fix instead of a raw
+/// OS error from `create_dir_all`.
+fn ensure_skills_dir(skills_dir: &Path) -> Result<(), String> {
+    let components = [skills_dir.parent(), Some(skills_dir)];
+    for path in components.into_iter().flatten() {
+        let metadata = match fs::symlink_metadata(path) {
+            Ok(metadata) => metadata,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(format!("访问 {:?} 失败: {}", path, e)),
+        };
+
+        if metadata.file_type().is_symlink() {
+            let target = fs::read_link(path)
+                .map(|t| t.display().to_string())
+                .unwrap_or_default();
+            match fs::metadata(path) {
+                Ok(resolved) if resolved.is_dir() => {}
+                Ok(_) => {
+                    error!("{:?} 指向的不是目录: {}", path, target);
+                    return Err(format!(
+                        "{:?} 是指向 {} 的符号链接，但目标不是目录；请修复该链接或将其删除后重试",
+                        path, target
+                    ));
+                }
+                Err(e) => {
+                    error!("{:?} 是失效的符号链接: {}", path, e);
+                    return Err(format!(
+                        "{:?} 是指向 {} 的失效符号链接；请创建目标目录或删除该链接后重试",
+                        path, target
+                    ));
+                }
+            }
+        } else if !metadata.is_dir() {
+            error!("{:?} 已存在但不是目录", path);
+            return Err(format!("{:?} 已存在但不是目录；请将其移走或删除后重试", path));
+        }
     }
-    if skill.name.len() < 1 {
-        errors.push("技能名称不能为空".to_string());
+
+    fs::create_dir_all(skills_dir).map_err(|e| {
+        error!("创建技能目录失败: {}", e);
+        format!("无法创建技能目录 {:?}: {}", skills_dir, e)
+    })
+}
+
+/// Environment variable listing extra skill directories, separated like `PATH`
+const EXTRA_SKILL_PATHS_ENV: &str = "CODESTUDIO_SKILL_PATHS";
+
+/// Parse the extra skill directories from `CODESTUDIO_SKILL_PATHS`, dropping empties and repeats
+fn extra_skill_dirs(value: Option<&std::ffi::OsStr>) -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = Vec::new();
+    for dir in value.map(std::env::split_paths).into_iter().flatten() {
+        if !dir.as_os_str().is_empty() && !dirs.contains(&dir) {
+            dirs.push(dir);
+        }
     }
-    if !skill.name.chars().all(|c| c.is_lowercase() || c.is_numeric() || c == '-') {
-        errors.push("技能名称只能包含小写字母、数字和连字符".to_string());
+    dirs
+}
+
+/// Manifest inside a skills directory listing disabled skill names, one per line
+const DISABLED_SKILLS_MANIFEST: &str = ".disabled";
+
+/// Read the disabled skill names for a skills directory
+///
+/// A missing manifest means every skill is enabled. Blank lines and `#` comments are ignored.
+fn read_disabled_skills(skills_dir: &Path) -> Result<BTreeSet<String>, String> {
+    match fs::read_to_string(skills_dir.join(DISABLED_SKILLS_MANIFEST)) {
+        Ok(content) => Ok(content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(String::from)
+            .collect()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(BTreeSet::new()),
+        Err(e) => Err(format!("读取禁用技能清单失败: {}", e)),
     }
+}
 
-    // Validate description
-    if skill.description.len() > 1024 {
-        errors.push("技能描述不能超过 1024 个字符".to_string());
+/// Add or remove a skill from the disabled manifest
+///
+/// The manifest is rewritten sorted, and removed once no skill is disabled.
+fn set_skill_enabled_in_manifest(skills_dir: &Path, name: &str, enabled: bool) -> Result<(), String> {
+    let mut disabled = read_disabled_skills(skills_dir)?;
+    let changed = if enabled {
+        disabled.remove(name)
+    } else {
+        disabled.insert(name.to_string())
+    };
+    if !changed {
+        return Ok(());
     }
-    if skill.description.len() < 10 {
-        warnings.push("建议提供更详细的技能描述（至少 10 个字符）".to_string());
+
+    let manifest = skills_dir.join(DISABLED_SKILLS_MANIFEST);
+    if disabled.is_empty() {
+        fs::remove_file(&manifest).map_err(|e| format!("删除禁用技能清单失败: {}", e))
+    } else {
+        let mut content = disabled.into_iter().collect::<Vec<_>>().join("\n");
+        content.push('\n');
+        write_atomic(&manifest, content).map_err(|e| format!("写入禁用技能清单失败: {}", e))
     }
+}
 
-    // Validate YAML frontmatter
-    if let Some(yaml_content) = &skill.yaml_frontmatter {
-        if let Err(e) = serde_yaml::from_str::<serde_yaml::Value>(yaml_content) {
-            errors.push(format!("YAML 语法错误: {}", e));
-        }
+/// Folder inside a skills directory that soft-deleted skills are moved to
+const SKILL_TRASH_DIR: &str = ".trash";
+
+/// Short token the caller must echo back to delete `name` without `confirm`
+fn skill_delete_token(name: &str) -> String {
+    use sha2::{Digest, Sha256};
+
+    let digest = Sha256::digest(format!("skill-delete:{}", name).as_bytes());
+    digest[..4].iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Delete skill `name` from `skills_dir` once the request is confirmed
+///
+/// Unconfirmed requests only report how many files would go. With `soft` the directory is
+/// moved to `.trash/<name>-<timestamp>` so it can be restored by hand.
+fn delete_skill_dir(
+    skills_dir: &Path,
+    name: &str,
+    confirm: bool,
+    confirm_token: Option<&str>,
+    soft: bool,
+) -> Result<SkillDeleteResult, String> {
+    let skill_dir = existing_skill_dir(skills_dir, name)?;
+
+    let file_count = walkdir::WalkDir::new(&skill_dir)
+        .into_iter()
+        .flatten()
+        .filter(|entry| entry.file_type().is_file())
+        .count();
+    let confirmation_token = skill_delete_token(name);
+    let mut result = SkillDeleteResult {
+        deleted: false,
+        file_count,
+        confirmation_token,
+        trash_path: None,
+    };
+
+    if !confirm && confirm_token.map(str::trim) != Some(result.confirmation_token.as_str()) {
+        info!("删除技能 '{}' 需要确认（{} 个文件）", name, file_count);
+        return Ok(result);
     }
 
-    // Check if files exist (only if file_path is not empty)
-    if !skill.file_path.is_empty() && !Path::new(&skill.file_path).exists() {
-        errors.push("技能目录不存在".to_string());
+    if soft {
+        let trash_dir = skills_dir.join(SKILL_TRASH_DIR);
+        fs::create_dir_all(&trash_dir).map_err(|e| format!("创建回收站目录失败: {}", e))?;
+        let timestamp = chrono::Local::now().format("%Y%m%d-%H%M%S%3f");
+        let target = trash_dir.join(format!("{}-{}", name, timestamp));
+        fs::rename(&skill_dir, &target).map_err(|e| format!("移动技能到回收站失败: {}", e))?;
+        info!("技能 '{}' 已移到回收站: {:?}", name, target);
+        result.trash_path = Some(target.to_string_lossy().to_string());
+    } else {
+        fs::remove_dir_all(&skill_dir).map_err(|e| e.to_string())?;
+        info!("已删除技能 '{}'（{} 个文件）", name, file_count);
     }
+    result.deleted = true;
 
-    ValidationResult {
-        is_valid: errors.is_empty(),
-        errors,
-        warnings,
+    // Drop any stale disabled entry so a re-created skill starts enabled
+    if let Err(e) = set_skill_enabled_in_manifest(skills_dir, name, true) {
+        warn!("{}", e);
+    }
+    if let Err(e) = record_skill_template_origin(skills_dir, name, None) {
+        warn!("{}", e);
     }
+    if let Err(e) = record_skill_usage(skills_dir, name, None) {
+        warn!("{}", e);
+    }
+
+    Ok(result)
 }
 
-/// List all skills (both personal and project)
-#[tauri::command]
-pub async fn skill_list_all(
-    app_handle: tauri::AppHandle,
-) -> Result<Vec<Skill>, String> {
-    let mut all_skills = Vec::new();
+/// How long trashed skills are kept before `skill_empty_trash` purges them by default
+const SKILL_TRASH_RETENTION_DAYS: u32 = 30;
 
-    // List personal skills
-    match skill_list_by_type(app_handle.clone(), "personal".to_string()).await {
-        Ok(mut personal_skills) => all_skills.append(&mut personal_skills),
-        Err(e) => warn!("获取个人技能失败: {}", e),
-    }
+/// Split a trash folder name (`<name>-<YYYYMMDD>-<HHMMSSmmm>`) into the skill name and
+/// when it was deleted
+fn parse_trash_entry_name(entry: &str) -> Option<(String, chrono::DateTime<chrono::Local>)> {
+    use chrono::{TimeZone, Timelike};
 
-    // List project skills
-    match skill_list_by_type(app_handle.clone(), "project".to_string()).await {
-        Ok(mut project_skills) => all_skills.append(&mut project_skills),
-        Err(e) => warn!("获取项目技能失败: {}", e),
+    let mut parts = entry.rsplitn(3, '-');
+    let time = parts.next()?;
+    let date = parts.next()?;
+    let name = parts.next().filter(|name| !name.is_empty())?;
+    if time.len() != 9 || !time.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
     }
 
-    Ok(all_skills)
+    let date = chrono::NaiveDate::parse_from_str(date, "%Y%m%d").ok()?;
+    let (hms, millis) = time.split_at(6);
+    let time = chrono::NaiveTime::parse_from_str(hms, "%H%M%S")
+        .ok()?
+        .with_nanosecond(millis.parse::<u32>().ok()? * 1_000_000)?;
+    let deleted_at = chrono::Local.from_local_datetime(&date.and_time(time)).earliest()?;
+    Some((name.to_string(), deleted_at))
 }
 
-/// List skills by type (personal or project)
-#[tauri::command]
-pub async fn skill_list_by_type(
-    app_handle: tauri::AppHandle,
-    skill_type: String,
-) -> Result<Vec<Skill>, String> {
-    let skills_dir = if skill_type == "personal" {
-        get_personal_skills_dir(&app_handle)?
-    } else {
-        get_project_skills_dir(&app_handle)?
+/// List the trashed skills of one skills directory, newest first
+///
+/// Entries whose folder name doesn't follow the trash naming scheme are skipped.
+fn list_trash_in_dir(skills_dir: &Path, skill_type: &str) -> Vec<TrashedSkill> {
+    let entries = match fs::read_dir(skills_dir.join(SKILL_TRASH_DIR)) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
     };
 
-    debug!("列出技能目录: {:?}", skills_dir);
+    let mut trashed: Vec<(chrono::DateTime<chrono::Local>, TrashedSkill)> = entries
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| {
+            let trash_id = entry.file_name().to_string_lossy().to_string();
+            let (name, deleted_at) = parse_trash_entry_name(&trash_id)?;
+            Some((
+                deleted_at,
+                TrashedSkill {
+                    name,
+                    skill_type: skill_type.to_string(),
+                    trash_id,
+                    deleted_at: deleted_at.to_rfc3339(),
+                    path: entry.path().to_string_lossy().to_string(),
+                },
+            ))
+        })
+        .collect();
+    trashed.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.name.cmp(&b.1.name)));
+    trashed.into_iter().map(|(_, skill)| skill).collect()
+}
 
-    // Ensure directory exists
-    if let Err(e) = fs::metadata(&skills_dir) {
-        if e.kind() == std::io::ErrorKind::NotFound {
-            info!("技能目录不存在，正在创建: {:?}", skills_dir);
-            if let Err(create_err) = fs::create_dir_all(&skills_dir) {
-                error!("创建技能目录失败: {}", create_err);
-                return Err(format!("无法创建技能目录: {}", create_err));
-            }
-            debug!("技能目录创建成功");
-        } else {
-            error!("获取技能目录元数据失败: {}", e);
-            return Err(format!("访问技能目录失败: {}", e));
+/// Move the most recently trashed copy of `name` back into `skills_dir`
+///
+/// Refuses when a live skill already uses the name. Returns the restored skill directory.
+fn restore_skill_from_trash(skills_dir: &Path, name: &str) -> Result<PathBuf, String> {
+    let target = skills_dir.join(name);
+    if target.exists() {
+        return Err(format!("技能 '{}' 已存在，请先重命名或删除后再恢复", name));
+    }
+
+    let entry = list_trash_in_dir(skills_dir, "")
+        .into_iter()
+        .find(|entry| entry.name == name)
+        .ok_or(format!("回收站中没有技能 '{}'", name))?;
+    fs::rename(&entry.path, &target).map_err(|e| format!("恢复技能失败: {}", e))?;
+    info!("已从回收站恢复技能 '{}'", name);
+    Ok(target)
+}
+
+/// Permanently remove trashed skills deleted before `cutoff`, returning how many were purged
+fn purge_trash_in_dir(skills_dir: &Path, cutoff: chrono::DateTime<chrono::Local>) -> Result<usize, String> {
+    let mut purged = 0;
+    for entry in list_trash_in_dir(skills_dir, "") {
+        let expired = chrono::DateTime::parse_from_rfc3339(&entry.deleted_at)
+            .map(|deleted_at| deleted_at < cutoff)
+            .unwrap_or(false);
+        if expired {
+            fs::remove_dir_all(&entry.path)
+                .map_err(|e| format!("清理回收站失败 {}: {}", entry.trash_id, e))?;
+            purged += 1;
         }
     }
+    Ok(purged)
+}
 
-    let mut skills = Vec::new();
+/// Sidecar inside a skills directory mapping skill names to their last-used time
+const SKILL_USAGE_SIDECAR: &str = ".usage.json";
 
-    // Read all subdirectories in skills dir
-    let entries = match fs::read_dir(&skills_dir) {
-        Ok(entries) => entries,
+/// Read the last-used times recorded for a skills directory; missing or unreadable data is empty
+fn read_skill_usage(skills_dir: &Path) -> BTreeMap<String, String> {
+    match fs::read_to_string(skills_dir.join(SKILL_USAGE_SIDECAR)) {
+        Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+            warn!("技能使用记录格式错误，已忽略: {}", e);
+            BTreeMap::new()
+        }),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
         Err(e) => {
-            error!("读取技能目录失败: {}", e);
-            return Err(format!("无法读取技能目录: {}", e));
+            warn!("读取技能使用记录失败: {}", e);
+            BTreeMap::new()
         }
-    };
+    }
+}
 
-    for entry in entries {
-        let entry = entry.map_err(|e| e.to_string())?;
-        let path = entry.path();
+/// Serializes read-modify-write cycles on usage sidecars so concurrent reads don't lose updates
+static SKILL_USAGE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Record `name` as used at `when` (or with None, forget it) in the skills directory's usage
+/// sidecar
+fn record_skill_usage(
+    skills_dir: &Path,
+    name: &str,
+    when: Option<chrono::DateTime<chrono::Utc>>,
+) -> Result<(), String> {
+    let _guard = SKILL_USAGE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut usage = read_skill_usage(skills_dir);
+    match when {
+        Some(when) => {
+            usage.insert(name.to_string(), when.to_rfc3339());
+        }
+        None => {
+            if usage.remove(name).is_none() {
+                return Ok(());
+            }
+        }
+    }
+    let content = serde_json::to_string_pretty(&usage)
+        .map_err(|e| format!("序列化技能使用记录失败: {}", e))?;
+    write_atomic(skills_dir.join(SKILL_USAGE_SIDECAR), content)
+        .map_err(|e| format!("写入技能使用记录失败: {}", e))
+}
 
-        if path.is_dir() {
-            let skill_file = path.join("SKILL.md");
+/// Sidecar inside a skills directory recording the template each skill was created from
+const SKILL_TEMPLATE_SIDECAR: &str = ".templates.json";
 
-            if skill_file.exists() {
-                match read_skill_file(app_handle.clone(), skill_file.to_string_lossy().to_string(), skill_type.clone()).await {
-                    Ok(mut skill) => {
-                        // Get additional files in the skill directory (optional, don't fail if this errors)
-                        match list_skill_files(path.clone()).await {
-                            Ok(files) => skill.files = files,
-                            Err(e) => debug!("获取技能文件列表失败（可选）: {}", e),
-                        }
+/// The template a skill was created from and the variables it was filled in with
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct SkillTemplateOrigin {
+    template_id: String,
+    #[serde(default)]
+    variables: BTreeMap<String, String>,
+}
 
-                        skills.push(skill);
-                    }
-                    Err(e) => warn!("读取技能失败: {}", e),
-                }
-            } else {
-                debug!("技能目录中没有 SKILL.md 文件: {:?}", path);
-            }
+/// Read the template origins recorded for a skills directory; missing or unreadable data is empty
+fn read_skill_template_origins(skills_dir: &Path) -> BTreeMap<String, SkillTemplateOrigin> {
+    match fs::read_to_string(skills_dir.join(SKILL_TEMPLATE_SIDECAR)) {
+        Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+            warn!("技能模板记录格式错误，已忽略: {}", e);
+            BTreeMap::new()
+        }),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
+        Err(e) => {
+            warn!("读取技能模板记录失败: {}", e);
+            BTreeMap::new()
         }
     }
+}
 
-    debug!("成功加载 {} 个技能", skills.len());
-    Ok(skills)
+/// Serializes read-modify-write cycles on template sidecars, like `SKILL_USAGE_LOCK`
+static SKILL_TEMPLATE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Record (or with None, forget) the template skill `name` was created from
+fn record_skill_template_origin(
+    skills_dir: &Path,
+    name: &str,
+    origin: Option<SkillTemplateOrigin>,
+) -> Result<(), String> {
+    let _guard = SKILL_TEMPLATE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut origins = read_skill_template_origins(skills_dir);
+    match origin {
+        Some(origin) => {
+            origins.insert(name.to_string(), origin);
+        }
+        None => {
+            if origins.remove(name).is_none() {
+                return Ok(());
+            }
+        }
+    }
+    let content = serde_json::to_string_pretty(&origins)
+        .map_err(|e| format!("序列化技能模板记录失败: {}", e))?;
+    write_atomic(skills_dir.join(SKILL_TEMPLATE_SIDECAR), content)
+        .map_err(|e| format!("写入技能模板记录失败: {}", e))
 }
 
-/// Read a skill from SKILL.md file
-#[tauri::command]
-pub async fn skill_read(
-    app_handle: tauri::AppHandle,
-    name: String,
-    skill_type: String,
-) -> Result<Skill, String> {
-    let skills_dir = if skill_type == "personal" {
-        get_personal_skills_dir(&app_handle)?
-    } else {
-        get_project_skills_dir(&app_handle)?
+/// Parse YAML frontmatter from SKILL.md content
+///
+/// Line endings are normalized first so Windows-authored (`\r\n`) files split
+/// exactly like Unix ones; the closing `---` must sit on its own line.
+fn parse_yaml_frontmatter(content: &str) -> Result<(Option<String>, String), String> {
+    let normalized = content.replace("\r\n", "\n");
+    let trimmed = normalized.trim();
+
+    let Some(after_opening) = trimmed.strip_prefix("---") else {
+        return Ok((None, trimmed.to_string()));
     };
 
-    let skill_path = skills_dir.join(&name).join("SKILL.md");
+    // Walk the remaining lines looking for a line that is exactly "---"
+    let mut offset = 0;
+    while offset <= after_opening.len() {
+        let line_end = after_opening[offset..]
+            .find('\n')
+            .map(|pos| offset + pos)
+            .unwrap_or(after_opening.len());
 
-    if !skill_path.exists() {
-        return Err(format!("技能 '{}' 不存在", name));
+        if offset > 0 && after_opening[offset..line_end].trim_end() == "---" {
+            let yaml_content = after_opening[..offset].trim();
+            let markdown_content = after_opening
+                .get(line_end + 1..)
+                .unwrap_or("")
+                .trim();
+            return Ok((Some(yaml_content.to_string()), markdown_content.to_string()));
+        }
+
+        offset = line_end + 1;
     }
 
-    read_skill_file(app_handle.clone(), skill_path.to_string_lossy().to_string(), skill_type).await
+    Err("未找到 YAML 前置元数据结束符 '---'".to_string())
 }
 
-/// Read skill file and parse it
-async fn read_skill_file(
-    _app_handle: tauri::AppHandle,
-    skill_file_path: String,
-    skill_type: String,
-) -> Result<Skill, String> {
-    let mut file = fs::File::open(&skill_file_path)
-        .map_err(|e| format!("打开文件失败: {}", e))?;
+/// Build the YAML frontmatter block for SKILL.md, letting serde_yaml handle quoting
+fn build_yaml_frontmatter(metadata: &SkillMetadata) -> Result<String, String> {
+    let yaml = serde_yaml::to_string(metadata)
+        .map_err(|e| format!("序列化 YAML 元数据失败: {}", e))?;
+    Ok(format!("---\n{}---\n", yaml))
+}
 
-    let mut content = String::new();
-    file.read_to_string(&mut content)
-        .map_err(|e| format!("读取文件失败: {}", e))?;
+/// Parse skill metadata from YAML frontmatter
+fn parse_skill_metadata(yaml_content: &str) -> Result<SkillMetadata, String> {
+    serde_yaml::from_str::<SkillMetadata>(yaml_content)
+        .map_err(|e| format!("解析 YAML 元数据失败: {}", e))
+}
 
-    let (yaml_frontmatter, markdown_content) = parse_yaml_frontmatter(&content)?;
+/// Tool names Claude Code recognizes in `allowed-tools`; keep in sync with the CLI
+pub const KNOWN_SKILL_TOOLS: &[&str] = &[
+    "Bash",
+    "BashOutput",
+    "Edit",
+    "ExitPlanMode",
+    "Glob",
+    "Grep",
+    "KillShell",
+    "LS",
+    "MultiEdit",
+    "NotebookEdit",
+    "NotebookRead",
+    "Read",
+    "Skill",
+    "SlashCommand",
+    "Task",
+    "TodoWrite",
+    "WebFetch",
+    "WebSearch",
+    "Write",
+];
 
-    let mut metadata = SkillMetadata {
-        name: skill_file_path
-            .split('/')
-            .nth_back(1)
-            .unwrap_or("")
-            .to_string(),
-        description: String::new(),
-        allowed_tools: None,
-    };
+/// Check `allowed-tools` entries, returning (errors, warnings)
+///
+/// Entries may carry a permission pattern such as `Bash(git:*)`; only the tool name
+/// before the parenthesis is checked. MCP tools (`mcp__server__tool`) are accepted as-is.
+fn validate_allowed_tools(tools: &[String]) -> (Vec<String>, Vec<String>) {
+    let mut errors = Vec::new();
+    let mut warnings = Vec::new();
 
-    // Parse metadata if YAML frontmatter exists
-    if let Some(yaml_content) = &yaml_frontmatter {
-        match parse_skill_metadata(yaml_content) {
-            Ok(parsed) => metadata = parsed,
-            Err(e) => warn!("解析元数据失败: {}", e),
+    for (index, tool) in tools.iter().enumerate() {
+        let tool = tool.trim();
+        if tool.is_empty() {
+            errors.push(format!("allowed-tools 第 {} 项为空", index + 1));
+            continue;
+        }
+
+        let tool_name = tool.split('(').next().unwrap_or(tool).trim();
+        if tool_name.starts_with("mcp__") || KNOWN_SKILL_TOOLS.contains(&tool_name) {
+            continue;
+        }
+
+        let suggestion = KNOWN_SKILL_TOOLS
+            .iter()
+            .find(|known| known.eq_ignore_ascii_case(tool_name));
+        match suggestion {
+            Some(known) => warnings.push(format!(
+                "未知的工具 '{}'，是否指的是 '{}'？",
+                tool_name, known
+            )),
+            None => warnings.push(format!("未知的工具 '{}'", tool_name)),
+        }
+    }
+
+    (errors, warnings)
+}
+
+/// Apply an `allowed-tools` update: `replace` swaps the whole list, then `add` appends
+/// and `remove` drops individual tools
+///
+/// The result keeps first-seen order without duplicates. A skill with no list stays
+/// unrestricted unless tools are replaced or added.
+fn merge_allowed_tools(
+    current: Option<Vec<String>>,
+    replace: Option<Vec<String>>,
+    add: &[String],
+    remove: &[String],
+) -> Option<Vec<String>> {
+    let base = replace.or(current);
+    if base.is_none() && add.is_empty() {
+        return None;
+    }
+
+    let mut merged: Vec<String> = Vec::new();
+    for tool in base.iter().flatten().chain(add) {
+        let tool = tool.trim();
+        if !tool.is_empty()
+            && !merged.iter().any(|t| t == tool)
+            && !remove.iter().any(|r| r.trim() == tool)
+        {
+            merged.push(tool.to_string());
+        }
+    }
+    Some(merged)
+}
+
+/// Validate a skill name used as a directory name
+/// Why `name` is not a valid skill name, or None if it is
+fn skill_name_problem(name: &str) -> Option<String> {
+    if name.is_empty() {
+        return Some("技能名称不能为空".to_string());
+    }
+    if name.len() > 64 {
+        return Some("技能名称不能超过 64 个字符".to_string());
+    }
+    if !name.chars().all(|c| c.is_lowercase() || c.is_numeric() || c == '-') {
+        return Some("技能名称只能包含小写字母、数字和连字符".to_string());
+    }
+    None
+}
+
+fn validate_skill_name(name: &str) -> Result<(), String> {
+    match skill_name_problem(name) {
+        Some(problem) => {
+            error!("技能名称无效 '{}': {}", name, problem);
+            Err(problem)
+        }
+        None => Ok(()),
+    }
+}
+
+/// Validate skill format
+/// Size limits for the SKILL.md body, which Claude loads into context on every invocation
+#[derive(Debug, Clone, Copy)]
+pub struct SkillSizeLimits {
+    /// Above this many words a warning is reported
+    pub warn_words: usize,
+    /// Above this many bytes a warning is reported
+    pub warn_bytes: usize,
+    /// Above this many words the skill is rejected
+    pub max_words: usize,
+    /// Above this many bytes the skill is rejected
+    pub max_bytes: usize,
+}
+
+impl Default for SkillSizeLimits {
+    fn default() -> Self {
+        Self {
+            warn_words: 5_000,
+            warn_bytes: 20 * 1024,
+            max_words: 25_000,
+            max_bytes: 100 * 1024,
         }
+    }
+}
+
+/// Check the SKILL.md body against `limits`, returning (errors, warnings)
+fn check_skill_body_size(markdown: &str, limits: &SkillSizeLimits) -> (Vec<String>, Vec<String>) {
+    let words = markdown.split_whitespace().count();
+    let bytes = markdown.len();
+    let mut errors = Vec::new();
+    let mut warnings = Vec::new();
+
+    if words > limits.max_words || bytes > limits.max_bytes {
+        errors.push(format!(
+            "SKILL.md 内容过大: {} 词 / {} 字节，上限为 {} 词 / {} 字节",
+            words, bytes, limits.max_words, limits.max_bytes
+        ));
+    } else if words > limits.warn_words || bytes > limits.warn_bytes {
+        warnings.push(format!(
+            "SKILL.md 内容较大: {} 词 / {} 字节（建议不超过 {} 词 / {} 字节），会占用每次调用的上下文，建议将细节拆分到附属文件",
+            words, bytes, limits.warn_words, limits.warn_bytes
+        ));
+    }
+
+    (errors, warnings)
+}
+
+/// Reject an oversized SKILL.md body before it is written, logging size warnings
+fn ensure_skill_body_size(name: &str, markdown: &str) -> Result<(), String> {
+    let (errors, warnings) = check_skill_body_size(markdown, &SkillSizeLimits::default());
+    for warning in &warnings {
+        warn!("技能 '{}': {}", name, warning);
+    }
+    if errors.is_empty() {
+        Ok(())
     } else {
-        // If no YAML frontmatter, try to extract name and description from first lines
-        let lines: Vec<&str> = markdown_content.lines().take(10).collect();
-        if !lines.is_empty() {
-            let first_line = lines[0].trim_start_matches('#').trim();
-            if !first_line.is_empty() {
-                metadata.name = first_line.to_string();
-            }
+        error!("技能 '{}' 内容过大", name);
+        Err(errors.join("; "))
+    }
+}
+
+fn validate_skill(skill: &Skill) -> ValidationResult {
+    let mut errors = Vec::new();
+    let mut warnings = Vec::new();
+
+    // Validate name
+    if skill.name.len() > 64 {
+        errors.push("技能名称不能超过 64 个字符".to_string());
+    }
+    if skill.name.len() < 1 {
+        errors.push("技能名称不能为空".to_string());
+    }
+    if !skill.name.chars().all(|c| c.is_lowercase() || c.is_numeric() || c == '-') {
+        errors.push("技能名称只能包含小写字母、数字和连字符".to_string());
+    }
+
+    // Validate description
+    if skill.description.len() > 1024 {
+        errors.push("技能描述不能超过 1024 个字符".to_string());
+    }
+    if skill.description.len() < 10 {
+        warnings.push("建议提供更详细的技能描述（至少 10 个字符）".to_string());
+    }
+
+    // Validate allowed tools
+    if let Some(tools) = &skill.allowed_tools {
+        let (tool_errors, tool_warnings) = validate_allowed_tools(tools);
+        errors.extend(tool_errors);
+        warnings.extend(tool_warnings);
+    }
+
+    // Validate SKILL.md body size
+    let (size_errors, size_warnings) =
+        check_skill_body_size(&skill.markdown_content, &SkillSizeLimits::default());
+    errors.extend(size_errors);
+    warnings.extend(size_warnings);
+
+    // Validate YAML frontmatter
+    if let Some(yaml_content) = &skill.yaml_frontmatter {
+        if let Err(e) = serde_yaml::from_str::<serde_yaml::Value>(yaml_content) {
+            errors.push(format!("YAML 语法错误: {}", e));
         }
     }
 
-    // Get file modification time
-    let file_metadata = fs::metadata(&skill_file_path)
-        .map_err(|e| e.to_string())?;
-    let last_modified = file_metadata.modified()
-        .map_err(|e| e.to_string())?
-        .duration_since(std::time::UNIX_EPOCH)
-        .map_err(|e| e.to_string())?
-        .as_secs();
+    // Check if files exist (only if file_path is not empty)
+    if !skill.file_path.is_empty() && !Path::new(&skill.file_path).exists() {
+        errors.push("技能目录不存在".to_string());
+    }
 
-    let last_modified_str = chrono::DateTime::from_timestamp(last_modified as i64, 0)
-        .unwrap_or_default()
-        .to_rfc3339();
+    ValidationResult {
+        is_valid: errors.is_empty(),
+        errors,
+        warnings,
+    }
+}
 
-    Ok(Skill {
-        name: metadata.name,
-        skill_type,
-        description: metadata.description,
-        file_path: {
-            // Extract directory path from skill_file_path
-            let path = Path::new(&skill_file_path);
-            if let Some(parent) = path.parent() {
-                // Remove trailing backslash if present (Windows)
-                let path_str = parent.to_string_lossy().to_string();
-                path_str.trim_end_matches(|c| c == '\\' || c == '/').to_string()
-            } else {
-                "".to_string()
+/// Sort skills in place by `"name"` (default), `"last_modified"` (newest first) or `"type"`
+///
+/// Ties fall back to type and then name so the order is stable across refreshes.
+fn sort_skills(skills: &mut [Skill], sort_by: Option<&str>) -> Result<(), String> {
+    match sort_by.unwrap_or("name") {
+        "name" => skills.sort_by(|a, b| {
+            a.name
+                .cmp(&b.name)
+                .then_with(|| a.skill_type.cmp(&b.skill_type))
+        }),
+        "last_modified" => skills.sort_by(|a, b| {
+            b.last_modified
+                .cmp(&a.last_modified)
+                .then_with(|| a.skill_type.cmp(&b.skill_type))
+                .then_with(|| a.name.cmp(&b.name))
+        }),
+        "type" => skills.sort_by(|a, b| {
+            a.skill_type
+                .cmp(&b.skill_type)
+                .then_with(|| a.name.cmp(&b.name))
+        }),
+        other => return Err(format!("不支持的排序方式: '{}'", other)),
+    }
+    Ok(())
+}
+
+/// List all skills: personal, project, and any extra directories in `CODESTUDIO_SKILL_PATHS`
+#[tauri::command]
+pub async fn skill_list_all(
+    app_handle: tauri::AppHandle,
+    project_path: Option<String>,
+    sort_by: Option<String>,
+) -> Result<Vec<Skill>, String> {
+    let mut all_skills = Vec::new();
+
+    // List personal skills
+    match skill_list_by_type(app_handle.clone(), "personal".to_string(), project_path.clone(), None).await {
+        Ok(mut personal_skills) => all_skills.append(&mut personal_skills),
+        Err(e) => warn!("获取个人技能失败: {}", e),
+    }
+
+    // List project skills
+    match skill_list_by_type(app_handle.clone(), "project".to_string(), project_path, None).await {
+        Ok(mut project_skills) => all_skills.append(&mut project_skills),
+        Err(e) => warn!("获取项目技能失败: {}", e),
+    }
+
+    // List skills from extra directories (e.g. a synced team folder)
+    let extra_dirs = extra_skill_dirs(std::env::var_os(EXTRA_SKILL_PATHS_ENV).as_deref());
+    all_skills.append(&mut list_extra_skills(&extra_dirs).await);
+
+    sort_skills(&mut all_skills, sort_by.as_deref())?;
+    Ok(all_skills)
+}
+
+/// Combine both listings into a summary, keeping one side when the other failed
+fn summarize_skills(
+    personal: Result<Vec<Skill>, String>,
+    project: Result<Vec<Skill>, String>,
+) -> SkillSummary {
+    let (personal, personal_error) = match personal {
+        Ok(skills) => (skills, None),
+        Err(e) => {
+            warn!("获取个人技能失败: {}", e);
+            (Vec::new(), Some(e))
+        }
+    };
+    let (project, project_error) = match project {
+        Ok(skills) => (skills, None),
+        Err(e) => {
+            warn!("获取项目技能失败: {}", e);
+            (Vec::new(), Some(e))
+        }
+    };
+
+    SkillSummary {
+        personal_count: personal.len(),
+        project_count: project.len(),
+        total: personal.len() + project.len(),
+        personal,
+        project,
+        personal_error,
+        project_error,
+    }
+}
+
+/// List personal and project skills concurrently, with per-type counts and errors
+#[tauri::command]
+pub async fn skill_summary(
+    app_handle: tauri::AppHandle,
+    project_path: Option<String>,
+) -> Result<SkillSummary, String> {
+    let (personal, project) = tokio::join!(
+        skill_list_by_type(app_handle.clone(), "personal".to_string(), project_path.clone(), None),
+        skill_list_by_type(app_handle.clone(), "project".to_string(), project_path, None),
+    );
+    Ok(summarize_skills(personal, project))
+}
+
+/// Number of personal and project skills, as returned by `skill_count`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SkillCount {
+    pub personal: usize,
+    pub project: usize,
+    pub total: usize,
+}
+
+/// Count the subdirectories of `skills_dir` that contain a SKILL.md, without reading them
+///
+/// A missing or unreadable directory counts as zero skills.
+fn count_skills_in_dir(skills_dir: &Path) -> usize {
+    let entries = match fs::read_dir(skills_dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            if skills_dir.exists() {
+                warn!("读取技能目录失败 {:?}: {}", skills_dir, e);
             }
-        },
-        yaml_frontmatter,
-        markdown_content,
-        files: Vec::new(), // Will be populated by caller
-        allowed_tools: metadata.allowed_tools,
-        last_modified: last_modified_str,
-    })
+            return 0;
+        }
+    };
+
+    entries
+        .flatten()
+        .filter(|entry| {
+            let path = entry.path();
+            path.is_dir() && path.join("SKILL.md").is_file()
+        })
+        .count()
 }
 
-/// List files in a skill directory
-async fn list_skill_files(skill_dir: PathBuf) -> Result<Vec<SkillFile>, String> {
-    debug!("列出技能文件: {:?}", skill_dir);
+/// Count personal and project skills without loading their contents
+#[tauri::command]
+pub async fn skill_count(
+    app_handle: tauri::AppHandle,
+    project_path: Option<String>,
+) -> Result<SkillCount, String> {
+    let personal = match get_personal_skills_dir(&app_handle) {
+        Ok(dir) => count_skills_in_dir(&dir),
+        Err(e) => {
+            warn!("获取个人技能目录失败: {}", e);
+            0
+        }
+    };
+    let project = match get_project_skills_dir(&app_handle, project_path.as_deref()) {
+        Ok(dir) => count_skills_in_dir(&dir),
+        Err(e) => {
+            warn!("获取项目技能目录失败: {}", e);
+            0
+        }
+    };
 
-    let mut files = Vec::new();
+    Ok(SkillCount {
+        personal,
+        project,
+        total: personal + project,
+    })
+}
 
-    if !skill_dir.exists() {
-        debug!("技能目录不存在: {:?}", skill_dir);
-        return Ok(files);
+/// Read every skill in each extra directory, tagging them with that directory as their source
+///
+/// Missing or unreadable directories are skipped. Same-named skills from different
+/// directories are all kept, told apart by `source`.
+async fn list_extra_skills(dirs: &[PathBuf]) -> Vec<Skill> {
+    let mut skills = Vec::new();
+    for dir in dirs {
+        if !dir.is_dir() {
+            warn!("额外技能目录不存在，已跳过: {:?}", dir);
+            continue;
+        }
+        match scan_skills_dir(dir, "extra", &dir.to_string_lossy()).await {
+            Ok(mut found) => skills.append(&mut found),
+            Err(e) => warn!("读取额外技能目录失败 {:?}: {}", dir, e),
+        }
     }
+    skills
+}
+
+/// Maximum number of skills read at once while scanning a skills directory
+const SKILL_LOAD_CONCURRENCY: usize = 8;
+
+/// Read all skills (subdirectories containing SKILL.md) directly under `skills_dir`
+async fn scan_skills_dir(skills_dir: &Path, skill_type: &str, source: &str) -> Result<Vec<Skill>, String> {
+    let skill_type = skill_type.to_string();
+    scan_skills_dir_with(skills_dir, source, SKILL_LOAD_CONCURRENCY, move |skill_dir| {
+        load_skill_dir(skill_dir, skill_type.clone())
+    })
+    .await
+}
+
+/// Read one skill directory: SKILL.md plus its helper file listing
+///
+/// The reads use blocking `std::fs`, so they run on tokio's blocking pool; that is what lets
+/// several skills load at once without parking the async worker threads.
+async fn load_skill_dir(skill_dir: PathBuf, skill_type: String) -> Result<Skill, String> {
+    tokio::task::spawn_blocking(move || {
+        let skill_file = skill_dir.join("SKILL.md").to_string_lossy().to_string();
+        let mut skill = read_skill_file_blocking(skill_file, skill_type)?;
+
+        // Get additional files in the skill directory (optional, don't fail if this errors)
+        match list_skill_files_blocking(skill_dir) {
+            Ok(files) => skill.files = files,
+            Err(e) => debug!("获取技能文件列表失败（可选）: {}", e),
+        }
+
+        Ok(skill)
+    })
+    .await
+    .map_err(|e| format!("读取技能任务异常退出: {}", e))?
+}
 
-    let entries = match fs::read_dir(&skill_dir) {
+/// Load every skill under `skills_dir` with `load`, running at most `concurrency` at once
+///
+/// Skills come back in directory order regardless of which finished first. A skill that
+/// fails to load is logged and left out without affecting the others.
+async fn scan_skills_dir_with<F, Fut>(
+    skills_dir: &Path,
+    source: &str,
+    concurrency: usize,
+    load: F,
+) -> Result<Vec<Skill>, String>
+where
+    F: Fn(PathBuf) -> Fut,
+    Fut: std::future::Future<Output = Result<Skill, String>> + Send + 'static,
+{
+    let disabled = read_disabled_skills(skills_dir).unwrap_or_else(|e| {
+        warn!("{}", e);
+        BTreeSet::new()
+    });
+
+    // Read all subdirectories in skills dir
+    let entries = match fs::read_dir(skills_dir) {
         Ok(entries) => entries,
         Err(e) => {
             error!("读取技能目录失败: {}", e);
-            return Err(format!("读取技能目录失败: {}", e));
+            return Err(format!("无法读取技能目录: {}", e));
         }
     };
 
+    let semaphore = Arc::new(tokio::sync::Semaphore::new(concurrency.max(1)));
+    let mut tasks = tokio::task::JoinSet::new();
+    let mut index = 0;
     for entry in entries {
-        let entry = match entry.map_err(|e| e.to_string()) {
-            Ok(entry) => entry,
-            Err(e) => {
-                warn!("读取目录项失败: {}", e);
-                continue;
-            }
-        };
+        let entry = entry.map_err(|e| e.to_string())?;
         let path = entry.path();
-        let name = path.file_name()
-            .unwrap_or_default()
-            .to_string_lossy()
-            .to_string();
 
-        debug!("发现文件: {} (is_dir: {})", name, path.is_dir());
+        if !path.is_dir() {
+            continue;
+        }
+        if !path.join("SKILL.md").exists() {
+            debug!("技能目录中没有 SKILL.md 文件: {:?}", path);
+            continue;
+        }
+
+        let dir_name = entry.file_name().to_string_lossy().to_string();
+        let semaphore = semaphore.clone();
+        let skill = load(path);
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire_owned().await;
+            (index, dir_name, skill.await)
+        });
+        index += 1;
+    }
+
+    let mut skills = Vec::new();
+    while let Some(joined) = tasks.join_next().await {
+        match joined {
+            Ok((index, dir_name, Ok(mut skill))) => {
+                skill.enabled = !disabled.contains(&dir_name);
+                skill.source = source.to_string();
+                skills.push((index, skill));
+            }
+            Ok((_, _, Err(e))) => warn!("读取技能失败: {}", e),
+            Err(e) => warn!("读取技能任务异常退出: {}", e),
+        }
+    }
+
+    skills.sort_by_key(|(index, _)| *index);
+    Ok(skills.into_iter().map(|(_, skill)| skill).collect())
+}
+
+/// List skills by type (personal or project)
+#[tauri::command]
+pub async fn skill_list_by_type(
+    app_handle: tauri::AppHandle,
+    skill_type: String,
+    project_path: Option<String>,
+    sort_by: Option<String>,
+) -> Result<Vec<Skill>, String> {
+    let skills_dir = skills_dir_for(&app_handle, &skill_type, project_path.as_deref())?;
+
+    debug!("列出技能目录: {:?}", skills_dir);
+
+    // Ensure directory exists
+    if !skills_dir.is_dir() {
+        info!("技能目录不存在，正在创建: {:?}", skills_dir);
+    }
+    ensure_skills_dir(&skills_dir)?;
+
+    let mut skills = scan_skills_dir(&skills_dir, &skill_type, &skill_type).await?;
+
+    sort_skills(&mut skills, sort_by.as_deref())?;
+
+    debug!("成功加载 {} 个技能", skills.len());
+    Ok(skills)
+}
+
+/// Read a skill from SKILL.md file
+#[tauri::command]
+pub async fn skill_read(
+    app_handle: tauri::AppHandle,
+    name: String,
+    skill_type: String,
+    project_path: Option<String>,
+) -> Result<Skill, String> {
+    let skills_dir = skills_dir_for(&app_handle, &skill_type, project_path.as_deref())?;
+
+    read_skill_in_dir(&skills_dir, &name, skill_type).await
+}
+
+/// Whether `class` is a code fence language class such as `language-rust`
+fn is_language_class(class: &str) -> bool {
+    class.strip_prefix("language-").is_some_and(|lang| {
+        !lang.is_empty()
+            && lang
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '+' | '#' | '.'))
+    })
+}
+
+/// Render markdown to HTML, dropping scripts, event handlers and other unsafe markup
+fn render_markdown_html(markdown: &str) -> String {
+    let options = Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH;
+    let mut html = String::new();
+    pulldown_cmark::html::push_html(&mut html, Parser::new_ext(markdown, options));
+
+    ammonia::Builder::default()
+        .add_tag_attributes("code", &["class"])
+        .attribute_filter(|element, attribute, value| match (element, attribute) {
+            ("code", "class") if !is_language_class(value) => None,
+            _ => Some(value.into()),
+        })
+        .clean(&html)
+        .to_string()
+}
+
+/// Split a SKILL.md into its frontmatter and rendered body
+fn render_skill_preview(content: &str) -> Result<SkillPreview, String> {
+    let (yaml_frontmatter, markdown_content) = parse_yaml_frontmatter(content)?;
+    let frontmatter = match yaml_frontmatter.as_deref().map(parse_skill_metadata) {
+        Some(Ok(metadata)) => Some(metadata),
+        Some(Err(e)) => {
+            warn!("解析元数据失败: {}", e);
+            None
+        }
+        None => None,
+    };
+
+    Ok(SkillPreview {
+        frontmatter,
+        html: render_markdown_html(&markdown_content),
+    })
+}
+
+/// Render a skill's SKILL.md body to sanitized HTML for previews
+///
+/// `content` previews unsaved editor text instead of the file on disk.
+#[tauri::command]
+pub async fn skill_preview(
+    app_handle: tauri::AppHandle,
+    name: String,
+    skill_type: String,
+    content: Option<String>,
+    project_path: Option<String>,
+) -> Result<SkillPreview, String> {
+    if let Some(content) = content {
+        return render_skill_preview(&content);
+    }
+
+    let skills_dir = skills_dir_for(&app_handle, &skill_type, project_path.as_deref())?;
+    let skill_path = skills_dir.join(&name).join("SKILL.md");
+    if !skill_path.exists() {
+        return Err(format!("技能 '{}' 不存在", name));
+    }
+
+    let content = fs::read_to_string(&skill_path).map_err(|e| format!("读取文件失败: {}", e))?;
+    render_skill_preview(&content)
+}
+
+/// Read skill `name` from `skills_dir` and note it as used
+///
+/// Failing to update the usage sidecar (e.g. on a read-only filesystem) only logs a warning.
+async fn read_skill_in_dir(skills_dir: &Path, name: &str, skill_type: String) -> Result<Skill, String> {
+    let skill_path = skills_dir.join(name).join("SKILL.md");
+
+    if !skill_path.exists() {
+        return Err(format!("技能 '{}' 不存在", name));
+    }
+
+    let mut skill = read_skill_file(skill_path.to_string_lossy().to_string(), skill_type).await?;
+    skill.enabled = !read_disabled_skills(skills_dir)?.contains(name);
+
+    if let Err(e) = record_skill_usage(skills_dir, name, Some(chrono::Utc::now())) {
+        warn!("技能 '{}': {}", name, e);
+    }
+    Ok(skill)
+}
+
+/// Order skills by last use, newest first; never-used skills follow, by name
+fn rank_recent_skills(
+    skills: Vec<(Skill, Option<String>)>,
+    limit: Option<usize>,
+) -> Vec<RecentSkill> {
+    let mut recent: Vec<_> = skills
+        .into_iter()
+        .map(|(skill, last_used)| {
+            let parsed = last_used
+                .as_deref()
+                .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok());
+            (parsed, RecentSkill { skill, last_used })
+        })
+        .collect();
+
+    recent.sort_by(|(a_time, a), (b_time, b)| {
+        b_time
+            .cmp(a_time)
+            .then_with(|| a.skill.name.cmp(&b.skill.name))
+            .then_with(|| a.skill.skill_type.cmp(&b.skill.skill_type))
+    });
+
+    recent
+        .into_iter()
+        .map(|(_, recent)| recent)
+        .take(limit.unwrap_or(usize::MAX))
+        .collect()
+}
+
+/// List personal and project skills by when they were last read, newest first
+#[tauri::command]
+pub async fn skill_recent(
+    app_handle: tauri::AppHandle,
+    limit: Option<usize>,
+    project_path: Option<String>,
+) -> Result<Vec<RecentSkill>, String> {
+    let mut skills = Vec::new();
+    for skill_type in ["personal", "project"] {
+        let skills_dir = skills_dir_for(&app_handle, skill_type, project_path.as_deref())?;
+        if !skills_dir.is_dir() {
+            continue;
+        }
+
+        let usage = read_skill_usage(&skills_dir);
+        match scan_skills_dir(&skills_dir, skill_type, skill_type).await {
+            Ok(found) => skills.extend(found.into_iter().map(|skill| {
+                let dir_name = Path::new(&skill.file_path)
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                let last_used = usage.get(&dir_name).cloned();
+                (skill, last_used)
+            })),
+            Err(e) => warn!("获取{}技能失败: {}", skill_type, e),
+        }
+    }
+
+    Ok(rank_recent_skills(skills, limit))
+}
+
+/// Read skill file and parse it
+async fn read_skill_file(
+    skill_file_path: String,
+    skill_type: String,
+) -> Result<Skill, String> {
+    read_skill_file_blocking(skill_file_path, skill_type)
+}
+
+/// `read_skill_file` for callers already on a blocking thread
+fn read_skill_file_blocking(
+    skill_file_path: String,
+    skill_type: String,
+) -> Result<Skill, String> {
+    let mut file = fs::File::open(&skill_file_path)
+        .map_err(|e| format!("打开文件失败: {}", e))?;
+
+    let mut content = String::new();
+    file.read_to_string(&mut content)
+        .map_err(|e| format!("读取文件失败: {}", e))?;
+
+    let (yaml_frontmatter, markdown_content) = parse_yaml_frontmatter(&content)?;
+
+    let mut metadata = SkillMetadata {
+        name: skill_file_path
+            .split('/')
+            .nth_back(1)
+            .unwrap_or("")
+            .to_string(),
+        description: String::new(),
+        allowed_tools: None,
+        extra: serde_yaml::Mapping::new(),
+    };
+
+    // Parse metadata if YAML frontmatter exists
+    if let Some(yaml_content) = &yaml_frontmatter {
+        match parse_skill_metadata(yaml_content) {
+            Ok(parsed) => metadata = parsed,
+            Err(e) => warn!("解析元数据失败: {}", e),
+        }
+    } else {
+        // If no YAML frontmatter, try to extract name and description from first lines
+        let lines: Vec<&str> = markdown_content.lines().take(10).collect();
+        if !lines.is_empty() {
+            let first_line = lines[0].trim_start_matches('#').trim();
+            if !first_line.is_empty() {
+                metadata.name = first_line.to_string();
+            }
+        }
+    }
+
+    // Get file modification time
+    let file_metadata = fs::metadata(&skill_file_path)
+        .map_err(|e| e.to_string())?;
+    let last_modified = file_metadata.modified()
+        .map_err(|e| e.to_string())?
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_err(|e| e.to_string())?
+        .as_secs();
+
+    let last_modified_str = chrono::DateTime::from_timestamp(last_modified as i64, 0)
+        .unwrap_or_default()
+        .to_rfc3339();
+
+    Ok(Skill {
+        name: metadata.name,
+        source: skill_type.clone(),
+        skill_type,
+        description: metadata.description,
+        file_path: {
+            // Extract directory path from skill_file_path
+            let path = Path::new(&skill_file_path);
+            if let Some(parent) = path.parent() {
+                // Remove trailing backslash if present (Windows)
+                let path_str = parent.to_string_lossy().to_string();
+                path_str.trim_end_matches(|c| c == '\\' || c == '/').to_string()
+            } else {
+                "".to_string()
+            }
+        },
+        yaml_frontmatter,
+        markdown_content,
+        files: Vec::new(), // Will be populated by caller
+        allowed_tools: metadata.allowed_tools,
+        extra_frontmatter: metadata.extra,
+        enabled: true,
+        last_modified: last_modified_str,
+    })
+}
+
+/// Maximum directory depth walked below a skill root
+const MAX_SKILL_FILE_DEPTH: usize = 8;
+
+/// List files in a skill directory, recursing into subdirectories
+///
+/// Each returned `SkillFile.path` is relative to the skill root using `/`
+/// separators (e.g. `scripts/build.sh`) so the UI can render a tree.
+async fn list_skill_files(skill_dir: PathBuf) -> Result<Vec<SkillFile>, String> {
+    list_skill_files_blocking(skill_dir)
+}
+
+/// `list_skill_files` for callers already on a blocking thread
+fn list_skill_files_blocking(skill_dir: PathBuf) -> Result<Vec<SkillFile>, String> {
+    debug!("列出技能文件: {:?}", skill_dir);
+
+    let mut files = Vec::new();
+
+    if !skill_dir.exists() {
+        debug!("技能目录不存在: {:?}", skill_dir);
+        return Ok(files);
+    }
+
+    let mut visited = std::collections::HashSet::new();
+    if let Ok(canonical) = fs::canonicalize(&skill_dir) {
+        visited.insert(canonical);
+    }
+
+    collect_skill_files(&skill_dir, &skill_dir, 0, &mut visited, &mut files)?;
+
+    debug!("技能文件列表完成: {} 个文件", files.len());
+    Ok(files)
+}
+
+/// Walk `dir` and append its entries to `files`, skipping directories already visited
+fn collect_skill_files(
+    root: &Path,
+    dir: &Path,
+    depth: usize,
+    visited: &mut std::collections::HashSet<PathBuf>,
+    files: &mut Vec<SkillFile>,
+) -> Result<(), String> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            error!("读取技能目录失败: {}", e);
+            return Err(format!("读取技能目录失败: {}", e));
+        }
+    };
+
+    for entry in entries {
+        let entry = match entry.map_err(|e| e.to_string()) {
+            Ok(entry) => entry,
+            Err(e) => {
+                warn!("读取目录项失败: {}", e);
+                continue;
+            }
+        };
+        let path = entry.path();
+        let is_dir = path.is_dir();
+
+        debug!("发现文件: {:?} (is_dir: {})", path, is_dir);
+
+        files.push(read_skill_file_entry(root, &path, is_dir));
+
+        if !is_dir {
+            continue;
+        }
+        if depth + 1 >= MAX_SKILL_FILE_DEPTH {
+            warn!("技能目录层级过深，停止遍历: {:?}", path);
+            continue;
+        }
+
+        // Symlinked directories may point back up the tree; only visit each real directory once
+        match fs::canonicalize(&path) {
+            Ok(canonical) if visited.insert(canonical) => {
+                if let Err(e) = collect_skill_files(root, &path, depth + 1, visited, files) {
+                    warn!("读取子目录失败 {:?}: {}", path, e);
+                }
+            }
+            Ok(_) => warn!("检测到符号链接循环，跳过: {:?}", path),
+            Err(e) => warn!("解析目录路径失败 {:?}: {}", path, e),
+        }
+    }
+
+    Ok(())
+}
+
+/// Build a `SkillFile` for a single entry, reading text content when possible
+fn read_skill_file_entry(root: &Path, path: &Path, is_dir: bool) -> SkillFile {
+    let name = path.file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+    let relative_path = path
+        .strip_prefix(root)
+        .unwrap_or(path)
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+
+    let size = if is_dir {
+        0
+    } else {
+        fs::metadata(path).map(|m| m.len()).unwrap_or(0)
+    };
+    let (content, is_binary) = if is_dir {
+        (None, false)
+    } else if has_binary_extension(path) {
+        debug!("跳过二进制文件: {} ({} 字节)", name, size);
+        (None, true)
+    } else {
+        match fs::read(path) {
+            // NUL bytes or invalid UTF-8 mean the file is binary
+            Ok(bytes) if bytes.contains(&0) => (None, true),
+            Ok(bytes) => match String::from_utf8(bytes) {
+                Ok(content) => {
+                    debug!("读取文件内容成功: {} ({} 字符)", name, content.len());
+                    (Some(content), false)
+                }
+                Err(_) => {
+                    debug!("检测到二进制文件: {} ({} 字节)", name, size);
+                    (None, true)
+                }
+            },
+            Err(e) => {
+                warn!("读取文件失败 {}: {}", name, e);
+                (None, false)
+            }
+        }
+    };
+
+    SkillFile {
+        name,
+        path: relative_path,
+        content,
+        is_directory: is_dir,
+        is_binary,
+        size,
+    }
+}
+
+/// Insert a listed file into `children`, creating intermediate directories on the way
+fn insert_tree_node(children: &mut Vec<TreeNode>, parts: &[&str], parent_path: &str, file: &SkillFile) {
+    let Some((first, rest)) = parts.split_first() else {
+        return;
+    };
+    let path = if parent_path.is_empty() {
+        first.to_string()
+    } else {
+        format!("{}/{}", parent_path, first)
+    };
+
+    let index = match children.iter().position(|node| node.name == *first) {
+        Some(index) => index,
+        None => {
+            children.push(TreeNode {
+                name: first.to_string(),
+                path: path.clone(),
+                is_directory: !rest.is_empty() || file.is_directory,
+                children: Vec::new(),
+                size: 0,
+            });
+            children.len() - 1
+        }
+    };
+
+    let node = &mut children[index];
+    if rest.is_empty() {
+        node.is_directory = file.is_directory;
+        if !file.is_directory {
+            node.size = file.size;
+        }
+    } else {
+        node.is_directory = true;
+        insert_tree_node(&mut node.children, rest, &path, file);
+    }
+}
+
+/// Sort directories first, then by name; fill in directory sizes
+fn finish_tree(nodes: &mut [TreeNode]) -> u64 {
+    nodes.sort_by(|a, b| {
+        b.is_directory
+            .cmp(&a.is_directory)
+            .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+            .then_with(|| a.name.cmp(&b.name))
+    });
+
+    let mut total = 0;
+    for node in nodes.iter_mut() {
+        if node.is_directory {
+            node.size = finish_tree(&mut node.children);
+        }
+        total += node.size;
+    }
+    total
+}
+
+/// Build a nested tree from the flat listing produced by `list_skill_files`
+fn build_skill_file_tree(skill_name: &str, files: &[SkillFile]) -> TreeNode {
+    let mut children = Vec::new();
+    for file in files {
+        let parts: Vec<&str> = file.path.split('/').filter(|p| !p.is_empty()).collect();
+        insert_tree_node(&mut children, &parts, "", file);
+    }
+    let size = finish_tree(&mut children);
+
+    TreeNode {
+        name: skill_name.to_string(),
+        path: String::new(),
+        is_directory: true,
+        children,
+        size,
+    }
+}
+
+/// Get a skill's files as a tree mirroring the on-disk layout
+#[tauri::command]
+pub async fn skill_get_file_tree(
+    app_handle: tauri::AppHandle,
+    skill_name: String,
+    skill_type: String,
+    project_path: Option<String>,
+) -> Result<TreeNode, String> {
+    let skill_dir = skill_dir_for(&app_handle, &skill_type, &skill_name, project_path.as_deref())?;
+
+    let files = list_skill_files(skill_dir).await?;
+    Ok(build_skill_file_tree(&skill_name, &files))
+}
+
+/// Create a new skill
+#[tauri::command]
+pub async fn skill_create(
+    app_handle: tauri::AppHandle,
+    name: String,
+    skill_type: String,
+    description: String,
+    markdown_content: String,
+    allowed_tools: Option<Vec<String>>,
+    project_path: Option<String>,
+) -> Result<Skill, String> {
+    debug!("开始创建技能: name={}, skill_type={}", name, skill_type);
+
+    // Validate input
+    validate_skill_name(&name)?;
+    if description.len() > 1024 {
+        error!("技能描述过长: {} 字符", description.len());
+        return Err("技能描述不能超过 1024 个字符".to_string());
+    }
+    ensure_skill_body_size(&name, &markdown_content)?;
+
+    let skills_dir = skills_dir_for(&app_handle, &skill_type, project_path.as_deref())?;
+
+    debug!("技能目录路径: {:?}", skills_dir);
+
+    let skill_dir = skills_dir.join(&name);
+    let skill_file = skill_dir.join("SKILL.md");
+
+    debug!("技能文件路径: {:?}", skill_file);
+
+    // Check if skill already exists
+    if skill_file.exists() {
+        error!("技能已存在: {}", name);
+        return Err(format!("技能 '{}' 已存在", name));
+    }
+
+    // Create skill directory
+    debug!("创建技能目录: {:?}", skill_dir);
+    ensure_skills_dir(&skills_dir)?;
+    fs::create_dir_all(&skill_dir)
+        .map_err(|e| {
+            error!("创建目录失败: {}", e);
+            format!("创建技能目录失败: {}", e)
+        })?;
+
+    // Build YAML frontmatter
+    let yaml_frontmatter = build_yaml_frontmatter(&SkillMetadata {
+        name: name.clone(),
+        description: description.clone(),
+        allowed_tools: allowed_tools.clone(),
+        extra: serde_yaml::Mapping::new(),
+    })?;
+
+    debug!("YAML frontmatter: {}", yaml_frontmatter);
+
+    // Write SKILL.md file
+    let content = format!("{}{}", yaml_frontmatter, markdown_content);
+    debug!("写入文件内容长度: {} 字符", content.len());
+
+    write_atomic(&skill_file, content)
+        .map_err(|e| {
+            error!("写入文件失败: {}", e);
+            format!("写入技能文件失败: {}", e)
+        })?;
+
+    debug!("技能创建成功: {}", name);
+
+    // Return the created skill
+    let skill = Skill {
+        name,
+        source: skill_type.clone(),
+        skill_type,
+        description,
+        file_path: skill_dir.to_string_lossy().to_string(),
+        yaml_frontmatter: Some(yaml_frontmatter),
+        markdown_content,
+        files: Vec::new(),
+        allowed_tools,
+        extra_frontmatter: serde_yaml::Mapping::new(),
+        enabled: true,
+        last_modified: chrono::Utc::now().to_rfc3339(),
+    };
+
+    Ok(skill)
+}
+
+/// Check a prospective skill name against the naming rules and the existing skill directories
+fn check_skill_name_availability(skills_dir: &Path, name: &str) -> SkillNameAvailability {
+    if let Some(problem) = skill_name_problem(name) {
+        return SkillNameAvailability {
+            valid_format: false,
+            available: false,
+            reason: Some(problem),
+        };
+    }
+
+    let taken = skills_dir.join(name).exists();
+    SkillNameAvailability {
+        valid_format: true,
+        available: !taken,
+        reason: taken.then(|| format!("技能 '{}' 已存在", name)),
+    }
+}
+
+/// Check whether `name` can be used for a new skill, so forms can validate while the user types
+#[tauri::command]
+pub async fn skill_name_available(
+    app_handle: tauri::AppHandle,
+    name: String,
+    skill_type: String,
+    project_path: Option<String>,
+) -> Result<SkillNameAvailability, String> {
+    let skills_dir = skills_dir_for(&app_handle, &skill_type, project_path.as_deref())?;
+    Ok(check_skill_name_availability(&skills_dir, &name))
+}
+
+/// Update an existing skill
+#[tauri::command]
+pub async fn skill_update(
+    app_handle: tauri::AppHandle,
+    name: String,
+    skill_type: String,
+    description: Option<String>,
+    markdown_content: Option<String>,
+    allowed_tools: Option<Vec<String>>,
+    add_tools: Option<Vec<String>>,
+    remove_tools: Option<Vec<String>>,
+    project_path: Option<String>,
+) -> Result<Skill, String> {
+    let skills_dir = skills_dir_for(&app_handle, &skill_type, project_path.as_deref())?;
+
+    let skill_dir = skills_dir.join(&name);
+    let skill_file = skill_dir.join("SKILL.md");
+
+    if !skill_file.exists() {
+        return Err(format!("技能 '{}' 不存在", name));
+    }
+
+    // Read current skill
+    let mut skill = read_skill_file(skill_file.to_string_lossy().to_string(), skill_type.clone()).await?;
+
+    // Update fields if provided
+    if let Some(desc) = description {
+        skill.description = desc;
+    }
+    if let Some(content) = markdown_content {
+        ensure_skill_body_size(&name, &content)?;
+        skill.markdown_content = content;
+    }
+    skill.allowed_tools = merge_allowed_tools(
+        skill.allowed_tools.take(),
+        allowed_tools,
+        &add_tools.unwrap_or_default(),
+        &remove_tools.unwrap_or_default(),
+    );
+
+    // Rebuild YAML frontmatter
+    let yaml_frontmatter = build_yaml_frontmatter(&SkillMetadata {
+        name: skill.name.clone(),
+        description: skill.description.clone(),
+        allowed_tools: skill.allowed_tools.clone(),
+        // Keep keys such as `version` or `tags` that this app doesn't manage
+        extra: skill.extra_frontmatter.clone(),
+    })?;
+
+    // Write updated content
+    let content = format!("{}{}", yaml_frontmatter, skill.markdown_content);
+    write_atomic(&skill_file, content).map_err(|e| e.to_string())?;
+
+    skill.yaml_frontmatter = Some(yaml_frontmatter);
+    skill.last_modified = chrono::Utc::now().to_rfc3339();
+
+    Ok(skill)
+}
+
+/// Recursively copy a directory, refusing to descend deeper than `MAX_SKILL_FILE_DEPTH`
+fn copy_dir_recursive(source: &Path, target: &Path, depth: usize) -> Result<(), String> {
+    if depth >= MAX_SKILL_FILE_DEPTH {
+        return Err(format!("技能目录层级过深: {:?}", source));
+    }
+
+    fs::create_dir_all(target).map_err(|e| format!("创建目录失败: {}", e))?;
+
+    for entry in fs::read_dir(source).map_err(|e| format!("读取目录失败: {}", e))? {
+        let entry = entry.map_err(|e| e.to_string())?;
+        let source_path = entry.path();
+        let target_path = target.join(entry.file_name());
+
+        if source_path.is_dir() {
+            copy_dir_recursive(&source_path, &target_path, depth + 1)?;
+        } else {
+            fs::copy(&source_path, &target_path)
+                .map_err(|e| format!("复制文件失败 {:?}: {}", source_path, e))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Rewrite the `name:` field of a SKILL.md, keeping all other frontmatter keys and the body
+fn rename_skill_frontmatter(content: &str, new_name: &str) -> Result<String, String> {
+    let (yaml_frontmatter, markdown_content) = parse_yaml_frontmatter(content)?;
+
+    let yaml = match yaml_frontmatter {
+        Some(yaml_content) => {
+            let mut value: serde_yaml::Value = serde_yaml::from_str(&yaml_content)
+                .map_err(|e| format!("解析 YAML 元数据失败: {}", e))?;
+            let mapping = value
+                .as_mapping_mut()
+                .ok_or("YAML 前置元数据必须是键值映射".to_string())?;
+            mapping.insert("name".into(), new_name.into());
+            serde_yaml::to_string(&value).map_err(|e| format!("序列化 YAML 元数据失败: {}", e))?
+        }
+        None => {
+            return Ok(format!(
+                "{}{}",
+                build_yaml_frontmatter(&SkillMetadata {
+                    name: new_name.to_string(),
+                    description: String::new(),
+                    allowed_tools: None,
+                    extra: serde_yaml::Mapping::new(),
+                })?,
+                markdown_content
+            ))
+        }
+    };
+
+    Ok(format!("---\n{}---\n{}", yaml, markdown_content))
+}
+
+/// Copy `source_dir` to `target_dir` and point the copied SKILL.md at `new_name`
+///
+/// The target must not exist; on failure the partial copy is removed and the source is
+/// never modified.
+fn duplicate_skill_dir(source_dir: &Path, target_dir: &Path, new_name: &str) -> Result<(), String> {
+    if !source_dir.join("SKILL.md").exists() {
+        return Err(format!("技能目录中没有 SKILL.md 文件: {:?}", source_dir));
+    }
+    if target_dir.exists() {
+        return Err(format!("技能 '{}' 已存在", new_name));
+    }
+
+    let result = copy_dir_recursive(source_dir, target_dir, 0).and_then(|_| {
+        let skill_file = target_dir.join("SKILL.md");
+        let content = fs::read_to_string(&skill_file)
+            .map_err(|e| format!("读取文件失败: {}", e))?;
+        let renamed = rename_skill_frontmatter(&content, new_name)?;
+        write_atomic(&skill_file, renamed).map_err(|e| format!("写入技能文件失败: {}", e))
+    });
+
+    if let Err(e) = &result {
+        error!("复制技能失败: {}", e);
+        if let Err(cleanup_err) = fs::remove_dir_all(target_dir) {
+            warn!("清理未完成的技能副本失败: {}", cleanup_err);
+        }
+    }
+
+    result
+}
+
+/// Duplicate skill `name` of `skill_type` as `new_name` of `target_type`, returning the new
+/// skill's directory
+///
+/// `skills_dir` maps a skill type to its skills directory. Both names are validated before
+/// they are joined.
+fn duplicate_skill_between(
+    skills_dir: impl Fn(&str) -> Result<PathBuf, String>,
+    name: &str,
+    skill_type: &str,
+    new_name: &str,
+    target_type: &str,
+) -> Result<PathBuf, String> {
+    validate_skill_name(new_name)?;
+
+    let source_dir = existing_skill_dir(&skills_dir(skill_type)?, name)?;
+    let target_skills_dir = skills_dir(target_type)?;
+    ensure_skills_dir(&target_skills_dir)?;
+
+    let target_dir = target_skills_dir.join(new_name);
+    duplicate_skill_dir(&source_dir, &target_dir, new_name)?;
+    Ok(target_dir)
+}
+
+/// Duplicate a skill, including all helper files, under a new name
+#[tauri::command]
+pub async fn skill_duplicate(
+    app_handle: tauri::AppHandle,
+    name: String,
+    new_name: String,
+    skill_type: String,
+    target_type: Option<String>,
+    project_path: Option<String>,
+) -> Result<Skill, String> {
+    let target_type = target_type.unwrap_or_else(|| skill_type.clone());
+    info!("复制技能: {} ({}) -> {} ({})", name, skill_type, new_name, target_type);
+
+    let target_dir = duplicate_skill_between(
+        |skill_type| skills_dir_for(&app_handle, skill_type, project_path.as_deref()),
+        &name,
+        &skill_type,
+        &new_name,
+        &target_type,
+    )?;
+
+    let skill_file = target_dir.join("SKILL.md");
+    let mut skill = read_skill_file(skill_file.to_string_lossy().to_string(), target_type).await?;
+    skill.files = list_skill_files(target_dir).await.unwrap_or_default();
+
+    Ok(skill)
+}
+
+/// Search ranking weights: a name hit outranks a description hit, which outranks a body hit
+const SEARCH_NAME_WEIGHT: u32 = 100;
+const SEARCH_DESCRIPTION_WEIGHT: u32 = 10;
+const SEARCH_CONTENT_WEIGHT: u32 = 1;
+
+/// Characters of context shown on each side of a body match
+const SEARCH_SNIPPET_CONTEXT: usize = 40;
+
+/// Find the byte offset of `needle_lower` in `haystack`, comparing case-insensitively
+fn find_case_insensitive(haystack: &str, needle_lower: &str) -> Option<usize> {
+    haystack.char_indices().map(|(i, _)| i).find(|&i| {
+        let mut rest = haystack[i..].chars().flat_map(char::to_lowercase);
+        needle_lower.chars().all(|c| rest.next() == Some(c))
+    })
+}
+
+/// Build a single-line snippet around a match starting at byte offset `start`
+fn build_search_snippet(content: &str, start: usize, match_chars: usize) -> String {
+    let before_chars: Vec<char> = content[..start].chars().collect();
+    let skip = before_chars.len().saturating_sub(SEARCH_SNIPPET_CONTEXT);
+    let before: String = before_chars[skip..].iter().collect();
+
+    let mut after_iter = content[start..].chars();
+    let after: String = after_iter
+        .by_ref()
+        .take(match_chars + SEARCH_SNIPPET_CONTEXT)
+        .collect();
+
+    let mut snippet = String::new();
+    if skip > 0 {
+        snippet.push('…');
+    }
+    snippet.push_str(&before);
+    snippet.push_str(&after);
+    if after_iter.next().is_some() {
+        snippet.push('…');
+    }
+
+    snippet.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Rank skills against a query; non-matching skills are dropped
+fn search_skills(skills: Vec<Skill>, query: &str) -> Vec<SkillSearchResult> {
+    let query_lower = query.trim().to_lowercase();
+    if query_lower.is_empty() {
+        return Vec::new();
+    }
+    let query_chars = query_lower.chars().count();
+
+    let mut results: Vec<SkillSearchResult> = skills
+        .into_iter()
+        .filter_map(|skill| {
+            let mut matched_fields = Vec::new();
+            let mut score = 0;
+            let mut snippet = None;
+
+            if find_case_insensitive(&skill.name, &query_lower).is_some() {
+                matched_fields.push("name".to_string());
+                score += SEARCH_NAME_WEIGHT;
+            }
+            if find_case_insensitive(&skill.description, &query_lower).is_some() {
+                matched_fields.push("description".to_string());
+                score += SEARCH_DESCRIPTION_WEIGHT;
+            }
+            if let Some(start) = find_case_insensitive(&skill.markdown_content, &query_lower) {
+                matched_fields.push("content".to_string());
+                score += SEARCH_CONTENT_WEIGHT;
+                snippet = Some(build_search_snippet(&skill.markdown_content, start, query_chars));
+            }
+
+            (score > 0).then_some(SkillSearchResult {
+                skill,
+                matched_fields,
+                score,
+                snippet,
+            })
+        })
+        .collect();
+
+    results.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.skill.name.cmp(&b.skill.name)));
+    results
+}
+
+/// Full-text search across skill names, descriptions and content
+#[tauri::command]
+pub async fn skill_search(
+    app_handle: tauri::AppHandle,
+    query: String,
+    skill_type: Option<String>,
+    project_path: Option<String>,
+) -> Result<Vec<SkillSearchResult>, String> {
+    let skills = match skill_type {
+        Some(skill_type) => skill_list_by_type(app_handle, skill_type, project_path, None).await?,
+        None => skill_list_all(app_handle, project_path, None).await?,
+    };
+
+    let results = search_skills(skills, &query);
+    debug!("技能搜索 '{}' 匹配 {} 个结果", query, results.len());
+    Ok(results)
+}
+
+/// Watch `dirs` recursively and call `on_change` once per changed path after each debounce window
+///
+/// The debounce thread exits when the returned watcher is dropped.
+fn start_skills_watcher<F>(dirs: &[PathBuf], on_change: F) -> Result<RecommendedWatcher, String>
+where
+    F: Fn(PathBuf) + Send + 'static,
+{
+    let (tx, rx) = mpsc::channel::<PathBuf>();
+
+    let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+        match res {
+            Ok(event) => {
+                if matches!(
+                    event.kind,
+                    EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+                ) {
+                    // Usage bookkeeping from skill_read and the trash folder aren't changes to
+                    // the skills themselves
+                    for path in event.paths {
+                        if path.file_name().is_some_and(|n| n == SKILL_USAGE_SIDECAR)
+                            || path.components().any(|c| c.as_os_str() == SKILL_TRASH_DIR)
+                        {
+                            continue;
+                        }
+                        let _ = tx.send(path);
+                    }
+                }
+            }
+            Err(e) => warn!("技能目录监听错误: {}", e),
+        }
+    })
+    .map_err(|e| format!("创建文件监听器失败: {}", e))?;
+
+    for dir in dirs {
+        watcher
+            .watch(dir, RecursiveMode::Recursive)
+            .map_err(|e| format!("监听目录失败 {:?}: {}", dir, e))?;
+        debug!("开始监听技能目录: {:?}", dir);
+    }
+
+    std::thread::spawn(move || {
+        while let Some(changed) =
+            next_debounced_batch(&rx, SKILL_WATCH_DEBOUNCE, SKILL_WATCH_MAX_DELAY)
+        {
+            for path in changed {
+                on_change(path);
+            }
+        }
+        debug!("技能目录监听已结束");
+    });
+
+    Ok(watcher)
+}
+
+/// Wait for the next change and collect the distinct paths that follow it
+///
+/// The batch ends once `quiet` passes without an event, or `max_delay` after its first event
+/// at the latest. Returns `None` when the sender is gone.
+fn next_debounced_batch(
+    rx: &mpsc::Receiver<PathBuf>,
+    quiet: Duration,
+    max_delay: Duration,
+) -> Option<Vec<PathBuf>> {
+    let first = rx.recv().ok()?;
+    let deadline = Instant::now() + max_delay;
+    let mut changed = vec![first];
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            break;
+        }
+        match rx.recv_timeout(quiet.min(remaining)) {
+            Ok(path) => {
+                if !changed.contains(&path) {
+                    changed.push(path);
+                }
+            }
+            Err(_) => break,
+        }
+    }
+    Some(changed)
+}
+
+/// Start watching the personal and project skills directories, emitting `skills-changed`
+///
+/// Directories that don't exist yet are skipped rather than created.
+#[tauri::command]
+pub async fn skill_watch_start(
+    app_handle: tauri::AppHandle,
+    project_path: Option<String>,
+) -> Result<(), String> {
+    let state = app_handle
+        .try_state::<SkillWatcherState>()
+        .ok_or("技能监听状态未初始化".to_string())?;
+    let mut guard = state.watcher.lock().map_err(|e| e.to_string())?;
+    if guard.is_some() {
+        debug!("技能目录监听已在运行");
+        return Ok(());
+    }
+
+    let mut dirs = Vec::new();
+    for dir in [
+        get_personal_skills_dir(&app_handle)?,
+        get_project_skills_dir(&app_handle, project_path.as_deref())?,
+    ] {
+        if !dir.is_dir() {
+            debug!("技能目录不存在，跳过监听: {:?}", dir);
+        } else if !dirs.contains(&dir) {
+            dirs.push(dir);
+        }
+    }
+
+    let app = app_handle.clone();
+    let watcher = start_skills_watcher(&dirs, move |path| {
+        let payload = SkillsChangedEvent {
+            path: path.to_string_lossy().to_string(),
+        };
+        let _ = app.emit("skills-changed", &payload);
+    })?;
+
+    *guard = Some(watcher);
+    info!("已开始监听技能目录: {:?}", dirs);
+    Ok(())
+}
+
+/// Stop watching the skills directories
+#[tauri::command]
+pub async fn skill_watch_stop(app_handle: tauri::AppHandle) -> Result<(), String> {
+    let state = app_handle
+        .try_state::<SkillWatcherState>()
+        .ok_or("技能监听状态未初始化".to_string())?;
+    let mut guard = state.watcher.lock().map_err(|e| e.to_string())?;
+    if guard.take().is_some() {
+        info!("已停止监听技能目录");
+    }
+    Ok(())
+}
+
+/// Built-in skill templates offered to new users
+fn builtin_skill_templates() -> Vec<SkillTemplate> {
+    let tools = |names: &[&str]| Some(names.iter().map(|s| s.to_string()).collect());
+
+    vec![
+        SkillTemplate {
+            id: "code-reviewer".to_string(),
+            title: "Code Reviewer".to_string(),
+            description: "Reviews code changes for correctness, readability and security issues. \
+                Use when asked to review a diff, pull request or file."
+                .to_string(),
+            allowed_tools: tools(&["Read", "Grep", "Glob"]),
+            markdown_content: "# {{name}}
+
+## Instructions
+
+1. Read the changed files and understand the intent of the change.
+2. Check for bugs, unhandled errors and edge cases.
+3. Flag security issues such as injection, unsafe input handling or leaked secrets.
+4. Suggest readability improvements only where they matter.
+
+## Output format
+
+- **Summary**: one paragraph describing the change
+- **Issues**: a list ordered by severity, each with file and line
+- **Suggestions**: optional, non-blocking improvements
+"
+            .to_string(),
+        },
+        SkillTemplate {
+            id: "api-docs".to_string(),
+            title: "API Docs".to_string(),
+            description: "Generates and updates API reference documentation from source code. \
+                Use when documenting endpoints, functions or public modules."
+                .to_string(),
+            allowed_tools: tools(&["Read", "Grep", "Glob", "Write", "Edit"]),
+            markdown_content: "# {{name}}
+
+## Instructions
+
+1. Locate the public API surface (endpoints, exported functions, types).
+2. For each item document its purpose, parameters, return value and errors.
+3. Include a short, runnable example for every endpoint or function.
+4. Keep existing documentation style and headings consistent.
+
+## Reference
+
+Place supporting material such as style guides in `references/` next to this file.
+"
+            .to_string(),
+        },
+        SkillTemplate {
+            id: "data-pipeline".to_string(),
+            title: "Data Pipeline".to_string(),
+            description: "Builds and debugs data processing pipelines that extract, transform \
+                and load data. Use when working with ETL scripts, CSV/JSON data or batch jobs."
+                .to_string(),
+            allowed_tools: tools(&["Read", "Write", "Edit", "Bash", "Glob"]),
+            markdown_content: "# {{name}}
+
+## Instructions
+
+1. Identify the data sources, their formats and expected volume.
+2. Validate input schemas before transforming anything.
+3. Keep each transformation step small, testable and idempotent.
+4. Log row counts between steps so data loss is easy to spot.
+
+## Scripts
+
+Put reusable helpers in `scripts/` and reference them from here.
+"
+            .to_string(),
+        },
+    ]
+}
+
+/// Look up a built-in template by id
+fn find_skill_template(template_id: &str) -> Result<SkillTemplate, String> {
+    builtin_skill_templates()
+        .into_iter()
+        .find(|template| template.id == template_id)
+        .ok_or(format!("未知的技能模板: '{}'", template_id))
+}
+
+/// Matches a `{{var}}` placeholder, allowing spaces inside the braces
+fn placeholder_regex() -> regex::Regex {
+    regex::Regex::new(r"\{\{\s*([A-Za-z0-9_-]+)\s*\}\}").expect("valid placeholder regex")
+}
+
+/// Replace `{{var}}` placeholders with values from `variables`, keeping unknown ones as written
+fn substitute_placeholders(text: &str, variables: &HashMap<String, String>) -> String {
+    placeholder_regex()
+        .replace_all(text, |caps: &regex::Captures| match variables.get(&caps[1]) {
+            Some(value) => value.clone(),
+            None => caps[0].to_string(),
+        })
+        .into_owned()
+}
+
+/// Fill in a template's description, allowed tools and body, returning warnings for leftovers
+///
+/// `name` is always the new skill's name and `description` defaults to the template's own
+/// description. A tool entry may expand to a comma-separated list (e.g. `{{tools}}`).
+fn render_skill_template(
+    template: &SkillTemplate,
+    name: &str,
+    variables: &HashMap<String, String>,
+) -> (SkillTemplate, Vec<String>) {
+    let mut variables = variables.clone();
+    variables.insert("name".to_string(), name.to_string());
+    let description = match variables.get("description") {
+        Some(description) => description.clone(),
+        None => substitute_placeholders(&template.description, &variables),
+    };
+    variables.insert("description".to_string(), description.clone());
+
+    let allowed_tools = template.allowed_tools.as_ref().map(|tools| {
+        tools
+            .iter()
+            .flat_map(|tool| {
+                substitute_placeholders(tool, &variables)
+                    .split(',')
+                    .map(|t| t.trim().to_string())
+                    .filter(|t| !t.is_empty())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>()
+    });
+    let rendered = SkillTemplate {
+        id: template.id.clone(),
+        title: template.title.clone(),
+        description,
+        allowed_tools,
+        markdown_content: substitute_placeholders(&template.markdown_content, &variables),
+    };
+
+    let regex = placeholder_regex();
+    let mut leftover = BTreeSet::new();
+    let texts = std::iter::once(&rendered.description)
+        .chain(rendered.allowed_tools.iter().flatten())
+        .chain(std::iter::once(&rendered.markdown_content));
+    for text in texts {
+        for caps in regex.captures_iter(text) {
+            leftover.insert(caps[1].to_string());
+        }
+    }
+    let warnings = leftover
+        .into_iter()
+        .map(|var| format!("模板占位符 {{{{{}}}}} 未提供取值，已原样保留", var))
+        .collect();
+
+    (rendered, warnings)
+}
+
+/// List the built-in skill templates
+#[tauri::command]
+pub async fn skill_list_templates() -> Result<Vec<SkillTemplate>, String> {
+    Ok(builtin_skill_templates())
+}
+
+/// Create a new skill from a built-in template, filling `{{var}}` placeholders from `variables`
+#[tauri::command]
+pub async fn skill_create_from_template(
+    app_handle: tauri::AppHandle,
+    name: String,
+    skill_type: String,
+    template_id: String,
+    variables: Option<HashMap<String, String>>,
+    project_path: Option<String>,
+) -> Result<SkillFromTemplateResult, String> {
+    let template = find_skill_template(&template_id)?;
+    info!("从模板 '{}' 创建技能: {}", template_id, name);
+
+    let variables = variables.unwrap_or_default();
+    let (rendered, warnings) = render_skill_template(&template, &name, &variables);
+    for warning in &warnings {
+        warn!("技能 '{}': {}", name, warning);
+    }
+
+    let skills_dir = skills_dir_for(&app_handle, &skill_type, project_path.as_deref())?;
+    let skill = skill_create(
+        app_handle,
+        name.clone(),
+        skill_type,
+        rendered.description,
+        rendered.markdown_content,
+        rendered.allowed_tools,
+        project_path,
+    )
+    .await?;
+
+    // Remembered so skill_diff_template can render the template exactly as it was filled in
+    let origin = SkillTemplateOrigin {
+        template_id,
+        variables: variables.into_iter().collect(),
+    };
+    if let Err(e) = record_skill_template_origin(&skills_dir, &name, Some(origin)) {
+        warn!("技能 '{}': {}", name, e);
+    }
+
+    Ok(SkillFromTemplateResult { skill, warnings })
+}
+
+/// Lines of context around each hunk in template diffs
+const SKILL_DIFF_CONTEXT_LINES: usize = 3;
+
+/// The SKILL.md `skill_create_from_template` writes for skill `name`
+fn render_template_skill_md(
+    template: &SkillTemplate,
+    name: &str,
+    variables: &HashMap<String, String>,
+) -> Result<String, String> {
+    let (rendered, _) = render_skill_template(template, name, variables);
+    let frontmatter = build_yaml_frontmatter(&SkillMetadata {
+        name: name.to_string(),
+        description: rendered.description,
+        allowed_tools: rendered.allowed_tools,
+        extra: serde_yaml::Mapping::new(),
+    })?;
+    Ok(format!("{}{}", frontmatter, rendered.markdown_content))
+}
+
+/// Diff `current` (a SKILL.md) against what `template` renders to for skill `name`
+///
+/// The template is rendered with the `variables` the skill was created with, so the diff
+/// shows only the changes made since then.
+fn diff_skill_against_template(
+    template: &SkillTemplate,
+    name: &str,
+    variables: &HashMap<String, String>,
+    current: &str,
+) -> Result<SkillTemplateDiff, String> {
+    let original = render_template_skill_md(template, name, variables)?;
+
+    let diff = TextDiff::from_lines(&original, current);
+    let (mut additions, mut deletions) = (0, 0);
+    for change in diff.iter_all_changes() {
+        match change.tag() {
+            ChangeTag::Insert => additions += 1,
+            ChangeTag::Delete => deletions += 1,
+            ChangeTag::Equal => {}
+        }
+    }
+    let diff = diff
+        .unified_diff()
+        .context_radius(SKILL_DIFF_CONTEXT_LINES)
+        .header(
+            &format!("template/{}", template.id),
+            &format!("{}/SKILL.md", name),
+        )
+        .to_string();
+
+    Ok(SkillTemplateDiff {
+        template_id: template.id.clone(),
+        diff,
+        additions,
+        deletions,
+    })
+}
+
+/// Show how a skill has diverged from the built-in template it was created from
+///
+/// Skills created from this template by `skill_create_from_template` are compared using the
+/// variables they were filled in with; other skills are compared with none.
+#[tauri::command]
+pub async fn skill_diff_template(
+    app_handle: tauri::AppHandle,
+    name: String,
+    skill_type: String,
+    template_id: String,
+    project_path: Option<String>,
+) -> Result<SkillTemplateDiff, String> {
+    let template = find_skill_template(&template_id)
+        .map_err(|_| format!("模板 '{}' 已不存在，无法与技能 '{}' 比较", template_id, name))?;
+
+    let skills_dir = skills_dir_for(&app_handle, &skill_type, project_path.as_deref())?;
+    let skill_path = skills_dir.join(&name).join("SKILL.md");
+    if !skill_path.exists() {
+        return Err(format!("技能 '{}' 不存在", name));
+    }
+    let current = fs::read_to_string(&skill_path).map_err(|e| format!("读取技能文件失败: {}", e))?;
+    let variables: HashMap<String, String> = read_skill_template_origins(&skills_dir)
+        .remove(&name)
+        .filter(|origin| origin.template_id == template_id)
+        .map(|origin| origin.variables.into_iter().collect())
+        .unwrap_or_default();
+
+    debug!("比较技能 '{}' 与模板 '{}'", name, template_id);
+    diff_skill_against_template(&template, &name, &variables, &current)
+}
+
+/// Upper bound on a downloaded skill, and on the unpacked contents of a skill zip
+const SKILL_IMPORT_MAX_BYTES: usize = 5 * 1024 * 1024;
+
+/// How long a skill download may take in total
+const SKILL_IMPORT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How many redirects a skill download may follow
+const SKILL_IMPORT_MAX_REDIRECTS: usize = 10;
+
+/// A downloaded skill before it is installed: SKILL.md plus helper files keyed by relative path
+struct ImportedSkill {
+    content: String,
+    files: Vec<(String, Vec<u8>)>,
+}
+
+/// Parse an import URL, accepting only https
+///
+/// GitHub file pages (`github.com/<owner>/<repo>/blob/<ref>/<path>`) and gist pages are
+/// rewritten to their raw download URLs so either kind of link can be pasted.
+fn resolve_skill_import_url(url: &str) -> Result<reqwest::Url, String> {
+    let parsed = reqwest::Url::parse(url.trim()).map_err(|e| format!("无效的链接: {}", e))?;
+    if parsed.scheme() != "https" {
+        return Err(format!("只支持 https 链接: {}", url));
+    }
+
+    let segments: Vec<&str> = parsed
+        .path_segments()
+        .map(|s| s.filter(|part| !part.is_empty()).collect())
+        .unwrap_or_default();
+    let raw = match (parsed.host_str(), segments.as_slice()) {
+        (Some("github.com"), [owner, repo, "blob", rest @ ..]) if !rest.is_empty() => Some(format!(
+            "https://raw.githubusercontent.com/{}/{}/{}",
+            owner,
+            repo,
+            rest.join("/")
+        )),
+        (Some("gist.github.com"), [user, id]) => {
+            Some(format!("https://gist.githubusercontent.com/{}/{}/raw", user, id))
+        }
+        _ => None,
+    };
+
+    match raw {
+        Some(raw) => reqwest::Url::parse(&raw).map_err(|e| format!("无效的链接: {}", e)),
+        None => Ok(parsed),
+    }
+}
+
+/// Download `url`, failing once the body grows past `max_bytes`
+///
+/// Redirects are only followed to https URLs, so an https link can't be bounced to plain http.
+async fn download_skill_bytes(
+    url: reqwest::Url,
+    max_bytes: usize,
+    timeout: Duration,
+) -> Result<Vec<u8>, String> {
+    let redirect_policy = reqwest::redirect::Policy::custom(|attempt| {
+        if attempt.url().scheme() != "https" {
+            let refused = format!("拒绝重定向到非 https 链接: {}", attempt.url());
+            attempt.error(refused)
+        } else if attempt.previous().len() >= SKILL_IMPORT_MAX_REDIRECTS {
+            attempt.error("重定向次数过多")
+        } else {
+            attempt.follow()
+        }
+    });
+    let client = reqwest::Client::builder()
+        .timeout(timeout)
+        .redirect(redirect_policy)
+        .build()
+        .map_err(|e| format!("创建 HTTP 客户端失败: {}", e))?;
+    let mut response = client.get(url.clone()).send().await.map_err(|e| {
+        // The redirect policy's own message is more useful than reqwest's generic wrapper
+        match std::error::Error::source(&e).filter(|_| e.is_redirect()) {
+            Some(reason) => format!("下载技能失败: {}", reason),
+            None => format!("下载技能失败: {}", e),
+        }
+    })?;
+
+    if !response.status().is_success() {
+        return Err(format!("下载技能失败: {} 返回 {}", url, response.status()));
+    }
+    let too_large = || format!("下载内容超过大小上限 {} 字节", max_bytes);
+    if response.content_length().is_some_and(|len| len > max_bytes as u64) {
+        return Err(too_large());
+    }
+
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| format!("下载技能失败: {}", e))?
+    {
+        if bytes.len() + chunk.len() > max_bytes {
+            return Err(too_large());
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    Ok(bytes)
+}
+
+/// Read a skill out of a zip archive
+///
+/// SKILL.md may sit at the archive root or inside a single top-level folder; only entries
+/// under that folder are kept. Entry names that escape the archive are refused, and the
+/// unpacked size is capped at `max_bytes`.
+fn unpack_skill_zip(bytes: &[u8], max_bytes: usize) -> Result<ImportedSkill, String> {
+    let mut archive = zip::ZipArchive::new(std::io::Cursor::new(bytes))
+        .map_err(|e| format!("无法读取压缩包: {}", e))?;
+
+    let mut root: Option<PathBuf> = None;
+    for index in 0..archive.len() {
+        let entry = archive
+            .by_index(index)
+            .map_err(|e| format!("无法读取压缩包: {}", e))?;
+        let path = entry
+            .enclosed_name()
+            .ok_or_else(|| format!("压缩包包含不安全的路径: {}", entry.name()))?;
+        if path.file_name().is_some_and(|n| n == "SKILL.md") && path.components().count() <= 2 {
+            let parent = path.parent().map(Path::to_path_buf).unwrap_or_default();
+            let shallower = match &root {
+                Some(current) => parent.components().count() < current.components().count(),
+                None => true,
+            };
+            if shallower {
+                root = Some(parent);
+            }
+        }
+    }
+    let root = root.ok_or("压缩包中没有 SKILL.md 文件".to_string())?;
+
+    let mut content = None;
+    let mut files = Vec::new();
+    let mut total = 0usize;
+    for index in 0..archive.len() {
+        let mut entry = archive
+            .by_index(index)
+            .map_err(|e| format!("无法读取压缩包: {}", e))?;
+        if entry.is_dir() {
+            continue;
+        }
+        let Some(path) = entry.enclosed_name() else {
+            return Err(format!("压缩包包含不安全的路径: {}", entry.name()));
+        };
+        let Ok(relative) = path.strip_prefix(&root) else {
+            continue;
+        };
+        let relative = relative.to_string_lossy().replace('\\', "/");
+
+        let mut data = Vec::new();
+        (&mut entry)
+            .take((max_bytes - total) as u64 + 1)
+            .read_to_end(&mut data)
+            .map_err(|e| format!("解压 {} 失败: {}", relative, e))?;
+        total += data.len();
+        if total > max_bytes {
+            return Err(format!("压缩包解压后超过大小上限 {} 字节", max_bytes));
+        }
+
+        if relative == "SKILL.md" {
+            content = Some(String::from_utf8(data).map_err(|_| "SKILL.md 不是有效的 UTF-8 文本".to_string())?);
+        } else {
+            files.push((relative, data));
+        }
+    }
+
+    Ok(ImportedSkill {
+        content: content.ok_or("压缩包中没有 SKILL.md 文件".to_string())?,
+        files,
+    })
+}
+
+/// Validate a downloaded skill and write it under `skills_dir`, returning the skill directory
+///
+/// The skill is named `name` when given, otherwise after its frontmatter `name`. Helper files
+/// are resolved against the new skill directory so they cannot land outside it; on failure
+/// the partially written skill is removed.
+fn install_imported_skill(
+    skills_dir: &Path,
+    name: Option<&str>,
+    imported: ImportedSkill,
+) -> Result<PathBuf, String> {
+    let (yaml_frontmatter, markdown_content) = parse_yaml_frontmatter(&imported.content)?;
+    let metadata = yaml_frontmatter
+        .as_deref()
+        .map(parse_skill_metadata)
+        .transpose()?;
+
+    let name = match (name.map(str::trim), &metadata) {
+        (Some(name), _) if !name.is_empty() => name.to_string(),
+        (_, Some(metadata)) => metadata.name.clone(),
+        _ => return Err("SKILL.md 缺少前置元数据，请指定技能名称".to_string()),
+    };
+    validate_skill_name(&name)?;
+
+    let validation = validate_skill(&Skill {
+        name: name.clone(),
+        source: String::new(),
+        skill_type: String::new(),
+        description: metadata.as_ref().map(|m| m.description.clone()).unwrap_or_default(),
+        file_path: String::new(),
+        yaml_frontmatter: yaml_frontmatter.clone(),
+        markdown_content,
+        files: Vec::new(),
+        allowed_tools: metadata.as_ref().and_then(|m| m.allowed_tools.clone()),
+        extra_frontmatter: serde_yaml::Mapping::new(),
+        enabled: true,
+        last_modified: String::new(),
+    });
+    if !validation.is_valid {
+        return Err(format!("技能校验失败: {}", validation.errors.join("; ")));
+    }
+
+    let content = if metadata.as_ref().map(|m| m.name.as_str()) == Some(name.as_str()) {
+        imported.content
+    } else {
+        rename_skill_frontmatter(&imported.content, &name)?
+    };
+
+    let skill_dir = skills_dir.join(&name);
+    if skill_dir.exists() {
+        return Err(format!("技能 '{}' 已存在", name));
+    }
+    fs::create_dir_all(&skill_dir).map_err(|e| format!("创建技能目录失败: {}", e))?;
+
+    let result = write_atomic(skill_dir.join("SKILL.md"), content)
+        .map_err(|e| format!("写入技能文件失败: {}", e))
+        .and_then(|_| {
+            for (relative, data) in &imported.files {
+                let target = resolve_skill_path(&skill_dir, relative)?;
+                if let Some(parent) = target.parent() {
+                    fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {}", e))?;
+                }
+                write_atomic(&target, data).map_err(|e| format!("写入文件 {} 失败: {}", relative, e))?;
+            }
+            Ok(())
+        });
+
+    if let Err(e) = &result {
+        error!("导入技能失败: {}", e);
+        if let Err(cleanup_err) = fs::remove_dir_all(&skill_dir) {
+            warn!("清理未完成的技能导入失败: {}", cleanup_err);
+        }
+    }
+    result.map(|_| skill_dir)
+}
+
+/// Import a skill from an https URL pointing at a SKILL.md or a zip of a skill folder
+#[tauri::command]
+pub async fn skill_import_from_url(
+    app_handle: tauri::AppHandle,
+    url: String,
+    skill_type: String,
+    name: Option<String>,
+    project_path: Option<String>,
+) -> Result<Skill, String> {
+    let download_url = resolve_skill_import_url(&url)?;
+    info!("从链接导入技能: {}", download_url);
+
+    let bytes =
+        download_skill_bytes(download_url.clone(), SKILL_IMPORT_MAX_BYTES, SKILL_IMPORT_TIMEOUT).await?;
+    let imported = if bytes.starts_with(b"PK\x03\x04") || download_url.path().ends_with(".zip") {
+        unpack_skill_zip(&bytes, SKILL_IMPORT_MAX_BYTES)?
+    } else {
+        ImportedSkill {
+            content: String::from_utf8(bytes).map_err(|_| "SKILL.md 不是有效的 UTF-8 文本".to_string())?,
+            files: Vec::new(),
+        }
+    };
+
+    let skills_dir = skills_dir_for(&app_handle, &skill_type, project_path.as_deref())?;
+    ensure_skills_dir(&skills_dir)?;
+
+    let skill_dir = install_imported_skill(&skills_dir, name.as_deref(), imported)?;
+    info!("技能已导入: {:?}", skill_dir);
+
+    let skill_file = skill_dir.join("SKILL.md");
+    let mut skill = read_skill_file(skill_file.to_string_lossy().to_string(), skill_type).await?;
+    skill.files = list_skill_files(skill_dir).await.unwrap_or_default();
+    Ok(skill)
+}
+
+/// Format version written into exported skill bundles
+const SKILL_BUNDLE_VERSION: u32 = 1;
+
+/// Collect every skill under `skills_dir` into a bundle, with helper files base64-encoded
+///
+/// Only subdirectories containing a SKILL.md count as skills, so `.trash` is left out.
+fn export_skills_in_dir(skills_dir: &Path, skill_type: &str) -> Result<SkillBundle, String> {
+    let mut skill_dirs: Vec<PathBuf> = match fs::read_dir(skills_dir) {
+        Ok(entries) => entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.is_dir() && path.join("SKILL.md").is_file())
+            .collect(),
+        Err(_) if !skills_dir.exists() => Vec::new(),
+        Err(e) => return Err(format!("读取技能目录失败: {}", e)),
+    };
+    skill_dirs.sort();
+
+    let mut skills = Vec::new();
+    for skill_dir in skill_dirs {
+        let name = skill_dir
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+        let content = fs::read_to_string(skill_dir.join("SKILL.md"))
+            .map_err(|e| format!("读取技能 {} 失败: {}", name, e))?;
+        let metadata = match parse_yaml_frontmatter(&content) {
+            Ok((Some(yaml), _)) => parse_skill_metadata(&yaml)
+                .map_err(|e| warn!("技能 {} 的前置元数据无效: {}", name, e))
+                .ok(),
+            _ => None,
+        };
+
+        let mut entries = Vec::new();
+        let mut visited = std::collections::HashSet::new();
+        if let Ok(canonical) = fs::canonicalize(&skill_dir) {
+            visited.insert(canonical);
+        }
+        collect_skill_files(&skill_dir, &skill_dir, 0, &mut visited, &mut entries)?;
+
+        let mut files = Vec::new();
+        for entry in entries {
+            if entry.is_directory || entry.path == "SKILL.md" {
+                continue;
+            }
+            let data = fs::read(skill_dir.join(&entry.path))
+                .map_err(|e| format!("读取文件 {}/{} 失败: {}", name, entry.path, e))?;
+            files.push(SkillBundleFile {
+                path: entry.path,
+                data: STANDARD.encode(data),
+            });
+        }
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+
+        skills.push(SkillBundleEntry {
+            name,
+            metadata,
+            content,
+            files,
+        });
+    }
+
+    Ok(SkillBundle {
+        version: SKILL_BUNDLE_VERSION,
+        exported_at: chrono::Local::now().to_rfc3339(),
+        skill_type: skill_type.to_string(),
+        skills,
+    })
+}
+
+/// Recreate the skills of a bundle under `skills_dir`
+///
+/// Each skill is installed like a URL import, so helper paths that would escape the skill
+/// directory are refused. Existing skills are skipped unless `overwrite` is set, in which
+/// case the old copy is put back if the replacement fails. One bad entry does not stop the rest.
+fn import_skill_bundle(
+    skills_dir: &Path,
+    bundle: SkillBundle,
+    overwrite: bool,
+) -> Result<SkillBundleImportResult, String> {
+    if bundle.version > SKILL_BUNDLE_VERSION {
+        return Err(format!("不支持的技能包版本: {}", bundle.version));
+    }
+
+    let mut result = SkillBundleImportResult::default();
+    for entry in bundle.skills {
+        let name = entry.name;
+        if let Err(e) = validate_skill_name(&name) {
+            result.failed.push(format!("{}: {}", name, e));
+            continue;
+        }
+
+        let files = entry
+            .files
+            .into_iter()
+            .map(|file| {
+                STANDARD
+                    .decode(&file.data)
+                    .map(|data| (file.path.clone(), data))
+                    .map_err(|e| format!("文件 {} 不是有效的 base64: {}", file.path, e))
+            })
+            .collect::<Result<Vec<_>, String>>();
+        let files = match files {
+            Ok(files) => files,
+            Err(e) => {
+                result.failed.push(format!("{}: {}", name, e));
+                continue;
+            }
+        };
+
+        let skill_dir = skills_dir.join(&name);
+        let backup = skills_dir.join(format!(".{}.import-backup", name));
+        if skill_dir.exists() {
+            if !overwrite {
+                result.skipped.push(name);
+                continue;
+            }
+            fs::rename(&skill_dir, &backup).map_err(|e| format!("备份技能 {} 失败: {}", name, e))?;
+        }
+
+        let imported = ImportedSkill {
+            content: entry.content,
+            files,
+        };
+        match install_imported_skill(skills_dir, Some(&name), imported) {
+            Ok(_) => {
+                if backup.exists() {
+                    if let Err(e) = fs::remove_dir_all(&backup) {
+                        warn!("删除技能备份失败 {:?}: {}", backup, e);
+                    }
+                }
+                result.imported.push(name);
+            }
+            Err(e) => {
+                if backup.exists() {
+                    fs::rename(&backup, &skill_dir).map_err(|e| format!("恢复技能 {} 失败: {}", name, e))?;
+                }
+                result.failed.push(format!("{}: {}", name, e));
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+/// Export every skill of one type into a single JSON file, returning how many were written
+#[tauri::command]
+pub async fn skills_export_all(
+    app_handle: tauri::AppHandle,
+    skill_type: String,
+    output_path: String,
+    project_path: Option<String>,
+) -> Result<usize, String> {
+    let skills_dir = skills_dir_for(&app_handle, &skill_type, project_path.as_deref())?;
+
+    let bundle = export_skills_in_dir(&skills_dir, &skill_type)?;
+    let json = serde_json::to_string_pretty(&bundle).map_err(|e| format!("序列化技能包失败: {}", e))?;
+    fs::write(&output_path, json).map_err(|e| format!("写入技能包失败: {}", e))?;
+
+    info!("已导出 {} 个技能到 {}", bundle.skills.len(), output_path);
+    Ok(bundle.skills.len())
+}
+
+/// Recreate skills from a JSON file written by `skills_export_all`
+#[tauri::command]
+pub async fn skills_import_all(
+    app_handle: tauri::AppHandle,
+    input_path: String,
+    skill_type: String,
+    overwrite: bool,
+    project_path: Option<String>,
+) -> Result<SkillBundleImportResult, String> {
+    let json = fs::read_to_string(&input_path).map_err(|e| format!("读取技能包失败: {}", e))?;
+    let bundle: SkillBundle = serde_json::from_str(&json).map_err(|e| format!("技能包格式无效: {}", e))?;
+
+    let skills_dir = skills_dir_for(&app_handle, &skill_type, project_path.as_deref())?;
+    ensure_skills_dir(&skills_dir)?;
+
+    let result = import_skill_bundle(&skills_dir, bundle, overwrite)?;
+    info!(
+        "技能包导入完成: 导入 {} 个, 跳过 {} 个, 失败 {} 个",
+        result.imported.len(),
+        result.skipped.len(),
+        result.failed.len()
+    );
+    Ok(result)
+}
+
+/// Delete a skill
+///
+/// Nothing is removed unless `confirm` is true or `confirm_token` matches the token from an
+/// earlier unconfirmed call; that call returns the file count so the UI can prompt first.
+/// `soft` moves the skill into the skills directory's `.trash` folder instead.
+#[tauri::command]
+pub async fn skill_delete(
+    app_handle: tauri::AppHandle,
+    name: String,
+    skill_type: String,
+    confirm: Option<bool>,
+    confirm_token: Option<String>,
+    soft: Option<bool>,
+    project_path: Option<String>,
+) -> Result<SkillDeleteResult, String> {
+    let skills_dir = skills_dir_for(&app_handle, &skill_type, project_path.as_deref())?;
+
+    delete_skill_dir(
+        &skills_dir,
+        &name,
+        confirm.unwrap_or(false),
+        confirm_token.as_deref(),
+        soft.unwrap_or(false),
+    )
+}
+
+/// List soft-deleted personal and project skills, newest first
+#[tauri::command]
+pub async fn skill_list_trash(
+    app_handle: tauri::AppHandle,
+    project_path: Option<String>,
+) -> Result<Vec<TrashedSkill>, String> {
+    let mut trashed = list_trash_in_dir(&get_personal_skills_dir(&app_handle)?, "personal");
+    trashed.extend(list_trash_in_dir(
+        &get_project_skills_dir(&app_handle, project_path.as_deref())?,
+        "project",
+    ));
+    trashed.sort_by(|a, b| b.deleted_at.cmp(&a.deleted_at));
+    Ok(trashed)
+}
+
+/// Move a soft-deleted skill back out of the trash
+///
+/// The most recently deleted copy wins if the skill was trashed more than once.
+#[tauri::command]
+pub async fn skill_restore(
+    app_handle: tauri::AppHandle,
+    name: String,
+    skill_type: String,
+    project_path: Option<String>,
+) -> Result<Skill, String> {
+    let skills_dir = skills_dir_for(&app_handle, &skill_type, project_path.as_deref())?;
+
+    let skill_dir = restore_skill_from_trash(&skills_dir, &name)?;
+    read_skill_file(skill_dir.join("SKILL.md").to_string_lossy().to_string(), skill_type).await
+}
+
+/// Permanently delete trashed skills older than `older_than_days` (default 30)
+///
+/// Returns how many trashed skills were removed; 0 days empties the trash entirely.
+#[tauri::command]
+pub async fn skill_empty_trash(
+    app_handle: tauri::AppHandle,
+    older_than_days: Option<u32>,
+    project_path: Option<String>,
+) -> Result<usize, String> {
+    let days = older_than_days.unwrap_or(SKILL_TRASH_RETENTION_DAYS);
+    let cutoff = chrono::Local::now() - chrono::Duration::days(days as i64);
+
+    let mut purged = purge_trash_in_dir(&get_personal_skills_dir(&app_handle)?, cutoff)?;
+    purged += purge_trash_in_dir(
+        &get_project_skills_dir(&app_handle, project_path.as_deref())?,
+        cutoff,
+    )?;
+    info!("已清理回收站中的 {} 个技能（保留 {} 天）", purged, days);
+    Ok(purged)
+}
+
+/// Enable or disable a skill without deleting it
+///
+/// Disabled skills are recorded in the skills directory's `.disabled` manifest.
+#[tauri::command]
+pub async fn skill_set_enabled(
+    app_handle: tauri::AppHandle,
+    name: String,
+    skill_type: String,
+    enabled: bool,
+    project_path: Option<String>,
+) -> Result<(), String> {
+    let skills_dir = skills_dir_for(&app_handle, &skill_type, project_path.as_deref())?;
+
+    if !skills_dir.join(&name).join("SKILL.md").exists() {
+        return Err(format!("技能 '{}' 不存在", name));
+    }
+
+    set_skill_enabled_in_manifest(&skills_dir, &name, enabled)?;
+    info!("技能 '{}' 已{}", name, if enabled { "启用" } else { "禁用" });
+    Ok(())
+}
+
+/// Validate a skill
+#[tauri::command]
+pub async fn skill_validate(
+    _app_handle: tauri::AppHandle,
+    name: String,
+    skill_type: String,
+    description: String,
+    markdown_content: String,
+    allowed_tools: Option<Vec<String>>,
+) -> Result<ValidationResult, String> {
+    let temp_skill = Skill {
+        name,
+        source: skill_type.clone(),
+        skill_type,
+        description,
+        file_path: String::new(),
+        yaml_frontmatter: None,
+        markdown_content,
+        files: Vec::new(),
+        allowed_tools,
+        extra_frontmatter: serde_yaml::Mapping::new(),
+        enabled: true,
+        last_modified: chrono::Utc::now().to_rfc3339(),
+    };
+
+    let validation_result = validate_skill(&temp_skill);
+    Ok(validation_result)
+}
+
+/// Read frontmatter that YAML rejects as plain `key: value` lines
+///
+/// Values are taken verbatim up to the end of the line, so unquoted `:` or `#` survive.
+/// Indented `- item` lines become a list under the preceding empty key. Any other
+/// line means the frontmatter can't be read confidently.
+fn parse_loose_frontmatter(yaml: &str) -> Result<serde_yaml::Mapping, String> {
+    use serde_yaml::Value;
+
+    let mut mapping = serde_yaml::Mapping::new();
+    let mut list_key: Option<String> = None;
+
+    for (index, line) in yaml.lines().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+
+        if line.starts_with(char::is_whitespace) {
+            let (Some(key), Some(item)) = (&list_key, trimmed.strip_prefix("- ")) else {
+                return Err(format!("第 {} 行无法识别: {}", index + 1, trimmed));
+            };
+            let entry = mapping
+                .entry(Value::String(key.clone()))
+                .or_insert_with(|| Value::Sequence(Vec::new()));
+            if let Value::Sequence(items) = entry {
+                items.push(Value::String(unquote(item.trim()).to_string()));
+            }
+            continue;
+        }
+
+        let Some((key, value)) = line.split_once(':') else {
+            return Err(format!("第 {} 行无法识别: {}", index + 1, trimmed));
+        };
+        let key = key.trim();
+        if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+            return Err(format!("第 {} 行的键名无效: {}", index + 1, key));
+        }
+        if mapping.contains_key(key) {
+            return Err(format!("前置元数据中的键 '{}' 重复", key));
+        }
+
+        let value = value.trim();
+        if value.is_empty() {
+            list_key = Some(key.to_string());
+            mapping.insert(Value::String(key.to_string()), Value::Null);
+        } else {
+            list_key = None;
+            mapping.insert(
+                Value::String(key.to_string()),
+                Value::String(unquote(value).to_string()),
+            );
+        }
+    }
+
+    Ok(mapping)
+}
+
+/// Strip one pair of matching surrounding quotes
+fn unquote(value: &str) -> &str {
+    for quote in ['"', '\''] {
+        if value.len() >= 2 && value.starts_with(quote) && value.ends_with(quote) {
+            return &value[1..value.len() - 1];
+        }
+    }
+    value
+}
+
+/// Render a scalar frontmatter value as text; lists and maps are rejected
+fn frontmatter_scalar(key: &str, value: &serde_yaml::Value) -> Result<Option<String>, String> {
+    use serde_yaml::Value;
+
+    match value {
+        Value::Null => Ok(None),
+        Value::String(s) => Ok(Some(s.trim().to_string()).filter(|s| !s.is_empty())),
+        Value::Bool(b) => Ok(Some(b.to_string())),
+        Value::Number(n) => Ok(Some(n.to_string())),
+        _ => Err(format!("'{}' 必须是文本，无法自动修复", key)),
+    }
+}
+
+/// Normalize a SKILL.md's frontmatter so `parse_skill_metadata` accepts it
+///
+/// Fills a missing name from the directory and a missing description with a
+/// placeholder, re-quotes values and rewrites `allowed-tools` as a YAML list.
+/// Returns an error explaining why when the file can't be fixed confidently.
+fn repair_skill_frontmatter(dir_name: &str, content: &str) -> Result<String, String> {
+    use serde_yaml::Value;
+
+    let (yaml, body) = parse_yaml_frontmatter(content)
+        .map_err(|e| format!("无法修复前置元数据: {}", e))?;
+
+    let mut mapping = match yaml.as_deref() {
+        None => serde_yaml::Mapping::new(),
+        Some(yaml) => match serde_yaml::from_str::<Value>(yaml) {
+            Ok(Value::Mapping(mapping)) => mapping,
+            Ok(Value::Null) => serde_yaml::Mapping::new(),
+            Ok(_) => return Err("无法修复前置元数据: 内容不是键值对".to_string()),
+            Err(_) => parse_loose_frontmatter(yaml)
+                .map_err(|e| format!("无法修复前置元数据: {}", e))?,
+        },
+    };
+
+    let name = match mapping.remove("name") {
+        Some(value) => frontmatter_scalar("name", &value)?,
+        None => None,
+    }
+    .unwrap_or_else(|| dir_name.to_string());
+
+    let description = match mapping.remove("description") {
+        Some(value) => frontmatter_scalar("description", &value)?,
+        None => None,
+    }
+    .unwrap_or_else(|| format!("Describe what the {} skill does and when to use it", name));
+
+    let raw_tools = match (mapping.remove("allowed-tools"), mapping.remove("allowed_tools")) {
+        (Some(_), Some(_)) => {
+            return Err("同时存在 allowed-tools 和 allowed_tools，无法自动合并".to_string())
+        }
+        (tools, other) => tools.or(other),
+    };
+    let allowed_tools = match raw_tools {
+        None | Some(Value::Null) => None,
+        Some(Value::String(text)) => Some(
+            text.trim_matches(['[', ']'])
+                .split([',', '\n'])
+                .map(|tool| unquote(tool.trim()).to_string())
+                .filter(|tool| !tool.is_empty())
+                .collect(),
+        ),
+        Some(Value::Sequence(items)) => Some(
+            items
+                .iter()
+                .map(|item| frontmatter_scalar("allowed-tools", item))
+                .collect::<Result<Vec<_>, _>>()?
+                .into_iter()
+                .flatten()
+                .collect(),
+        ),
+        Some(_) => return Err("allowed-tools 格式无法识别".to_string()),
+    };
+
+    let frontmatter = build_yaml_frontmatter(&SkillMetadata {
+        name,
+        description,
+        allowed_tools,
+        extra: mapping,
+    })?;
+    let repaired = format!("{}{}", frontmatter, body);
+
+    // Only hand back something the regular reader accepts
+    let (yaml, _) = parse_yaml_frontmatter(&repaired)?;
+    parse_skill_metadata(&yaml.unwrap_or_default())?;
+    Ok(repaired)
+}
+
+/// Rewrite a skill's SKILL.md frontmatter into a valid form and re-validate it
+///
+/// Leaves the file untouched and returns an error when it can't be repaired confidently.
+#[tauri::command]
+pub async fn skill_repair_frontmatter(
+    app_handle: tauri::AppHandle,
+    name: String,
+    skill_type: String,
+    project_path: Option<String>,
+) -> Result<ValidationResult, String> {
+    let skills_dir = skills_dir_for(&app_handle, &skill_type, project_path.as_deref())?;
+
+    let skill_file = skills_dir.join(&name).join("SKILL.md");
+    if !skill_file.exists() {
+        return Err(format!("技能 '{}' 不存在", name));
+    }
+
+    let content = fs::read_to_string(&skill_file).map_err(|e| format!("读取文件失败: {}", e))?;
+    let repaired = repair_skill_frontmatter(&name, &content)?;
+    if repaired != content {
+        write_atomic(&skill_file, &repaired).map_err(|e| format!("写入技能文件失败: {}", e))?;
+        info!("已修复技能 '{}' 的前置元数据", name);
+    }
+
+    let skill = read_skill_file(skill_file.to_string_lossy().to_string(), skill_type).await?;
+    Ok(validate_skill(&skill))
+}
+
+/// Create a file in a skill directory
+#[tauri::command]
+pub async fn skill_create_file(
+    app_handle: tauri::AppHandle,
+    skill_name: String,
+    skill_type: String,
+    file_name: String,
+    content: String,
+    project_path: Option<String>,
+) -> Result<(), String> {
+    let skills_dir = skills_dir_for(&app_handle, &skill_type, project_path.as_deref())?;
+
+    create_skill_file_in(&skills_dir, &skill_name, &file_name, &content).map(|_| ())
+}
+
+/// Write `file_name` inside skill `skill_name` of `skills_dir`, returning the written path
+fn create_skill_file_in(
+    skills_dir: &Path,
+    skill_name: &str,
+    file_name: &str,
+    content: &str,
+) -> Result<PathBuf, String> {
+    let skill_dir = existing_skill_dir(skills_dir, skill_name)?;
+    let file_path = resolve_skill_path(&skill_dir, file_name)?;
+
+    // Write the file
+    write_atomic(&file_path, content).map_err(|e| e.to_string())?;
+
+    Ok(file_path)
+}
+
+/// Resolve `file_name` relative to `skill_dir`, rejecting paths that escape the skill root
+///
+/// Absolute paths and `..` components that climb above the root are refused before any
+/// filesystem access; the deepest existing ancestor is then canonicalized so symlinks
+/// inside the skill cannot point outside it either.
+fn resolve_skill_path(skill_dir: &Path, file_name: &str) -> Result<PathBuf, String> {
+    let mut normalized = PathBuf::new();
+    for component in Path::new(file_name).components() {
+        match component {
+            Component::Normal(part) => normalized.push(part),
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !normalized.pop() {
+                    return Err(format!("路径 '{}' 超出技能目录范围", file_name));
+                }
+            }
+            Component::RootDir | Component::Prefix(_) => {
+                return Err(format!("不允许使用绝对路径: '{}'", file_name));
+            }
+        }
+    }
+    if normalized.as_os_str().is_empty() {
+        return Err("文件名不能为空".to_string());
+    }
+
+    let resolved = skill_dir.join(&normalized);
+
+    let canonical_root = fs::canonicalize(skill_dir)
+        .map_err(|e| format!("解析技能目录失败: {}", e))?;
+    let mut existing = resolved.as_path();
+    while !existing.exists() {
+        existing = match existing.parent() {
+            Some(parent) => parent,
+            None => break,
+        };
+    }
+    let canonical = fs::canonicalize(existing)
+        .map_err(|e| format!("解析路径失败: {}", e))?;
+    if !canonical.starts_with(&canonical_root) {
+        return Err(format!("路径 '{}' 超出技能目录范围", file_name));
+    }
+
+    Ok(resolved)
+}
+
+/// Move `from` to `to` within `skill_dir`, creating intermediate directories as needed
+fn move_skill_entry(skill_dir: &Path, from: &str, to: &str) -> Result<PathBuf, String> {
+    let source = resolve_skill_path(skill_dir, from)?;
+    let target = resolve_skill_path(skill_dir, to)?;
+
+    if !source.exists() {
+        return Err(format!("文件 '{}' 不存在", from));
+    }
+    if target.exists() {
+        return Err(format!("目标 '{}' 已存在", to));
+    }
+    if source == skill_dir.join("SKILL.md") {
+        return Err("SKILL.md 不能被重命名或移动".to_string());
+    }
+    if target.starts_with(&source) {
+        return Err("不能将目录移动到其自身内部".to_string());
+    }
+
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {}", e))?;
+    }
+    fs::rename(&source, &target).map_err(|e| format!("移动文件失败: {}", e))?;
+
+    debug!("技能文件已移动: {:?} -> {:?}", source, target);
+    Ok(target)
+}
+
+/// Copy `file_name` from `source_dir` to `target_name` in `target_dir`
+///
+/// Both paths go through `resolve_skill_path`, so neither side can escape its skill.
+/// An existing target is only replaced when `overwrite` is set.
+fn copy_skill_entry(
+    source_dir: &Path,
+    file_name: &str,
+    target_dir: &Path,
+    target_name: &str,
+    overwrite: bool,
+) -> Result<PathBuf, String> {
+    let source = resolve_skill_path(source_dir, file_name)?;
+    let target = resolve_skill_path(target_dir, target_name)?;
+
+    if !source.exists() {
+        return Err(format!("文件 '{}' 不存在", file_name));
+    }
+    if source == target {
+        return Err("源文件与目标相同".to_string());
+    }
+    if source.is_dir() && target.starts_with(&source) {
+        return Err("不能将目录复制到其自身内部".to_string());
+    }
+    if target.exists() {
+        if !overwrite {
+            return Err(format!("目标 '{}' 已存在", target_name));
+        }
+        if target.is_dir() {
+            fs::remove_dir_all(&target).map_err(|e| format!("删除已有目标失败: {}", e))?;
+        } else if source.is_dir() {
+            fs::remove_file(&target).map_err(|e| format!("删除已有目标失败: {}", e))?;
+        }
+    }
+
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {}", e))?;
+    }
+    if source.is_dir() {
+        copy_dir_recursive(&source, &target, 0)?;
+    } else {
+        fs::copy(&source, &target).map_err(|e| format!("复制文件失败: {}", e))?;
+    }
+
+    debug!("技能文件已复制: {:?} -> {:?}", source, target);
+    Ok(target)
+}
+
+/// Copy a file from one skill to another (or within the same skill)
+///
+/// The copy keeps the source's relative path unless `new_name` is given.
+#[tauri::command]
+pub async fn skill_copy_file(
+    app_handle: tauri::AppHandle,
+    from_skill: String,
+    from_type: String,
+    file_name: String,
+    to_skill: String,
+    to_type: String,
+    new_name: Option<String>,
+    overwrite: Option<bool>,
+    project_path: Option<String>,
+) -> Result<String, String> {
+    let source_skills_dir = skills_dir_for(&app_handle, &from_type, project_path.as_deref())?;
+    let target_skills_dir = skills_dir_for(&app_handle, &to_type, project_path.as_deref())?;
+
+    let target_name = new_name
+        .filter(|name| !name.trim().is_empty())
+        .unwrap_or_else(|| file_name.clone());
+    copy_file_between_skills(
+        &source_skills_dir,
+        &from_skill,
+        &file_name,
+        &target_skills_dir,
+        &to_skill,
+        &target_name,
+        overwrite.unwrap_or(false),
+    )?;
+    Ok(target_name)
+}
+
+/// Copy `file_name` of skill `from_skill` to `target_name` of skill `to_skill`
+///
+/// Both skill names are validated as well as both file paths, so neither side can reach
+/// outside a single skill directory.
+fn copy_file_between_skills(
+    source_skills_dir: &Path,
+    from_skill: &str,
+    file_name: &str,
+    target_skills_dir: &Path,
+    to_skill: &str,
+    target_name: &str,
+    overwrite: bool,
+) -> Result<PathBuf, String> {
+    let source_dir = existing_skill_dir(source_skills_dir, from_skill)?;
+    let target_dir = existing_skill_dir(target_skills_dir, to_skill)?;
+    copy_skill_entry(&source_dir, file_name, &target_dir, target_name, overwrite)
+}
+
+/// Rename a file in a skill directory
+#[tauri::command]
+pub async fn skill_rename_file(
+    app_handle: tauri::AppHandle,
+    skill_name: String,
+    skill_type: String,
+    old_file: String,
+    new_file: String,
+    project_path: Option<String>,
+) -> Result<(), String> {
+    let skill_dir = skill_dir_for(&app_handle, &skill_type, &skill_name, project_path.as_deref())?;
+
+    move_skill_entry(&skill_dir, &old_file, &new_file)?;
+    Ok(())
+}
+
+/// Move a file into a subdirectory of a skill, keeping its file name
+#[tauri::command]
+pub async fn skill_move_file(
+    app_handle: tauri::AppHandle,
+    skill_name: String,
+    skill_type: String,
+    file_name: String,
+    target_dir: String,
+    project_path: Option<String>,
+) -> Result<String, String> {
+    let skill_dir = skill_dir_for(&app_handle, &skill_type, &skill_name, project_path.as_deref())?;
+
+    let base_name = Path::new(&file_name)
+        .file_name()
+        .ok_or(format!("无效的文件名: '{}'", file_name))?
+        .to_string_lossy()
+        .to_string();
+    let new_file = format!("{}/{}", target_dir.trim_end_matches(['/', '\\']), base_name);
+
+    move_skill_entry(&skill_dir, &file_name, &new_file)?;
+    Ok(new_file)
+}
+
+/// Read a file from a skill directory
+#[tauri::command]
+pub async fn skill_read_file(
+    app_handle: tauri::AppHandle,
+    skill_name: String,
+    skill_type: String,
+    file_name: String,
+    project_path: Option<String>,
+) -> Result<String, String> {
+    let skill_dir = skill_dir_for(&app_handle, &skill_type, &skill_name, project_path.as_deref())?;
+    let file_path = resolve_skill_path(&skill_dir, &file_name)?;
+
+    if !file_path.exists() {
+        return Err(format!("文件 '{}' 不存在", file_name));
+    }
+
+    if file_path.is_dir() {
+        return Err("指定路径是目录，不是文件".to_string());
+    }
+
+    fs::read_to_string(&file_path).map_err(|e| e.to_string())
+}
+
+/// Delete a file from a skill directory
+#[tauri::command]
+pub async fn skill_delete_file(
+    app_handle: tauri::AppHandle,
+    skill_name: String,
+    skill_type: String,
+    file_name: String,
+    project_path: Option<String>,
+) -> Result<(), String> {
+    let skill_dir = skill_dir_for(&app_handle, &skill_type, &skill_name, project_path.as_deref())?;
+    let file_path = resolve_skill_path(&skill_dir, &file_name)?;
+
+    if !file_path.exists() {
+        return Err(format!("文件 '{}' 不存在", file_name));
+    }
+
+    if file_path.is_dir() {
+        fs::remove_dir_all(&file_path).map_err(|e| e.to_string())?;
+    } else {
+        fs::remove_file(&file_path).map_err(|e| e.to_string())?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a SKILL.md the same way skill_create does and parses it back like skill_read
+    fn round_trip(metadata: &SkillMetadata, body: &str) -> (SkillMetadata, String) {
+        let content = format!("{}{}", build_yaml_frontmatter(metadata).unwrap(), body);
+        let (yaml, markdown) = parse_yaml_frontmatter(&content).unwrap();
+        (parse_skill_metadata(&yaml.unwrap()).unwrap(), markdown)
+    }
+
+    #[test]
+    fn test_frontmatter_round_trip_with_special_characters() {
+        let descriptions = [
+            r#"Reviews "pull requests" and flags issues"#,
+            "Usage: run it with key: value pairs",
+            "- starts with a dash & has # hash",
+            "First line\nSecond line\n  indented third line",
+        ];
+
+        for description in descriptions {
+            let metadata = SkillMetadata {
+                name: "my-skill".to_string(),
+                description: description.to_string(),
+                allowed_tools: Some(vec!["Read".to_string(), "Grep".to_string()]),
+                extra: serde_yaml::Mapping::new(),
+            };
+
+            let (parsed, markdown) = round_trip(&metadata, "# My Skill\n\nBody text");
+            assert_eq!(parsed.name, "my-skill");
+            assert_eq!(parsed.description, description);
+            assert_eq!(
+                parsed.allowed_tools,
+                Some(vec!["Read".to_string(), "Grep".to_string()])
+            );
+            assert_eq!(markdown, "# My Skill\n\nBody text");
+        }
+    }
+
+    #[test]
+    fn test_parse_frontmatter_lf_and_crlf_split_identically() {
+        let lf = "---\nname: my-skill\ndescription: Does things\n---\n\n# My Skill\n\nBody line\n";
+        let crlf = lf.replace('\n', "\r\n");
+
+        let (lf_yaml, lf_body) = parse_yaml_frontmatter(lf).unwrap();
+        let (crlf_yaml, crlf_body) = parse_yaml_frontmatter(&crlf).unwrap();
+
+        assert_eq!(
+            lf_yaml.as_deref(),
+            Some("name: my-skill\ndescription: Does things")
+        );
+        assert_eq!(lf_body, "# My Skill\n\nBody line");
+        assert_eq!(crlf_yaml, lf_yaml);
+        assert_eq!(crlf_body, lf_body);
+    }
+
+    #[test]
+    fn test_parse_frontmatter_edge_cases() {
+        // No frontmatter at all
+        let (yaml, body) = parse_yaml_frontmatter("# Title\r\n\r\nText").unwrap();
+        assert!(yaml.is_none());
+        assert_eq!(body, "# Title\n\nText");
+
+        // Frontmatter with no body
+        let (yaml, body) = parse_yaml_frontmatter("---\r\nname: a\r\n---").unwrap();
+        assert_eq!(yaml.as_deref(), Some("name: a"));
+        assert_eq!(body, "");
+
+        // A "---" inside a value line is not a closing marker
+        let (yaml, body) =
+            parse_yaml_frontmatter("---\nname: a---\ndescription: b\n---\nBody").unwrap();
+        assert_eq!(yaml.as_deref(), Some("name: a---\ndescription: b"));
+        assert_eq!(body, "Body");
+
+        // Missing closing marker
+        assert!(parse_yaml_frontmatter("---\nname: a\n").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_list_skill_files_marks_binary_files() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let skill_dir = temp_dir.path().to_path_buf();
+        fs::write(skill_dir.join("notes.md"), "# Notes\nplain text").unwrap();
+        fs::write(skill_dir.join("blob.dat"), [0x89u8, 0x00, 0xff, 0xfe, 0x10]).unwrap();
+        fs::write(skill_dir.join("icon.png"), "not really a png").unwrap();
+
+        let mut files = list_skill_files(skill_dir).await.unwrap();
+        files.sort_by(|a, b| a.name.cmp(&b.name));
+        assert_eq!(files.len(), 3);
+
+        let blob = &files[0];
+        assert_eq!(blob.name, "blob.dat");
+        assert!(blob.is_binary);
+        assert!(blob.content.is_none());
+        assert_eq!(blob.size, 5);
+
+        let icon = &files[1];
+        assert_eq!(icon.name, "icon.png");
+        assert!(icon.is_binary);
+        assert!(icon.content.is_none());
+        assert_eq!(icon.size, 16);
+
+        let notes = &files[2];
+        assert_eq!(notes.name, "notes.md");
+        assert!(!notes.is_binary);
+        assert_eq!(notes.content.as_deref(), Some("# Notes\nplain text"));
+        assert_eq!(notes.size, 18);
+    }
+
+    #[tokio::test]
+    async fn test_list_skill_files_recurses_with_relative_paths() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let skill_dir = temp_dir.path().to_path_buf();
+        fs::create_dir_all(skill_dir.join("scripts")).unwrap();
+        fs::create_dir_all(skill_dir.join("references").join("api")).unwrap();
+        fs::write(skill_dir.join("SKILL.md"), "# Skill").unwrap();
+        fs::write(skill_dir.join("scripts").join("build.sh"), "echo hi").unwrap();
+        fs::write(skill_dir.join("references").join("api").join("v1.md"), "v1").unwrap();
+
+        let files = list_skill_files(skill_dir).await.unwrap();
+        let mut paths: Vec<(String, bool)> = files
+            .iter()
+            .map(|f| (f.path.clone(), f.is_directory))
+            .collect();
+        paths.sort();
+
+        assert_eq!(
+            paths,
+            vec![
+                ("SKILL.md".to_string(), false),
+                ("references".to_string(), true),
+                ("references/api".to_string(), true),
+                ("references/api/v1.md".to_string(), false),
+                ("scripts".to_string(), true),
+                ("scripts/build.sh".to_string(), false),
+            ]
+        );
+
+        let nested = files.iter().find(|f| f.path == "references/api/v1.md").unwrap();
+        assert_eq!(nested.name, "v1.md");
+        assert_eq!(nested.content.as_deref(), Some("v1"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_list_skill_files_skips_symlink_loops() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let skill_dir = temp_dir.path().to_path_buf();
+        fs::create_dir_all(skill_dir.join("nested")).unwrap();
+        fs::write(skill_dir.join("nested").join("file.txt"), "x").unwrap();
+        std::os::unix::fs::symlink(&skill_dir, skill_dir.join("nested").join("loop")).unwrap();
+
+        let files = list_skill_files(skill_dir).await.unwrap();
+        let mut paths: Vec<String> = files.iter().map(|f| f.path.clone()).collect();
+        paths.sort();
+
+        assert_eq!(paths, vec!["nested", "nested/file.txt", "nested/loop"]);
+    }
+
+    #[test]
+    fn test_copy_skill_file_within_skill() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let skill_dir = write_test_skill(temp_dir.path(), "tools");
+
+        let target =
+            copy_skill_entry(&skill_dir, "scripts/run.sh", &skill_dir, "bin/run-copy.sh", false)
+                .unwrap();
+        assert_eq!(target, skill_dir.join("bin").join("run-copy.sh"));
+        assert_eq!(fs::read_to_string(&target).unwrap(), "echo run");
+        // The source is left in place
+        assert!(skill_dir.join("scripts").join("run.sh").exists());
+
+        // Copying onto itself or onto an existing file needs care
+        assert!(copy_skill_entry(&skill_dir, "scripts/run.sh", &skill_dir, "scripts/run.sh", true)
+            .is_err());
+        assert!(
+            copy_skill_entry(&skill_dir, "scripts/lib/util.sh", &skill_dir, "bin/run-copy.sh", false)
+                .is_err()
+        );
+        copy_skill_entry(&skill_dir, "scripts/lib/util.sh", &skill_dir, "bin/run-copy.sh", true)
+            .unwrap();
+        assert_eq!(fs::read_to_string(&target).unwrap(), "echo util");
+    }
+
+    #[test]
+    fn test_copy_skill_file_across_types() {
+        let personal_root = tempfile::TempDir::new().unwrap();
+        let project_root = tempfile::TempDir::new().unwrap();
+        let source = write_test_skill(personal_root.path(), "helpers");
+        let target = write_test_skill(project_root.path(), "deploy");
+
+        // The target skill already has its own util.sh
+        copy_skill_entry(&source, "scripts/lib/util.sh", &target, "scripts/lib/util.sh", false)
+            .unwrap_err();
+        copy_skill_entry(&source, "scripts/lib", &target, "shared", false).unwrap();
+        assert_eq!(
+            fs::read_to_string(target.join("shared").join("util.sh")).unwrap(),
+            "echo util"
+        );
+
+        // Neither side may leave its skill directory
+        assert!(copy_skill_entry(&source, "../deploy/SKILL.md", &target, "x.md", false).is_err());
+        assert!(copy_skill_entry(&source, "scripts/run.sh", &target, "../run.sh", false).is_err());
+        assert!(!project_root.path().join("run.sh").exists());
+    }
+
+    #[test]
+    fn test_copy_file_between_skills_rejects_traversal_in_skill_names() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let personal = temp_dir.path().join("personal");
+        let project = temp_dir.path().join("project");
+        write_test_skill(&personal, "helpers");
+        write_test_skill(&project, "deploy");
+        fs::create_dir_all(temp_dir.path().join("x")).unwrap();
+        fs::write(personal.join("notes.md"), "outside any skill").unwrap();
+
+        // Source skill name climbing to the skills root
+        assert!(copy_file_between_skills(
+            &personal, "..", "personal/notes.md", &project, "deploy", "notes.md", false
+        )
+        .is_err());
+        // Target skill name escaping the skills root
+        assert!(copy_file_between_skills(
+            &personal, "helpers", "scripts/run.sh", &project, "../x", "run.sh", false
+        )
+        .is_err());
+        assert!(!project.join("deploy").join("notes.md").exists());
+        assert!(!temp_dir.path().join("x").join("run.sh").exists());
+
+        copy_file_between_skills(
+            &personal, "helpers", "scripts/run.sh", &project, "deploy", "bin/run.sh", false,
+        )
+        .unwrap();
+        assert!(project.join("deploy").join("bin").join("run.sh").exists());
+    }
+
+    #[test]
+    fn test_custom_frontmatter_fields_survive_update() {
+        let original = "---\nname: release-notes\ndescription: Writes release notes\nversion: 1.2.0\nauthor: Jane Doe\ntags:\n  - docs\n  - release\nallowed-tools: Read\n---\n\n# Release notes\n";
+
+        let (yaml, body) = parse_yaml_frontmatter(original).unwrap();
+        let mut metadata = parse_skill_metadata(&yaml.unwrap()).unwrap();
+        assert_eq!(metadata.extra.len(), 3);
+        assert_eq!(metadata.extra.get("version").and_then(|v| v.as_str()), Some("1.2.0"));
+        assert_eq!(metadata.extra.get("author").and_then(|v| v.as_str()), Some("Jane Doe"));
+        // Known keys are not duplicated into `extra`
+        assert!(metadata.extra.get("allowed-tools").is_none());
+
+        // Modify a managed field and write the file back like skill_update does
+        metadata.description = "Drafts release notes from the changelog".to_string();
+        metadata.allowed_tools = Some(vec!["Read".to_string(), "Grep".to_string()]);
+        let rewritten = format!("{}{}", build_yaml_frontmatter(&metadata).unwrap(), body);
+
+        let (yaml, body) = parse_yaml_frontmatter(&rewritten).unwrap();
+        let reparsed = parse_skill_metadata(&yaml.unwrap()).unwrap();
+        assert_eq!(reparsed.description, "Drafts release notes from the changelog");
+        assert_eq!(
+            reparsed.allowed_tools,
+            Some(vec!["Read".to_string(), "Grep".to_string()])
+        );
+        assert_eq!(reparsed.extra, metadata.extra);
+        let tags: Vec<&str> = reparsed.extra["tags"]
+            .as_sequence()
+            .unwrap()
+            .iter()
+            .filter_map(|tag| tag.as_str())
+            .collect();
+        assert_eq!(tags, vec!["docs", "release"]);
+        assert_eq!(body, "# Release notes");
+    }
+
+    #[test]
+    fn test_skill_exposes_extra_frontmatter_as_json() {
+        let mut skill = test_skill("tagged", "A skill with tags", "# Body");
+        skill.extra_frontmatter = serde_yaml::from_str("version: 2
+tags: [a, b]").unwrap();
+
+        let json = serde_json::to_value(&skill).unwrap();
+        assert_eq!(json["extra_frontmatter"]["version"], 2);
+        assert_eq!(json["extra_frontmatter"]["tags"], serde_json::json!(["a", "b"]));
+    }
+
+    /// A body of `count` one-letter words, two bytes each
+    fn words(count: usize) -> String {
+        "a ".repeat(count)
+    }
+
+    #[test]
+    fn test_skill_body_word_warning_threshold() {
+        let limits = SkillSizeLimits::default();
+
+        for count in [limits.warn_words - 1, limits.warn_words] {
+            let (errors, warnings) = check_skill_body_size(&words(count), &limits);
+            assert!(errors.is_empty() && warnings.is_empty(), "{} words", count);
+        }
+
+        let (errors, warnings) = check_skill_body_size(&words(limits.warn_words + 1), &limits);
+        assert!(errors.is_empty());
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("5001 词"));
+    }
+
+    #[test]
+    fn test_skill_body_byte_warning_threshold() {
+        let limits = SkillSizeLimits::default();
+
+        for bytes in [limits.warn_bytes - 1, limits.warn_bytes] {
+            let (errors, warnings) = check_skill_body_size(&"x".repeat(bytes), &limits);
+            assert!(errors.is_empty() && warnings.is_empty(), "{} bytes", bytes);
+        }
+
+        let (errors, warnings) = check_skill_body_size(&"x".repeat(limits.warn_bytes + 1), &limits);
+        assert!(errors.is_empty());
+        assert!(warnings[0].contains("20481 字节"));
+    }
+
+    #[test]
+    fn test_skill_body_hard_limits() {
+        let limits = SkillSizeLimits::default();
+
+        // At the ceiling it is only a warning
+        for body in [words(limits.max_words), "x".repeat(limits.max_bytes)] {
+            let (errors, warnings) = check_skill_body_size(&body, &limits);
+            assert!(errors.is_empty());
+            assert_eq!(warnings.len(), 1);
+        }
+        let (errors, _) = check_skill_body_size(&words(limits.max_words - 1), &limits);
+        assert!(errors.is_empty());
+
+        let (errors, warnings) = check_skill_body_size(&words(limits.max_words + 1), &limits);
+        assert_eq!(errors.len(), 1);
+        assert!(warnings.is_empty());
+        assert!(errors[0].contains("25001 词"));
+
+        let (errors, _) = check_skill_body_size(&"x".repeat(limits.max_bytes + 1), &limits);
+        assert!(errors[0].contains("102401 字节"));
+        assert!(ensure_skill_body_size("big", &"x".repeat(limits.max_bytes + 1)).is_err());
+        assert!(ensure_skill_body_size("ok", &words(10)).is_ok());
+    }
+
+    #[test]
+    fn test_validate_skill_reports_size_problems() {
+        let limits = SkillSizeLimits::default();
+        let mut skill = test_skill("big-skill", "A skill with a long body", &words(limits.warn_words + 1));
+        let result = validate_skill(&skill);
+        assert!(result.is_valid);
+        assert!(result.warnings.iter().any(|w| w.contains("SKILL.md")));
+
+        skill.markdown_content = "x".repeat(limits.max_bytes + 1);
+        let result = validate_skill(&skill);
+        assert!(!result.is_valid);
+        assert!(result.errors.iter().any(|e| e.contains("102401 字节")));
+    }
+
+    /// Flatten a tree into (path, is_directory, size) rows in display order
+    fn tree_rows(node: &TreeNode) -> Vec<(String, bool, u64)> {
+        let mut rows = Vec::new();
+        for child in &node.children {
+            rows.push((child.path.clone(), child.is_directory, child.size));
+            rows.extend(tree_rows(child));
+        }
+        rows
+    }
+
+    #[tokio::test]
+    async fn test_file_tree_of_empty_skill() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+
+        let files = list_skill_files(temp_dir.path().to_path_buf()).await.unwrap();
+        let tree = build_skill_file_tree("empty", &files);
+
+        assert_eq!(tree.name, "empty");
+        assert_eq!(tree.path, "");
+        assert!(tree.is_directory);
+        assert!(tree.children.is_empty());
+        assert_eq!(tree.size, 0);
+    }
+
+    #[tokio::test]
+    async fn test_file_tree_of_flat_skill() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let skill_dir = temp_dir.path().to_path_buf();
+        fs::write(skill_dir.join("SKILL.md"), "# Skill").unwrap();
+        fs::write(skill_dir.join("b.txt"), "bb").unwrap();
+        fs::write(skill_dir.join("a.txt"), "a").unwrap();
+
+        let files = list_skill_files(skill_dir).await.unwrap();
+        let tree = build_skill_file_tree("flat", &files);
+
+        assert_eq!(
+            tree_rows(&tree),
+            vec![
+                ("a.txt".to_string(), false, 1),
+                ("b.txt".to_string(), false, 2),
+                ("SKILL.md".to_string(), false, 7),
+            ]
+        );
+        assert_eq!(tree.size, 10);
+    }
+
+    #[tokio::test]
+    async fn test_file_tree_of_nested_skill() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let skill_dir = write_test_skill(temp_dir.path(), "nested");
+        fs::create_dir_all(skill_dir.join("assets")).unwrap();
+        fs::write(skill_dir.join("README.md"), "readme").unwrap();
+
+        let files = list_skill_files(skill_dir).await.unwrap();
+        let tree = build_skill_file_tree("nested", &files);
+
+        // Directories come first at every level, then files alphabetically
+        assert_eq!(
+            tree_rows(&tree)
+                .into_iter()
+                .map(|(path, is_dir, _)| (path, is_dir))
+                .collect::<Vec<_>>(),
+            vec![
+                ("assets".to_string(), true),
+                ("scripts".to_string(), true),
+                ("scripts/lib".to_string(), true),
+                ("scripts/lib/util.sh".to_string(), false),
+                ("scripts/run.sh".to_string(), false),
+                ("README.md".to_string(), false),
+                ("SKILL.md".to_string(), false),
+            ]
+        );
+
+        let scripts = &tree.children[1];
+        assert_eq!(scripts.name, "scripts");
+        assert_eq!(scripts.children[0].name, "lib");
+        assert_eq!(scripts.children[0].children[0].name, "util.sh");
+        // "echo run" + "echo util"
+        assert_eq!(scripts.size, 17);
+        assert!(tree.children[0].children.is_empty());
+    }
+
+    /// Creates `<root>/<name>` with a SKILL.md and a nested helper file
+    fn write_test_skill(root: &Path, name: &str) -> PathBuf {
+        let skill_dir = root.join(name);
+        fs::create_dir_all(skill_dir.join("scripts").join("lib")).unwrap();
+        fs::write(
+            skill_dir.join("SKILL.md"),
+            format!(
+                "---\nname: {}\ndescription: Original skill\nallowed-tools: Read, Grep\n---\n\n# Body",
+                name
+            ),
+        )
+        .unwrap();
+        fs::write(skill_dir.join("scripts").join("run.sh"), "echo run").unwrap();
+        fs::write(skill_dir.join("scripts").join("lib").join("util.sh"), "echo util").unwrap();
+        skill_dir
+    }
+
+    #[test]
+    fn test_duplicate_skill_same_type() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let source = write_test_skill(temp_dir.path(), "original");
+        let target = temp_dir.path().join("copy");
+
+        duplicate_skill_dir(&source, &target, "copy").unwrap();
+
+        let content = fs::read_to_string(target.join("SKILL.md")).unwrap();
+        let (yaml, body) = parse_yaml_frontmatter(&content).unwrap();
+        let metadata = parse_skill_metadata(&yaml.unwrap()).unwrap();
+        assert_eq!(metadata.name, "copy");
+        assert_eq!(metadata.description, "Original skill");
+        assert_eq!(
+            metadata.allowed_tools,
+            Some(vec!["Read".to_string(), "Grep".to_string()])
+        );
+        assert_eq!(body, "# Body");
+        assert_eq!(
+            fs::read_to_string(target.join("scripts").join("lib").join("util.sh")).unwrap(),
+            "echo util"
+        );
+
+        // Source is untouched
+        let source_content = fs::read_to_string(source.join("SKILL.md")).unwrap();
+        assert!(source_content.contains("name: original"));
+    }
+
+    #[test]
+    fn test_duplicate_skill_cross_type() {
+        let personal = tempfile::TempDir::new().unwrap();
+        let project = tempfile::TempDir::new().unwrap();
+        let source = write_test_skill(personal.path(), "shared");
+        let target = project.path().join("shared");
+
+        duplicate_skill_dir(&source, &target, "shared").unwrap();
+
+        assert!(target.join("scripts").join("run.sh").exists());
+        assert!(target.join("scripts").join("lib").join("util.sh").exists());
+        assert!(source.join("scripts").join("run.sh").exists());
+    }
+
+    /// Skills dirs for a personal root and a project root, routed like `skills_dir_for`
+    fn test_skills_dirs<'a>(
+        personal: &'a Path,
+        project: &'a Path,
+    ) -> impl Fn(&str) -> Result<PathBuf, String> + 'a {
+        move |skill_type| {
+            ensure_managed_skill_type(skill_type)?;
+            Ok(if skill_type == "personal" {
+                personal.join("skills")
+            } else {
+                project.join(".claude").join("skills")
+            })
+        }
+    }
+
+    #[test]
+    fn test_duplicate_skill_between_routes_personal_to_project() {
+        let home = tempfile::TempDir::new().unwrap();
+        let project = tempfile::TempDir::new().unwrap();
+        let skills_dirs = test_skills_dirs(home.path(), project.path());
+        let source = write_test_skill(&home.path().join("skills"), "shared");
+
+        let target =
+            duplicate_skill_between(&skills_dirs, "shared", "personal", "shared", "project")
+                .unwrap();
+
+        assert_eq!(target, project.path().join(".claude").join("skills").join("shared"));
+        assert!(target.join("scripts").join("lib").join("util.sh").exists());
+        assert!(source.join("SKILL.md").exists());
+
+        // And back again under a new name
+        let back =
+            duplicate_skill_between(&skills_dirs, "shared", "project", "shared-copy", "personal")
+                .unwrap();
+        assert_eq!(back, home.path().join("skills").join("shared-copy"));
+        let content = fs::read_to_string(back.join("SKILL.md")).unwrap();
+        assert!(content.contains("name: shared-copy"));
+    }
+
+    #[test]
+    fn test_duplicate_skill_between_rejects_unsafe_source() {
+        let home = tempfile::TempDir::new().unwrap();
+        let project = tempfile::TempDir::new().unwrap();
+        let skills_dirs = test_skills_dirs(home.path(), project.path());
+        write_test_skill(&home.path().join("skills"), "shared");
+
+        // ".." would copy the whole personal root (and everything next to it)
+        for name in ["..", ".", "../skills"] {
+            assert!(
+                duplicate_skill_between(&skills_dirs, name, "personal", "copy", "project")
+                    .is_err(),
+                "{:?} should be rejected",
+                name
+            );
+        }
+        assert!(!project.path().join(".claude").join("skills").join("copy").exists());
+
+        // Extra skill dirs are read-only
+        assert!(duplicate_skill_between(&skills_dirs, "shared", "personal", "c", "extra").is_err());
+    }
+
+    #[test]
+    fn test_duplicate_skill_rejects_existing_target() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let source = write_test_skill(temp_dir.path(), "original");
+        let existing = write_test_skill(temp_dir.path(), "taken");
+
+        let err = duplicate_skill_dir(&source, &existing, "taken").unwrap_err();
+        assert!(err.contains("taken"));
+        // Neither skill was modified
+        assert!(fs::read_to_string(existing.join("SKILL.md")).unwrap().contains("name: taken"));
+        assert!(fs::read_to_string(source.join("SKILL.md")).unwrap().contains("name: original"));
+    }
+
+    fn test_skill(name: &str, description: &str, markdown_content: &str) -> Skill {
+        Skill {
+            name: name.to_string(),
+            skill_type: "personal".to_string(),
+            source: "personal".to_string(),
+            description: description.to_string(),
+            file_path: String::new(),
+            yaml_frontmatter: None,
+            markdown_content: markdown_content.to_string(),
+            files: Vec::new(),
+            allowed_tools: None,
+            extra_frontmatter: serde_yaml::Mapping::new(),
+            enabled: true,
+            last_modified: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_search_skills_matches_each_field() {
+        let skills = vec![
+            test_skill("pdf-tools", "Fill forms", "Nothing relevant"),
+            test_skill("forms", "Extract PDF text", "Nothing relevant"),
+            test_skill("notes", "Take notes", "Use pdftotext to read PDFs."),
+            test_skill("unrelated", "Other", "Other body"),
+        ];
+
+        let results = search_skills(skills, "PDF");
+        let names: Vec<&str> = results.iter().map(|r| r.skill.name.as_str()).collect();
+        assert_eq!(names, vec!["pdf-tools", "forms", "notes"]);
+
+        assert_eq!(results[0].matched_fields, vec!["name"]);
+        assert_eq!(results[1].matched_fields, vec!["description"]);
+        assert_eq!(results[2].matched_fields, vec!["content"]);
+        assert!(results[0].snippet.is_none());
+        assert_eq!(
+            results[2].snippet.as_deref(),
+            Some("Use pdftotext to read PDFs.")
+        );
+    }
+
+    #[test]
+    fn test_search_skills_ranks_multiple_hits_higher() {
+        let skills = vec![
+            test_skill("review", "Code review helper", "Body"),
+            test_skill("reviewer", "Reviews code", "Run the review checklist"),
+        ];
+
+        let results = search_skills(skills, "review");
+        assert_eq!(results[0].skill.name, "reviewer");
+        assert_eq!(
+            results[0].matched_fields,
+            vec!["name", "description", "content"]
+        );
+        assert_eq!(results[1].skill.name, "review");
+        assert!(results[0].score > results[1].score);
+    }
+
+    #[test]
+    fn test_search_skills_snippet_is_trimmed_around_match() {
+        let body = format!("{}needle{}", "a ".repeat(50), " b".repeat(50));
+        let results = search_skills(vec![test_skill("x", "y", &body)], "NEEDLE");
+
+        let snippet = results[0].snippet.as_deref().unwrap();
+        assert!(snippet.starts_with('…'));
+        assert!(snippet.ends_with('…'));
+        assert!(snippet.contains("needle"));
+        assert!(search_skills(vec![test_skill("x", "y", &body)], "  ").is_empty());
+    }
+
+    fn tools(names: &[&str]) -> Vec<String> {
+        names.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_validate_allowed_tools_accepts_known_tools() {
+        let (errors, warnings) = validate_allowed_tools(&tools(&[
+            "Read",
+            "Bash(git status:*)",
+            "WebFetch",
+            "mcp__github__create_issue",
+        ]));
+        assert!(errors.is_empty());
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn test_validate_allowed_tools_warns_on_misspelled_tools() {
+        let (errors, warnings) = validate_allowed_tools(&tools(&["Bashh", "read", "Grep"]));
+        assert!(errors.is_empty());
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[0].contains("Bashh"));
+        assert!(warnings[1].contains("'read'") && warnings[1].contains("'Read'"));
+    }
+
+    #[test]
+    fn test_validate_allowed_tools_rejects_empty_entries() {
+        let (errors, warnings) = validate_allowed_tools(&tools(&["Read", "", "   "]));
+        assert_eq!(errors.len(), 2);
+        assert!(warnings.is_empty());
+
+        let mut skill = test_skill("my-skill", "A sufficiently long description", "Body");
+        skill.allowed_tools = Some(tools(&["Read", " "]));
+        let result = validate_skill(&skill);
+        assert!(!result.is_valid);
+    }
+
+    #[test]
+    fn test_skills_watcher_reports_created_file() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let watched = fs::canonicalize(temp_dir.path()).unwrap();
+        let (tx, rx) = mpsc::channel();
+
+        let _watcher = start_skills_watcher(&[watched.clone()], move |path| {
+            let _ = tx.send(path);
+        })
+        .unwrap();
+
+        let skill_dir = watched.join("new-skill");
+        fs::create_dir_all(&skill_dir).unwrap();
+        fs::write(skill_dir.join("SKILL.md"), "# New").unwrap();
+
+        let path = rx.recv_timeout(Duration::from_secs(10)).unwrap();
+        assert!(path.starts_with(&watched));
+    }
+
+    #[test]
+    fn test_debounced_batch_flushes_under_continuous_events() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        let (tx, rx) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let writer_stop = stop.clone();
+        // A writer that never leaves a quiet window until the batch has been delivered
+        let writer = std::thread::spawn(move || {
+            let mut i = 0;
+            while !writer_stop.load(Ordering::SeqCst) {
+                let _ = tx.send(PathBuf::from(format!("SKILL.md.{}", i % 3)));
+                i += 1;
+                std::thread::sleep(Duration::from_millis(5));
+            }
+        });
+
+        let quiet = Duration::from_millis(200);
+        let batch = next_debounced_batch(&rx, quiet, Duration::from_millis(100)).unwrap();
+        stop.store(true, Ordering::SeqCst);
+        writer.join().unwrap();
+
+        // Only the maximum delay can have ended this batch
+        assert!(!batch.is_empty() && batch.len() <= 3);
+    }
+
+    #[test]
+    fn test_debounced_batch_ends_when_sender_is_gone() {
+        let (tx, rx) = mpsc::channel();
+        tx.send(PathBuf::from("a")).unwrap();
+        tx.send(PathBuf::from("a")).unwrap();
+        tx.send(PathBuf::from("b")).unwrap();
+        drop(tx);
+
+        let quiet = Duration::from_secs(1);
+        let batch = next_debounced_batch(&rx, quiet, quiet).unwrap();
+        assert_eq!(batch, vec![PathBuf::from("a"), PathBuf::from("b")]);
+        assert!(next_debounced_batch(&rx, quiet, quiet).is_none());
+    }
+
+    #[test]
+    fn test_move_skill_entry_renames_file() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let skill_dir = write_test_skill(temp_dir.path(), "my-skill");
+
+        move_skill_entry(&skill_dir, "scripts/run.sh", "scripts/start.sh").unwrap();
+
+        assert!(!skill_dir.join("scripts").join("run.sh").exists());
+        assert_eq!(
+            fs::read_to_string(skill_dir.join("scripts").join("start.sh")).unwrap(),
+            "echo run"
+        );
+    }
+
+    #[test]
+    fn test_move_skill_entry_into_new_subfolder() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let skill_dir = write_test_skill(temp_dir.path(), "my-skill");
+        fs::write(skill_dir.join("notes.md"), "notes").unwrap();
+
+        move_skill_entry(&skill_dir, "notes.md", "references/docs/notes.md").unwrap();
+
+        assert!(!skill_dir.join("notes.md").exists());
+        assert!(skill_dir.join("references").join("docs").join("notes.md").exists());
+
+        // Source missing and target existing both fail cleanly
+        assert!(move_skill_entry(&skill_dir, "notes.md", "other.md").is_err());
+        assert!(move_skill_entry(&skill_dir, "scripts/run.sh", "scripts/lib/util.sh").is_err());
+        assert!(skill_dir.join("scripts").join("run.sh").exists());
+    }
+
+    #[test]
+    fn test_move_skill_entry_rejects_path_traversal() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let skill_dir = write_test_skill(temp_dir.path(), "my-skill");
+
+        assert!(move_skill_entry(&skill_dir, "scripts/run.sh", "../escaped.sh").is_err());
+        assert!(move_skill_entry(&skill_dir, "scripts/run.sh", "scripts/../../escaped.sh").is_err());
+        assert!(move_skill_entry(&skill_dir, "../my-skill/scripts/run.sh", "moved.sh").is_err());
+        assert!(move_skill_entry(&skill_dir, "SKILL.md", "README.md").is_err());
+
+        assert!(skill_dir.join("scripts").join("run.sh").exists());
+        assert!(!temp_dir.path().join("escaped.sh").exists());
+    }
+
+    #[test]
+    fn test_resolve_skill_path_allows_nested_files() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let skill_dir = write_test_skill(temp_dir.path(), "my-skill");
+
+        assert_eq!(
+            resolve_skill_path(&skill_dir, "scripts/./lib/../run.sh").unwrap(),
+            skill_dir.join("scripts").join("run.sh")
+        );
+        assert_eq!(
+            resolve_skill_path(&skill_dir, "new/dir/file.md").unwrap(),
+            skill_dir.join("new").join("dir").join("file.md")
+        );
+    }
+
+    #[test]
+    fn test_resolve_skill_path_rejects_escapes() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let skill_dir = write_test_skill(temp_dir.path(), "my-skill");
+        let outside = temp_dir.path().join("outside.txt");
+        fs::write(&outside, "keep me").unwrap();
+
+        for file_name in [
+            "../outside.txt",
+            "../../.bashrc",
+            "scripts/../../outside.txt",
+            "",
+            ".",
+        ] {
+            assert!(
+                resolve_skill_path(&skill_dir, file_name).is_err(),
+                "{} should be rejected",
+                file_name
+            );
+        }
+
+        let absolute = outside.to_string_lossy().to_string();
+        assert!(resolve_skill_path(&skill_dir, &absolute).is_err());
+        assert_eq!(fs::read_to_string(&outside).unwrap(), "keep me");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_resolve_skill_path_rejects_symlink_escape() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let skill_dir = write_test_skill(temp_dir.path(), "my-skill");
+        let outside_dir = temp_dir.path().join("outside");
+        fs::create_dir_all(&outside_dir).unwrap();
+        std::os::unix::fs::symlink(&outside_dir, skill_dir.join("link")).unwrap();
+
+        assert!(resolve_skill_path(&skill_dir, "link/secret.txt").is_err());
+    }
+
+    #[test]
+    fn test_only_personal_and_project_skill_types_are_managed() {
+        assert!(ensure_managed_skill_type("personal").is_ok());
+        assert!(ensure_managed_skill_type("project").is_ok());
+        // Extra skills are read-only and must never fall through to the project directory
+        for skill_type in ["extra", "", "Personal", "user"] {
+            assert!(
+                ensure_managed_skill_type(skill_type).is_err(),
+                "{} should be rejected",
+                skill_type
+            );
+        }
+    }
+
+    #[test]
+    fn test_existing_skill_dir_rejects_traversal_in_skill_name() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let skills_dir = temp_dir.path().join("skills");
+        write_test_skill(&skills_dir, "my-skill");
+        fs::create_dir_all(skills_dir.join(".trash")).unwrap();
+        fs::write(temp_dir.path().join("secret.txt"), "keep me").unwrap();
+
+        for name in ["..", ".", "../skills", ".trash", "my-skill/.."] {
+            assert!(
+                existing_skill_dir(&skills_dir, name).is_err(),
+                "{} should be rejected",
+                name
+            );
+        }
+        assert!(create_skill_file_in(&skills_dir, "..", "secret.txt", "pwned").is_err());
+        assert_eq!(
+            fs::read_to_string(temp_dir.path().join("secret.txt")).unwrap(),
+            "keep me"
+        );
+        assert_eq!(
+            existing_skill_dir(&skills_dir, "my-skill").unwrap(),
+            skills_dir.join("my-skill")
+        );
+    }
+
+    #[test]
+    fn test_project_skills_dir_for_explicit_path() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let project = temp_dir.path().join("my-project");
+        let project_str = project.to_string_lossy().to_string();
+
+        assert_eq!(
+            project_skills_dir_for(&project_str),
+            project.join(".claude").join("skills")
+        );
+    }
+
+    #[test]
+    fn test_sort_skills_by_each_mode() {
+        let mut skills = vec![
+            test_skill("zeta", "d", "b"),
+            test_skill("alpha", "d", "b"),
+            test_skill("mid", "d", "b"),
+            test_skill("alpha", "d", "b"),
+        ];
+        skills[0].last_modified = "2024-03-01T00:00:00+00:00".to_string();
+        skills[1].last_modified = "2024-01-01T00:00:00+00:00".to_string();
+        skills[2].last_modified = "2024-02-01T00:00:00+00:00".to_string();
+        skills[2].skill_type = "project".to_string();
+        skills[3].skill_type = "project".to_string();
+
+        let order = |skills: &[Skill]| {
+            skills
+                .iter()
+                .map(|s| format!("{}:{}", s.skill_type, s.name))
+                .collect::<Vec<_>>()
+        };
+
+        sort_skills(&mut skills, None).unwrap();
+        assert_eq!(
+            order(&skills),
+            vec!["personal:alpha", "project:alpha", "project:mid", "personal:zeta"]
+        );
+
+        sort_skills(&mut skills, Some("type")).unwrap();
+        assert_eq!(
+            order(&skills),
+            vec!["personal:alpha", "personal:zeta", "project:alpha", "project:mid"]
+        );
+
+        sort_skills(&mut skills, Some("last_modified")).unwrap();
+        assert_eq!(
+            order(&skills),
+            vec!["personal:zeta", "project:mid", "personal:alpha", "project:alpha"]
+        );
+
+        assert!(sort_skills(&mut skills, Some("size")).is_err());
+    }
+
+    #[test]
+    fn test_builtin_templates_produce_valid_skills() {
+        let templates = builtin_skill_templates();
+        assert!(!templates.is_empty());
+
+        for template in templates {
+            let (rendered, warnings) =
+                render_skill_template(&template, "from-template", &HashMap::new());
+            assert!(warnings.is_empty(), "{}: {:?}", template.id, warnings);
+            let mut skill = test_skill(
+                "from-template",
+                &rendered.description,
+                &rendered.markdown_content,
+            );
+            skill.allowed_tools = template.allowed_tools.clone();
+            skill.yaml_frontmatter = Some(
+                serde_yaml::to_string(&SkillMetadata {
+                    name: skill.name.clone(),
+                    description: skill.description.clone(),
+                    allowed_tools: skill.allowed_tools.clone(),
+                    extra: serde_yaml::Mapping::new(),
+                })
+                .unwrap(),
+            );
+
+            let result = validate_skill(&skill);
+            assert!(result.is_valid, "{}: {:?}", template.id, result.errors);
+            assert!(result.warnings.is_empty(), "{}: {:?}", template.id, result.warnings);
+            assert!(skill.markdown_content.starts_with("# from-template"));
+        }
+    }
+
+    #[test]
+    fn test_find_skill_template_unknown_id() {
+        assert!(find_skill_template("code-reviewer").is_ok());
+        let err = find_skill_template("does-not-exist").unwrap_err();
+        assert!(err.contains("does-not-exist"));
+    }
+
+    #[test]
+    fn test_frontmatter_keeps_hyphenated_allowed_tools_key() {
+        let frontmatter = build_yaml_frontmatter(&SkillMetadata {
+            name: "my-skill".to_string(),
+            description: "Does things".to_string(),
+            allowed_tools: Some(vec!["Read".to_string(), "Write".to_string()]),
+            extra: serde_yaml::Mapping::new(),
+        })
+        .unwrap();
+
+        assert!(frontmatter.starts_with("---\n"));
+        assert!(frontmatter.ends_with("---\n"));
+        assert!(frontmatter.contains("allowed-tools:\n- Read\n- Write\n"));
+    }
+
+    #[test]
+    fn test_frontmatter_omits_missing_allowed_tools() {
+        let frontmatter = build_yaml_frontmatter(&SkillMetadata {
+            name: "my-skill".to_string(),
+            description: "Does things".to_string(),
+            allowed_tools: None,
+            extra: serde_yaml::Mapping::new(),
+        })
+        .unwrap();
+
+        assert!(!frontmatter.contains("allowed-tools"));
+    }
+
+    #[test]
+    fn test_disabled_manifest_absent_means_all_enabled() {
+        let dir = tempfile::TempDir::new().unwrap();
+
+        assert!(read_disabled_skills(dir.path()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_set_skill_enabled_toggles_manifest() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let manifest = dir.path().join(DISABLED_SKILLS_MANIFEST);
+
+        set_skill_enabled_in_manifest(dir.path(), "pdf-tools", false).unwrap();
+        set_skill_enabled_in_manifest(dir.path(), "notes", false).unwrap();
+        set_skill_enabled_in_manifest(dir.path(), "notes", false).unwrap();
+        assert_eq!(fs::read_to_string(&manifest).unwrap(), "notes\npdf-tools\n");
+
+        set_skill_enabled_in_manifest(dir.path(), "notes", true).unwrap();
+        let disabled = read_disabled_skills(dir.path()).unwrap();
+        assert!(disabled.contains("pdf-tools"));
+        assert!(!disabled.contains("notes"));
+
+        set_skill_enabled_in_manifest(dir.path(), "pdf-tools", true).unwrap();
+        assert!(!manifest.exists());
+    }
+
+    #[test]
+    fn test_disabled_manifest_ignores_comments_and_blank_lines() {
+        let dir = tempfile::TempDir::new().unwrap();
+        fs::write(dir.path().join(DISABLED_SKILLS_MANIFEST), "# off for now\n\n  notes  \n").unwrap();
+
+        let disabled = read_disabled_skills(dir.path()).unwrap();
+        assert_eq!(disabled.into_iter().collect::<Vec<_>>(), vec!["notes".to_string()]);
+    }
+
+    #[test]
+    fn test_personal_skills_dir_honors_claude_config_dir() {
+        let home = tempfile::TempDir::new().unwrap();
+        let config = tempfile::TempDir::new().unwrap();
+        let home_path = Some(home.path().to_path_buf());
+        let config_dir = Some(config.path().as_os_str());
+
+        // Unset or empty: the home directory's .claude
+        assert_eq!(
+            resolve_personal_skills_dir(None, None, home_path.clone()).unwrap(),
+            home.path().join(".claude").join("skills")
+        );
+        assert_eq!(
+            resolve_personal_skills_dir(None, Some("".as_ref()), home_path.clone()).unwrap(),
+            home.path().join(".claude").join("skills")
+        );
+
+        // CLAUDE_CONFIG_DIR replaces ~/.claude, as it does for the CLI
+        assert_eq!(
+            resolve_personal_skills_dir(None, config_dir, home_path.clone()).unwrap(),
+            config.path().join("skills")
+        );
+        assert_eq!(
+            resolve_personal_skills_dir(None, config_dir, None).unwrap(),
+            config.path().join("skills")
+        );
+
+        // An app setting wins over both; a blank one is ignored
+        let custom = home.path().join("my-skills");
+        let setting = custom.to_str();
+        assert_eq!(
+            resolve_personal_skills_dir(setting, config_dir, home_path.clone()).unwrap(),
+            custom
+        );
+        assert_eq!(
+            resolve_personal_skills_dir(Some("  "), config_dir, home_path).unwrap(),
+            config.path().join("skills")
+        );
+
+        assert!(resolve_personal_skills_dir(None, None, None).is_err());
+    }
+
+    #[test]
+    fn test_extra_skill_dirs_parse_like_path() {
+        let first = PathBuf::from("team-skills");
+        let second = PathBuf::from("shared-skills");
+        let joined = std::env::join_paths([&first, &second, &first]).unwrap();
+
+        assert_eq!(extra_skill_dirs(Some(&joined)), vec![first, second]);
+        assert!(extra_skill_dirs(None).is_empty());
+        assert!(extra_skill_dirs(Some(std::ffi::OsStr::new(""))).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_extra_skill_dirs_tag_source_and_keep_collisions() {
+        let team = tempfile::TempDir::new().unwrap();
+        let shared = tempfile::TempDir::new().unwrap();
+        write_test_skill(team.path(), "deploy");
+        write_test_skill(team.path(), "review");
+        write_test_skill(shared.path(), "deploy");
+        let missing = team.path().join("does-not-exist");
+
+        let dirs = vec![team.path().to_path_buf(), missing, shared.path().to_path_buf()];
+        let mut skills = list_extra_skills(&dirs).await;
+        skills.sort_by(|a, b| (&a.name, &a.source).cmp(&(&b.name, &b.source)));
+
+        let team_source = team.path().to_string_lossy().to_string();
+        let shared_source = shared.path().to_string_lossy().to_string();
+        let mut expected = vec![
+            ("deploy", team_source.clone()),
+            ("deploy", shared_source),
+            ("review", team_source),
+        ];
+        expected.sort();
+        let found: Vec<_> = skills.iter().map(|s| (s.name.as_str(), s.source.clone())).collect();
+        assert_eq!(found, expected);
+        assert!(skills.iter().all(|s| s.skill_type == "extra" && s.enabled));
+        assert!(skills.iter().all(|s| !s.files.is_empty()));
+    }
+
+    #[test]
+    fn test_merge_allowed_tools_replace() {
+        let current = Some(tools(&["Read", "Grep"]));
+
+        assert_eq!(
+            merge_allowed_tools(current.clone(), Some(tools(&["Bash", "Read", "Bash"])), &[], &[]),
+            Some(tools(&["Bash", "Read"]))
+        );
+        // No patch keeps the existing list, including "unrestricted"
+        assert_eq!(merge_allowed_tools(current.clone(), None, &[], &[]), current);
+        assert_eq!(merge_allowed_tools(None, None, &[], &[]), None);
+    }
+
+    #[test]
+    fn test_merge_allowed_tools_add() {
+        let current = Some(tools(&["Read", "Grep"]));
+
+        assert_eq!(
+            merge_allowed_tools(current, None, &tools(&["Grep", " Write ", "Edit"]), &[]),
+            Some(tools(&["Read", "Grep", "Write", "Edit"]))
+        );
+        assert_eq!(
+            merge_allowed_tools(None, None, &tools(&["Read"]), &[]),
+            Some(tools(&["Read"]))
+        );
+    }
+
+    #[test]
+    fn test_merge_allowed_tools_remove() {
+        let current = Some(tools(&["Read", "Grep", "Bash"]));
+
+        assert_eq!(
+            merge_allowed_tools(current.clone(), None, &[], &tools(&["Grep", "Missing"])),
+            Some(tools(&["Read", "Bash"]))
+        );
+        // Removal wins over an add of the same tool
+        assert_eq!(
+            merge_allowed_tools(current, None, &tools(&["Write"]), &tools(&["Write", "Read"])),
+            Some(tools(&["Grep", "Bash"]))
+        );
+        assert_eq!(merge_allowed_tools(None, None, &[], &tools(&["Read"])), None);
+    }
+
+    fn parse_repaired(content: &str) -> (SkillMetadata, String) {
+        let (yaml, body) = parse_yaml_frontmatter(content).unwrap();
+        (parse_skill_metadata(&yaml.unwrap()).unwrap(), body)
+    }
+
+    #[test]
+    fn test_repair_fills_missing_description() {
+        let original = "---\nallowed-tools: \"Read, Grep\"\n---\n\n# PDF tools\n";
+        assert!(parse_skill_metadata("allowed-tools: \"Read, Grep\"").is_err());
+
+        let repaired = repair_skill_frontmatter("pdf-tools", original).unwrap();
+        let (metadata, body) = parse_repaired(&repaired);
+        assert_eq!(metadata.name, "pdf-tools");
+        assert!(metadata.description.contains("pdf-tools"));
+        assert_eq!(
+            metadata.allowed_tools,
+            Some(vec!["Read".to_string(), "Grep".to_string()])
+        );
+        assert_eq!(body, "# PDF tools");
+    }
+
+    #[test]
+    fn test_repair_quotes_special_characters_and_tool_list() {
+        let original = "---\nname: notes\ndescription: Notes: quick capture #daily\nallowed-tools:\n  - Read\n  - Write\nversion: 2\n---\nBody";
+        assert!(parse_yaml_frontmatter(original)
+            .map(|(yaml, _)| parse_skill_metadata(&yaml.unwrap()))
+            .unwrap()
+            .is_err());
+
+        let repaired = repair_skill_frontmatter("notes", original).unwrap();
+        assert!(repaired.contains("allowed-tools:\n- Read\n- Write\n"));
+        let (metadata, body) = parse_repaired(&repaired);
+        assert_eq!(metadata.description, "Notes: quick capture #daily");
+        assert_eq!(metadata.extra.get("version").and_then(|v| v.as_str()), Some("2"));
+        assert_eq!(body, "Body");
+
+        // Repairing valid output again changes nothing
+        assert_eq!(repair_skill_frontmatter("notes", &repaired).unwrap(), repaired);
+    }
+
+    #[test]
+    fn test_repair_refuses_unreadable_frontmatter() {
+        let unterminated = "---\nname: broken\ndescription: never closed\n\n# Body";
+        assert!(repair_skill_frontmatter("broken", unterminated).is_err());
+
+        let prose = "---\nname: broken\nthis line is not a key value pair\n---\nBody";
+        let err = repair_skill_frontmatter("broken", prose).unwrap_err();
+        assert!(err.contains("第 2 行"));
+
+        let nested = "---\nname: broken\ndescription:\n  nested: map\n---\nBody";
+        assert!(repair_skill_frontmatter("broken", nested).is_err());
+    }
+
+    #[test]
+    fn test_summarize_skills_counts_both_types() {
+        let summary = summarize_skills(
+            Ok(vec![test_skill("a", "First", ""), test_skill("b", "Second", "")]),
+            Ok(vec![test_skill("c", "Third", "")]),
+        );
+
+        assert_eq!((summary.personal_count, summary.project_count, summary.total), (2, 1, 3));
+        assert_eq!(summary.project[0].name, "c");
+        assert!(summary.personal_error.is_none() && summary.project_error.is_none());
+    }
+
+    #[test]
+    fn test_summarize_skills_with_one_side_empty() {
+        let summary = summarize_skills(Ok(Vec::new()), Ok(vec![test_skill("c", "Third", "")]));
+
+        assert_eq!((summary.personal_count, summary.project_count, summary.total), (0, 1, 1));
+        assert!(summary.personal_error.is_none());
+    }
+
+    #[test]
+    fn test_summarize_skills_keeps_other_side_on_error() {
+        let summary = summarize_skills(
+            Ok(vec![test_skill("a", "First", "")]),
+            Err("无法读取技能目录: permission denied".to_string()),
+        );
+
+        assert_eq!(summary.personal.len(), 1);
+        assert!(summary.project.is_empty());
+        assert_eq!((summary.personal_count, summary.project_count, summary.total), (1, 0, 1));
+        assert!(summary.personal_error.is_none());
+        assert!(summary.project_error.unwrap().contains("permission denied"));
+    }
+
+    /// Serve `routes` on a loopback port, returning the base URL
+    async fn serve_skill_files(routes: axum::Router) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, routes).await.unwrap() });
+        format!("http://{}", addr)
+    }
+
+    #[test]
+    fn test_resolve_skill_import_url() {
+        assert!(resolve_skill_import_url("http://example.com/SKILL.md").is_err());
+        assert!(resolve_skill_import_url("file:///tmp/SKILL.md").is_err());
+
+        assert_eq!(
+            resolve_skill_import_url("https://github.com/acme/skills/blob/main/pdf/SKILL.md")
+                .unwrap()
+                .as_str(),
+            "https://raw.githubusercontent.com/acme/skills/main/pdf/SKILL.md"
+        );
+        assert_eq!(
+            resolve_skill_import_url("https://gist.github.com/alice/abc123").unwrap().as_str(),
+            "https://gist.githubusercontent.com/alice/abc123/raw"
+        );
+        assert_eq!(
+            resolve_skill_import_url("https://example.com/skills/pdf.zip").unwrap().as_str(),
+            "https://example.com/skills/pdf.zip"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_import_downloaded_skill_md() {
+        let skill_md = "---\nname: pdf-tools\ndescription: Fill and merge PDF forms\n---\n# PDF\n";
+        let base = serve_skill_files(
+            axum::Router::new().route("/SKILL.md", axum::routing::get(move || async move { skill_md })),
+        )
+        .await;
+
+        let url = reqwest::Url::parse(&format!("{}/SKILL.md", base)).unwrap();
+        let bytes = download_skill_bytes(url, 1024, Duration::from_secs(5)).await.unwrap();
+        let imported = ImportedSkill {
+            content: String::from_utf8(bytes).unwrap(),
+            files: Vec::new(),
+        };
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let skill_dir = install_imported_skill(temp_dir.path(), None, imported).unwrap();
+        assert_eq!(skill_dir, temp_dir.path().join("pdf-tools"));
+        assert_eq!(fs::read_to_string(skill_dir.join("SKILL.md")).unwrap(), skill_md);
+    }
+
+    #[tokio::test]
+    async fn test_import_rejects_oversized_download() {
+        let base = serve_skill_files(
+            axum::Router::new().route("/SKILL.md", axum::routing::get(|| async { "x".repeat(4096) })),
+        )
+        .await;
+
+        let url = reqwest::Url::parse(&format!("{}/SKILL.md", base)).unwrap();
+        let err = download_skill_bytes(url, 1024, Duration::from_secs(5)).await.unwrap_err();
+        assert!(err.contains("1024"));
+    }
+
+    #[tokio::test]
+    async fn test_import_rejects_oversized_chunked_download() {
+        // A streamed body has no Content-Length, so only the running total can catch it
+        let base = serve_skill_files(axum::Router::new().route(
+            "/SKILL.md",
+            axum::routing::get(|| async {
+                let chunks = (0..8)
+                    .map(|_| Ok::<_, std::io::Error>(axum::body::Bytes::from("x".repeat(512))));
+                axum::body::Body::from_stream(futures_util::stream::iter(chunks))
+            }),
+        ))
+        .await;
+
+        let url = reqwest::Url::parse(&format!("{}/SKILL.md", base)).unwrap();
+        let err = download_skill_bytes(url, 1024, Duration::from_secs(5)).await.unwrap_err();
+        assert!(err.contains("1024"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_import_refuses_redirect_to_plain_http() {
+        let base = serve_skill_files(
+            axum::Router::new()
+                .route(
+                    "/moved",
+                    axum::routing::get(|| async { axum::response::Redirect::temporary("/SKILL.md") }),
+                )
+                .route("/SKILL.md", axum::routing::get(|| async { "---\nname: x\n---\n" })),
+        )
+        .await;
+
+        let url = reqwest::Url::parse(&format!("{}/moved", base)).unwrap();
+        let err = download_skill_bytes(url, 1024, Duration::from_secs(5)).await.unwrap_err();
+        assert!(err.contains("https"), "{}", err);
+    }
+
+    #[test]
+    fn test_import_skill_zip() {
+        use std::io::Write;
+        use zip::write::SimpleFileOptions;
+
+        let build_zip = |entries: &[(&str, &str)]| {
+            let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+            for (name, content) in entries {
+                writer.start_file(*name, SimpleFileOptions::default()).unwrap();
+                writer.write_all(content.as_bytes()).unwrap();
+            }
+            writer.finish().unwrap().into_inner()
+        };
+
+        // SKILL.md inside a top-level folder, installed under an explicit name
+        let archive = build_zip(&[
+            ("pdf/SKILL.md", "---\nname: pdf\ndescription: Fill and merge PDF forms\n---\n# PDF\n"),
+            ("pdf/scripts/fill.py", "print('fill')\n"),
+        ]);
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let imported = unpack_skill_zip(&archive, 1024).unwrap();
+        let skill_dir = install_imported_skill(temp_dir.path(), Some("pdf-forms"), imported).unwrap();
+        assert_eq!(
+            fs::read_to_string(skill_dir.join("scripts/fill.py")).unwrap(),
+            "print('fill')\n"
+        );
+        let content = fs::read_to_string(skill_dir.join("SKILL.md")).unwrap();
+        let (yaml, _) = parse_yaml_frontmatter(&content).unwrap();
+        assert_eq!(parse_skill_metadata(&yaml.unwrap()).unwrap().name, "pdf-forms");
+
+        // Entries climbing out of the archive are refused
+        let archive = build_zip(&[
+            ("SKILL.md", "---\nname: evil\ndescription: Escapes the skill dir\n---\n"),
+            ("../../evil.sh", "rm -rf ~\n"),
+        ]);
+        assert!(unpack_skill_zip(&archive, 1024).unwrap_err().contains("evil.sh"));
+
+        // Unpacked size counts against the cap
+        let archive = build_zip(&[("SKILL.md", "x".repeat(2048).as_str())]);
+        assert!(unpack_skill_zip(&archive, 1024).is_err());
+    }
+
+    #[test]
+    fn test_ensure_skills_dir_reports_claude_file() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        fs::write(temp_dir.path().join(".claude"), "not a directory").unwrap();
+
+        let err = ensure_skills_dir(&temp_dir.path().join(".claude").join("skills")).unwrap_err();
+        assert!(err.contains(".claude"));
+        assert!(err.contains("不是目录"));
+
+        // Project skill dirs go through the same check
+        let err = ensure_skills_dir(&project_skills_dir_for(&temp_dir.path().to_string_lossy()))
+            .unwrap_err();
+        assert!(err.contains("不是目录"));
+    }
+
+    #[test]
+    fn test_ensure_skills_dir_creates_missing_components() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let skills_dir = temp_dir.path().join(".claude").join("skills");
+
+        ensure_skills_dir(&skills_dir).unwrap();
+        assert!(skills_dir.is_dir());
+        // Idempotent once the directory exists
+        ensure_skills_dir(&skills_dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_ensure_skills_dir_follows_symlinks() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let home = temp_dir.path().join("home");
+        fs::create_dir_all(&home).unwrap();
+
+        // A dangling .claude link is reported, not created through
+        std::os::unix::fs::symlink(temp_dir.path().join("missing"), home.join(".claude")).unwrap();
+        let err = ensure_skills_dir(&home.join(".claude").join("skills")).unwrap_err();
+        assert!(err.contains("符号链接"));
+        assert!(!temp_dir.path().join("missing").exists());
+
+        // A link to a real directory is followed
+        fs::remove_file(home.join(".claude")).unwrap();
+        let dotfiles = temp_dir.path().join("dotfiles").join("claude");
+        fs::create_dir_all(&dotfiles).unwrap();
+        std::os::unix::fs::symlink(&dotfiles, home.join(".claude")).unwrap();
+        ensure_skills_dir(&home.join(".claude").join("skills")).unwrap();
+        assert!(dotfiles.join("skills").is_dir());
+    }
 
-        let is_dir = path.is_dir();
-        let content = if is_dir {
-            None
-        } else {
-            match fs::read_to_string(&path) {
-                Ok(content) => {
-                    debug!("读取文件内容成功: {} ({} 字符)", name, content.len());
-                    Some(content)
-                },
-                Err(e) => {
-                    warn!("读取文件失败 {}: {}", name, e);
-                    None
+    #[tokio::test]
+    async fn test_scan_skills_dir_loads_concurrently() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        for i in 0..24 {
+            write_test_skill(temp_dir.path(), &format!("skill-{:02}", i));
+        }
+        // A broken skill is skipped without failing the rest
+        fs::create_dir_all(temp_dir.path().join("broken")).unwrap();
+        fs::write(temp_dir.path().join("broken").join("SKILL.md"), "---\nname: [\n").unwrap();
+
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let loader = {
+            let (in_flight, peak) = (in_flight.clone(), peak.clone());
+            move |skill_dir: PathBuf| {
+                let (in_flight, peak) = (in_flight.clone(), peak.clone());
+                async move {
+                    let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(20)).await;
+                    let skill = load_skill_dir(skill_dir, "personal".to_string()).await;
+                    in_flight.fetch_sub(1, Ordering::SeqCst);
+                    skill
                 }
             }
         };
 
-        files.push(SkillFile {
-            name,
-            path: path.to_string_lossy().to_string(),
-            content,
-            is_directory: is_dir,
-        });
+        let mut skills = scan_skills_dir_with(temp_dir.path(), "personal", 4, loader)
+            .await
+            .unwrap();
+        assert_eq!(skills.len(), 24);
+        let peak = peak.load(Ordering::SeqCst);
+        assert!(peak > 1, "skills were loaded one at a time");
+        assert!(peak <= 4, "concurrency limit exceeded: {}", peak);
+
+        sort_skills(&mut skills, None).unwrap();
+        assert_eq!(skills[0].name, "skill-00");
+        assert_eq!(skills[23].name, "skill-23");
     }
 
-    debug!("技能文件列表完成: {} 个文件", files.len());
-    Ok(files)
-}
+    fn placeholder_template() -> SkillTemplate {
+        SkillTemplate {
+            id: "custom".to_string(),
+            title: "Custom".to_string(),
+            description: "Reviews {{language}} code. Use when asked to review {{language}} changes.".to_string(),
+            allowed_tools: Some(vec!["Read".to_string(), "{{ tools }}".to_string()]),
+            markdown_content: "# {{name}}\n\n{{description}}\n\nFollow the {{language}} style guide.\n".to_string(),
+        }
+    }
 
-/// Create a new skill
-#[tauri::command]
-pub async fn skill_create(
-    app_handle: tauri::AppHandle,
-    name: String,
-    skill_type: String,
-    description: String,
-    markdown_content: String,
-    allowed_tools: Option<Vec<String>>,
-) -> Result<Skill, String> {
-    debug!("开始创建技能: name={}, skill_type={}", name, skill_type);
+    #[test]
+    fn test_render_skill_template_substitutes_all_variables() {
+        let variables: HashMap<String, String> = [
+            ("language", "Rust"),
+            ("tools", "Grep, Bash(cargo:*)"),
+            ("description", "Reviews Rust crates for unsafe code and panics in library paths."),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
 
-    // Validate input
-    if name.is_empty() {
-        error!("技能名称为空");
-        return Err("技能名称不能为空".to_string());
-    }
-    if name.len() > 64 {
-        error!("技能名称过长: {} 字符", name.len());
-        return Err("技能名称不能超过 64 个字符".to_string());
-    }
-    if !name.chars().all(|c| c.is_lowercase() || c.is_numeric() || c == '-') {
-        error!("技能名称格式不正确: {}", name);
-        return Err("技能名称只能包含小写字母、数字和连字符".to_string());
+        let (rendered, warnings) = render_skill_template(&placeholder_template(), "rust-review", &variables);
+        assert!(warnings.is_empty(), "{:?}", warnings);
+        assert_eq!(rendered.description, variables["description"]);
+        assert_eq!(
+            rendered.allowed_tools,
+            Some(vec!["Read".to_string(), "Grep".to_string(), "Bash(cargo:*)".to_string()])
+        );
+        assert!(rendered.markdown_content.starts_with("# rust-review\n\nReviews Rust crates"));
+        assert!(!rendered.markdown_content.contains("{{"));
+
+        let mut skill = test_skill("rust-review", &rendered.description, &rendered.markdown_content);
+        skill.allowed_tools = rendered.allowed_tools;
+        let result = validate_skill(&skill);
+        assert!(result.is_valid, "{:?}", result.errors);
     }
-    if description.len() > 1024 {
-        error!("技能描述过长: {} 字符", description.len());
-        return Err("技能描述不能超过 1024 个字符".to_string());
+
+    #[test]
+    fn test_render_skill_template_warns_about_leftover_placeholders() {
+        let variables = HashMap::from([("tools".to_string(), "Grep".to_string())]);
+
+        let (rendered, warnings) = render_skill_template(&placeholder_template(), "review", &variables);
+        // The default description is rendered too, so `{{language}}` shows up once
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("{{language}}"));
+        assert!(rendered.markdown_content.contains("Follow the {{language}} style guide."));
     }
 
-    let skills_dir = if skill_type == "personal" {
-        get_personal_skills_dir(&app_handle)?
-    } else {
-        get_project_skills_dir(&app_handle)?
-    };
+    #[tokio::test]
+    async fn test_recent_skills_ordered_by_last_read() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        for name in ["alpha", "bravo", "charlie", "delta"] {
+            write_test_skill(temp_dir.path(), name);
+        }
 
-    debug!("技能目录路径: {:?}", skills_dir);
+        for name in ["charlie", "alpha", "delta"] {
+            read_skill_in_dir(temp_dir.path(), name, "personal".to_string())
+                .await
+                .unwrap();
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        // Re-reading moves a skill back to the front
+        read_skill_in_dir(temp_dir.path(), "charlie", "personal".to_string())
+            .await
+            .unwrap();
 
-    let skill_dir = skills_dir.join(&name);
-    let skill_file = skill_dir.join("SKILL.md");
+        let usage = read_skill_usage(temp_dir.path());
+        let skills = scan_skills_dir(temp_dir.path(), "personal", "personal").await.unwrap();
+        let with_usage = skills
+            .into_iter()
+            .map(|skill| {
+                let last_used = usage.get(&skill.name).cloned();
+                (skill, last_used)
+            })
+            .collect();
 
-    debug!("技能文件路径: {:?}", skill_file);
+        let recent = rank_recent_skills(with_usage, None);
+        let names: Vec<_> = recent.iter().map(|r| r.skill.name.as_str()).collect();
+        assert_eq!(names, vec!["charlie", "delta", "alpha", "bravo"]);
+        assert!(recent[3].last_used.is_none());
 
-    // Check if skill already exists
-    if skill_file.exists() {
-        error!("技能已存在: {}", name);
-        return Err(format!("技能 '{}' 已存在", name));
+        let used_now = Some(chrono::Utc::now().to_rfc3339());
+        let top = rank_recent_skills(
+            vec![(test_skill("a", "", ""), None), (test_skill("b", "", ""), used_now)],
+            Some(1),
+        );
+        assert_eq!(top.len(), 1);
+        assert_eq!(top[0].skill.name, "b");
     }
 
-    // Create skill directory
-    debug!("创建技能目录: {:?}", skill_dir);
-    fs::create_dir_all(&skill_dir)
-        .map_err(|e| {
-            error!("创建目录失败: {}", e);
-            format!("创建技能目录失败: {}", e)
-        })?;
+    #[test]
+    fn test_concurrent_usage_records_are_all_kept() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let skills_dir = temp_dir.path().to_path_buf();
 
-    // Build YAML frontmatter
-    let yaml_frontmatter = format!(
-        "---\nname: {}\ndescription: \"{}\"{}\n---\n",
-        name,
-        description,
-        if let Some(tools) = &allowed_tools {
-            format!("\nallowed-tools: {}", tools.join(", "))
-        } else {
-            String::new()
+        let readers: Vec<_> = (0..8)
+            .map(|i| {
+                let skills_dir = skills_dir.clone();
+                std::thread::spawn(move || {
+                    for round in 0..5 {
+                        let name = format!("skill-{}-{}", i, round);
+                        record_skill_usage(&skills_dir, &name, Some(chrono::Utc::now())).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for reader in readers {
+            reader.join().unwrap();
         }
-    );
 
-    debug!("YAML frontmatter: {}", yaml_frontmatter);
+        assert_eq!(read_skill_usage(&skills_dir).len(), 40);
+    }
 
-    // Write SKILL.md file
-    let content = format!("{}{}", yaml_frontmatter, markdown_content);
-    debug!("写入文件内容长度: {} 字符", content.len());
+    #[tokio::test]
+    async fn test_delete_skill_forgets_usage() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        write_test_skill(temp_dir.path(), "alpha");
+        write_test_skill(temp_dir.path(), "bravo");
+        for name in ["alpha", "bravo"] {
+            read_skill_in_dir(temp_dir.path(), name, "personal".to_string())
+                .await
+                .unwrap();
+        }
 
-    fs::write(&skill_file, content)
-        .map_err(|e| {
-            error!("写入文件失败: {}", e);
-            format!("写入技能文件失败: {}", e)
-        })?;
+        delete_skill_dir(temp_dir.path(), "alpha", true, None, false).unwrap();
 
-    debug!("技能创建成功: {}", name);
+        let usage = read_skill_usage(temp_dir.path());
+        assert!(!usage.contains_key("alpha"));
+        assert!(usage.contains_key("bravo"));
+    }
 
-    // Return the created skill
-    let skill = Skill {
-        name,
-        skill_type,
-        description,
-        file_path: skill_dir.to_string_lossy().to_string(),
-        yaml_frontmatter: Some(yaml_frontmatter),
-        markdown_content,
-        files: Vec::new(),
-        allowed_tools,
-        last_modified: chrono::Utc::now().to_rfc3339(),
-    };
+    #[tokio::test]
+    async fn test_read_skill_survives_unwritable_usage_sidecar() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        write_test_skill(temp_dir.path(), "alpha");
+        // A directory where the sidecar should be makes every write fail
+        fs::create_dir(temp_dir.path().join(SKILL_USAGE_SIDECAR)).unwrap();
 
-    Ok(skill)
-}
+        let skill = read_skill_in_dir(temp_dir.path(), "alpha", "personal".to_string())
+            .await
+            .unwrap();
+        assert_eq!(skill.name, "alpha");
+        assert!(read_skill_usage(temp_dir.path()).is_empty());
+    }
 
-/// Update an existing skill
-#[tauri::command]
-pub async fn skill_update(
-    app_handle: tauri::AppHandle,
-    name: String,
-    skill_type: String,
-    description: Option<String>,
-    markdown_content: Option<String>,
-    allowed_tools: Option<Vec<String>>,
-) -> Result<Skill, String> {
-    let skills_dir = if skill_type == "personal" {
-        get_personal_skills_dir(&app_handle)?
-    } else {
-        get_project_skills_dir(&app_handle)?
-    };
+    #[test]
+    fn test_diff_unmodified_skill_against_template_is_empty() {
+        let template = find_skill_template("data-pipeline").unwrap();
+        let content = render_template_skill_md(&template, "etl", &HashMap::new()).unwrap();
 
-    let skill_dir = skills_dir.join(&name);
-    let skill_file = skill_dir.join("SKILL.md");
+        let diff = diff_skill_against_template(&template, "etl", &HashMap::new(), &content).unwrap();
+        assert_eq!(diff.template_id, "data-pipeline");
+        assert_eq!(diff.diff, "");
+        assert_eq!((diff.additions, diff.deletions), (0, 0));
+    }
 
-    if !skill_file.exists() {
-        return Err(format!("技能 '{}' 不存在", name));
+    #[test]
+    fn test_diff_modified_skill_against_template_shows_hunks() {
+        let template = find_skill_template("data-pipeline").unwrap();
+        let content = render_template_skill_md(&template, "etl", &HashMap::new())
+            .unwrap()
+            .replace(
+                "4. Log row counts between steps so data loss is easy to spot.\n",
+                "4. Log row counts and checksums between steps.\n5. Never drop rows silently.\n",
+            );
+
+        let diff = diff_skill_against_template(&template, "etl", &HashMap::new(), &content).unwrap();
+        assert_eq!((diff.additions, diff.deletions), (2, 1));
+        assert!(diff.diff.starts_with("--- template/data-pipeline\n+++ etl/SKILL.md\n"));
+        assert_eq!(diff.diff.matches("@@ ").count(), 1);
+        assert!(diff.diff.contains("\n-4. Log row counts between steps so data loss is easy to spot.\n"));
+        assert!(diff.diff.contains("\n+4. Log row counts and checksums between steps.\n"));
+        assert!(diff.diff.contains("\n+5. Never drop rows silently.\n"));
     }
 
-    // Read current skill
-    let mut skill = read_skill_file(app_handle.clone(), skill_file.to_string_lossy().to_string(), skill_type.clone()).await?;
+    #[test]
+    fn test_diff_skill_created_with_variables_is_empty() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let skills_dir = temp_dir.path();
+        let template = placeholder_template();
+        let variables = HashMap::from([
+            ("language".to_string(), "Rust".to_string()),
+            ("tools".to_string(), "Grep".to_string()),
+        ]);
 
-    // Update fields if provided
-    if let Some(desc) = description {
-        skill.description = desc;
+        let content = render_template_skill_md(&template, "rust-review", &variables).unwrap();
+        let origin = SkillTemplateOrigin {
+            template_id: template.id.clone(),
+            variables: variables.clone().into_iter().collect(),
+        };
+        record_skill_template_origin(skills_dir, "rust-review", Some(origin)).unwrap();
+
+        // Rendering with the recorded variables reproduces the file exactly
+        let recorded: HashMap<String, String> = read_skill_template_origins(skills_dir)
+            .remove("rust-review")
+            .unwrap()
+            .variables
+            .into_iter()
+            .collect();
+        let diff = diff_skill_against_template(&template, "rust-review", &recorded, &content).unwrap();
+        assert_eq!((diff.diff.as_str(), diff.additions, diff.deletions), ("", 0, 0));
+
+        // Without them every substituted value would show up as drift
+        let unfilled =
+            diff_skill_against_template(&template, "rust-review", &HashMap::new(), &content).unwrap();
+        assert!(unfilled.additions > 0);
+
+        record_skill_template_origin(skills_dir, "rust-review", None).unwrap();
+        assert!(read_skill_template_origins(skills_dir).is_empty());
     }
-    if let Some(content) = markdown_content {
-        skill.markdown_content = content;
+
+    #[test]
+    fn test_concurrent_template_origins_are_all_kept() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let skills_dir = temp_dir.path().to_path_buf();
+        let origin = |i: usize| SkillTemplateOrigin {
+            template_id: "code-reviewer".to_string(),
+            variables: BTreeMap::from([("index".to_string(), i.to_string())]),
+        };
+        for i in 0..4 {
+            record_skill_template_origin(&skills_dir, &format!("old-{}", i), Some(origin(i)))
+                .unwrap();
+        }
+
+        // Creates and deletes racing on the same sidecar
+        let workers: Vec<_> = (0..8)
+            .map(|i| {
+                let skills_dir = skills_dir.clone();
+                std::thread::spawn(move || {
+                    let name = format!("new-{}", i);
+                    record_skill_template_origin(&skills_dir, &name, Some(origin(i))).unwrap();
+                    if i < 4 {
+                        let old = format!("old-{}", i);
+                        record_skill_template_origin(&skills_dir, &old, None).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for worker in workers {
+            worker.join().unwrap();
+        }
+
+        let origins = read_skill_template_origins(&skills_dir);
+        let names: Vec<_> = origins.keys().cloned().collect();
+        let expected: Vec<_> = (0..8).map(|i| format!("new-{}", i)).collect();
+        assert_eq!(names, expected);
     }
-    if let Some(tools) = allowed_tools {
-        skill.allowed_tools = Some(tools);
+
+    #[test]
+    fn test_allowed_tools_styles_round_trip_to_the_same_list() {
+        let expected = Some(vec!["Bash".to_string(), "Read".to_string(), "Grep".to_string()]);
+        let styles = [
+            "name: t\ndescription: d\nallowed-tools: Bash, Read, Grep\n",
+            "name: t\ndescription: d\nallowed-tools:\n  - Bash\n  - Read\n  - Grep\n",
+            "name: t\ndescription: d\nallowed-tools: [Bash, Read, Grep]\n",
+            "name: t\ndescription: d\nallowed-tools: |\n  Bash\n  Read\n  Grep\n",
+            "name: t\ndescription: d\nallowed_tools: >-\n  Bash,\n  Read, Grep\n",
+        ];
+
+        let mut rewritten = Vec::new();
+        for yaml in styles {
+            let metadata = parse_skill_metadata(yaml).unwrap();
+            assert_eq!(metadata.allowed_tools, expected, "{}", yaml);
+
+            let frontmatter = build_yaml_frontmatter(&metadata).unwrap();
+            assert!(frontmatter.contains("allowed-tools:\n- Bash\n- Read\n- Grep\n"), "{}", frontmatter);
+            let (yaml, _) = parse_yaml_frontmatter(&frontmatter).unwrap();
+            assert_eq!(parse_skill_metadata(&yaml.unwrap()).unwrap().allowed_tools, expected);
+            rewritten.push(frontmatter);
+        }
+        rewritten.dedup();
+        assert_eq!(rewritten.len(), 1);
     }
 
-    // Rebuild YAML frontmatter
-    let yaml_frontmatter = format!(
-        "---\nname: {}\ndescription: \"{}\"{}\n---\n",
-        skill.name,
-        skill.description,
-        if let Some(tools) = &skill.allowed_tools {
-            format!("\nallowed-tools: {}", tools.join(", "))
-        } else {
-            String::new()
+    #[tokio::test]
+    async fn test_count_skills_matches_populated_dir() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        for name in ["alpha", "beta", "gamma"] {
+            write_test_skill(root, name);
         }
-    );
+        fs::create_dir_all(root.join("no-skill-md")).unwrap();
+        fs::write(root.join("README.md"), "not a skill").unwrap();
 
-    // Write updated content
-    let content = format!("{}{}", yaml_frontmatter, skill.markdown_content);
-    fs::write(&skill_file, content).map_err(|e| e.to_string())?;
+        let loaded = scan_skills_dir(root, "personal", "personal").await.unwrap();
+        assert_eq!(count_skills_in_dir(root), 3);
+        assert_eq!(count_skills_in_dir(root), loaded.len());
 
-    skill.yaml_frontmatter = Some(yaml_frontmatter);
-    skill.last_modified = chrono::Utc::now().to_rfc3339();
+        assert_eq!(count_skills_in_dir(&root.join("missing")), 0);
+    }
 
-    Ok(skill)
-}
+    #[test]
+    fn test_skill_preview_renders_markdown() {
+        let content = "---\nname: preview\ndescription: Renders things\nallowed-tools: Read\n---\n\n# Preview\n\n- one\n- two\n\n```rust\nfn main() {}\n```\n";
+        let preview = render_skill_preview(content).unwrap();
 
-/// Delete a skill
-#[tauri::command]
-pub async fn skill_delete(
-    app_handle: tauri::AppHandle,
-    name: String,
-    skill_type: String,
-) -> Result<(), String> {
-    let skills_dir = if skill_type == "personal" {
-        get_personal_skills_dir(&app_handle)?
-    } else {
-        get_project_skills_dir(&app_handle)?
-    };
+        let frontmatter = preview.frontmatter.unwrap();
+        assert_eq!(frontmatter.name, "preview");
+        assert_eq!(frontmatter.allowed_tools, Some(vec!["Read".to_string()]));
+        assert!(preview.html.contains("<h1>Preview</h1>"), "{}", preview.html);
+        assert!(preview.html.contains("<ul>\n<li>one</li>\n<li>two</li>\n</ul>"), "{}", preview.html);
+        assert!(
+            preview.html.contains("<pre><code class=\"language-rust\">fn main() {}\n</code></pre>"),
+            "{}",
+            preview.html
+        );
+    }
 
-    let skill_dir = skills_dir.join(&name);
+    #[test]
+    fn test_skill_preview_strips_scripts() {
+        let content = "# Title\n\n<script>alert('x')</script>\n\n<img src=\"a.png\" onerror=\"alert('y')\">\n\n[link](javascript:alert('z'))\n\n```\" onmouseover=\"alert('w')\nplain\n```\n";
+        let preview = render_skill_preview(content).unwrap();
 
-    if !skill_dir.exists() {
-        return Err(format!("技能 '{}' 不存在", name));
+        assert!(preview.frontmatter.is_none());
+        assert!(preview.html.contains("<h1>Title</h1>"));
+        assert!(preview.html.contains("<img src=\"a.png\">"), "{}", preview.html);
+        for unsafe_text in ["<script", "alert", "onerror", "javascript:", "onmouseover"] {
+            assert!(!preview.html.contains(unsafe_text), "{}: {}", unsafe_text, preview.html);
+        }
+        assert!(!is_language_class("language-"));
+        assert!(is_language_class("language-c++"));
     }
 
-    // Remove the entire skill directory
-    fs::remove_dir_all(&skill_dir).map_err(|e| e.to_string())?;
+    #[test]
+    fn test_skill_delete_requires_confirmation() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let skill_dir = write_test_skill(root, "doomed");
 
-    Ok(())
-}
+        let preview = delete_skill_dir(root, "doomed", false, None, false).unwrap();
+        assert!(!preview.deleted);
+        assert_eq!(preview.file_count, 3);
+        assert!(skill_dir.join("SKILL.md").exists());
 
-/// Validate a skill
-#[tauri::command]
-pub async fn skill_validate(
-    _app_handle: tauri::AppHandle,
-    name: String,
-    skill_type: String,
-    description: String,
-    markdown_content: String,
-) -> Result<ValidationResult, String> {
-    let temp_skill = Skill {
-        name,
-        skill_type,
-        description,
-        file_path: String::new(),
-        yaml_frontmatter: None,
-        markdown_content,
-        files: Vec::new(),
-        allowed_tools: None,
-        last_modified: chrono::Utc::now().to_rfc3339(),
-    };
+        let wrong = delete_skill_dir(root, "doomed", false, Some("nope"), false).unwrap();
+        assert!(!wrong.deleted);
+        assert_eq!(preview.confirmation_token, skill_delete_token("doomed"));
+        assert_ne!(preview.confirmation_token, skill_delete_token("other"));
 
-    let validation_result = validate_skill(&temp_skill);
-    Ok(validation_result)
-}
+        let deleted =
+            delete_skill_dir(root, "doomed", false, Some(&preview.confirmation_token), false).unwrap();
+        assert!(deleted.deleted);
+        assert!(deleted.trash_path.is_none());
+        assert!(!skill_dir.exists());
 
-/// Create a file in a skill directory
-#[tauri::command]
-pub async fn skill_create_file(
-    app_handle: tauri::AppHandle,
-    skill_name: String,
-    skill_type: String,
-    file_name: String,
-    content: String,
-) -> Result<(), String> {
-    let skills_dir = if skill_type == "personal" {
-        get_personal_skills_dir(&app_handle)?
-    } else {
-        get_project_skills_dir(&app_handle)?
-    };
+        write_test_skill(root, "doomed");
+        assert!(delete_skill_dir(root, "doomed", true, None, false).unwrap().deleted);
+        assert!(!skill_dir.exists());
+        assert!(delete_skill_dir(root, "doomed", true, None, false).is_err());
+    }
 
-    let skill_dir = skills_dir.join(&skill_name);
-    let file_path = skill_dir.join(&file_name);
+    #[test]
+    fn test_skill_delete_rejects_names_outside_a_single_skill() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let skills_dir = temp_dir.path().join("skills");
+        write_test_skill(&skills_dir, "keep");
+        fs::create_dir_all(skills_dir.join(".trash").join("old-20240101-000000000")).unwrap();
+        fs::write(temp_dir.path().join("settings.json"), "{}").unwrap();
 
-    // Ensure the skill directory exists
-    if !skill_dir.exists() {
-        return Err(format!("技能 '{}' 不存在", skill_name));
+        for name in ["..", ".", ".trash"] {
+            assert!(
+                delete_skill_dir(&skills_dir, name, true, None, false).is_err(),
+                "{} should be rejected",
+                name
+            );
+            assert!(delete_skill_dir(&skills_dir, name, false, None, true).is_err());
+        }
+        assert!(temp_dir.path().join("settings.json").exists());
+        assert!(skills_dir.join("keep").join("SKILL.md").exists());
+        assert!(skills_dir.join(".trash").join("old-20240101-000000000").exists());
     }
 
-    // Write the file
-    fs::write(&file_path, content).map_err(|e| e.to_string())?;
+    #[tokio::test]
+    async fn test_skill_soft_delete_moves_to_trash() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        write_test_skill(root, "keep");
+        write_test_skill(root, "trashed");
+        set_skill_enabled_in_manifest(root, "trashed", false).unwrap();
 
-    Ok(())
-}
+        let result = delete_skill_dir(root, "trashed", true, None, true).unwrap();
+        assert!(result.deleted);
+        assert!(!root.join("trashed").exists());
+        let trash_path = PathBuf::from(result.trash_path.unwrap());
+        assert_eq!(trash_path.parent().unwrap(), root.join(SKILL_TRASH_DIR));
+        assert!(trash_path.file_name().unwrap().to_string_lossy().starts_with("trashed-"));
+        assert!(trash_path.join("scripts").join("run.sh").exists());
+        assert!(read_disabled_skills(root).unwrap().is_empty());
 
-/// Read a file from a skill directory
-#[tauri::command]
-pub async fn skill_read_file(
-    app_handle: tauri::AppHandle,
-    skill_name: String,
-    skill_type: String,
-    file_name: String,
-) -> Result<String, String> {
-    let skills_dir = if skill_type == "personal" {
-        get_personal_skills_dir(&app_handle)?
-    } else {
-        get_project_skills_dir(&app_handle)?
-    };
+        // Trashed skills don't show up as skills
+        let names: Vec<String> = scan_skills_dir(root, "personal", "personal")
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|skill| skill.name)
+            .collect();
+        assert_eq!(names, vec!["keep".to_string()]);
+        assert_eq!(count_skills_in_dir(root), 1);
+    }
 
-    let skill_dir = skills_dir.join(&skill_name);
-    let file_path = skill_dir.join(&file_name);
+    #[tokio::test]
+    async fn test_skill_delete_list_trash_restore() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        write_test_skill(root, "my-skill");
 
-    if !file_path.exists() {
-        return Err(format!("文件 '{}' 不存在", file_name));
+        let result = delete_skill_dir(root, "my-skill", true, None, true).unwrap();
+        let trashed = list_trash_in_dir(root, "project");
+        assert_eq!(trashed.len(), 1);
+        assert_eq!(trashed[0].name, "my-skill");
+        assert_eq!(trashed[0].skill_type, "project");
+        assert_eq!(Some(trashed[0].path.clone()), result.trash_path);
+        assert!(chrono::DateTime::parse_from_rfc3339(&trashed[0].deleted_at).is_ok());
+
+        let restored = restore_skill_from_trash(root, "my-skill").unwrap();
+        assert_eq!(restored, root.join("my-skill"));
+        assert!(restored.join("scripts").join("lib").join("util.sh").exists());
+        assert!(list_trash_in_dir(root, "project").is_empty());
+        let skill_file = restored.join("SKILL.md").to_string_lossy().to_string();
+        let skill = read_skill_file(skill_file, "project".to_string()).await.unwrap();
+        assert_eq!(skill.name, "my-skill");
+
+        assert!(restore_skill_from_trash(root, "my-skill").is_err());
+        assert_eq!(
+            parse_trash_entry_name("a-b-20250102-030405678").map(|(name, _)| name),
+            Some("a-b".to_string())
+        );
+        assert!(parse_trash_entry_name("plain-folder").is_none());
     }
 
-    if file_path.is_dir() {
-        return Err("指定路径是目录，不是文件".to_string());
+    #[test]
+    fn test_skill_restore_rejects_name_collision_and_purge_respects_cutoff() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        write_test_skill(root, "taken");
+        delete_skill_dir(root, "taken", true, None, true).unwrap();
+        write_test_skill(root, "taken");
+
+        let err = restore_skill_from_trash(root, "taken").unwrap_err();
+        assert!(err.contains("已存在"), "{}", err);
+        assert_eq!(list_trash_in_dir(root, "personal").len(), 1);
+
+        let an_hour_ago = chrono::Local::now() - chrono::Duration::hours(1);
+        assert_eq!(purge_trash_in_dir(root, an_hour_ago).unwrap(), 0);
+        let soon = chrono::Local::now() + chrono::Duration::seconds(1);
+        assert_eq!(purge_trash_in_dir(root, soon).unwrap(), 1);
+        assert!(list_trash_in_dir(root, "personal").is_empty());
+        assert!(root.join("taken").join("SKILL.md").exists());
     }
 
-    fs::read_to_string(&file_path).map_err(|e| e.to_string())
-}
+    #[test]
+    fn test_project_skill_file_lands_under_given_project() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let project = temp_dir.path().join("web-project");
+        let skills_dir = project_skills_dir_for(&project.to_string_lossy());
+        write_test_skill(&skills_dir, "deploy");
 
-/// Delete a file from a skill directory
-#[tauri::command]
-pub async fn skill_delete_file(
-    app_handle: tauri::AppHandle,
-    skill_name: String,
-    skill_type: String,
-    file_name: String,
-) -> Result<(), String> {
-    let skills_dir = if skill_type == "personal" {
-        get_personal_skills_dir(&app_handle)?
-    } else {
-        get_project_skills_dir(&app_handle)?
-    };
+        let written = create_skill_file_in(&skills_dir, "deploy", "scripts/notes.md", "# Notes").unwrap();
+        let expected = project
+            .join(".claude")
+            .join("skills")
+            .join("deploy")
+            .join("scripts")
+            .join("notes.md");
+        assert_eq!(written.canonicalize().unwrap(), expected.canonicalize().unwrap());
+        assert_eq!(fs::read_to_string(&expected).unwrap(), "# Notes");
 
-    let skill_dir = skills_dir.join(&skill_name);
-    let file_path = skill_dir.join(&file_name);
+        // A skill that only exists in another project is not found here
+        let other = project_skills_dir_for(&temp_dir.path().join("other").to_string_lossy());
+        assert!(create_skill_file_in(&other, "deploy", "notes.md", "x").is_err());
+    }
 
-    if !file_path.exists() {
-        return Err(format!("文件 '{}' 不存在", file_name));
+    #[test]
+    fn test_skill_bundle_round_trip() {
+        let source = tempfile::TempDir::new().unwrap();
+        write_test_skill(source.path(), "alpha");
+        let beta = write_test_skill(source.path(), "beta");
+        let binary = vec![0u8, 159, 146, 150, 255, 0, 10];
+        fs::create_dir_all(beta.join("assets")).unwrap();
+        fs::write(beta.join("assets").join("logo.png"), &binary).unwrap();
+        fs::create_dir_all(source.path().join(SKILL_TRASH_DIR).join("old-20240101-000000000")).unwrap();
+
+        let bundle = export_skills_in_dir(source.path(), "personal").unwrap();
+        let names: Vec<&str> = bundle.skills.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["alpha", "beta"]);
+        assert_eq!(bundle.skills[0].metadata.as_ref().unwrap().description, "Original skill");
+        assert_eq!(bundle.skills[1].files.len(), 3);
+
+        let json = serde_json::to_string(&bundle).unwrap();
+        let target = tempfile::TempDir::new().unwrap();
+        let result = import_skill_bundle(target.path(), serde_json::from_str(&json).unwrap(), false).unwrap();
+        assert_eq!(result.imported, vec!["alpha", "beta"]);
+        assert!(result.skipped.is_empty() && result.failed.is_empty());
+
+        for name in ["alpha", "beta"] {
+            assert_eq!(
+                fs::read_to_string(target.path().join(name).join("SKILL.md")).unwrap(),
+                fs::read_to_string(source.path().join(name).join("SKILL.md")).unwrap()
+            );
+            assert_eq!(
+                fs::read_to_string(target.path().join(name).join("scripts/lib/util.sh")).unwrap(),
+                "echo util"
+            );
+        }
+        assert_eq!(fs::read(target.path().join("beta/assets/logo.png")).unwrap(), binary);
     }
 
-    if file_path.is_dir() {
-        fs::remove_dir_all(&file_path).map_err(|e| e.to_string())?;
-    } else {
-        fs::remove_file(&file_path).map_err(|e| e.to_string())?;
+    #[test]
+    fn test_skill_bundle_import_guards_paths_and_existing_skills() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let skills_dir = temp_dir.path().join("skills");
+        fs::create_dir_all(&skills_dir).unwrap();
+        write_test_skill(&skills_dir, "kept");
+
+        let entry = |name: &str, path: &str| SkillBundleEntry {
+            name: name.to_string(),
+            metadata: None,
+            content: format!("---\nname: {}\ndescription: Imported\n---\n\n# Body", name),
+            files: vec![SkillBundleFile {
+                path: path.to_string(),
+                data: STANDARD.encode("payload"),
+            }],
+        };
+        let bundle = SkillBundle {
+            version: SKILL_BUNDLE_VERSION,
+            exported_at: String::new(),
+            skill_type: "personal".to_string(),
+            skills: vec![
+                entry("kept", "notes.md"),
+                entry("escape", "../../escaped.txt"),
+                entry("fine", "docs/notes.md"),
+            ],
+        };
+
+        let result = import_skill_bundle(&skills_dir, bundle, false).unwrap();
+        assert_eq!(result.imported, vec!["fine"]);
+        assert_eq!(result.skipped, vec!["kept"]);
+        assert_eq!(result.failed.len(), 1);
+        assert!(result.failed[0].starts_with("escape:"));
+        assert!(!temp_dir.path().join("escaped.txt").exists());
+        assert!(!skills_dir.join("escape").exists());
+        assert!(fs::read_to_string(skills_dir.join("kept/SKILL.md")).unwrap().contains("Original skill"));
+
+        let overwrite = SkillBundle {
+            version: SKILL_BUNDLE_VERSION,
+            exported_at: String::new(),
+            skill_type: "personal".to_string(),
+            skills: vec![entry("kept", "notes.md")],
+        };
+        let result = import_skill_bundle(&skills_dir, overwrite, true).unwrap();
+        assert_eq!(result.imported, vec!["kept"]);
+        assert!(fs::read_to_string(skills_dir.join("kept/SKILL.md")).unwrap().contains("Imported"));
+        assert!(!skills_dir.join("kept/scripts").exists());
+        assert!(!skills_dir.join(".kept.import-backup").exists());
     }
 
-    Ok(())
+    #[test]
+    fn test_skill_name_availability() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        write_test_skill(temp_dir.path(), "code-review");
+
+        assert_eq!(
+            check_skill_name_availability(temp_dir.path(), "deploy-2"),
+            SkillNameAvailability {
+                valid_format: true,
+                available: true,
+                reason: None,
+            }
+        );
+
+        let taken = check_skill_name_availability(temp_dir.path(), "code-review");
+        assert!(taken.valid_format);
+        assert!(!taken.available);
+        assert!(taken.reason.unwrap().contains("已存在"));
+
+        for invalid in ["", "Code_Review", "has space", &"a".repeat(65)] {
+            let result = check_skill_name_availability(temp_dir.path(), invalid);
+            assert!(!result.valid_format && !result.available, "{:?}", invalid);
+            assert!(result.reason.is_some());
+        }
+    }
 }
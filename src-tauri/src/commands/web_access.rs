@@ -0,0 +1,212 @@
+use std::net::Ipv4Addr;
+
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use qrcode::render::{svg, unicode};
+use qrcode::QrCode;
+use serde::Serialize;
+
+/// Port used by `codestudio-web` when `--port` is not given
+pub const DEFAULT_WEB_PORT: u16 = 8080;
+
+/// Interface name prefixes that belong to VPNs, containers and VMs rather than the LAN
+const VIRTUAL_INTERFACE_PREFIXES: &[&str] = &[
+    "docker", "br-", "veth", "virbr", "vbox", "vmnet", "utun", "tun", "tap", "wg", "zt", "tailscale",
+];
+
+/// How the web server can be reached from another device
+#[derive(Debug, Clone, Serialize)]
+pub struct WebAccessInfo {
+    pub url: String,
+    pub ip: String,
+    pub port: u16,
+    /// `data:image/svg+xml;base64,...` QR code encoding `url`
+    pub qr_data_uri: String,
+}
+
+/// List the machine's non-loopback IPv4 addresses with their interface names
+pub fn lan_ipv4_addresses() -> Vec<(String, Ipv4Addr)> {
+    let interfaces = match if_addrs::get_if_addrs() {
+        Ok(interfaces) => interfaces,
+        Err(e) => {
+            log::warn!("Failed to enumerate network interfaces: {}", e);
+            return Vec::new();
+        }
+    };
+
+    interfaces
+        .into_iter()
+        .filter(|iface| !iface.is_loopback())
+        .filter_map(|iface| match iface.ip() {
+            std::net::IpAddr::V4(ip) => Some((iface.name, ip)),
+            std::net::IpAddr::V6(_) => None,
+        })
+        .collect()
+}
+
+/// Rank an address by how likely a phone on the same network can reach it
+fn lan_address_score(name: &str, ip: &Ipv4Addr) -> u8 {
+    if ip.is_loopback() || ip.is_unspecified() || ip.is_link_local() {
+        return 0;
+    }
+
+    let name = name.to_lowercase();
+    let is_virtual = VIRTUAL_INTERFACE_PREFIXES
+        .iter()
+        .any(|prefix| name.starts_with(prefix));
+    let octets = ip.octets();
+
+    let score = match octets {
+        [192, 168, ..] => 4,
+        [10, ..] => 3,
+        [172, b, ..] if (16..=31).contains(&b) => 2,
+        _ => 1,
+    };
+
+    if is_virtual {
+        score
+    } else {
+        score + 4
+    }
+}
+
+/// Pick the most likely LAN address, preferring private ranges on physical interfaces
+pub fn pick_lan_address(addresses: &[(String, Ipv4Addr)]) -> Option<Ipv4Addr> {
+    addresses
+        .iter()
+        .map(|(name, ip)| (lan_address_score(name, ip), *ip))
+        .filter(|(score, _)| *score > 0)
+        // max_by_key keeps the last maximum; reverse so interface order breaks ties
+        .rev()
+        .max_by_key(|(score, _)| *score)
+        .map(|(_, ip)| ip)
+}
+
+/// Build the URL a phone should open to reach the web server
+pub fn web_access_url(ip: Ipv4Addr, port: u16) -> String {
+    format!("http://{}:{}/", ip, port)
+}
+
+/// Render a QR code as Unicode half blocks for printing to a terminal
+pub fn render_qr_terminal(data: &str) -> Result<String, String> {
+    let code = QrCode::new(data.as_bytes()).map_err(|e| format!("Failed to build QR code: {}", e))?;
+    // Inverted colors so the code scans on dark terminal backgrounds
+    Ok(code
+        .render::<unicode::Dense1x2>()
+        .dark_color(unicode::Dense1x2::Light)
+        .light_color(unicode::Dense1x2::Dark)
+        .quiet_zone(true)
+        .build())
+}
+
+/// Render a QR code as an SVG data URI for the desktop UI
+pub fn render_qr_svg_data_uri(data: &str) -> Result<String, String> {
+    let code = QrCode::new(data.as_bytes()).map_err(|e| format!("Failed to build QR code: {}", e))?;
+    let svg = code
+        .render::<svg::Color>()
+        .min_dimensions(256, 256)
+        .quiet_zone(true)
+        .build();
+    Ok(format!("data:image/svg+xml;base64,{}", STANDARD.encode(svg)))
+}
+
+/// Get the LAN URL of the web server and a QR code for opening it on a phone
+#[tauri::command]
+pub async fn get_web_access_qr(port: Option<u16>) -> Result<WebAccessInfo, String> {
+    let port = port.unwrap_or(DEFAULT_WEB_PORT);
+    let ip = pick_lan_address(&lan_ipv4_addresses())
+        .ok_or_else(|| "No LAN IPv4 address found on this machine".to_string())?;
+    let url = web_access_url(ip, port);
+    let qr_data_uri = render_qr_svg_data_uri(&url)?;
+
+    Ok(WebAccessInfo {
+        url,
+        ip: ip.to_string(),
+        port,
+        qr_data_uri,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn addr(name: &str, ip: &str) -> (String, Ipv4Addr) {
+        (name.to_string(), ip.parse().unwrap())
+    }
+
+    /// Decode a QR code by rasterizing its modules and scanning them back
+    fn decode_qr(data: &str) -> String {
+        const SCALE: usize = 4;
+        const QUIET: usize = 4;
+
+        let code = QrCode::new(data.as_bytes()).unwrap();
+        let width = code.width();
+        let colors = code.to_colors();
+        let size = (width + QUIET * 2) * SCALE;
+
+        let mut image = rqrr::PreparedImage::prepare_from_greyscale(size, size, |x, y| {
+            let (mx, my) = (x / SCALE, y / SCALE);
+            if mx < QUIET || my < QUIET || mx >= width + QUIET || my >= width + QUIET {
+                return 255;
+            }
+            match colors[(my - QUIET) * width + (mx - QUIET)] {
+                qrcode::Color::Dark => 0,
+                qrcode::Color::Light => 255,
+            }
+        });
+        let grids = image.detect_grids();
+        assert_eq!(grids.len(), 1);
+        grids[0].decode().unwrap().1
+    }
+
+    #[test]
+    fn test_pick_lan_address_prefers_physical_private_range() {
+        let addresses = vec![
+            addr("docker0", "172.17.0.1"),
+            addr("tailscale0", "100.101.1.2"),
+            addr("en0", "192.168.1.23"),
+            addr("en1", "10.0.0.5"),
+        ];
+        assert_eq!(
+            pick_lan_address(&addresses),
+            Some("192.168.1.23".parse().unwrap())
+        );
+
+        // Link-local and loopback are never picked
+        let addresses = vec![addr("lo", "127.0.0.1"), addr("en0", "169.254.3.4")];
+        assert_eq!(pick_lan_address(&addresses), None);
+
+        // Virtual interfaces are still better than nothing
+        let addresses = vec![addr("docker0", "172.17.0.1")];
+        assert_eq!(
+            pick_lan_address(&addresses),
+            Some("172.17.0.1".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn test_web_access_url_is_well_formed() {
+        let url = web_access_url("192.168.1.23".parse().unwrap(), 8080);
+        assert_eq!(url, "http://192.168.1.23:8080/");
+
+        let parsed = reqwest::Url::parse(&url).unwrap();
+        assert_eq!(parsed.scheme(), "http");
+        assert_eq!(parsed.host_str(), Some("192.168.1.23"));
+        assert_eq!(parsed.port(), Some(8080));
+    }
+
+    #[test]
+    fn test_qr_encodes_exact_url() {
+        let url = web_access_url("10.0.0.5".parse().unwrap(), 3000);
+        assert_eq!(decode_qr(&url), url);
+
+        let data_uri = render_qr_svg_data_uri(&url).unwrap();
+        let svg = STANDARD
+            .decode(data_uri.strip_prefix("data:image/svg+xml;base64,").unwrap())
+            .unwrap();
+        assert!(String::from_utf8(svg).unwrap().contains("<svg"));
+
+        let terminal = render_qr_terminal(&url).unwrap();
+        assert!(terminal.lines().count() > 10);
+    }
+}
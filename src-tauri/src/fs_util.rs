@@ -0,0 +1,122 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Distinguishes temp files written concurrently by the same process
+static TEMP_FILE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Replace `path` with `contents` so readers only ever see the old file or the new one
+///
+/// The data is written and synced to a temp file in the same directory, which is then renamed
+/// over `path`. An existing file keeps its permissions. If anything fails, the temp file is
+/// removed and `path` is left as it was.
+pub fn write_atomic(path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    write_atomic_with(path.as_ref(), contents.as_ref(), |temp, path| {
+        fs::rename(temp, path)
+    })
+}
+
+/// `write_atomic` with the final step supplied by the caller, which must move `temp` to `path`
+///
+/// Lets a caller do extra work (e.g. back up the old file) only once the new data is on disk.
+pub fn write_atomic_with<F>(path: &Path, contents: &[u8], commit: F) -> io::Result<()>
+where
+    F: FnOnce(&Path, &Path) -> io::Result<()>,
+{
+    let temp_path = temp_path_for(path)?;
+    let result = write_temp_file(&temp_path, path, contents).and_then(|_| commit(&temp_path, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result
+}
+
+/// A hidden sibling of `path`, e.g. `.SKILL.md.1234-0.tmp`, so the rename stays on one filesystem
+fn temp_path_for(path: &Path) -> io::Result<PathBuf> {
+    let file_name = path.file_name().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} has no file name", path.display()),
+        )
+    })?;
+    let unique = TEMP_FILE_COUNTER.fetch_add(1, Ordering::Relaxed);
+    Ok(path.with_file_name(format!(
+        ".{}.{}-{}.tmp",
+        file_name.to_string_lossy(),
+        std::process::id(),
+        unique
+    )))
+}
+
+/// Write and sync the temp file, copying the permissions of the file it will replace
+fn write_temp_file(temp_path: &Path, path: &Path, contents: &[u8]) -> io::Result<()> {
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(temp_path)?;
+    file.write_all(contents)?;
+    if let Ok(metadata) = fs::metadata(path) {
+        file.set_permissions(metadata.permissions())?;
+    }
+    file.sync_all()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dir_entries(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_write_atomic_creates_and_replaces() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("SKILL.md");
+
+        write_atomic(&path, "first").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "first");
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o640)).unwrap();
+        }
+
+        write_atomic(&path, "second").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "second");
+        assert_eq!(dir_entries(temp_dir.path()), vec!["SKILL.md"]);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o640);
+        }
+    }
+
+    #[test]
+    fn test_interrupted_write_leaves_original_intact() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join(".mcp.json");
+        fs::write(&path, r#"{"mcpServers":{}}"#).unwrap();
+
+        // Simulate the process dying after the temp file is written but before the rename
+        let err = write_atomic_with(&path, b"{\"mcpServers\":{\"half", |temp, path| {
+            assert_eq!(fs::read_to_string(temp).unwrap(), "{\"mcpServers\":{\"half");
+            assert_eq!(fs::read_to_string(path).unwrap(), r#"{"mcpServers":{}}"#);
+            Err(io::Error::new(io::ErrorKind::Interrupted, "killed"))
+        })
+        .unwrap_err();
+
+        assert_eq!(err.kind(), io::ErrorKind::Interrupted);
+        assert_eq!(fs::read_to_string(&path).unwrap(), r#"{"mcpServers":{}}"#);
+        assert_eq!(dir_entries(temp_dir.path()), vec![".mcp.json"]);
+    }
+}
@@ -1,27 +1,262 @@
 use std::fs::OpenOptions;
-use std::io::{self, Write};
-use std::path::PathBuf;
-use std::sync::Mutex;
+use std::borrow::Cow;
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, SyncSender, TrySendError};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
 
-// Custom writer that writes to both file and stderr
+/// Maximum number of log lines queued for the writer thread before new lines are dropped
+const LOG_CHANNEL_CAPACITY: usize = 8192;
+
+/// How long `shutdown_logger` waits for queued lines to reach the disk
+const LOG_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Number of days log files are kept when CODESTUDIO_LOG_RETENTION_DAYS is not set
+const DEFAULT_LOG_RETENTION_DAYS: i64 = 30;
+
+/// Environment variable overriding the log retention period (in days)
+const LOG_RETENTION_ENV: &str = "CODESTUDIO_LOG_RETENTION_DAYS";
+
+/// Environment variable enabling gzip compression of logs older than N days
+const LOG_COMPRESS_AFTER_ENV: &str = "CODESTUDIO_LOG_COMPRESS_AFTER_DAYS";
+
+/// Environment variable selecting the log line format ("pretty" or "json")
+const LOG_FORMAT_ENV: &str = "CODESTUDIO_LOG_FORMAT";
+
+/// Environment variable disabling emoji level prefixes ("1" for all outputs, or "file"/"stderr")
+const LOG_NO_EMOJI_ENV: &str = "CODESTUDIO_LOG_NO_EMOJI";
+
+/// Level prefixes as (emoji, plain ASCII) pairs: Claude-related first, then general
+const LEVEL_PREFIXES: [[(&str, &str); 5]; 2] = [
+    [
+        ("🔴 [CLAUDE ERROR]", "[CLAUDE ERROR]"),
+        ("⚠️  [CLAUDE WARN]", "[CLAUDE WARN]"),
+        ("ℹ️  [CLAUDE INFO]", "[CLAUDE INFO]"),
+        ("🔍 [CLAUDE DEBUG]", "[CLAUDE DEBUG]"),
+        ("🔎 [CLAUDE TRACE]", "[CLAUDE TRACE]"),
+    ],
+    [
+        ("❌ [ERROR]", "[ERROR]"),
+        ("⚠️  [WARN]", "[WARN]"),
+        ("ℹ️  [INFO]", "[INFO]"),
+        ("🔍 [DEBUG]", "[DEBUG]"),
+        ("🔎 [TRACE]", "[TRACE]"),
+    ],
+];
+
+/// Which outputs keep emoji level prefixes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct EmojiTargets {
+    file: bool,
+    stderr: bool,
+}
+
+impl EmojiTargets {
+    fn from_env() -> Self {
+        Self::parse(
+            std::env::var(LOG_NO_EMOJI_ENV).ok().as_deref(),
+            io::stderr().is_terminal(),
+        )
+    }
+
+    /// Non-TTY stderr (CI, pipes) is kept plain unless emoji are explicitly allowed there
+    fn parse(no_emoji: Option<&str>, stderr_is_tty: bool) -> Self {
+        match no_emoji.map(|v| v.trim().to_ascii_lowercase()).as_deref() {
+            Some("1" | "true" | "yes" | "all") => Self { file: false, stderr: false },
+            Some("file") => Self { file: false, stderr: stderr_is_tty },
+            Some("stderr") => Self { file: true, stderr: false },
+            Some("0" | "false" | "no" | "none") => Self { file: true, stderr: true },
+            _ => Self { file: true, stderr: stderr_is_tty },
+        }
+    }
+
+    fn any(&self) -> bool {
+        self.file || self.stderr
+    }
+}
+
+/// Prefix shown before the level for a record
+fn level_prefix(level: log::Level, claude_related: bool, emoji: bool) -> &'static str {
+    let group = if claude_related { 0 } else { 1 };
+    let index = match level {
+        log::Level::Error => 0,
+        log::Level::Warn => 1,
+        log::Level::Info => 2,
+        log::Level::Debug => 3,
+        log::Level::Trace => 4,
+    };
+    let (emoji_prefix, plain_prefix) = LEVEL_PREFIXES[group][index];
+    if emoji {
+        emoji_prefix
+    } else {
+        plain_prefix
+    }
+}
+
+/// Replace an emoji level prefix in a formatted line with its ASCII form
+fn strip_emoji_prefix(line: &[u8]) -> Cow<'_, [u8]> {
+    let Ok(text) = std::str::from_utf8(line) else {
+        return Cow::Borrowed(line);
+    };
+    for (emoji_prefix, plain_prefix) in LEVEL_PREFIXES.iter().flatten() {
+        if text.contains(emoji_prefix) {
+            return Cow::Owned(text.replacen(emoji_prefix, plain_prefix, 1).into_bytes());
+        }
+    }
+    Cow::Borrowed(line)
+}
+
+/// Log line format written to the file and stderr
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LogFormat {
+    /// Human-readable lines with emoji level prefixes (default)
+    Pretty,
+    /// One JSON object per line for log aggregators
+    Json,
+}
+
+impl LogFormat {
+    fn from_env() -> Self {
+        match std::env::var(LOG_FORMAT_ENV) {
+            Ok(value) if value.trim().eq_ignore_ascii_case("json") => LogFormat::Json,
+            _ => LogFormat::Pretty,
+        }
+    }
+}
+
+/// Wraps env_logger so the level filter can change at runtime via `set_log_level`
+struct ReloadableLogger {
+    inner: env_logger::Logger,
+}
+
+impl log::Log for ReloadableLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        crate::commands::logs::log_enabled(metadata)
+    }
+
+    fn log(&self, record: &log::Record) {
+        if self.enabled(record.metadata()) {
+            self.inner.log(record);
+        }
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
+/// Messages sent to the background log writer thread
+enum LogMessage {
+    Line(Vec<u8>),
+    Flush(SyncSender<()>),
+}
+
+/// Queue feeding a dedicated writer thread, so logging never waits on disk I/O
+#[derive(Clone)]
+struct AsyncLogSink {
+    sender: SyncSender<LogMessage>,
+    dropped: Arc<AtomicUsize>,
+}
+
+/// Writer thread of the installed logger, flushed by `shutdown_logger`
+static LOG_SINK: OnceLock<AsyncLogSink> = OnceLock::new();
+
+impl AsyncLogSink {
+    /// Spawn the writer thread; lines are also echoed to stderr when `echo_stderr` is set
+    ///
+    /// Lines arrive with emoji prefixes if any output wants them; outputs in `emoji`
+    /// that don't are given the ASCII form.
+    fn spawn<W: Write + Send + 'static>(
+        mut file: W,
+        echo_stderr: bool,
+        emoji: EmojiTargets,
+        capacity: usize,
+    ) -> io::Result<Self> {
+        let (sender, receiver) = mpsc::sync_channel::<LogMessage>(capacity);
+        let dropped = Arc::new(AtomicUsize::new(0));
+        let dropped_counter = dropped.clone();
+
+        std::thread::Builder::new()
+            .name("log-writer".to_string())
+            .spawn(move || {
+                for message in receiver {
+                    match message {
+                        LogMessage::Line(buf) => {
+                            let lost = dropped_counter.swap(0, Ordering::Relaxed);
+                            if lost > 0 {
+                                let notice = format!("[log] {} line(s) dropped: log queue was full\n", lost);
+                                let _ = file.write_all(notice.as_bytes());
+                            }
+                            if emoji.file {
+                                let _ = file.write_all(&buf);
+                            } else {
+                                let _ = file.write_all(&strip_emoji_prefix(&buf));
+                            }
+                            if echo_stderr {
+                                if emoji.stderr {
+                                    let _ = io::stderr().write_all(&buf);
+                                } else {
+                                    let _ = io::stderr().write_all(&strip_emoji_prefix(&buf));
+                                }
+                            }
+                        }
+                        LogMessage::Flush(ack) => {
+                            let _ = file.flush();
+                            let _ = io::stderr().flush();
+                            let _ = ack.send(());
+                        }
+                    }
+                }
+                let _ = file.flush();
+            })?;
+
+        Ok(Self { sender, dropped })
+    }
+
+    /// Queue a line without blocking; counts it as dropped if the queue is full
+    fn send(&self, buf: &[u8]) {
+        match self.sender.try_send(LogMessage::Line(buf.to_vec())) {
+            Ok(()) | Err(TrySendError::Disconnected(_)) => {}
+            Err(TrySendError::Full(_)) => {
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Wait until every line queued so far has been written, up to `timeout`
+    fn flush(&self, timeout: Duration) -> bool {
+        let (ack_tx, ack_rx) = mpsc::sync_channel(1);
+        if self.sender.send(LogMessage::Flush(ack_tx)).is_err() {
+            return false;
+        }
+        ack_rx.recv_timeout(timeout).is_ok()
+    }
+}
+
+/// Flush queued log lines to disk; call once before the process exits
+pub fn shutdown_logger() {
+    if let Some(sink) = LOG_SINK.get() {
+        if !sink.flush(LOG_SHUTDOWN_TIMEOUT) {
+            eprintln!("Timed out flushing log file on shutdown");
+        }
+    }
+}
+
+// Custom writer that hands lines to the background writer (file, plus stderr in dev mode)
 struct DualWriter {
-    file: Mutex<std::fs::File>,
+    sink: AsyncLogSink,
 }
 
 impl Write for DualWriter {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
-        let mut file = self.file.lock().unwrap();
-        // Write to file
-        file.write_all(buf)?;
-        // Also write to stderr
-        io::stderr().write_all(buf)?;
+        self.sink.send(buf);
         Ok(buf.len())
     }
 
     fn flush(&mut self) -> io::Result<()> {
-        let mut file = self.file.lock().unwrap();
-        file.flush()?;
-        io::stderr().flush()?;
+        // Flushing happens on the writer thread; never block the caller here
         Ok(())
     }
 }
@@ -32,24 +267,7 @@ impl Write for DualWriter {
 /// - Production: <exe_dir>/logs/codestudio-YYYYMMDD.log (next to the .exe file)
 pub fn init_logger() {
     // Get log directory - prefer exe directory in production, current directory in dev
-    let log_dir = if cfg!(debug_assertions) {
-        // Development mode: use current directory
-        std::env::current_dir()
-            .unwrap_or_else(|_| PathBuf::from("."))
-            .join("logs")
-    } else {
-        // Production mode: use exe directory
-        std::env::current_exe()
-            .ok()
-            .and_then(|exe_path| exe_path.parent().map(|p| p.to_path_buf()))
-            .map(|exe_dir| exe_dir.join("logs"))
-            .unwrap_or_else(|| {
-                // Fallback to current directory if we can't determine exe path
-                std::env::current_dir()
-                    .unwrap_or_else(|_| PathBuf::from("."))
-                    .join("logs")
-            })
-    };
+    let log_dir = crate::commands::logs::log_directory();
 
     // Create log directory if it doesn't exist
     if let Err(e) = std::fs::create_dir_all(&log_dir) {
@@ -61,19 +279,16 @@ pub fn init_logger() {
         return;
     }
 
-    // Clean up old log files (keep last 30 days)
-    cleanup_old_logs(&log_dir);
+    // Clean up old log files (keep last N days, 30 by default)
+    let retention_days = log_retention_days();
+    let compress_after_days = log_compress_after_days();
+    cleanup_old_logs(&log_dir, retention_days, compress_after_days);
 
     // Create log file path with timestamp
-    let timestamp = chrono::Local::now().format("%Y%m%d");
-    let log_file = log_dir.join(format!("codestudio-{}.log", timestamp));
+    let log_file = crate::commands::logs::today_log_file();
 
     // Open log file for appending
-    let file = match OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(&log_file)
-    {
+    let file = match open_log_file(&log_file) {
         Ok(f) => f,
         Err(e) => {
             eprintln!("Failed to open log file {:?}: {}", log_file, e);
@@ -85,80 +300,81 @@ pub fn init_logger() {
         }
     };
 
-    // Create dual writer that writes to both file and stderr
-    let dual_writer = DualWriter {
-        file: Mutex::new(file),
+    // Hand the file to a background writer thread; echo to stderr only in development
+    let emoji = EmojiTargets::from_env();
+    let sink = match AsyncLogSink::spawn(file, cfg!(debug_assertions), emoji, LOG_CHANNEL_CAPACITY) {
+        Ok(sink) => sink,
+        Err(e) => {
+            eprintln!("Failed to start log writer thread: {}", e);
+            // Fallback to stderr only
+            env_logger::Builder::from_default_env()
+                .filter_level(log::LevelFilter::Warn)
+                .init();
+            return;
+        }
     };
+    let _ = LOG_SINK.set(sink.clone());
+    let dual_writer = DualWriter { sink };
 
-    // Configure logger to write to both file and stderr
+    // Configure the level filter
     // Default to Info level for Claude-related modules to capture all important logs
-    // Can be overridden by RUST_LOG environment variable
-    let mut builder = env_logger::Builder::from_default_env();
+    // Can be overridden by RUST_LOG environment variable, or at runtime via set_log_level
+    let mut filter_builder = env_logger::filter::Builder::new();
     
-    // If RUST_LOG is not set, use Info level for Claude modules and Warn for others
-    if std::env::var("RUST_LOG").is_err() {
-        builder.filter_level(log::LevelFilter::Warn); // Default for all modules
+    if let Ok(rust_log) = std::env::var("RUST_LOG") {
+        filter_builder.parse(&rust_log);
+    } else {
+        // If RUST_LOG is not set, use Info level for Claude modules and Warn for others
+        filter_builder.filter_level(log::LevelFilter::Warn); // Default for all modules
         // Set Info level for Claude-related modules to capture all important logs
-        builder.filter_module("codestudio::commands::claude", log::LevelFilter::Info);
-        builder.filter_module("codestudio::commands::agents", log::LevelFilter::Info);
-        builder.filter_module("codestudio::claude_binary", log::LevelFilter::Info);
-        builder.filter_module("codestudio::process", log::LevelFilter::Info);
+        filter_builder.filter_module("codestudio::commands::claude", log::LevelFilter::Info);
+        filter_builder.filter_module("codestudio::commands::agents", log::LevelFilter::Info);
+        filter_builder.filter_module("codestudio::claude_binary", log::LevelFilter::Info);
+        filter_builder.filter_module("codestudio::process", log::LevelFilter::Info);
     }
+    let base_filter = filter_builder.build();
+    
+    // The writer itself accepts everything; ReloadableLogger does the filtering
+    let mut builder = env_logger::Builder::new();
+    builder.filter_level(log::LevelFilter::Trace);
     
+    let log_format = LogFormat::from_env();
+
     builder
         .target(env_logger::Target::Pipe(Box::new(dual_writer)))
-        .format(|buf, record| {
+        .format(move |buf, record| {
             use std::io::Write;
-            let timestamp = chrono::Local::now().format("%Y-%m-%d %H:%M:%S%.3f");
-            let module_path = record.module_path().unwrap_or("unknown");
-            let file_path = record.file().unwrap_or("unknown");
-            let line = record.line().map(|l| l.to_string()).unwrap_or_else(|| "?".to_string());
-            
-            // Detect Claude-related logs more comprehensively
-            let args_str = record.args().to_string();
-            let is_claude_related = module_path.contains("claude") 
-                || module_path.contains("Claude")
-                || args_str.contains("Claude")
-                || args_str.contains("claude")
-                || args_str.contains("CLAUDE")
-                || file_path.contains("claude");
-            
-            // Enhanced prefix for Claude-related logs
-            let prefix = if is_claude_related {
-                match record.level() {
-                    log::Level::Error => "🔴 [CLAUDE ERROR]",
-                    log::Level::Warn => "⚠️  [CLAUDE WARN]",
-                    log::Level::Info => "ℹ️  [CLAUDE INFO]",
-                    log::Level::Debug => "🔍 [CLAUDE DEBUG]",
-                    log::Level::Trace => "🔎 [CLAUDE TRACE]",
-                }
-            } else {
-                match record.level() {
-                    log::Level::Error => "❌ [ERROR]",
-                    log::Level::Warn => "⚠️  [WARN]",
-                    log::Level::Info => "ℹ️  [INFO]",
-                    log::Level::Debug => "🔍 [DEBUG]",
-                    log::Level::Trace => "🔎 [TRACE]",
-                }
-            };
-            
-            writeln!(
-                buf,
-                "[{}] {} [{}] {}:{} - {}",
-                timestamp,
-                prefix,
-                record.level(),
-                file_path,
-                line,
-                record.args()
-            )
-        })
-        .init();
+            let timestamp = chrono::Local::now().format("%Y-%m-%d %H:%M:%S%.3f").to_string();
+
+            if log_format == LogFormat::Json {
+                return writeln!(buf, "{}", format_json_record(record, &timestamp));
+            }
+
+            writeln!(buf, "{}", format_pretty_record(record, &timestamp, emoji.any()))
+        });
+
+    let max_level = base_filter.filter();
+    crate::commands::logs::install_base_filter(base_filter);
+    let logger = ReloadableLogger {
+        inner: builder.build(),
+    };
+    if let Err(e) = log::set_boxed_logger(Box::new(logger)) {
+        eprintln!("Failed to install logger: {}", e);
+        return;
+    }
+    log::set_max_level(max_level);
 
     log::info!("==========================================");
     log::info!("Logging initialized successfully");
     log::info!("Log file: {:?}", log_file);
     log::info!("Log directory: {:?}", log_dir);
+    log::info!("Log retention: {} day(s) (set {} to override)", retention_days, LOG_RETENTION_ENV);
+    match compress_after_days {
+        Some(days) => log::info!("Log compression: gzip after {} day(s)", days),
+        None => log::info!("Log compression: disabled (set {} to enable)", LOG_COMPRESS_AFTER_ENV),
+    }
+    log::info!("Log format: {:?} (set {}=json for JSON lines)", log_format, LOG_FORMAT_ENV);
+    log::info!("Emoji prefixes: file={}, stderr={} (set {} to disable)", emoji.file, emoji.stderr, LOG_NO_EMOJI_ENV);
     if cfg!(debug_assertions) {
         log::info!("Mode: Development (logs in current directory)");
     } else {
@@ -172,8 +388,134 @@ pub fn init_logger() {
     log::info!("==========================================");
 }
 
-/// Clean up old log files, keeping only the last 30 days
-fn cleanup_old_logs(log_dir: &PathBuf) {
+/// Open `path` for appending and record it as the file `get_log_info` reports
+fn open_log_file(path: &Path) -> io::Result<std::fs::File> {
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    crate::commands::logs::set_active_log_file(path.to_path_buf());
+    Ok(file)
+}
+
+/// Detect Claude-related log records by module, file or message content
+fn is_claude_related(record: &log::Record) -> bool {
+    let module_path = record.module_path().unwrap_or("unknown");
+    let file_path = record.file().unwrap_or("unknown");
+    let args_str = record.args().to_string();
+    module_path.contains("claude")
+        || module_path.contains("Claude")
+        || args_str.contains("Claude")
+        || args_str.contains("claude")
+        || args_str.contains("CLAUDE")
+        || file_path.contains("claude")
+}
+
+/// Render a log record in the human-readable format
+fn format_pretty_record(record: &log::Record, timestamp: &str, emoji: bool) -> String {
+    let file_path = record.file().unwrap_or("unknown");
+    let line = record.line().map(|l| l.to_string()).unwrap_or_else(|| "?".to_string());
+
+    // Enhanced prefix for Claude-related logs
+    let prefix = level_prefix(record.level(), is_claude_related(record), emoji);
+
+    format!(
+        "[{}] {} [{}] {}:{} - {}",
+        timestamp,
+        prefix,
+        record.level(),
+        file_path,
+        line,
+        record.args()
+    )
+}
+
+/// Render a log record as a single-line JSON object
+fn format_json_record(record: &log::Record, timestamp: &str) -> String {
+    serde_json::json!({
+        "timestamp": timestamp,
+        "level": record.level().to_string(),
+        "module": record.module_path().unwrap_or("unknown"),
+        "file": record.file().unwrap_or("unknown"),
+        "line": record.line(),
+        "message": record.args().to_string(),
+        "claude_related": is_claude_related(record),
+    })
+    .to_string()
+}
+
+/// Parse a retention period, accepting only positive whole numbers of days
+fn parse_retention_days(value: &str) -> Option<i64> {
+    value.trim().parse::<i64>().ok().filter(|days| *days > 0)
+}
+
+/// Read the log retention period from the environment, falling back to the default
+fn log_retention_days() -> i64 {
+    match std::env::var(LOG_RETENTION_ENV) {
+        Ok(value) => parse_retention_days(&value).unwrap_or_else(|| {
+            eprintln!(
+                "Invalid {}={:?} (expected a positive integer), using {} days",
+                LOG_RETENTION_ENV, value, DEFAULT_LOG_RETENTION_DAYS
+            );
+            DEFAULT_LOG_RETENTION_DAYS
+        }),
+        Err(_) => DEFAULT_LOG_RETENTION_DAYS,
+    }
+}
+
+/// Read the optional "compress after N days" threshold from the environment
+fn log_compress_after_days() -> Option<i64> {
+    let value = std::env::var(LOG_COMPRESS_AFTER_ENV).ok()?;
+    let days = parse_retention_days(&value);
+    if days.is_none() {
+        eprintln!(
+            "Invalid {}={:?} (expected a positive integer), log compression disabled",
+            LOG_COMPRESS_AFTER_ENV, value
+        );
+    }
+    days
+}
+
+/// Extract the date from "codestudio-YYYYMMDD.log" or "codestudio-YYYYMMDD.log.gz"
+fn log_file_date(file_name: &str) -> Option<chrono::NaiveDate> {
+    let date_str = file_name
+        .strip_prefix("codestudio-")
+        .and_then(|s| s.strip_suffix(".log").or_else(|| s.strip_suffix(".log.gz")))?;
+    chrono::NaiveDate::parse_from_str(date_str, "%Y%m%d").ok()
+}
+
+/// Gzip `path` into `<path>.gz` and remove the original
+fn compress_log_file(path: &Path) -> io::Result<PathBuf> {
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+
+    let mut gz_name = path.as_os_str().to_owned();
+    gz_name.push(".gz");
+    let gz_path = PathBuf::from(gz_name);
+
+    let result = (|| {
+        let mut input = std::fs::File::open(path)?;
+        let output = std::fs::File::create(&gz_path)?;
+        let mut encoder = GzEncoder::new(output, Compression::default());
+        io::copy(&mut input, &mut encoder)?;
+        encoder.finish()?.sync_all()
+    })();
+
+    match result {
+        Ok(()) => {
+            std::fs::remove_file(path)?;
+            Ok(gz_path)
+        }
+        Err(e) => {
+            // Don't leave a truncated archive next to the original
+            let _ = std::fs::remove_file(&gz_path);
+            Err(e)
+        }
+    }
+}
+
+/// Clean up old log files, keeping only the last `retention_days` days
+///
+/// When `compress_after_days` is set, plain `.log` files older than that are gzipped
+/// in place and only deleted once they pass the full retention cutoff.
+fn cleanup_old_logs(log_dir: &Path, retention_days: i64, compress_after_days: Option<i64>) {
     use std::fs;
     
     let entries = match fs::read_dir(log_dir) {
@@ -181,25 +523,35 @@ fn cleanup_old_logs(log_dir: &PathBuf) {
         Err(_) => return,
     };
 
-    let cutoff_date = chrono::Local::now() - chrono::Duration::days(30);
+    let today = chrono::Local::now().date_naive();
+    let cutoff_date = today - chrono::Duration::days(retention_days);
+    let compress_date = compress_after_days.map(|days| today - chrono::Duration::days(days));
     let mut deleted_count = 0;
+    let mut compressed_count = 0;
 
     for entry in entries.flatten() {
         let path = entry.path();
-        if path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("log") {
-            // Try to extract date from filename like "codestudio-20240101.log"
-            if let Some(file_name) = path.file_name().and_then(|n| n.to_str()) {
-                if let Some(date_str) = file_name.strip_prefix("codestudio-").and_then(|s| s.strip_suffix(".log")) {
-                    if let Ok(file_date) = chrono::NaiveDate::parse_from_str(date_str, "%Y%m%d") {
-                        if file_date < cutoff_date.date_naive() {
-                            if let Err(e) = fs::remove_file(&path) {
-                                eprintln!("Failed to delete old log file {:?}: {}", path, e);
-                            } else {
-                                deleted_count += 1;
-                            }
-                        }
-                    }
-                }
+        if !path.is_file() {
+            continue;
+        }
+        let Some(file_name) = path.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        // Only touch files named like "codestudio-20240101.log[.gz]"
+        let Some(file_date) = log_file_date(file_name) else {
+            continue;
+        };
+
+        if file_date < cutoff_date {
+            if let Err(e) = fs::remove_file(&path) {
+                eprintln!("Failed to delete old log file {:?}: {}", path, e);
+            } else {
+                deleted_count += 1;
+            }
+        } else if file_name.ends_with(".log") && compress_date.is_some_and(|date| file_date < date) {
+            match compress_log_file(&path) {
+                Ok(_) => compressed_count += 1,
+                Err(e) => eprintln!("Failed to compress old log file {:?}: {}", path, e),
             }
         }
     }
@@ -207,5 +559,333 @@ fn cleanup_old_logs(log_dir: &PathBuf) {
     if deleted_count > 0 {
         eprintln!("Cleaned up {} old log file(s)", deleted_count);
     }
+    if compressed_count > 0 {
+        eprintln!("Compressed {} old log file(s)", compressed_count);
+    }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn dated_log_name(days_ago: i64) -> String {
+        let date = chrono::Local::now() - chrono::Duration::days(days_ago);
+        format!("codestudio-{}.log", date.format("%Y%m%d"))
+    }
+
+    #[test]
+    fn test_format_json_record_is_valid_json() {
+        let line = format_json_record(
+            &log::Record::builder()
+                .args(format_args!("said \"hi\"\nthen left"))
+                .level(log::Level::Warn)
+                .module_path(Some("codestudio::commands::claude"))
+                .file(Some("src/commands/claude.rs"))
+                .line(Some(42))
+                .build(),
+            "2024-01-01 12:00:00.000",
+        );
+
+        assert!(!line.contains('\n'));
+        let value: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(value["timestamp"], "2024-01-01 12:00:00.000");
+        assert_eq!(value["level"], "WARN");
+        assert_eq!(value["module"], "codestudio::commands::claude");
+        assert_eq!(value["file"], "src/commands/claude.rs");
+        assert_eq!(value["line"], 42);
+        assert_eq!(value["message"], "said \"hi\"\nthen left");
+        assert_eq!(value["claude_related"], true);
+    }
+
+    #[test]
+    fn test_pretty_format_is_ascii_without_emoji() {
+        for level in [
+            log::Level::Error,
+            log::Level::Warn,
+            log::Level::Info,
+            log::Level::Debug,
+            log::Level::Trace,
+        ] {
+            for module in ["codestudio::commands::claude", "codestudio::commands::mcp"] {
+                let line = format_pretty_record(
+                    &log::Record::builder()
+                        .args(format_args!("server started"))
+                        .level(level)
+                        .module_path(Some(module))
+                        .file(Some("src/commands/mcp.rs"))
+                        .line(Some(7))
+                        .build(),
+                    "2024-01-01 12:00:00.000",
+                    false,
+                );
+                assert!(line.is_ascii(), "{}", line);
+
+                // The writer-side conversion yields the same ASCII form
+                let decorated = format_pretty_record(
+                    &log::Record::builder()
+                        .args(format_args!("server started"))
+                        .level(level)
+                        .module_path(Some(module))
+                        .file(Some("src/commands/mcp.rs"))
+                        .line(Some(7))
+                        .build(),
+                    "2024-01-01 12:00:00.000",
+                    true,
+                );
+                assert!(!decorated.is_ascii());
+                assert_eq!(strip_emoji_prefix(decorated.as_bytes()).as_ref(), line.as_bytes());
+            }
+        }
+    }
+
+    #[test]
+    fn test_emoji_targets_from_flag() {
+        assert_eq!(
+            EmojiTargets::parse(Some("1"), true),
+            EmojiTargets { file: false, stderr: false }
+        );
+        assert_eq!(
+            EmojiTargets::parse(Some("stderr"), true),
+            EmojiTargets { file: true, stderr: false }
+        );
+        assert_eq!(
+            EmojiTargets::parse(Some("file"), true),
+            EmojiTargets { file: false, stderr: true }
+        );
+        assert_eq!(
+            EmojiTargets::parse(None, false),
+            EmojiTargets { file: true, stderr: false }
+        );
+    }
+
+    /// Writer that is deliberately slow, standing in for a congested disk
+    struct SlowWriter {
+        written: Arc<std::sync::Mutex<Vec<u8>>>,
+    }
+
+    impl Write for SlowWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            std::thread::sleep(Duration::from_micros(200));
+            self.written.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Writer that blocks in its first write until released, standing in for a stalled disk
+    struct StalledWriter {
+        entered: mpsc::Sender<()>,
+        release: mpsc::Receiver<()>,
+        stalled: bool,
+        written: Arc<std::sync::Mutex<Vec<u8>>>,
+    }
+
+    impl Write for StalledWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            if !self.stalled {
+                self.stalled = true;
+                let _ = self.entered.send(());
+                let _ = self.release.recv();
+            }
+            self.written.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_async_sink_drops_lines_when_queue_is_full() {
+        let (entered_tx, entered_rx) = mpsc::channel();
+        let (release_tx, release_rx) = mpsc::channel();
+        let written = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let capacity = 4;
+        let sink = AsyncLogSink::spawn(
+            StalledWriter {
+                entered: entered_tx,
+                release: release_rx,
+                stalled: false,
+                written: written.clone(),
+            },
+            false,
+            EmojiTargets {
+                file: true,
+                stderr: false,
+            },
+            capacity,
+        )
+        .unwrap();
+
+        // Park the writer thread inside its first write, leaving the queue empty
+        sink.send(b"first\n");
+        entered_rx.recv_timeout(Duration::from_secs(10)).unwrap();
+
+        // With nothing draining the queue, a blocking send would never return here
+        let mut writer = DualWriter { sink: sink.clone() };
+        for i in 0..capacity + 3 {
+            writer.write_all(format!("line {}\n", i).as_bytes()).unwrap();
+            writer.flush().unwrap();
+        }
+        assert_eq!(sink.dropped.load(Ordering::Relaxed), 3);
+
+        release_tx.send(()).unwrap();
+        assert!(sink.flush(Duration::from_secs(10)));
+
+        let output = String::from_utf8(written.lock().unwrap().clone()).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(
+            lines,
+            vec![
+                "first",
+                "[log] 3 line(s) dropped: log queue was full",
+                "line 0",
+                "line 1",
+                "line 2",
+                "line 3",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_async_sink_accounts_for_every_line() {
+        let written = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = AsyncLogSink::spawn(
+            SlowWriter {
+                written: written.clone(),
+            },
+            false,
+            EmojiTargets {
+                file: true,
+                stderr: false,
+            },
+            64,
+        )
+        .unwrap();
+
+        let threads = 8;
+        let lines_per_thread = 500;
+        let handles: Vec<_> = (0..threads)
+            .map(|t| {
+                let mut writer = DualWriter { sink: sink.clone() };
+                std::thread::spawn(move || {
+                    for i in 0..lines_per_thread {
+                        let line = format!("line {} {}\n", t, i);
+                        writer.write_all(line.as_bytes()).unwrap();
+                        writer.flush().unwrap();
+                    }
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        assert!(sink.flush(Duration::from_secs(30)));
+
+        let output = String::from_utf8(written.lock().unwrap().clone()).unwrap();
+        let kept = output.lines().filter(|l| l.starts_with("line ")).count();
+        let dropped_now = sink.dropped.load(Ordering::Relaxed);
+        let reported: usize = output
+            .lines()
+            .filter_map(|l| l.strip_prefix("[log] "))
+            .filter_map(|l| l.split(' ').next()?.parse::<usize>().ok())
+            .sum();
+        assert_eq!(kept + reported + dropped_now, threads * lines_per_thread);
+        assert!(kept > 0);
+    }
+
+    #[test]
+    fn test_parse_retention_days() {
+        assert_eq!(parse_retention_days("7"), Some(7));
+        assert_eq!(parse_retention_days(" 90 "), Some(90));
+        assert_eq!(parse_retention_days("0"), None);
+        assert_eq!(parse_retention_days("-3"), None);
+        assert_eq!(parse_retention_days("week"), None);
+    }
+
+    #[test]
+    fn test_cleanup_old_logs_respects_retention_window() {
+        let temp_dir = TempDir::new().unwrap();
+        let log_dir = temp_dir.path();
+
+        for days_ago in [0, 5, 10, 40] {
+            std::fs::write(log_dir.join(dated_log_name(days_ago)), "log").unwrap();
+        }
+        std::fs::write(log_dir.join("other.log"), "keep").unwrap();
+
+        cleanup_old_logs(log_dir, 7, None);
+
+        assert!(log_dir.join(dated_log_name(0)).exists());
+        assert!(log_dir.join(dated_log_name(5)).exists());
+        assert!(!log_dir.join(dated_log_name(10)).exists());
+        assert!(!log_dir.join(dated_log_name(40)).exists());
+        assert!(log_dir.join("other.log").exists());
+    }
+
+    #[test]
+    fn test_log_file_date_recognizes_both_suffixes() {
+        let date = chrono::NaiveDate::from_ymd_opt(2024, 1, 31).unwrap();
+        assert_eq!(log_file_date("codestudio-20240131.log"), Some(date));
+        assert_eq!(log_file_date("codestudio-20240131.log.gz"), Some(date));
+        assert_eq!(log_file_date("codestudio-2024.log"), None);
+        assert_eq!(log_file_date("other-20240131.log"), None);
+    }
+
+    #[test]
+    fn test_cleanup_old_logs_compresses_before_deleting() {
+        use flate2::read::GzDecoder;
+        use std::io::Read;
+
+        let temp_dir = TempDir::new().unwrap();
+        let log_dir = temp_dir.path();
+        let contents = "line one\nline two\n".repeat(100);
+
+        let recent = dated_log_name(1);
+        let old = dated_log_name(10);
+        let expired_gz = format!("{}.gz", dated_log_name(40));
+        std::fs::write(log_dir.join(&recent), "recent").unwrap();
+        std::fs::write(log_dir.join(&old), &contents).unwrap();
+        std::fs::write(log_dir.join(&expired_gz), "stale").unwrap();
+
+        cleanup_old_logs(log_dir, 30, Some(3));
+
+        assert!(log_dir.join(&recent).exists());
+        assert!(!log_dir.join(&old).exists());
+        assert!(!log_dir.join(&expired_gz).exists());
+
+        let gz_path = log_dir.join(format!("{}.gz", old));
+        let mut decoder = GzDecoder::new(std::fs::File::open(&gz_path).unwrap());
+        let mut decompressed = String::new();
+        decoder.read_to_string(&mut decompressed).unwrap();
+        assert_eq!(decompressed, contents);
+    }
+
+    #[tokio::test]
+    async fn test_log_info_reports_the_file_being_written() {
+        let temp_dir = TempDir::new().unwrap();
+        let log_file = temp_dir.path().join(dated_log_name(0));
+
+        let emoji = EmojiTargets {
+            file: true,
+            stderr: false,
+        };
+        let sink = AsyncLogSink::spawn(open_log_file(&log_file).unwrap(), false, emoji, 16).unwrap();
+        sink.send(b"[INFO] hello from the logger\n");
+        assert!(sink.flush(Duration::from_secs(2)));
+
+        let info = crate::commands::logs::get_log_info().await.unwrap();
+        assert_eq!(Path::new(&info.log_file), log_file);
+        assert_eq!(Path::new(&info.log_dir), temp_dir.path());
+        assert!(std::fs::read_to_string(&info.log_file)
+            .unwrap()
+            .contains("hello from the logger"));
+        assert_eq!(info.mode, if cfg!(debug_assertions) { "development" } else { "production" });
+    }
+}
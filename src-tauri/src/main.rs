@@ -4,10 +4,12 @@
 mod checkpoint;
 mod claude_binary;
 mod commands;
+mod fs_util;
 mod logger;
 mod process;
 
 use checkpoint::state::CheckpointState;
+use claude_binary::ClaudeBinaryCache;
 use commands::agents::{
     cleanup_finished_processes, create_agent, delete_agent, execute_agent, export_agent,
     export_agent_to_file, fetch_github_agent_content, fetch_github_agents, get_agent,
@@ -19,11 +21,12 @@ use commands::agents::{
     stream_session_output, update_agent, AgentDb,
 };
 use commands::claude::{
-    cancel_claude_execution, check_auto_checkpoint, check_claude_version, cleanup_old_checkpoints,
+    cancel_claude_execution, check_auto_checkpoint, checkpoint_list, checkpoint_restore_file, check_claude_version,
+    claude_check_update, cleanup_old_checkpoints,
     clear_checkpoint_manager, continue_claude_code, create_checkpoint, create_project,
     execute_claude_code, find_claude_md_files, fork_from_checkpoint, get_checkpoint_diff,
     get_checkpoint_settings, get_checkpoint_state_stats, get_claude_session_output,
-    get_claude_settings, get_file_server_url, get_home_directory, get_hooks_config, get_project_sessions,
+    get_claude_settings, get_claude_version, invalidate_claude_binary_cache, get_file_server_url, get_home_directory, get_hooks_config, get_project_sessions,
     get_recently_modified_files, get_session_timeline, get_system_prompt, list_checkpoints,
     list_directory_contents, list_project_files, list_projects, list_running_claude_sessions, load_session_history,
     open_new_session, read_claude_md_file, read_text_file, restore_checkpoint, resume_claude_code,
@@ -32,15 +35,26 @@ use commands::claude::{
     update_hooks_config, validate_hook_command, ClaudeProcessState, FileServerState,
 };
 use commands::mcp::{
-    mcp_add, mcp_add_json, mcp_get, mcp_get_config_paths,
+    mcp_add, mcp_add_json, mcp_add_many, mcp_stop_logs, mcp_stream_logs, MCPLogStreams, mcp_export, mcp_get, mcp_get_config_paths,
+    mcp_get_raw, mcp_list_raw,
     mcp_get_server_status, mcp_list, mcp_read_project_config, mcp_remove,
-    mcp_reset_project_choices, mcp_save_project_config, mcp_serve, mcp_test_connection, mcp_update,
+    mcp_reset_project_choices, mcp_save_project_config, mcp_serve, mcp_serve_status, mcp_serve_stop,
+    mcp_test_all, mcp_test_connection, mcp_toggle,
+    mcp_get_status_history, mcp_migrate_scope, mcp_rename, mcp_update, mcp_validate_project_config,
+    MCPListCache, MCPStatusHistory,
 };
 
+use commands::logs::{get_log_info, get_recent_logs, set_log_level};
+use commands::processes::{kill_process, list_running_processes};
 use commands::proxy::{apply_proxy_settings, get_proxy_settings, save_proxy_settings};
 use commands::skills::{
-    skill_create, skill_create_file, skill_delete, skill_delete_file, skill_list_all,
-    skill_list_by_type, skill_read, skill_read_file, skill_update, skill_validate,
+    skill_copy_file, skill_count, skill_create, skill_create_file, skill_create_from_template, skill_delete,
+    skill_delete_file, skill_diff_template, skill_duplicate, skill_empty_trash, skill_get_file_tree,
+    skill_import_from_url, skill_list_all, skill_list_by_type, skill_list_templates, skill_list_trash,
+    skill_move_file, skill_name_available, skill_preview, skill_read, skill_read_file, skill_recent,
+    skill_rename_file, skill_repair_frontmatter, skill_restore, skill_search, skill_set_enabled, skill_summary,
+    skill_update, skill_validate, skill_watch_start, skill_watch_stop, skills_export_all, skills_import_all,
+    SkillWatcherState,
 };
 use commands::storage::{
     storage_delete_row, storage_execute_sql, storage_insert_row, storage_list_tables,
@@ -49,6 +63,7 @@ use commands::storage::{
 use commands::usage::{
     get_session_stats, get_usage_by_date_range, get_usage_details, get_usage_stats,
 };
+use commands::web_access::get_web_access_qr;
 use process::ProcessRegistryState;
 use std::sync::Mutex;
 use tauri::Manager;
@@ -149,12 +164,27 @@ fn main() {
             // Initialize process registry
             app.manage(ProcessRegistryState::default());
 
+            // Initialize resolved claude binary cache
+            app.manage(ClaudeBinaryCache::default());
+
             // Initialize Claude process state
             app.manage(ClaudeProcessState::default());
 
             // Initialize file server state
             app.manage(FileServerState::default());
 
+            // Initialize MCP server list cache
+            app.manage(MCPListCache::default());
+
+            // Initialize MCP connection check history
+            app.manage(MCPStatusHistory::default());
+
+            // Initialize MCP log streaming state
+            app.manage(MCPLogStreams::default());
+
+            // Initialize skills directory watcher state
+            app.manage(SkillWatcherState::default());
+
             // Apply window vibrancy with rounded corners on macOS
             #[cfg(target_os = "macos")]
             {
@@ -201,6 +231,9 @@ fn main() {
             open_new_session,
             get_system_prompt,
             check_claude_version,
+            get_claude_version,
+            claude_check_update,
+            invalidate_claude_binary_cache,
             save_system_prompt,
             save_claude_settings,
             find_claude_md_files,
@@ -226,7 +259,9 @@ fn main() {
             // Checkpoint Management
             create_checkpoint,
             restore_checkpoint,
+            checkpoint_restore_file,
             list_checkpoints,
+            checkpoint_list,
             fork_from_checkpoint,
             get_session_timeline,
             update_checkpoint_settings,
@@ -276,16 +311,30 @@ fn main() {
             mcp_add,
             mcp_list,
             mcp_get,
+            mcp_get_raw,
+            mcp_list_raw,
             mcp_remove,
             mcp_update,
             mcp_add_json,
+            mcp_add_many,
             mcp_serve,
+            mcp_serve_stop,
+            mcp_serve_status,
             mcp_test_connection,
+            mcp_test_all,
+            mcp_get_status_history,
             mcp_reset_project_choices,
             mcp_get_server_status,
             mcp_get_config_paths,
             mcp_read_project_config,
             mcp_save_project_config,
+            mcp_export,
+            mcp_toggle,
+            mcp_validate_project_config,
+            mcp_rename,
+            mcp_migrate_scope,
+            mcp_stream_logs,
+            mcp_stop_logs,
             // Storage Management
             storage_list_tables,
             storage_read_table,
@@ -305,15 +354,54 @@ fn main() {
             // Skills Management
             skill_list_all,
             skill_list_by_type,
+            skill_summary,
+            skill_count,
             skill_read,
+            skill_preview,
+            skill_recent,
             skill_create,
+            skill_name_available,
             skill_update,
             skill_delete,
+            skill_list_trash,
+            skill_restore,
+            skill_empty_trash,
+            skill_set_enabled,
             skill_validate,
+            skill_repair_frontmatter,
             skill_create_file,
             skill_read_file,
+            skill_copy_file,
+            skill_get_file_tree,
             skill_delete_file,
+            skill_duplicate,
+            skill_import_from_url,
+            skills_export_all,
+            skills_import_all,
+            skill_rename_file,
+            skill_move_file,
+            skill_search,
+            skill_list_templates,
+            skill_create_from_template,
+            skill_diff_template,
+            skill_watch_start,
+            skill_watch_stop,
+            // Logs
+            get_recent_logs,
+            get_log_info,
+            set_log_level,
+            // Processes
+            list_running_processes,
+            kill_process,
+            // Web Access
+            get_web_access_qr,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|_app, event| {
+            if let tauri::RunEvent::Exit = event {
+                // Make sure queued log lines reach the log file
+                logger::shutdown_logger();
+            }
+        });
 }
@@ -4,13 +4,22 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use tokio::process::Child;
 
+/// How often registered children are checked for a natural exit
+const EXIT_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Arguments longer than this are shortened in `ProcessInfo::command`
+const MAX_COMMAND_ARG_CHARS: usize = 80;
+
 /// Type of process being tracked
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ProcessType {
     AgentRun { agent_id: i64, agent_name: String },
     ClaudeSession { session_id: String },
+    /// A server started with `claude mcp serve`
+    McpServer { name: String },
 }
 
 /// Information about a running agent process
@@ -23,6 +32,40 @@ pub struct ProcessInfo {
     pub project_path: String,
     pub task: String,
     pub model: String,
+    /// Command line the process was started with (long arguments shortened)
+    #[serde(default)]
+    pub command: String,
+}
+
+/// Render a command line for display, shortening long arguments such as prompts
+pub fn describe_command(cmd: &std::process::Command) -> String {
+    std::iter::once(cmd.get_program())
+        .chain(cmd.get_args())
+        .map(|part| {
+            let part = part.to_string_lossy();
+            if part.chars().count() > MAX_COMMAND_ARG_CHARS {
+                let short: String = part.chars().take(MAX_COMMAND_ARG_CHARS).collect();
+                format!("{}…", short)
+            } else {
+                part.into_owned()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Send `signal` to the process group led by `pid`
+///
+/// Returns false without signalling when `pid` doesn't lead its own group,
+/// so the app's own process group is never targeted.
+#[cfg(unix)]
+fn signal_process_group(pid: u32, signal: libc::c_int) -> bool {
+    let pid = pid as libc::pid_t;
+    if pid <= 0 {
+        return false;
+    }
+    // SAFETY: getpgid and kill only inspect/signal processes and take plain integers
+    unsafe { libc::getpgid(pid) == pid && libc::kill(-pid, signal) == 0 }
 }
 
 /// Information about a running process with handle
@@ -65,6 +108,7 @@ impl ProcessRegistry {
         project_path: String,
         task: String,
         model: String,
+        command: String,
         child: Child,
     ) -> Result<(), String> {
         let process_info = ProcessInfo {
@@ -78,6 +122,7 @@ impl ProcessRegistry {
             project_path,
             task,
             model,
+            command,
         };
 
         self.register_process_internal(run_id, process_info, child)
@@ -93,6 +138,7 @@ impl ProcessRegistry {
         project_path: String,
         task: String,
         model: String,
+        command: String,
     ) -> Result<(), String> {
         let process_info = ProcessInfo {
             run_id,
@@ -105,6 +151,7 @@ impl ProcessRegistry {
             project_path,
             task,
             model,
+            command,
         };
 
         // For sidecar processes, we register without the child handle since it's managed differently
@@ -128,6 +175,7 @@ impl ProcessRegistry {
         project_path: String,
         task: String,
         model: String,
+        command: String,
     ) -> Result<i64, String> {
         let run_id = self.generate_id()?;
 
@@ -139,6 +187,7 @@ impl ProcessRegistry {
             project_path,
             task,
             model,
+            command,
         };
 
         // Register without child - Claude sessions use ClaudeProcessState for process management
@@ -154,6 +203,63 @@ impl ProcessRegistry {
         Ok(run_id)
     }
 
+    /// Register a spawned MCP server, refusing if one with the same name is already tracked
+    ///
+    /// On refusal `child` is dropped, so callers should spawn it with `kill_on_drop`.
+    pub fn register_mcp_server(
+        &self,
+        name: String,
+        pid: u32,
+        command: String,
+        child: Child,
+    ) -> Result<i64, String> {
+        let mut processes = self.processes.lock().map_err(|e| e.to_string())?;
+        if let Some(existing) = Self::find_mcp_server(&processes, &name) {
+            return Err(format!(
+                "MCP server '{}' is already running (id {})",
+                name, existing.run_id
+            ));
+        }
+
+        let run_id = self.generate_id()?;
+        let child = Arc::new(Mutex::new(Some(child)));
+        let process_handle = ProcessHandle {
+            info: ProcessInfo {
+                run_id,
+                process_type: ProcessType::McpServer { name },
+                pid,
+                started_at: Utc::now(),
+                project_path: String::new(),
+                task: String::new(),
+                model: String::new(),
+                command,
+            },
+            child: child.clone(),
+            live_output: Arc::new(Mutex::new(String::new())),
+        };
+
+        processes.insert(run_id, process_handle);
+        drop(processes);
+
+        self.watch_for_exit(run_id, child);
+        Ok(run_id)
+    }
+
+    /// Get the running MCP server with the given name, if any
+    pub fn get_mcp_server(&self, name: &str) -> Result<Option<ProcessInfo>, String> {
+        let processes = self.processes.lock().map_err(|e| e.to_string())?;
+        Ok(Self::find_mcp_server(&processes, name))
+    }
+
+    fn find_mcp_server(processes: &HashMap<i64, ProcessHandle>, name: &str) -> Option<ProcessInfo> {
+        processes
+            .values()
+            .find(|handle| {
+                matches!(&handle.info.process_type, ProcessType::McpServer { name: n } if n == name)
+            })
+            .map(|handle| handle.info.clone())
+    }
+
     /// Internal method to register any process
     fn register_process_internal(
         &self,
@@ -163,16 +269,57 @@ impl ProcessRegistry {
     ) -> Result<(), String> {
         let mut processes = self.processes.lock().map_err(|e| e.to_string())?;
 
+        let child = Arc::new(Mutex::new(Some(child)));
         let process_handle = ProcessHandle {
             info: process_info,
-            child: Arc::new(Mutex::new(Some(child))),
+            child: child.clone(),
             live_output: Arc::new(Mutex::new(String::new())),
         };
 
         processes.insert(run_id, process_handle);
+        drop(processes);
+
+        self.watch_for_exit(run_id, child);
         Ok(())
     }
 
+    /// Remove the process from the registry once its child exits on its own
+    fn watch_for_exit(&self, run_id: i64, child: Arc<Mutex<Option<Child>>>) {
+        let processes = Arc::downgrade(&self.processes);
+
+        tauri::async_runtime::spawn(async move {
+            loop {
+                tokio::time::sleep(EXIT_POLL_INTERVAL).await;
+
+                let exited = match child.lock() {
+                    Ok(mut guard) => match guard.as_mut() {
+                        Some(child) => !matches!(child.try_wait(), Ok(None)),
+                        // Handle cleared by kill_process, which unregisters itself
+                        None => return,
+                    },
+                    Err(_) => true,
+                };
+                if !exited {
+                    continue;
+                }
+
+                if let Some(processes) = processes.upgrade() {
+                    if let Ok(mut processes) = processes.lock() {
+                        // Only remove the entry this watcher was started for
+                        let is_same_process = processes
+                            .get(&run_id)
+                            .is_some_and(|handle| Arc::ptr_eq(&handle.child, &child));
+                        if is_same_process {
+                            log::info!("Process {} exited, removing it from the registry", run_id);
+                            processes.remove(&run_id);
+                        }
+                    }
+                }
+                return;
+            }
+        });
+    }
+
     /// Get all running Claude sessions
     pub fn get_running_claude_sessions(&self) -> Result<Vec<ProcessInfo>, String> {
         let processes = self.processes.lock().map_err(|e| e.to_string())?;
@@ -208,14 +355,15 @@ impl ProcessRegistry {
         Ok(())
     }
 
-    /// Get all running processes
-    #[allow(dead_code)]
+    /// Get all running processes, oldest first
     pub fn get_running_processes(&self) -> Result<Vec<ProcessInfo>, String> {
         let processes = self.processes.lock().map_err(|e| e.to_string())?;
-        Ok(processes
+        let mut running: Vec<ProcessInfo> = processes
             .values()
             .map(|handle| handle.info.clone())
-            .collect())
+            .collect();
+        running.sort_by_key(|info| (info.started_at, info.run_id));
+        Ok(running)
     }
 
     /// Get all running agent processes
@@ -257,6 +405,12 @@ impl ProcessRegistry {
             run_id, pid
         );
 
+        // Take down anything the process spawned along with it
+        #[cfg(unix)]
+        if signal_process_group(pid, libc::SIGKILL) {
+            info!("Sent SIGKILL to process group {}", pid);
+        }
+
         // Send kill signal to the process
         let kill_sent = {
             let mut child_guard = child_arc.lock().map_err(|e| e.to_string())?;
@@ -537,3 +691,82 @@ impl Default for ProcessRegistryState {
         Self(Arc::new(ProcessRegistry::new()))
     }
 }
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    /// Spawn a long-running dummy process in its own process group
+    fn spawn_sleeper() -> (Child, String) {
+        let mut cmd = tokio::process::Command::new("sleep");
+        cmd.arg("30").process_group(0);
+        let command = describe_command(cmd.as_std());
+        (cmd.spawn().unwrap(), command)
+    }
+
+    fn register(registry: &ProcessRegistry, run_id: i64, child: Child, command: String) {
+        let pid = child.id().unwrap();
+        registry
+            .register_process(
+                run_id,
+                1,
+                "test-agent".to_string(),
+                pid,
+                "/tmp".to_string(),
+                "task".to_string(),
+                "sonnet".to_string(),
+                command,
+                child,
+            )
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_list_and_kill_process() {
+        let registry = ProcessRegistry::new();
+        let (child, command) = spawn_sleeper();
+        let pid = child.id().unwrap();
+        register(&registry, 7, child, command);
+
+        let running = registry.get_running_processes().unwrap();
+        assert_eq!(running.len(), 1);
+        assert_eq!(running[0].run_id, 7);
+        assert_eq!(running[0].pid, pid);
+        assert_eq!(running[0].command, "sleep 30");
+
+        assert!(registry.kill_process(7).await.unwrap());
+        assert!(registry.get_running_processes().unwrap().is_empty());
+
+        // The whole process group is gone
+        let alive = unsafe { libc::kill(pid as libc::pid_t, 0) } == 0;
+        assert!(!alive);
+
+        // Unknown ids report false rather than erroring
+        assert!(!registry.kill_process(7).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_registry_cleans_up_naturally_exited_process() {
+        let registry = ProcessRegistry::new();
+        let child = tokio::process::Command::new("true").spawn().unwrap();
+        register(&registry, 8, child, "true".to_string());
+
+        for _ in 0..20 {
+            if registry.get_running_processes().unwrap().is_empty() {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(250)).await;
+        }
+        panic!("exited process was not removed from the registry");
+    }
+
+    #[test]
+    fn test_describe_command_shortens_long_arguments() {
+        let mut cmd = std::process::Command::new("claude");
+        cmd.arg("-p").arg("x".repeat(200));
+        let described = describe_command(&cmd);
+        assert!(described.starts_with("claude -p xxx"));
+        assert!(described.ends_with('…'));
+        assert_eq!(described.chars().count(), "claude -p ".len() + MAX_COMMAND_ARG_CHARS + 1);
+    }
+}
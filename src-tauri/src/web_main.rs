@@ -3,6 +3,7 @@ use clap::Parser;
 mod checkpoint;
 mod claude_binary;
 mod commands;
+mod fs_util;
 mod logger;
 mod process;
 mod web_server;
@@ -11,13 +12,29 @@ mod web_server;
 #[command(name = "codestudio-web")]
 #[command(about = "CodeStudio Web Server - Access CodeStudio from your phone")]
 struct Args {
-    /// Port to run the web server on
+    /// Port to run the web server on (0 picks a free port)
     #[arg(short, long, default_value = "8080")]
     port: u16,
 
-    /// Host to bind to (0.0.0.0 for all interfaces)
+    /// Fall back to a free port if the requested one is already in use
+    #[arg(long)]
+    auto_port: bool,
+
+    /// Host to bind to (0.0.0.0 for all interfaces); repeat to bind several
     #[arg(short = 'H', long, default_value = "0.0.0.0")]
-    host: String,
+    host: Vec<String>,
+
+    /// Keep serving on the hosts that bound if some of them fail
+    #[arg(long)]
+    best_effort_bind: bool,
+
+    /// Access token required on every request (falls back to CODESTUDIO_WEB_TOKEN)
+    #[arg(long)]
+    auth_token: Option<String>,
+
+    /// Only allow requests that read state; anything that changes it gets 403
+    #[arg(long)]
+    read_only: bool,
 }
 
 #[tokio::main]
@@ -27,13 +44,40 @@ async fn main() {
     let args = Args::parse();
 
     println!("🚀 Starting CodeStudio Web Server...");
-    println!(
-        "📱 Will be accessible from phones at: http://{}:{}",
-        args.host, args.port
-    );
+    for host in &args.host {
+        println!("📱 Requested address: http://{}:{}", host, args.port);
+    }
+
+    let auth_token = args
+        .auth_token
+        .or_else(|| std::env::var("CODESTUDIO_WEB_TOKEN").ok())
+        .filter(|token| !token.trim().is_empty());
 
-    if let Err(e) = web_server::start_web_mode(Some(args.port)).await {
+    let result = match web_server::start_web_mode(
+        &args.host,
+        Some(args.port),
+        auth_token,
+        args.auto_port,
+        args.best_effort_bind,
+        args.read_only,
+    )
+    .await
+    {
+        Ok(server) => {
+            // Printed once the listener is bound, for scripts waiting on startup
+            println!("✅ Ready on port {}", server.port);
+            // Returns after Ctrl+C/SIGTERM once in-flight requests have drained
+            server.wait().await
+        }
+        Err(e) => Err(e),
+    };
+
+    if let Err(e) = result {
         eprintln!("❌ Failed to start web server: {}", e);
+        logger::shutdown_logger();
         std::process::exit(1);
     }
+
+    println!("👋 CodeStudio Web Server exited");
+    logger::shutdown_logger();
 }
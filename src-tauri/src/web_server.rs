@@ -1,10 +1,11 @@
 #![allow(dead_code)]
 
 use axum::extract::ws::{Message, WebSocket};
-use axum::http::Method;
+use axum::http::{header, HeaderMap, HeaderValue, Method, StatusCode};
 use axum::{
-    extract::{Path, State as AxumState, WebSocketUpgrade},
-    response::{Html, Json, Response},
+    extract::{MatchedPath, Path, Query, Request, State as AxumState, WebSocketUpgrade},
+    middleware::{self, Next},
+    response::{Html, IntoResponse, Json, Response},
     routing::get,
     Router,
 };
@@ -12,10 +13,12 @@ use chrono;
 use futures_util::{SinkExt, StreamExt};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use std::future::IntoFuture;
 use std::net::SocketAddr;
 use std::sync::Arc;
 use tokio::net::TcpListener;
-use tokio::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::{watch, Mutex};
 use tower_http::cors::{Any, CorsLayer};
 use tower_http::services::ServeDir;
 use which;
@@ -66,6 +69,140 @@ pub struct AppState {
     // Track active WebSocket sessions for Claude execution
     pub active_sessions:
         Arc<Mutex<std::collections::HashMap<String, tokio::sync::mpsc::Sender<String>>>>,
+    // Token required on every request; None leaves the server open
+    pub auth_token: Option<Arc<str>>,
+    // Reject requests that change state (see `is_mutating_request`)
+    pub read_only: bool,
+}
+
+/// Cookie set after a successful `?token=` login so the browser stays authenticated
+const AUTH_COOKIE_NAME: &str = "codestudio_token";
+
+/// How long in-flight requests get to finish once a shutdown starts
+const SHUTDOWN_GRACE_PERIOD: Duration = Duration::from_secs(10);
+
+/// Compare two secrets without short-circuiting on the first differing byte
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Extract the token from `Authorization: Bearer <token>`
+fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+        .map(str::trim)
+}
+
+/// Extract the login cookie value
+fn cookie_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .find_map(|pair| {
+            let (name, value) = pair.trim().split_once('=')?;
+            (name == AUTH_COOKIE_NAME).then_some(value)
+        })
+}
+
+/// Extract and decode `token=<token>` from a query string (used by the login link)
+fn query_token(query: Option<&str>) -> Option<String> {
+    url::form_urlencoded::parse(query?.as_bytes())
+        .find(|(name, _)| name == "token")
+        .map(|(_, value)| value.into_owned())
+}
+
+/// Percent-encode the token for the login cookie, which can't hold `;`, spaces and the like
+fn cookie_value(token: &str) -> String {
+    url::form_urlencoded::byte_serialize(token.as_bytes()).collect()
+}
+
+/// Reject requests without a valid bearer token or login cookie when a token is configured
+///
+/// Opening any page with `?token=<token>` logs the browser in by setting a cookie.
+async fn require_auth(AxumState(state): AxumState<AppState>, request: Request, next: Next) -> Response {
+    let Some(expected) = state.auth_token.clone() else {
+        return next.run(request).await;
+    };
+    let expected_cookie = cookie_value(&expected);
+    let matches = |candidate: Option<&str>, expected: &str| {
+        candidate.is_some_and(|token| constant_time_eq(token.as_bytes(), expected.as_bytes()))
+    };
+
+    let headers = request.headers();
+    if matches(bearer_token(headers), &expected)
+        || matches(cookie_token(headers), &expected_cookie)
+    {
+        return next.run(request).await;
+    }
+
+    if matches(query_token(request.uri().query()).as_deref(), &expected) {
+        let mut response = next.run(request).await;
+        let cookie = format!(
+            "{}={}; Path=/; HttpOnly; SameSite=Strict",
+            AUTH_COOKIE_NAME, expected_cookie
+        );
+        if let Ok(value) = HeaderValue::from_str(&cookie) {
+            response.headers_mut().append(header::SET_COOKIE, value);
+        }
+        return response;
+    }
+
+    (
+        StatusCode::UNAUTHORIZED,
+        Json(ApiResponse::<()>::error(
+            "Unauthorized: missing or invalid access token".to_string(),
+        )),
+    )
+        .into_response()
+}
+
+/// GET routes that still change state: they run or stop Claude
+///
+/// Together with `is_mutating_request` this is the single list `--read-only` consults, so
+/// classify new routes here when they are added.
+const MUTATING_ROUTES: &[&str] = &[
+    "/api/sessions/execute",
+    "/api/sessions/continue",
+    "/api/sessions/resume",
+    "/api/sessions/{sessionId}/cancel",
+    "/ws/claude",
+];
+
+/// Whether a request to `route` (the matched route template) may change state
+///
+/// Anything other than GET/HEAD/OPTIONS counts as a write (create, update, delete).
+fn is_mutating_request(method: &Method, route: &str) -> bool {
+    !matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS) || MUTATING_ROUTES.contains(&route)
+}
+
+/// Reject mutating requests with 403 when the server runs with `--read-only`
+async fn require_writable(AxumState(state): AxumState<AppState>, request: Request, next: Next) -> Response {
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str())
+        .unwrap_or_else(|| request.uri().path());
+    if !state.read_only || !is_mutating_request(request.method(), route) {
+        return next.run(request).await;
+    }
+
+    (
+        StatusCode::FORBIDDEN,
+        Json(ApiResponse::<()>::error(format!(
+            "Forbidden: {} {} is not allowed in read-only mode",
+            request.method(),
+            request.uri().path()
+        ))),
+    )
+        .into_response()
 }
 
 #[derive(Debug, Deserialize)]
@@ -108,6 +245,14 @@ impl<T> ApiResponse<T> {
     }
 }
 
+/// Liveness probe for reverse proxies and reconnecting clients
+async fn healthz() -> Json<serde_json::Value> {
+    Json(json!({
+        "status": "ok",
+        "version": env!("CARGO_PKG_VERSION"),
+    }))
+}
+
 /// Serve the React frontend
 async fn serve_frontend() -> Html<&'static str> {
     Html(include_str!("../../dist/index.html"))
@@ -203,6 +348,26 @@ async fn mcp_list() -> Json<ApiResponse<Vec<serde_json::Value>>> {
     Json(ApiResponse::success(vec![]))
 }
 
+#[derive(Deserialize)]
+pub struct RecentLogsQuery {
+    #[serde(default = "default_recent_log_lines")]
+    pub lines: usize,
+    #[serde(default, rename = "levelFilter")]
+    pub level_filter: Option<String>,
+}
+
+fn default_recent_log_lines() -> usize {
+    200
+}
+
+/// Tail today's log file
+async fn get_recent_logs(Query(query): Query<RecentLogsQuery>) -> Json<ApiResponse<Vec<String>>> {
+    match commands::logs::get_recent_logs(query.lines, query.level_filter).await {
+        Ok(lines) => Json(ApiResponse::success(lines)),
+        Err(e) => Json(ApiResponse::error(e)),
+    }
+}
+
 /// Load session history from JSONL file
 async fn load_session_history(
     Path((session_id, project_id)): Path<(String, String)>,
@@ -792,10 +957,218 @@ async fn send_to_session(state: &AppState, session_id: &str, message: String) {
     }
 }
 
+/// `url` with the token embedded, so opening it also logs the browser in
+fn login_url(url: &str, auth_token: Option<&str>) -> String {
+    let Some(token) = auth_token else {
+        return url.to_string();
+    };
+    match url::Url::parse(url) {
+        Ok(mut parsed) => {
+            parsed.query_pairs_mut().append_pair("token", token);
+            parsed.to_string()
+        }
+        Err(_) => url.to_string(),
+    }
+}
+
+/// Print the LAN URL and a scannable QR code so a phone can open the UI
+fn print_phone_access(port: u16, auth_token: Option<&str>) {
+    use crate::commands::web_access::{
+        lan_ipv4_addresses, pick_lan_address, render_qr_terminal, web_access_url,
+    };
+
+    let Some(ip) = pick_lan_address(&lan_ipv4_addresses()) else {
+        println!("📱 Access from phone: http://YOUR_PC_IP:{}", port);
+        return;
+    };
+
+    let url = web_access_url(ip, port);
+    println!("📱 Access from phone: {}", url);
+
+    let qr_url = login_url(&url, auth_token);
+    match render_qr_terminal(&qr_url) {
+        Ok(qr) => println!("📷 Scan to open on your phone:\n{}", qr),
+        Err(e) => println!("⚠️  Could not render QR code: {}", e),
+    }
+}
+
+/// A web server accepting connections on a background task
+pub struct WebServerHandle {
+    /// Port the server actually bound to
+    pub port: u16,
+    /// Every address a listener is bound to
+    pub addrs: Vec<SocketAddr>,
+    task: tokio::task::JoinHandle<std::io::Result<()>>,
+    shutdown: watch::Sender<bool>,
+}
+
+impl WebServerHandle {
+    /// Wait until the server stops
+    pub async fn wait(self) -> Result<(), Box<dyn std::error::Error>> {
+        self.task.await??;
+        Ok(())
+    }
+
+    /// Stop accepting connections
+    pub fn abort(&self) {
+        self.task.abort();
+    }
+
+    /// Start a graceful shutdown; `wait` returns once it has finished
+    pub fn shutdown(&self) {
+        let _ = self.shutdown.send(true);
+    }
+
+    /// Channel that triggers a graceful shutdown when `true` is sent
+    ///
+    /// Useful when the handle itself is owned elsewhere (e.g. by `wait`).
+    pub fn shutdown_sender(&self) -> watch::Sender<bool> {
+        self.shutdown.clone()
+    }
+}
+
+/// Resolve once a shutdown has been requested
+///
+/// A dropped sender is not a request: the server keeps running until told otherwise.
+async fn shutdown_requested(mut rx: watch::Receiver<bool>) {
+    if rx.wait_for(|requested| *requested).await.is_err() {
+        std::future::pending::<()>().await;
+    }
+}
+
+/// Resolve on Ctrl+C, or SIGTERM on Unix
+async fn termination_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {}
+                    _ = sigterm.recv() => {}
+                }
+                return;
+            }
+            Err(e) => eprintln!("⚠️  Could not listen for SIGTERM: {}", e),
+        }
+    }
+    if let Err(e) = tokio::signal::ctrl_c().await {
+        eprintln!("⚠️  Could not listen for Ctrl+C: {}", e);
+        std::future::pending::<()>().await;
+    }
+}
+
+/// Serve every listener until they fail or a shutdown is requested
+///
+/// On shutdown the listeners stop accepting connections and in-flight requests get
+/// `grace` to finish before the server stops regardless.
+async fn serve_until_shutdown(
+    listeners: Vec<TcpListener>,
+    app: Router,
+    shutdown: watch::Receiver<bool>,
+    grace: Duration,
+) -> std::io::Result<()> {
+    let servers = listeners.into_iter().map(|listener| {
+        axum::serve(listener, app.clone())
+            .with_graceful_shutdown(shutdown_requested(shutdown.clone()))
+            .into_future()
+    });
+    let serve = futures_util::future::try_join_all(servers);
+    tokio::pin!(serve);
+
+    tokio::select! {
+        result = &mut serve => return result.map(|_| ()),
+        _ = shutdown_requested(shutdown) => {}
+    }
+
+    println!(
+        "🛑 Shutting down: no longer accepting connections, waiting up to {}s for in-flight requests",
+        grace.as_secs()
+    );
+    match tokio::time::timeout(grace, serve).await {
+        Ok(result) => {
+            println!("✅ Web server stopped");
+            result.map(|_| ())
+        }
+        Err(_) => {
+            println!(
+                "⚠️  In-flight requests did not finish within {}s, stopping anyway",
+                grace.as_secs()
+            );
+            Ok(())
+        }
+    }
+}
+
+/// Bind the listener, falling back to an OS-assigned port when `auto_port` is set
+///
+/// Port 0 always asks the OS for a free port.
+async fn bind_listener(host: &str, port: u16, auto_port: bool) -> std::io::Result<TcpListener> {
+    match TcpListener::bind((host, port)).await {
+        Err(e) if auto_port && e.kind() == std::io::ErrorKind::AddrInUse => {
+            println!("⚠️  Port {} is already in use, picking a free port instead", port);
+            TcpListener::bind((host, 0)).await
+        }
+        result => result,
+    }
+}
+
+/// Bind one listener per host, all on the same port
+///
+/// The first host settles the port (including any `auto_port` fallback); the rest
+/// reuse it. Any failed bind is an error unless `best_effort` is set, in which case
+/// it is reported and skipped as long as at least one listener is bound.
+async fn bind_listeners(
+    hosts: &[String],
+    port: u16,
+    auto_port: bool,
+    best_effort: bool,
+) -> Result<Vec<TcpListener>, String> {
+    let default_hosts = ["0.0.0.0".to_string()];
+    let hosts = if hosts.is_empty() { &default_hosts[..] } else { hosts };
+
+    let mut listeners: Vec<TcpListener> = Vec::new();
+    let mut failures = Vec::new();
+    for host in hosts {
+        let result = match listeners.first() {
+            Some(first) => match first.local_addr() {
+                Ok(addr) => TcpListener::bind((host.as_str(), addr.port())).await,
+                Err(e) => Err(e),
+            },
+            None => bind_listener(host, port, auto_port).await,
+        };
+        match result {
+            Ok(listener) => listeners.push(listener),
+            Err(e) => {
+                let message = format!("Failed to bind {}: {}", host, e);
+                if !best_effort {
+                    return Err(message);
+                }
+                println!("⚠️  {}, continuing without it", message);
+                failures.push(message);
+            }
+        }
+    }
+
+    if listeners.is_empty() {
+        return Err(failures.join("; "));
+    }
+    Ok(listeners)
+}
+
 /// Create the web server
-pub async fn create_web_server(port: u16) -> Result<(), Box<dyn std::error::Error>> {
+pub async fn create_web_server(
+    hosts: &[String],
+    port: u16,
+    auth_token: Option<String>,
+    auto_port: bool,
+    best_effort_bind: bool,
+    read_only: bool,
+) -> Result<WebServerHandle, Box<dyn std::error::Error>> {
     let state = AppState {
         active_sessions: Arc::new(Mutex::new(std::collections::HashMap::new())),
+        auth_token: auth_token.filter(|t| !t.is_empty()).map(Arc::from),
+        read_only,
     };
 
     // CORS layer to allow requests from phone browsers
@@ -828,6 +1201,8 @@ pub async fn create_web_server(port: u16) -> Result<(), Box<dyn std::error::Erro
         .route("/api/slash-commands", get(list_slash_commands))
         // MCP
         .route("/api/mcp/servers", get(mcp_list))
+        // Logs
+        .route("/api/logs/recent", get(get_recent_logs))
         // Session history
         .route(
             "/api/sessions/{session_id}/history/{project_id}",
@@ -851,23 +1226,367 @@ pub async fn create_web_server(port: u16) -> Result<(), Box<dyn std::error::Erro
         // Serve static assets
         .nest_service("/assets", ServeDir::new("../dist/assets"))
         .nest_service("/vite.svg", ServeDir::new("../dist/vite.svg"))
+        .route_layer(middleware::from_fn_with_state(state.clone(), require_writable))
+        .layer(middleware::from_fn_with_state(state.clone(), require_auth))
+        // Added after the auth layer so health checks work without a token
+        .route("/healthz", get(healthz))
         .layer(cors)
-        .with_state(state);
-
-    let addr = SocketAddr::from(([0, 0, 0, 0], port));
-    println!("🌐 Web server running on http://0.0.0.0:{}", port);
-    println!("📱 Access from phone: http://YOUR_PC_IP:{}", port);
-
-    let listener = TcpListener::bind(addr).await?;
-    axum::serve(listener, app).await?;
+        .with_state(state.clone());
+
+    let listeners = bind_listeners(hosts, port, auto_port, best_effort_bind).await?;
+    let addrs = listeners
+        .iter()
+        .map(|listener| listener.local_addr())
+        .collect::<std::io::Result<Vec<_>>>()?;
+    let port = addrs[0].port();
+    for addr in &addrs {
+        println!("🌐 Web server running on http://{}", addr);
+    }
+    if addrs.iter().any(|addr| addr.ip().is_unspecified()) {
+        print_phone_access(port, state.auth_token.as_deref());
+    }
+    if state.auth_token.is_none() {
+        println!("⚠️  WARNING: the web server is NOT authenticated.");
+        println!("⚠️  Anyone on your network can control CodeStudio. Use --auth-token to require a token.");
+    }
+    if state.read_only {
+        println!("🔒 Read-only mode: requests that change state are rejected");
+    }
 
-    Ok(())
+    let (shutdown, shutdown_rx) = watch::channel(false);
+    let task = tokio::spawn(serve_until_shutdown(
+        listeners,
+        app,
+        shutdown_rx,
+        SHUTDOWN_GRACE_PERIOD,
+    ));
+
+    Ok(WebServerHandle {
+        port,
+        addrs,
+        task,
+        shutdown,
+    })
 }
 
 /// Start web server mode (alternative to Tauri GUI)
-pub async fn start_web_mode(port: Option<u16>) -> Result<(), Box<dyn std::error::Error>> {
+///
+/// Returns once the server is listening, so a successful return is the readiness
+/// signal; the handle carries the port that was bound. Ctrl+C or SIGTERM triggers a
+/// graceful shutdown, as does `WebServerHandle::shutdown`.
+pub async fn start_web_mode(
+    hosts: &[String],
+    port: Option<u16>,
+    auth_token: Option<String>,
+    auto_port: bool,
+    best_effort_bind: bool,
+    read_only: bool,
+) -> Result<WebServerHandle, Box<dyn std::error::Error>> {
     let port = port.unwrap_or(8080);
 
     println!("🚀 Starting CodeStudio in web server mode...");
-    create_web_server(port).await
+    let server =
+        create_web_server(hosts, port, auth_token, auto_port, best_effort_bind, read_only).await?;
+
+    let shutdown = server.shutdown_sender();
+    tokio::spawn(async move {
+        tokio::select! {
+            _ = termination_signal() => {
+                println!("🛑 Received shutdown signal");
+                let _ = shutdown.send(true);
+            }
+            // Every receiver is gone once the server has stopped
+            _ = shutdown.closed() => {}
+        }
+    });
+
+    Ok(server)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Serve a single authenticated route on an ephemeral port
+    async fn spawn_test_server(auth_token: Option<&str>) -> SocketAddr {
+        let state = AppState {
+            active_sessions: Arc::new(Mutex::new(std::collections::HashMap::new())),
+            auth_token: auth_token.map(Arc::from),
+            read_only: false,
+        };
+        let app = Router::new()
+            .route("/api/ping", get(|| async { "pong" }))
+            .layer(middleware::from_fn_with_state(state.clone(), require_auth))
+            .with_state(state);
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        addr
+    }
+
+    #[tokio::test]
+    async fn test_auth_accepts_valid_token() {
+        let addr = spawn_test_server(Some("s3cret")).await;
+        let client = reqwest::Client::new();
+        let url = format!("http://{}/api/ping", addr);
+
+        let response = client.get(&url).bearer_auth("s3cret").send().await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+
+        let response = client
+            .get(&url)
+            .header("Cookie", "other=1; codestudio_token=s3cret")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+
+        // The login link sets a cookie for later requests
+        let response = client.get(format!("{}?token=s3cret", url)).send().await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+        let cookie = response.headers().get("set-cookie").unwrap().to_str().unwrap();
+        assert!(cookie.starts_with("codestudio_token=s3cret;"));
+    }
+
+    #[tokio::test]
+    async fn test_login_link_works_for_tokens_that_need_encoding() {
+        let token = "a&b+c=d%e #f;g";
+        let addr = spawn_test_server(Some(token)).await;
+        let client = reqwest::Client::new();
+        let mut url = url::Url::parse(&format!("http://{}/api/ping", addr)).unwrap();
+        url.query_pairs_mut().append_pair("token", token);
+
+        let response = client.get(url.as_str()).send().await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+        let cookie = response.headers().get("set-cookie").unwrap().to_str().unwrap();
+        let (pair, _) = cookie.split_once(';').unwrap();
+        assert!(!pair.contains(' '));
+
+        // The cookie the browser sends back logs it in
+        let response = client
+            .get(format!("http://{}/api/ping", addr))
+            .header("Cookie", pair)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+
+        // Bearer tokens are sent as-is
+        let response = client
+            .get(format!("http://{}/api/ping", addr))
+            .bearer_auth(token)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+    }
+
+    #[test]
+    fn test_login_url_encodes_token() {
+        let url = "http://192.168.1.20:8080/";
+        assert_eq!(login_url(url, None), url);
+        assert_eq!(
+            login_url(url, Some("a&b+c=d #e")),
+            "http://192.168.1.20:8080/?token=a%26b%2Bc%3Dd+%23e"
+        );
+        assert_eq!(query_token(Some("token=a%26b%2Bc%3Dd+%23e")).as_deref(), Some("a&b+c=d #e"));
+    }
+
+    #[tokio::test]
+    async fn test_auth_rejects_missing_or_wrong_token() {
+        let addr = spawn_test_server(Some("s3cret")).await;
+        let client = reqwest::Client::new();
+        let url = format!("http://{}/api/ping", addr);
+
+        let response = client.get(&url).send().await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::UNAUTHORIZED);
+
+        let response = client.get(&url).bearer_auth("wrong").send().await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::UNAUTHORIZED);
+
+        let response = client.get(format!("{}?token=s3cre", url)).send().await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_auto_port_falls_back_when_taken() {
+        let occupied = std::net::TcpListener::bind("0.0.0.0:0").unwrap();
+        let taken_port = occupied.local_addr().unwrap().port();
+
+        let hosts = vec!["0.0.0.0".to_string()];
+        let server = start_web_mode(&hosts, Some(taken_port), None, true, false, false)
+            .await
+            .unwrap();
+        assert_ne!(server.port, taken_port);
+        assert_ne!(server.port, 0);
+        assert!(tokio::net::TcpStream::connect(("127.0.0.1", server.port))
+            .await
+            .is_ok());
+        server.abort();
+
+        // Without auto_port the conflict is reported to the caller
+        let err = start_web_mode(&hosts, Some(taken_port), None, false, false, false).await;
+        assert!(err.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_auth_disabled_without_token() {
+        let addr = spawn_test_server(None).await;
+        let response = reqwest::get(format!("http://{}/api/ping", addr)).await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_healthz_is_public() {
+        let hosts = vec!["127.0.0.1".to_string()];
+        let token = Some("s3cret".to_string());
+        let server = start_web_mode(&hosts, Some(0), token, false, false, false)
+            .await
+            .unwrap();
+        let base = format!("http://127.0.0.1:{}", server.port);
+
+        let response = reqwest::get(format!("{}/healthz", base)).await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+        let body: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(body["status"], "ok");
+        assert_eq!(body["version"], env!("CARGO_PKG_VERSION"));
+
+        // Everything else still needs the token
+        let response = reqwest::get(format!("{}/api/projects", base)).await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::UNAUTHORIZED);
+
+        server.abort();
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_binds_every_requested_host() {
+        let hosts = vec!["127.0.0.1".to_string(), "127.0.0.2".to_string()];
+        let server = start_web_mode(&hosts, Some(0), None, false, false, false).await.unwrap();
+
+        assert_eq!(server.addrs.len(), 2);
+        for host in ["127.0.0.1", "127.0.0.2"] {
+            let response = reqwest::get(format!("http://{}:{}/healthz", host, server.port))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), reqwest::StatusCode::OK);
+        }
+        server.abort();
+    }
+
+    #[tokio::test]
+    async fn test_failed_bind_is_fatal_unless_best_effort() {
+        // 192.0.2.1 (TEST-NET-1) is never a local address
+        let hosts = vec!["127.0.0.1".to_string(), "192.0.2.1".to_string()];
+
+        let err = start_web_mode(&hosts, Some(0), None, false, false, false)
+            .await
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("Failed to bind 192.0.2.1"));
+
+        let server = start_web_mode(&hosts, Some(0), None, false, true, false).await.unwrap();
+        assert_eq!(server.addrs.len(), 1);
+        assert_eq!(server.addrs[0].ip().to_string(), "127.0.0.1");
+        server.abort();
+    }
+
+    #[test]
+    fn test_mutating_request_classification() {
+        assert!(!is_mutating_request(&Method::GET, "/api/projects"));
+        assert!(!is_mutating_request(&Method::GET, "/api/mcp/servers"));
+        assert!(is_mutating_request(&Method::GET, "/api/sessions/execute"));
+        assert!(is_mutating_request(&Method::GET, "/ws/claude"));
+        assert!(is_mutating_request(&Method::DELETE, "/api/mcp/servers/{name}"));
+        assert!(is_mutating_request(&Method::POST, "/api/mcp/servers"));
+    }
+
+    #[tokio::test]
+    async fn test_read_only_blocks_mutating_requests() {
+        let spawn = |read_only: bool| async move {
+            let state = AppState {
+                active_sessions: Arc::new(Mutex::new(std::collections::HashMap::new())),
+                auth_token: None,
+                read_only,
+            };
+            let app = Router::new()
+                .route(
+                    "/api/mcp/servers/{name}",
+                    get(|| async { "server" }).delete(|| async { "removed" }),
+                )
+                .route("/api/sessions/{sessionId}/cancel", get(|| async { "cancelled" }))
+                .route_layer(middleware::from_fn_with_state(state.clone(), require_writable))
+                .with_state(state);
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let addr = listener.local_addr().unwrap();
+            tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+            addr
+        };
+        let client = reqwest::Client::new();
+
+        let addr = spawn(true).await;
+        let url = format!("http://{}/api/mcp/servers/fs", addr);
+        let response = client.get(&url).send().await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+        let response = client.delete(&url).send().await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::FORBIDDEN);
+        let response = client
+            .get(format!("http://{}/api/sessions/abc/cancel", addr))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::FORBIDDEN);
+
+        let addr = spawn(false).await;
+        let response = client
+            .delete(format!("http://{}/api/mcp/servers/fs", addr))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_graceful_shutdown_stops_server() {
+        let hosts = vec!["127.0.0.1".to_string()];
+        let server = start_web_mode(&hosts, Some(0), None, false, false, false).await.unwrap();
+        let port = server.port;
+        let response = reqwest::get(format!("http://127.0.0.1:{}/healthz", port)).await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+
+        server.shutdown_sender().send(true).unwrap();
+        tokio::time::timeout(SHUTDOWN_GRACE_PERIOD, server.wait())
+            .await
+            .expect("server did not stop within the grace period")
+            .unwrap();
+
+        assert!(tokio::net::TcpStream::connect(("127.0.0.1", port)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_gives_up_on_stuck_requests() {
+        let app = Router::new().route(
+            "/slow",
+            get(|| async { std::future::pending::<&str>().await }),
+        );
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (shutdown, shutdown_rx) = watch::channel(false);
+        let grace = Duration::from_millis(200);
+        let task = tokio::spawn(serve_until_shutdown(vec![listener], app, shutdown_rx, grace));
+
+        let stuck = tokio::spawn(reqwest::get(format!("http://{}/slow", addr)));
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        shutdown.send(true).unwrap();
+
+        tokio::time::timeout(grace * 5, task)
+            .await
+            .expect("shutdown did not honour the grace period")
+            .unwrap()
+            .unwrap();
+        stuck.abort();
+    }
 }
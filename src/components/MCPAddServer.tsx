@@ -6,7 +6,7 @@ import { Label } from "@/components/ui/label";
 import { Tabs, TabsList, TabsTrigger, TabsContent } from "@/components/ui/tabs";
 import { SelectComponent } from "@/components/ui/select";
 import { Card } from "@/components/ui/card";
-import { api } from "@/lib/api";
+import { api, isMcpError } from "@/lib/api";
 import { useTrackEvent } from "@/hooks";
 
 interface MCPAddServerProps {
@@ -195,7 +195,7 @@ export const MCPAddServer: React.FC<MCPAddServerProps> = ({
         onError(result.message);
       }
     } catch (error) {
-      onError("Failed to add server");
+      onError(isMcpError(error) ? error.message : "Failed to add server");
       console.error("Failed to add stdio server:", error);
     } finally {
       setSaving(false);
@@ -254,7 +254,7 @@ export const MCPAddServer: React.FC<MCPAddServerProps> = ({
         onError(result.message);
       }
     } catch (error) {
-      onError("Failed to add server");
+      onError(isMcpError(error) ? error.message : "Failed to add server");
       console.error("Failed to add SSE server:", error);
     } finally {
       setSaving(false);
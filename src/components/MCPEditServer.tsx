@@ -150,7 +150,9 @@ export const MCPEditServer: React.FC<MCPEditServerProps> = ({
         parsedArgs,
         env,
         server.transport === "sse" ? url : undefined,
-        scope
+        scope,
+        // Keep auth headers of remote servers; the form doesn't edit them
+        server.transport !== "stdio" ? server.headers : undefined
       );
 
       if (result.success) {
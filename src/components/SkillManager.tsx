@@ -323,7 +323,7 @@ export const SkillManager: React.FC<SkillManagerProps> = ({ className }) => {
     if (!confirm(`Are you sure you want to delete "${skill.name}"?`)) return;
 
     try {
-      await api.skillDelete(skill.name, skill.skill_type);
+      await api.skillDelete(skill.name, skill.skill_type, undefined, true);
       await loadSkills();
     } catch (error) {
       console.error("Failed to delete skill:", error);
@@ -4,7 +4,8 @@ import type { HooksConfiguration } from '@/types/hooks';
 /** Process type for tracking in ProcessRegistry */
 export type ProcessType = 
   | { AgentRun: { agent_id: number; agent_name: string } }
-  | { ClaudeSession: { session_id: string } };
+  | { ClaudeSession: { session_id: string } }
+  | { McpServer: { name: string } };
 
 /** Information about a running process */
 export interface ProcessInfo {
@@ -15,6 +16,8 @@ export interface ProcessInfo {
   project_path: string;
   task: string;
   model: string;
+  /** Command line the process was started with (long arguments shortened) */
+  command: string;
 }
 
 /**
@@ -76,6 +79,29 @@ export interface ClaudeVersionStatus {
   output: string;
 }
 
+/**
+ * Installed vs. latest published Claude Code version, as returned by claudeCheckUpdate
+ */
+export interface ClaudeUpdateStatus {
+  /** Installed version, if it could be detected */
+  current?: string | null;
+  /** Latest published version, if the registry could be reached */
+  latest?: string | null;
+  /** Whether latest is newer than current; null when either is unknown */
+  update_available?: boolean | null;
+}
+
+/**
+ * Parsed version of the claude binary
+ */
+export interface ClaudeVersion {
+  /** Trimmed output of `claude --version` */
+  raw: string;
+  major: number;
+  minor: number;
+  patch: number;
+}
+
 /**
  * Represents a CLAUDE.md file found in the project
  */
@@ -255,6 +281,7 @@ export interface Checkpoint {
   messageIndex: number;
   timestamp: string;
   description?: string;
+  tags: string[];
   parentCheckpointId?: string;
   metadata: CheckpointMetadata;
 }
@@ -337,7 +364,10 @@ export interface FileDiff {
   path: string;
   additions: number;
   deletions: number;
+  /** Unified diff text; absent for binary files */
   diffContent?: string;
+  /** Whether the file is binary, in which case no text diff is produced */
+  isBinary: boolean;
 }
 
 /**
@@ -356,6 +386,8 @@ export interface MCPServer {
   env: Record<string, string>;
   /** URL endpoint (for SSE) */
   url?: string;
+  /** HTTP headers sent to the endpoint (for SSE) */
+  headers?: Record<string, string>;
   /** Configuration scope: "local", "project", or "user" */
   scope: string;
   /** Whether the server is currently active */
@@ -378,9 +410,82 @@ export interface SkillMetadata {
  */
 export interface SkillFile {
   name: string;
-  path: string;
+  path: string; // relative to the skill root, "/"-separated
   content?: string;
   is_directory: boolean;
+  is_binary: boolean;
+  size: number;
+}
+
+/**
+ * A node in a skill's file tree
+ */
+export interface TreeNode {
+  name: string;
+  path: string; // relative to the skill root, "/"-separated; empty for the root
+  is_directory: boolean;
+  children: TreeNode[];
+  size: number; // for directories, the total size of the files below it
+}
+
+/**
+ * Parsed SKILL.md frontmatter; keys other than name/description/allowed-tools are passed through
+ */
+export interface SkillFrontmatter {
+  name: string;
+  description: string;
+  "allowed-tools"?: string[];
+  [key: string]: unknown;
+}
+
+/**
+ * Whether a name can be used for a new skill, as returned by skillNameAvailable
+ */
+export interface SkillNameAvailability {
+  valid_format: boolean;
+  /** False when the format is invalid or a skill directory with that name exists */
+  available: boolean;
+  reason?: string | null;
+}
+
+/**
+ * Outcome of skillsImportAll
+ */
+export interface SkillBundleImportResult {
+  imported: string[];
+  /** Already present and overwrite was false */
+  skipped: string[];
+  /** "<name>: <reason>" */
+  failed: string[];
+}
+
+/**
+ * Outcome of skillDelete; when `deleted` is false nothing was touched
+ */
+export interface SkillDeleteResult {
+  deleted: boolean;
+  file_count: number; // files in the skill directory, for the confirmation prompt
+  confirmation_token: string; // pass back as confirmToken to delete without confirm
+  trash_path?: string | null; // where a soft-deleted skill was moved
+}
+
+/**
+ * A soft-deleted skill in a skills directory's .trash folder
+ */
+export interface TrashedSkill {
+  name: string;
+  skill_type: string;
+  trash_id: string; // folder name inside .trash, <name>-<timestamp>
+  deleted_at: string; // RFC 3339
+  path: string;
+}
+
+/**
+ * A SKILL.md rendered for display, as returned by skillPreview
+ */
+export interface SkillPreview {
+  frontmatter?: SkillFrontmatter | null; // null when missing or unparseable
+  html: string; // sanitized; code blocks keep language-* classes for highlighting
 }
 
 /**
@@ -388,16 +493,50 @@ export interface SkillFile {
  */
 export interface Skill {
   name: string;
-  skill_type: string; // "personal" or "project"
+  skill_type: string; // "personal", "project" or "extra" (read-only, from CODESTUDIO_SKILL_PATHS)
+  source: string; // "personal", "project" or the extra skills directory the skill came from
   description: string;
   file_path: string;
   yaml_frontmatter?: string;
   markdown_content: string;
   files: SkillFile[];
   allowed_tools?: string[];
+  extra_frontmatter: Record<string, unknown>; // frontmatter keys other than name/description/allowed-tools
+  enabled: boolean; // false when listed in the skills dir's .disabled manifest
   last_modified: string;
 }
 
+/**
+ * A skill with when it was last read, as returned by skillRecent
+ */
+export interface RecentSkill {
+  skill: Skill;
+  /** RFC 3339 timestamp; absent if the skill has never been read */
+  last_used?: string;
+}
+
+/**
+ * Personal and project skills with their counts, as returned by skillSummary
+ */
+export interface SkillSummary {
+  personal: Skill[];
+  project: Skill[];
+  personal_count: number;
+  project_count: number;
+  total: number;
+  personal_error?: string | null; // set when personal skills couldn't be listed
+  project_error?: string | null; // set when project skills couldn't be listed
+}
+
+/**
+ * Number of personal and project skills, as returned by skillCount
+ */
+export interface SkillCount {
+  personal: number;
+  project: number;
+  total: number;
+}
+
 /**
  * Validation result for a skill
  */
@@ -419,6 +558,42 @@ export interface ServerStatus {
   last_checked?: number;
 }
 
+/**
+ * Result of probing an MCP server
+ */
+export interface ConnectionTestResult {
+  success: boolean;
+  transport: string;
+  /** Time taken by the probe in milliseconds */
+  latency_ms: number;
+  message: string;
+  /** Concrete failure reason if the probe failed */
+  error?: string;
+}
+
+/**
+ * One row of the connectivity matrix returned by mcpTestAll
+ */
+export interface ServerReachability {
+  name: string;
+  scope: string;
+  reachable: boolean;
+  /** Time taken by the probe in milliseconds */
+  latency_ms: number;
+  /** Why the probe failed, if it did */
+  error?: string | null;
+}
+
+/**
+ * Payload of the "mcp-log" event emitted while streaming a server's output
+ */
+export interface MCPLogEvent {
+  server: string;
+  /** "stdout", "stderr" or "system" for lifecycle notices */
+  stream: string;
+  line: string;
+}
+
 /**
  * MCP configuration file paths
  */
@@ -431,11 +606,41 @@ export interface MCPConfigPaths {
   user: string;
 }
 
+/**
+ * One recorded connection check of an MCP server
+ */
+export interface StatusCheck {
+  /** Unix timestamp in seconds */
+  timestamp: number;
+  reachable: boolean;
+  latency_ms: number;
+  error?: string | null;
+}
+
+/**
+ * A server in a saved .mcp.json that the claude CLI would reject
+ */
+export interface RejectedServer {
+  name: string;
+  errors: string[];
+}
+
+/**
+ * Result of mcpSaveProjectConfig
+ */
+export interface ProjectConfigSaveResult {
+  message: string;
+  /** Only filled in when the save was verified */
+  rejected: RejectedServer[];
+}
+
 /**
  * MCP configuration for project scope (.mcp.json)
  */
 export interface MCPProjectConfig {
   mcpServers: Record<string, MCPServerConfig>;
+  /** Undefined ${VAR} references per server; only set when read with expandEnv */
+  envWarnings?: Record<string, string[]>;
 }
 
 /**
@@ -443,7 +648,7 @@ export interface MCPProjectConfig {
  */
 export interface MCPServerConfig {
   type: string;
-  command: string;
+  command?: string;
   args: string[];
   env: Record<string, string>;
   url?: string;
@@ -489,6 +694,40 @@ export interface AddServerResult {
   server_name?: string;
 }
 
+/**
+ * Result of removing an MCP server; `removed` is false when the server wasn't configured
+ */
+export interface RemoveServerResult {
+  success: boolean;
+  removed: boolean;
+  scope?: string;
+  message: string;
+}
+
+/**
+ * Structured error thrown by MCP commands such as mcpAdd, mcpRemove, mcpGet and mcpList
+ */
+export type McpError =
+  | { kind: "BinaryNotFound"; message: string }
+  | { kind: "CommandFailed"; message: string; stderr: string }
+  | { kind: "Timeout"; message: string; timeout_secs: number }
+  | { kind: "ParseError"; message: string }
+  | { kind: "DuplicateName"; message: string; name: string; scope: string }
+  | { kind: "NotFound"; message: string; name: string }
+  | { kind: "Io"; message: string };
+
+/**
+ * Whether a caught error is a structured MCP error
+ */
+export function isMcpError(error: unknown): error is McpError {
+  return (
+    typeof error === "object" &&
+    error !== null &&
+    typeof (error as McpError).kind === "string" &&
+    typeof (error as McpError).message === "string"
+  );
+}
+
 /**
  * Import result for multiple servers
  */
@@ -498,6 +737,30 @@ export interface ImportResult {
   servers: ImportServerResult[];
 }
 
+/**
+ * Payload of the "mcp-import-progress" event, emitted once per server during a Claude Desktop import
+ */
+export interface MCPImportProgress {
+  name: string;
+  /** 1-based position of this server in the import */
+  index: number;
+  total: number;
+  success: boolean;
+}
+
+/**
+ * One server in a batch passed to mcpAddMany
+ */
+export interface MCPServerSpec {
+  name: string;
+  transport: string;
+  command?: string;
+  args?: string[];
+  env?: Record<string, string>;
+  url?: string;
+  headers?: Record<string, string>;
+}
+
 /**
  * Result for individual server import
  */
@@ -507,6 +770,30 @@ export interface ImportServerResult {
   error?: string;
 }
 
+/**
+ * LAN address of the web server and a QR code for opening it on a phone
+ */
+export interface WebAccessInfo {
+  url: string;
+  ip: string;
+  port: number;
+  /** data:image/svg+xml;base64 URI encoding `url` */
+  qr_data_uri: string;
+}
+
+/**
+ * Where the backend writes its logs and how verbose they are
+ */
+export interface LogInfo {
+  log_dir: string;
+  log_file: string;
+  /** Level for modules without a specific filter, e.g. "warn" */
+  level: string;
+  /** RUST_LOG if set, otherwise the built-in defaults */
+  filter: string;
+  mode: "development" | "production";
+}
+
 /**
  * API client for interacting with the Rust backend
  */
@@ -681,6 +968,45 @@ export const api = {
     }
   },
 
+  /**
+   * Checks the npm registry for a Claude Code release newer than the installed one
+   * @returns Promise resolving to the current and latest versions; lookups are cached for a few hours
+   */
+  async claudeCheckUpdate(): Promise<ClaudeUpdateStatus> {
+    try {
+      return await apiCall<ClaudeUpdateStatus>("claude_check_update");
+    } catch (error) {
+      console.error("Failed to check for Claude updates:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Gets the parsed version of the claude binary
+   * @returns Promise resolving to the major/minor/patch version
+   */
+  async getClaudeVersion(): Promise<ClaudeVersion> {
+    try {
+      return await apiCall<ClaudeVersion>("get_claude_version");
+    } catch (error) {
+      console.error("Failed to get Claude version:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Forgets the cached claude binary path and version so the next lookup re-detects them
+   * @returns Promise resolving when the cache is cleared
+   */
+  async invalidateClaudeBinaryCache(): Promise<void> {
+    try {
+      return await apiCall<void>("invalidate_claude_binary_cache");
+    } catch (error) {
+      console.error("Failed to invalidate Claude binary cache:", error);
+      throw error;
+    }
+  },
+
   /**
    * Saves the CLAUDE.md system prompt file
    * @param content - The new content for the system prompt
@@ -1269,14 +1595,16 @@ export const api = {
     projectId: string,
     projectPath: string,
     messageIndex?: number,
-    description?: string
+    description?: string,
+    tags?: string[]
   ): Promise<CheckpointResult> {
     return apiCall("create_checkpoint", {
       sessionId,
       projectId,
       projectPath,
       messageIndex,
-      description
+      description,
+      tags
     });
   },
 
@@ -1297,6 +1625,25 @@ export const api = {
     });
   },
 
+  /**
+   * Restores a single file from a checkpoint, leaving all other files as they are
+   */
+  async checkpointRestoreFile(
+    checkpointId: string,
+    relativePath: string,
+    sessionId: string,
+    projectId: string,
+    projectPath: string
+  ): Promise<CheckpointResult> {
+    return apiCall("checkpoint_restore_file", {
+      checkpointId,
+      relativePath,
+      sessionId,
+      projectId,
+      projectPath
+    });
+  },
+
   /**
    * Lists all checkpoints for a session
    */
@@ -1312,6 +1659,23 @@ export const api = {
     });
   },
 
+  /**
+   * Lists checkpoints matching a tag or description substring
+   */
+  async checkpointList(
+    sessionId: string,
+    projectId: string,
+    projectPath: string,
+    filter?: string
+  ): Promise<Checkpoint[]> {
+    return apiCall("checkpoint_list", {
+      sessionId,
+      projectId,
+      projectPath,
+      filter
+    });
+  },
+
   /**
    * Forks a new timeline branch from a checkpoint
    */
@@ -1505,6 +1869,8 @@ export const api = {
 
   /**
    * Adds a new MCP server
+   * Invalid input resolves with `success: false`; duplicates and CLI failures throw an McpError
+   * @param cwd - Optional directory to run the CLI from, so project scope targets its .mcp.json
    */
   async mcpAdd(
     name: string,
@@ -1513,7 +1879,10 @@ export const api = {
     args: string[] = [],
     env: Record<string, string> = {},
     url?: string,
-    scope: string = "local"
+    scope: string = "local",
+    headers?: Record<string, string>,
+    force: boolean = false,
+    cwd?: string
   ): Promise<AddServerResult> {
     try {
       return await apiCall<AddServerResult>("mcp_add", {
@@ -1523,7 +1892,10 @@ export const api = {
         args,
         env,
         url,
-        scope
+        headers,
+        scope,
+        force,
+        cwd
       });
     } catch (error) {
       console.error("Failed to add MCP server:", error);
@@ -1533,11 +1905,16 @@ export const api = {
 
   /**
    * Lists all configured MCP servers
+   * @param forceRefresh - Bypass the short-lived server list cache
+   * @param scopeFilter - Only return servers in this scope
    */
-  async mcpList(): Promise<MCPServer[]> {
+  async mcpList(
+    forceRefresh: boolean = false,
+    scopeFilter?: "local" | "project" | "user"
+  ): Promise<MCPServer[]> {
     try {
 
-      const result = await apiCall<MCPServer[]>("mcp_list");
+      const result = await apiCall<MCPServer[]>("mcp_list", { forceRefresh, scopeFilter });
 
       return result;
     } catch (error) {
@@ -1558,12 +1935,38 @@ export const api = {
     }
   },
 
+  /**
+   * Returns what `claude mcp get` printed for a server, decoded but not parsed
+   * @param name - The server name
+   */
+  async mcpGetRaw(name: string): Promise<string> {
+    try {
+      return await apiCall<string>("mcp_get_raw", { name });
+    } catch (error) {
+      console.error("Failed to get raw MCP server output:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Returns what `claude mcp list` printed, decoded but not parsed
+   */
+  async mcpListRaw(): Promise<string> {
+    try {
+      return await apiCall<string>("mcp_list_raw");
+    } catch (error) {
+      console.error("Failed to get raw MCP list output:", error);
+      throw error;
+    }
+  },
+
   /**
    * Removes an MCP server
+   * @param cwd - Optional directory to run the CLI from, so project scope targets its .mcp.json
    */
-  async mcpRemove(name: string): Promise<string> {
+  async mcpRemove(name: string, cwd?: string): Promise<RemoveServerResult> {
     try {
-      return await apiCall<string>("mcp_remove", { name });
+      return await apiCall<RemoveServerResult>("mcp_remove", { name, cwd });
     } catch (error) {
       console.error("Failed to remove MCP server:", error);
       throw error;
@@ -1572,6 +1975,7 @@ export const api = {
 
   /**
    * Updates an existing MCP server (remove + add)
+   * @param cwd - Optional directory to run the CLI from, so project scope targets its .mcp.json
    */
   async mcpUpdate(
     oldName: string,
@@ -1581,7 +1985,9 @@ export const api = {
     args: string[] = [],
     env: Record<string, string> = {},
     url?: string,
-    scope: string = "local"
+    scope: string = "local",
+    headers?: Record<string, string>,
+    cwd?: string
   ): Promise<AddServerResult> {
     try {
       return await apiCall<AddServerResult>("mcp_update", {
@@ -1592,7 +1998,9 @@ export const api = {
         args,
         env,
         url,
+        headers,
         scope,
+        cwd,
       });
     } catch (error) {
       console.error("Failed to update MCP server:", error);
@@ -1600,6 +2008,26 @@ export const api = {
     }
   },
 
+  /**
+   * Moves an MCP server to another scope, keeping its configuration
+   * @param name - The server name
+   * @param fromScope - The scope it currently lives in
+   * @param toScope - The scope to move it to, e.g. "project" to share it with the team
+   * @returns Promise resolving to the result; on failure the server is restored in fromScope
+   */
+  async mcpMigrateScope(name: string, fromScope: string, toScope: string): Promise<AddServerResult> {
+    try {
+      return await apiCall<AddServerResult>("mcp_migrate_scope", {
+        name,
+        from_scope: fromScope,
+        to_scope: toScope,
+      });
+    } catch (error) {
+      console.error("Failed to migrate MCP server scope:", error);
+      throw error;
+    }
+  },
+
   /**
    * Adds an MCP server from JSON configuration
    */
@@ -1613,7 +2041,7 @@ export const api = {
   },
 
   /**
-   * Imports MCP servers from Claude Desktop
+   * Imports MCP servers from Claude Desktop, emitting an "mcp-import-progress" event per server
    */
   async mcpAddFromClaudeDesktop(scope: string = "local"): Promise<ImportResult> {
     try {
@@ -1624,30 +2052,125 @@ export const api = {
     }
   },
 
+  /**
+   * Adds several MCP servers to one scope, reporting each server's outcome
+   * @param cwd - Optional directory to run the CLI from, so project scope targets its .mcp.json
+   */
+  async mcpAddMany(
+    servers: MCPServerSpec[],
+    scope: string = "local",
+    cwd?: string
+  ): Promise<ImportResult> {
+    try {
+      return await apiCall<ImportResult>("mcp_add_many", { servers, scope, cwd });
+    } catch (error) {
+      console.error("Failed to add MCP servers:", error);
+      throw error;
+    }
+  },
+
   /**
    * Starts Claude Code as an MCP server
+   * @returns Promise resolving to the process id to pass to mcpServeStop
    */
-  async mcpServe(): Promise<string> {
+  async mcpServe(): Promise<number> {
     try {
-      return await apiCall<string>("mcp_serve");
+      return await apiCall<number>("mcp_serve");
     } catch (error) {
       console.error("Failed to start MCP server:", error);
       throw error;
     }
   },
 
+  /**
+   * Stops the MCP server started by mcpServe; resolves to false if it had already exited
+   */
+  async mcpServeStop(id: number): Promise<boolean> {
+    try {
+      return await apiCall<boolean>("mcp_serve_stop", { id });
+    } catch (error) {
+      console.error("Failed to stop MCP server:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Gets the running MCP server started by mcpServe, or null when it isn't running
+   */
+  async mcpServeStatus(): Promise<ProcessInfo | null> {
+    try {
+      return await apiCall<ProcessInfo | null>("mcp_serve_status");
+    } catch (error) {
+      console.error("Failed to get MCP server status:", error);
+      throw error;
+    }
+  },
+
   /**
    * Tests connection to an MCP server
    */
-  async mcpTestConnection(name: string): Promise<string> {
+  async mcpTestConnection(name: string): Promise<ConnectionTestResult> {
     try {
-      return await apiCall<string>("mcp_test_connection", { name });
+      return await apiCall<ConnectionTestResult>("mcp_test_connection", { name });
     } catch (error) {
       console.error("Failed to test MCP connection:", error);
       throw error;
     }
   },
 
+  /**
+   * Probes every configured MCP server, optionally limited to one scope
+   * @param scope - Only test servers in this scope ("local", "project" or "user")
+   * @returns One reachability row per server; unreachable servers don't fail the call
+   */
+  async mcpTestAll(scope?: string): Promise<ServerReachability[]> {
+    try {
+      return await apiCall<ServerReachability[]>("mcp_test_all", { scope });
+    } catch (error) {
+      console.error("Failed to test MCP servers:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Returns a server's recent connection checks, oldest first
+   * @param name - The server name
+   * @param limit - Only return this many of the newest checks
+   */
+  async mcpGetStatusHistory(name: string, limit?: number): Promise<StatusCheck[]> {
+    try {
+      return await apiCall<StatusCheck[]>("mcp_get_status_history", { name, limit });
+    } catch (error) {
+      console.error("Failed to get MCP status history:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Launches a stdio MCP server and streams its output as "mcp-log" events
+   */
+  async mcpStreamLogs(name: string): Promise<void> {
+    try {
+      return await apiCall<void>("mcp_stream_logs", { name });
+    } catch (error) {
+      console.error("Failed to stream MCP server logs:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Stops a log stream started by mcpStreamLogs
+   * @returns Promise resolving to false if no stream was running
+   */
+  async mcpStopLogs(name: string): Promise<boolean> {
+    try {
+      return await apiCall<boolean>("mcp_stop_logs", { name });
+    } catch (error) {
+      console.error("Failed to stop MCP server logs:", error);
+      throw error;
+    }
+  },
+
   /**
    * Resets project-scoped server approval choices
    */
@@ -1686,10 +2209,13 @@ export const api = {
 
   /**
    * Reads .mcp.json from the current project
+   * @param projectPath - The project root
+   * @param expandEnv - Resolve ${VAR} / ${VAR:-default} references (default false); only for
+   *   display or launching, never for a config that will be saved back
    */
-  async mcpReadProjectConfig(projectPath: string): Promise<MCPProjectConfig> {
+  async mcpReadProjectConfig(projectPath: string, expandEnv?: boolean): Promise<MCPProjectConfig> {
     try {
-      return await apiCall<MCPProjectConfig>("mcp_read_project_config", { projectPath });
+      return await apiCall<MCPProjectConfig>("mcp_read_project_config", { projectPath, expandEnv });
     } catch (error) {
       console.error("Failed to read project MCP config:", error);
       throw error;
@@ -1698,10 +2224,17 @@ export const api = {
 
   /**
    * Saves .mcp.json to the current project
+   * @param projectPath - The project root
+   * @param config - The configuration to write
+   * @param verify - Also check the saved file and report servers the claude CLI would reject
    */
-  async mcpSaveProjectConfig(projectPath: string, config: MCPProjectConfig): Promise<string> {
+  async mcpSaveProjectConfig(
+    projectPath: string,
+    config: MCPProjectConfig,
+    verify = false
+  ): Promise<ProjectConfigSaveResult> {
     try {
-      return await apiCall<string>("mcp_save_project_config", { projectPath, config });
+      return await apiCall<ProjectConfigSaveResult>("mcp_save_project_config", { projectPath, config, verify });
     } catch (error) {
       console.error("Failed to save project MCP config:", error);
       throw error;
@@ -1722,8 +2255,8 @@ export const api = {
   },
 
   /**
-   * Set the Claude binary path in settings
-   * @param path - The absolute path to the Claude binary
+   * Set the Claude binary path override (stored in ~/.claude/codestudio.json)
+   * @param path - The absolute path to the Claude binary, or "" to return to auto-detection
    * @returns Promise resolving when the path is saved
    */
   async setClaudeBinaryPath(path: string): Promise<void> {
@@ -2094,29 +2627,160 @@ export const api = {
     }
   },
 
+  /**
+   * Reads the tail of today's log file
+   * @param lines - Maximum number of lines to return
+   * @param levelFilter - Optional minimum level ("error", "warn", "info", "debug", "trace")
+   * @returns Promise resolving to log lines, oldest first
+   */
+  async getRecentLogs(lines: number, levelFilter?: string): Promise<string[]> {
+    try {
+      return await apiCall<string[]>("get_recent_logs", { lines, levelFilter });
+    } catch (error) {
+      console.error("Failed to read recent logs:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Reports where logs are written, for a "reveal logs" button
+   * @returns Promise resolving to the log directory, active log file, level and dev/production mode
+   */
+  async getLogInfo(): Promise<LogInfo> {
+    try {
+      return await apiCall<LogInfo>("get_log_info");
+    } catch (error) {
+      console.error("Failed to get log info:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Changes the log level at runtime without restarting
+   * @param level - New level ("off", "error", "warn", "info", "debug", "trace")
+   * @param module - Optional module path (e.g. "codestudio::commands::mcp"); omit for all modules
+   * @returns Promise resolving to the previous level
+   */
+  async setLogLevel(level: string, module?: string): Promise<string> {
+    try {
+      return await apiCall<string>("set_log_level", { module, level });
+    } catch (error) {
+      console.error("Failed to set log level:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Lists every tracked Claude/agent process, oldest first
+   * @returns Promise resolving to the running processes
+   */
+  async listRunningProcesses(): Promise<ProcessInfo[]> {
+    try {
+      return await apiCall<ProcessInfo[]>("list_running_processes");
+    } catch (error) {
+      console.error("Failed to list running processes:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Terminates a tracked process and, on Unix, everything it spawned
+   * @param id - The process run_id
+   * @returns Promise resolving to false if no such process was tracked
+   */
+  async killProcess(id: number): Promise<boolean> {
+    try {
+      return await apiCall<boolean>("kill_process", { id });
+    } catch (error) {
+      console.error("Failed to kill process:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Gets the LAN URL of the web server and a QR code for scanning it with a phone
+   * @param port - Web server port (defaults to 8080)
+   * @returns Promise resolving to the access URL and QR data URI
+   */
+  async getWebAccessQr(port?: number): Promise<WebAccessInfo> {
+    try {
+      return await apiCall<WebAccessInfo>("get_web_access_qr", { port });
+    } catch (error) {
+      console.error("Failed to get web access QR code:", error);
+      throw error;
+    }
+  },
+
   // Skills API methods
 
   /**
    * Lists all skills (both personal and project)
+   * @param projectPath - Optional project root; project skills resolve to <projectPath>/.claude/skills
+   * @param sortBy - Optional sort order: "name" (default), "last_modified" or "type"
    * @returns Promise resolving to array of skills
    */
-  async skillListAll(): Promise<Skill[]> {
+  async skillListAll(projectPath?: string, sortBy?: string): Promise<Skill[]> {
     try {
-      return await apiCall<Skill[]>("skill_list_all");
+      return await apiCall<Skill[]>("skill_list_all", { projectPath, sortBy });
     } catch (error) {
       console.error("Failed to list all skills:", error);
       throw error;
     }
   },
 
+  /**
+   * Counts personal and project skills without loading them; missing directories count as zero
+   * @param projectPath - Optional project root; project skills resolve to <projectPath>/.claude/skills
+   * @returns Promise resolving to the per-type and total counts
+   */
+  async skillCount(projectPath?: string): Promise<SkillCount> {
+    try {
+      return await apiCall<SkillCount>("skill_count", { projectPath });
+    } catch (error) {
+      console.error("Failed to count skills:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Lists personal and project skills in one call, with counts and per-type errors
+   * @param projectPath - Optional project root; project skills resolve to <projectPath>/.claude/skills
+   * @returns Promise resolving to the grouped skills
+   */
+  async skillSummary(projectPath?: string): Promise<SkillSummary> {
+    try {
+      return await apiCall<SkillSummary>("skill_summary", { projectPath });
+    } catch (error) {
+      console.error("Failed to get skill summary:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Lists personal and project skills by when they were last read, newest first; unread skills come last
+   * @param limit - Optional maximum number of skills to return
+   * @param projectPath - Optional project root; project skills resolve to <projectPath>/.claude/skills
+   * @returns Promise resolving to the skills with their last-used time
+   */
+  async skillRecent(limit?: number, projectPath?: string): Promise<RecentSkill[]> {
+    try {
+      return await apiCall<RecentSkill[]>("skill_recent", { limit, projectPath });
+    } catch (error) {
+      console.error("Failed to list recent skills:", error);
+      throw error;
+    }
+  },
+
   /**
    * Lists skills by type (personal or project)
    * @param skillType - Type of skills to list: "personal" or "project"
+   * @param projectPath - Optional project root; project skills resolve to <projectPath>/.claude/skills
+   * @param sortBy - Optional sort order: "name" (default), "last_modified" or "type"
    * @returns Promise resolving to array of skills
    */
-  async skillListByType(skillType: string): Promise<Skill[]> {
+  async skillListByType(skillType: string, projectPath?: string, sortBy?: string): Promise<Skill[]> {
     try {
-      return await apiCall<Skill[]>("skill_list_by_type", { skillType });
+      return await apiCall<Skill[]>("skill_list_by_type", { skillType, projectPath, sortBy });
     } catch (error) {
       console.error(`Failed to list ${skillType} skills:`, error);
       throw error;
@@ -2127,17 +2791,61 @@ export const api = {
    * Reads a skill by name and type
    * @param name - The skill name
    * @param skillType - The skill type: "personal" or "project"
+   * @param projectPath - Optional project root; project skills resolve to <projectPath>/.claude/skills
    * @returns Promise resolving to the skill
    */
-  async skillRead(name: string, skillType: string): Promise<Skill> {
+  async skillRead(name: string, skillType: string, projectPath?: string): Promise<Skill> {
     try {
-      return await apiCall<Skill>("skill_read", { name, skill_type: skillType });
+      return await apiCall<Skill>("skill_read", { name, skill_type: skillType, projectPath });
     } catch (error) {
       console.error(`Failed to read skill ${name}:`, error);
       throw error;
     }
   },
 
+  /**
+   * Renders a skill's SKILL.md body to sanitized HTML
+   * @param name - The skill name
+   * @param skillType - The skill type: "personal" or "project"
+   * @param content - Optional unsaved SKILL.md text to render instead of the file on disk
+   * @param projectPath - Optional project root; project skills resolve to <projectPath>/.claude/skills
+   * @returns Promise resolving to the rendered HTML and parsed frontmatter
+   */
+  async skillPreview(
+    name: string,
+    skillType: string,
+    content?: string,
+    projectPath?: string
+  ): Promise<SkillPreview> {
+    try {
+      return await apiCall<SkillPreview>("skill_preview", {
+        name,
+        skillType,
+        content,
+        projectPath,
+      });
+    } catch (error) {
+      console.error(`Failed to preview skill ${name}:`, error);
+      throw error;
+    }
+  },
+
+  /**
+   * Checks whether a name can be used for a new skill, for validating as the user types
+   * @param name - The prospective skill name
+   * @param skillType - The skill type: "personal" or "project"
+   * @param projectPath - Optional project root; project skills resolve to <projectPath>/.claude/skills
+   * @returns Promise resolving to the format check and whether the name is free
+   */
+  async skillNameAvailable(name: string, skillType: string, projectPath?: string): Promise<SkillNameAvailability> {
+    try {
+      return await apiCall<SkillNameAvailability>("skill_name_available", { name, skillType, projectPath });
+    } catch (error) {
+      console.error("Failed to check skill name:", error);
+      throw error;
+    }
+  },
+
   /**
    * Creates a new skill
    * @param name - The skill name
@@ -2145,6 +2853,7 @@ export const api = {
    * @param description - The skill description
    * @param markdownContent - The markdown content
    * @param allowedTools - Optional list of allowed tools
+   * @param projectPath - Optional project root; project skills resolve to <projectPath>/.claude/skills
    * @returns Promise resolving to the created skill
    */
   async skillCreate(
@@ -2152,7 +2861,8 @@ export const api = {
     skillType: string,
     description: string,
     markdownContent: string,
-    allowedTools?: string[]
+    allowedTools?: string[],
+    projectPath?: string
   ): Promise<Skill> {
     try {
       return await apiCall<Skill>("skill_create", {
@@ -2161,6 +2871,7 @@ export const api = {
         description,
         markdownContent,
         allowedTools,
+        projectPath,
       });
     } catch (error) {
       console.error(`Failed to create skill ${name}:`, error);
@@ -2174,7 +2885,10 @@ export const api = {
    * @param skillType - The skill type: "personal" or "project"
    * @param description - Optional updated description
    * @param markdownContent - Optional updated markdown content
-   * @param allowedTools - Optional updated allowed tools
+   * @param allowedTools - Optional replacement for the whole allowed tools list
+   * @param projectPath - Optional project root; project skills resolve to <projectPath>/.claude/skills
+   * @param addTools - Optional tools to append to the list
+   * @param removeTools - Optional tools to drop from the list
    * @returns Promise resolving to the updated skill
    */
   async skillUpdate(
@@ -2182,7 +2896,10 @@ export const api = {
     skillType: string,
     description?: string,
     markdownContent?: string,
-    allowedTools?: string[]
+    allowedTools?: string[],
+    projectPath?: string,
+    addTools?: string[],
+    removeTools?: string[]
   ): Promise<Skill> {
     try {
       return await apiCall<Skill>("skill_update", {
@@ -2191,6 +2908,9 @@ export const api = {
         description,
         markdownContent,
         allowedTools,
+        addTools,
+        removeTools,
+        projectPath,
       });
     } catch (error) {
       console.error(`Failed to update skill ${name}:`, error);
@@ -2199,33 +2919,175 @@ export const api = {
   },
 
   /**
-   * Deletes a skill
+   * Imports a skill from an https URL to a SKILL.md (GitHub file and gist links work) or a zipped skill folder
+   * @param url - The https URL to download
+   * @param skillType - The skill type: "personal" or "project"
+   * @param name - Optional skill name; defaults to the name in the downloaded frontmatter
+   * @param projectPath - Optional project root; project skills resolve to <projectPath>/.claude/skills
+   * @returns Promise resolving to the installed skill
+   */
+  async skillImportFromUrl(url: string, skillType: string, name?: string, projectPath?: string): Promise<Skill> {
+    try {
+      return await apiCall<Skill>("skill_import_from_url", { url, skillType, name, projectPath });
+    } catch (error) {
+      console.error(`Failed to import skill from ${url}:`, error);
+      throw error;
+    }
+  },
+
+  /**
+   * Exports every skill of one type, helper files included, into a single JSON file
+   * @param skillType - The skill type: "personal" or "project"
+   * @param outputPath - Where to write the JSON bundle
+   * @param projectPath - Optional project root; project skills resolve to <projectPath>/.claude/skills
+   * @returns Promise resolving to the number of skills exported
+   */
+  async skillsExportAll(skillType: string, outputPath: string, projectPath?: string): Promise<number> {
+    try {
+      return await apiCall<number>("skills_export_all", { skillType, outputPath, projectPath });
+    } catch (error) {
+      console.error("Failed to export skills:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Recreates skills from a JSON bundle written by skillsExportAll
+   * @param inputPath - The JSON bundle to read
+   * @param skillType - The skill type: "personal" or "project"
+   * @param overwrite - Replace skills that already exist instead of skipping them
+   * @param projectPath - Optional project root; project skills resolve to <projectPath>/.claude/skills
+   * @returns Promise resolving to which skills were imported, skipped or failed
+   */
+  async skillsImportAll(
+    inputPath: string,
+    skillType: string,
+    overwrite = false,
+    projectPath?: string
+  ): Promise<SkillBundleImportResult> {
+    try {
+      return await apiCall<SkillBundleImportResult>("skills_import_all", {
+        inputPath,
+        skillType,
+        overwrite,
+        projectPath,
+      });
+    } catch (error) {
+      console.error("Failed to import skills:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Deletes a skill; without confirm or a matching confirmToken nothing is removed
    * @param name - The skill name
    * @param skillType - The skill type: "personal" or "project"
-   * @returns Promise resolving when the skill is deleted
+   * @param projectPath - Optional project root; project skills resolve to <projectPath>/.claude/skills
+   * @param confirm - Delete right away
+   * @param soft - Move the skill to the skills directory's .trash folder instead of deleting it
+   * @param confirmToken - Token from an earlier unconfirmed call, as an alternative to confirm
+   * @returns Promise resolving to whether the skill was deleted and how many files it had
    */
-  async skillDelete(name: string, skillType: string): Promise<void> {
+  async skillDelete(
+    name: string,
+    skillType: string,
+    projectPath?: string,
+    confirm: boolean = false,
+    soft: boolean = false,
+    confirmToken?: string
+  ): Promise<SkillDeleteResult> {
     try {
-      return await apiCall<void>("skill_delete", { name, skillType });
+      return await apiCall<SkillDeleteResult>("skill_delete", {
+        name,
+        skillType,
+        confirm,
+        confirmToken,
+        soft,
+        projectPath,
+      });
     } catch (error) {
       console.error(`Failed to delete skill ${name}:`, error);
       throw error;
     }
   },
 
+  /**
+   * Lists soft-deleted personal and project skills, newest first
+   * @param projectPath - Optional project root; project skills resolve to <projectPath>/.claude/skills
+   * @returns Promise resolving to the trashed skills
+   */
+  async skillListTrash(projectPath?: string): Promise<TrashedSkill[]> {
+    try {
+      return await apiCall<TrashedSkill[]>("skill_list_trash", { projectPath });
+    } catch (error) {
+      console.error("Failed to list trashed skills:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Moves the most recently soft-deleted copy of a skill back into place
+   * @param name - The skill name
+   * @param skillType - The skill type: "personal" or "project"
+   * @param projectPath - Optional project root; project skills resolve to <projectPath>/.claude/skills
+   * @returns Promise resolving to the restored skill; rejects if a live skill already has the name
+   */
+  async skillRestore(name: string, skillType: string, projectPath?: string): Promise<Skill> {
+    try {
+      return await apiCall<Skill>("skill_restore", { name, skillType, projectPath });
+    } catch (error) {
+      console.error(`Failed to restore skill ${name}:`, error);
+      throw error;
+    }
+  },
+
+  /**
+   * Permanently deletes trashed skills older than the retention window
+   * @param olderThanDays - Optional age in days (default 30); 0 empties the trash
+   * @param projectPath - Optional project root; project skills resolve to <projectPath>/.claude/skills
+   * @returns Promise resolving to the number of trashed skills removed
+   */
+  async skillEmptyTrash(olderThanDays?: number, projectPath?: string): Promise<number> {
+    try {
+      return await apiCall<number>("skill_empty_trash", { olderThanDays, projectPath });
+    } catch (error) {
+      console.error("Failed to empty skill trash:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Enables or disables a skill without deleting it
+   * @param name - The skill name
+   * @param skillType - The skill type: "personal" or "project"
+   * @param enabled - Whether the skill should be enabled
+   * @param projectPath - Optional project root; project skills resolve to <projectPath>/.claude/skills
+   * @returns Promise resolving when the manifest is updated
+   */
+  async skillSetEnabled(name: string, skillType: string, enabled: boolean, projectPath?: string): Promise<void> {
+    try {
+      return await apiCall<void>("skill_set_enabled", { name, skillType, enabled, projectPath });
+    } catch (error) {
+      console.error(`Failed to ${enabled ? "enable" : "disable"} skill ${name}:`, error);
+      throw error;
+    }
+  },
+
   /**
    * Validates a skill format
    * @param name - The skill name
    * @param skillType - The skill type: "personal" or "project"
    * @param description - The skill description
    * @param markdownContent - The markdown content
+   * @param allowedTools - Optional allowed tools to check against the known tool list
    * @returns Promise resolving to validation result
    */
   async skillValidate(
     name: string,
     skillType: string,
     description: string,
-    markdownContent: string
+    markdownContent: string,
+    allowedTools?: string[]
   ): Promise<ValidationResult> {
     try {
       return await apiCall<ValidationResult>("skill_validate", {
@@ -2233,6 +3095,7 @@ export const api = {
         skillType,
         description,
         markdownContent,
+        allowedTools,
       });
     } catch (error) {
       console.error(`Failed to validate skill ${name}:`, error);
@@ -2240,21 +3103,45 @@ export const api = {
     }
   },
 
+  /**
+   * Rewrites a skill's SKILL.md frontmatter into a valid form
+   * @param name - The skill name
+   * @param skillType - The skill type: "personal" or "project"
+   * @param projectPath - Optional project root; project skills resolve to <projectPath>/.claude/skills
+   * @returns Promise resolving to the validation result after the repair; rejects when the file can't be fixed
+   */
+  async skillRepairFrontmatter(name: string, skillType: string, projectPath?: string): Promise<ValidationResult> {
+    try {
+      return await apiCall<ValidationResult>("skill_repair_frontmatter", { name, skillType, projectPath });
+    } catch (error) {
+      console.error(`Failed to repair frontmatter of skill ${name}:`, error);
+      throw error;
+    }
+  },
+
   /**
    * Creates a file in a skill directory
    * @param skillName - The skill name
    * @param skillType - The skill type: "personal" or "project"
    * @param fileName - The file name to create
    * @param content - The file content
+   * @param projectPath - Optional project root; project skills resolve to <projectPath>/.claude/skills
    * @returns Promise resolving when the file is created
    */
-  async skillCreateFile(skillName: string, skillType: string, fileName: string, content: string): Promise<void> {
+  async skillCreateFile(
+    skillName: string,
+    skillType: string,
+    fileName: string,
+    content: string,
+    projectPath?: string
+  ): Promise<void> {
     try {
       return await apiCall<void>("skill_create_file", {
         skillName,
         skillType,
         fileName,
         content,
+        projectPath,
       });
     } catch (error) {
       console.error(`Failed to create file ${fileName} in skill ${skillName}:`, error);
@@ -2267,14 +3154,16 @@ export const api = {
    * @param skillName - The skill name
    * @param skillType - The skill type: "personal" or "project"
    * @param fileName - The file name to read
+   * @param projectPath - Optional project root; project skills resolve to <projectPath>/.claude/skills
    * @returns Promise resolving to the file content
    */
-  async skillReadFile(skillName: string, skillType: string, fileName: string): Promise<string> {
+  async skillReadFile(skillName: string, skillType: string, fileName: string, projectPath?: string): Promise<string> {
     try {
       return await apiCall<string>("skill_read_file", {
         skillName,
         skillType,
         fileName,
+        projectPath,
       });
     } catch (error) {
       console.error(`Failed to read file ${fileName} from skill ${skillName}:`, error);
@@ -2282,19 +3171,80 @@ export const api = {
     }
   },
 
+  /**
+   * Copies a file or folder from one skill to another, or within the same skill
+   * @param fromSkill - The source skill name
+   * @param fromType - The source skill type: "personal" or "project"
+   * @param fileName - Path of the file relative to the source skill
+   * @param toSkill - The target skill name
+   * @param toType - The target skill type: "personal" or "project"
+   * @param newName - Optional path in the target skill; defaults to `fileName`
+   * @param overwrite - Replace an existing target instead of failing
+   * @param projectPath - Optional project root; project skills resolve to <projectPath>/.claude/skills
+   * @returns Promise resolving to the path of the copy in the target skill
+   */
+  async skillCopyFile(
+    fromSkill: string,
+    fromType: string,
+    fileName: string,
+    toSkill: string,
+    toType: string,
+    newName?: string,
+    overwrite: boolean = false,
+    projectPath?: string
+  ): Promise<string> {
+    try {
+      return await apiCall<string>("skill_copy_file", {
+        fromSkill,
+        fromType,
+        fileName,
+        toSkill,
+        toType,
+        newName,
+        overwrite,
+        projectPath,
+      });
+    } catch (error) {
+      console.error(`Failed to copy file ${fileName} from skill ${fromSkill}:`, error);
+      throw error;
+    }
+  },
+
+  /**
+   * Gets a skill's files as a tree, directories first then alphabetically
+   * @param skillName - The skill name
+   * @param skillType - The skill type: "personal" or "project"
+   * @param projectPath - Optional project root; project skills resolve to <projectPath>/.claude/skills
+   * @returns Promise resolving to the root node, named after the skill
+   */
+  async skillGetFileTree(skillName: string, skillType: string, projectPath?: string): Promise<TreeNode> {
+    try {
+      return await apiCall<TreeNode>("skill_get_file_tree", {
+        skillName,
+        skillType,
+        projectPath,
+      });
+    } catch (error) {
+      console.error(`Failed to get file tree of skill ${skillName}:`, error);
+      throw error;
+    }
+  },
+
   /**
    * Deletes a file from a skill directory
    * @param skillName - The skill name
    * @param skillType - The skill type: "personal" or "project"
    * @param fileName - The file name to delete
+   * @param projectPath - Optional project root; project skills resolve to <projectPath>/.claude/skills
    * @returns Promise resolving when the file is deleted
    */
-  async skillDeleteFile(skillName: string, skillType: string, fileName: string): Promise<void> {
+  async skillDeleteFile(skillName: string, skillType: string, fileName: string, projectPath?: string): Promise<void> {
     try {
       return await apiCall<void>("skill_delete_file", {
         skillName,
         skillType,
         fileName,
+        projectPath,
       });
     } catch (error) {
       console.error(`Failed to delete file ${fileName} from skill ${skillName}:`, error);
@@ -224,6 +224,9 @@ function mapCommandToEndpoint(command: string, _params?: any): string {
     'mcp_read_project_config': '/api/mcp/project-config',
     'mcp_save_project_config': '/api/mcp/project-config',
     
+    // Logs
+    'get_recent_logs': '/api/logs/recent',
+    
     // Binary and installation management
     'get_claude_binary_path': '/api/settings/claude/binary-path',
     'set_claude_binary_path': '/api/settings/claude/binary-path',